@@ -105,6 +105,80 @@ impl FromStr for TagRange {
     }
 }
 
+/// The value multiplicity of an attribute,
+/// the number of values which an element of this attribute may hold.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Vm {
+    /// A number of values within the given bounds, inclusive
+    /// (e.g. `1` or `1-3`)
+    Bounded(u32, u32),
+    /// At least the given number of values (e.g. `1-n`)
+    Unbounded(u32),
+    /// Any non-zero multiple of the given number of values (e.g. `2-2n`)
+    Multiple(u32),
+}
+
+impl Vm {
+    /// Check whether an element with the given number of values
+    /// fits this value multiplicity.
+    pub fn contains(self, n: u32) -> bool {
+        match self {
+            Vm::Bounded(min, max) => n >= min && n <= max,
+            Vm::Unbounded(min) => n >= min,
+            Vm::Multiple(k) => n > 0 && n.checked_rem(k) == Some(0),
+        }
+    }
+}
+
+impl std::fmt::Display for Vm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Vm::Bounded(min, max) if min == max => write!(f, "{}", min),
+            Vm::Bounded(min, max) => write!(f, "{}-{}", min, max),
+            Vm::Unbounded(min) => write!(f, "{}-n", min),
+            Vm::Multiple(k) => write!(f, "{}-{}n", k, k),
+        }
+    }
+}
+
+/// An error returned when parsing an invalid value multiplicity.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum VmParseError {
+    #[snafu(display("invalid value multiplicity `{}`", vm))]
+    InvalidVm { vm: String, backtrace: Backtrace },
+}
+
+impl FromStr for Vm {
+    type Err = VmParseError;
+
+    /// Parse a value multiplicity as written in the standard,
+    /// such as `1`, `1-3`, `1-n` or `2-2n`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let fail = || InvalidVm { vm: s }.fail();
+        let number = |n: &str| n.parse::<u32>().ok();
+
+        let mut parts = s.splitn(2, '-');
+        let min = match parts.next().and_then(number) {
+            Some(min) => min,
+            None => return fail(),
+        };
+        match parts.next() {
+            None => Ok(Vm::Bounded(min, min)),
+            Some("n") => Ok(Vm::Unbounded(min)),
+            Some(max) => match max.strip_suffix('n') {
+                Some(k) if number(k) == Some(min) && min > 0 => Ok(Vm::Multiple(min)),
+                Some(_) => fail(),
+                None => match number(max) {
+                    Some(max) if max >= min => Ok(Vm::Bounded(min, max)),
+                    _ => fail(),
+                },
+            },
+        }
+    }
+}
+
 /** Type trait for a dictionary of DICOM attributes. Attribute dictionaries provide the
  * means to convert a tag to an alias and vice versa, as well as a form of retrieving
  * additional information about the attribute.
@@ -141,6 +215,20 @@ pub trait DictionaryEntry {
     /// The _typical_ value representation of the attribute.
     /// In some edge cases, an element might not have this VR.
     fn vr(&self) -> VR;
+    /// The alternative value representation of the attribute, if any.
+    /// Some attributes may take either VR
+    /// depending on other attributes (e.g. `US` or `SS`).
+    fn alternative_vr(&self) -> Option<VR> {
+        None
+    }
+    /// The value multiplicity of the attribute, if known.
+    fn vm(&self) -> Option<Vm> {
+        None
+    }
+    /// The display name of the attribute (e.g. "Patient's Name"), if known.
+    fn name(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// A data type for a dictionary entry with full ownership.
@@ -175,6 +263,12 @@ pub struct DictionaryEntryRef<'a> {
     pub alias: &'a str,
     /// The _typical_  value representation of the attribute
     pub vr: VR,
+    /// The alternative value representation of the attribute, if any
+    pub vr2: Option<VR>,
+    /// The value multiplicity of the attribute, if known
+    pub vm: Option<Vm>,
+    /// The display name of the attribute (e.g. "Patient's Name"), if known
+    pub name: Option<&'a str>,
 }
 
 impl<'a> DictionaryEntry for DictionaryEntryRef<'a> {
@@ -187,6 +281,15 @@ impl<'a> DictionaryEntry for DictionaryEntryRef<'a> {
     fn vr(&self) -> VR {
         self.vr
     }
+    fn alternative_vr(&self) -> Option<VR> {
+        self.vr2
+    }
+    fn vm(&self) -> Option<Vm> {
+        self.vm
+    }
+    fn name(&self) -> Option<&str> {
+        self.name
+    }
 }
 
 /// Utility data structure that resolves to a DICOM attribute tag
@@ -223,8 +326,8 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::TagRange;
-    use crate::header::Tag;
+    use super::{DictionaryEntry, DictionaryEntryRef, TagRange, Vm};
+    use crate::header::{Tag, VR};
 
     #[test]
     fn test_parse_tag_range() {
@@ -240,4 +343,53 @@ mod tests {
         let tag: TagRange = "1234,56xx".parse().unwrap();
         assert_eq!(tag, TagRange::Element100(Tag(0x1234, 0x5600)));
     }
+
+    #[test]
+    fn test_parse_vm() {
+        assert_eq!("1".parse::<Vm>().unwrap(), Vm::Bounded(1, 1));
+        assert_eq!("1-3".parse::<Vm>().unwrap(), Vm::Bounded(1, 3));
+        assert_eq!("1-n".parse::<Vm>().unwrap(), Vm::Unbounded(1));
+        assert_eq!("2-n".parse::<Vm>().unwrap(), Vm::Unbounded(2));
+        assert_eq!("2-2n".parse::<Vm>().unwrap(), Vm::Multiple(2));
+        assert_eq!(" 3-3n ".parse::<Vm>().unwrap(), Vm::Multiple(3));
+
+        for vm in &["", "n", "1-", "3-1", "2-3n", "0-0n", "1-n or 1"] {
+            assert!(vm.parse::<Vm>().is_err(), "`{}` should be invalid", vm);
+        }
+
+        for vm in &["1", "1-3", "1-n", "2-2n"] {
+            assert_eq!(vm.parse::<Vm>().unwrap().to_string(), *vm);
+        }
+    }
+
+    #[test]
+    fn test_vm_contains() {
+        assert!(Vm::Bounded(1, 1).contains(1));
+        assert!(!Vm::Bounded(1, 1).contains(2));
+        assert!(!Vm::Bounded(1, 3).contains(0));
+        assert!(Vm::Unbounded(1).contains(1));
+        assert!(Vm::Unbounded(1).contains(100));
+        assert!(!Vm::Unbounded(1).contains(0));
+        assert!(Vm::Multiple(2).contains(4));
+        assert!(!Vm::Multiple(2).contains(3));
+        assert!(!Vm::Multiple(2).contains(0));
+    }
+
+    #[test]
+    fn test_entry_with_unbounded_vm() {
+        // as emitted by the dictionary builder
+        const ENTRIES: &[DictionaryEntryRef<'static>] = &[DictionaryEntryRef {
+            tag: TagRange::Single(Tag(0x0008, 0x0008)),
+            alias: "ImageType",
+            vr: VR::CS,
+            vr2: None,
+            vm: Some(Vm::Unbounded(2)),
+            name: Some("Image Type"),
+        }];
+
+        let entry = &ENTRIES[0];
+        assert_eq!(entry.vm(), Some(Vm::Unbounded(2)));
+        assert!(entry.vm().unwrap().contains(4));
+        assert!(!entry.vm().unwrap().contains(1));
+    }
 }
@@ -8,6 +8,7 @@ use num_traits::NumCast;
 use snafu::{Backtrace, Snafu};
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
 use std::str::{from_utf8, FromStr};
 
@@ -82,12 +83,12 @@ pub trait Header: HasLength {
 /// This type implements `HasLength`, but cannot be instantiated.
 /// This makes it so that `Value<EmptyObject>` is sure to be either a primitive
 /// value or a sequence with no items.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum EmptyObject {}
 
 impl HasLength for EmptyObject {
     fn length(&self) -> Length {
-        unreachable!()
+        match *self {}
     }
 }
 
@@ -538,6 +539,7 @@ impl<I, P> DataElement<I, P> {
 /// A data structure for a data element header, containing
 /// a tag, value representation and specified length.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataElementHeader {
     /// DICOM tag
     pub tag: Tag,
@@ -590,6 +592,19 @@ impl From<SequenceItemHeader> for DataElementHeader {
     }
 }
 
+/// Arbitrary headers are made of arbitrary parts,
+/// which need not agree with each other.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DataElementHeader {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(DataElementHeader::new(
+            u.arbitrary::<Tag>()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+        ))
+    }
+}
+
 /// Data type for describing a sequence item data element.
 /// If the element represents an item, it will also contain
 /// the specified length.
@@ -658,6 +673,7 @@ impl Header for SequenceItemHeader {
 
 /// An enum type for a DICOM value representation.
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VR {
     /// Application Entity
     AE,
@@ -841,6 +857,18 @@ impl fmt::Display for VR {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for VR {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        use VR::*;
+        u.choose(&[
+            AE, AS, AT, CS, DA, DS, DT, FL, FD, IS, LO, LT, OB, OD, OF, OL, OV, OW, PN, SH, SL, SQ,
+            SS, ST, SV, TM, UC, UI, UL, UN, UR, US, UT, UV,
+        ])
+        .copied()
+    }
+}
+
 /// Idiomatic alias for a tag's group number.
 pub type GroupNumber = u16;
 /// Idiomatic alias for a tag's element number.
@@ -853,6 +881,7 @@ pub type ElementNumber = u16;
 /// for converting it to a tuple. Both `(u16, u16)` and `[u16; 2]` can be
 /// efficiently converted to this type as well.
 #[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tag(pub GroupNumber, pub ElementNumber);
 
 impl Tag {
@@ -867,6 +896,24 @@ impl Tag {
     pub fn element(self) -> ElementNumber {
         self.1
     }
+
+    /// Create a tag from its packed form,
+    /// with the group in the upper 16 bits
+    /// and the element in the lower 16 bits.
+    #[inline]
+    pub const fn from_u32(packed: u32) -> Tag {
+        Tag((packed >> 16) as u16, packed as u16)
+    }
+
+    /// Pack the tag into a single number,
+    /// with the group in the upper 16 bits
+    /// and the element in the lower 16 bits.
+    ///
+    /// Packed tags compare in the same order as tags.
+    #[inline]
+    pub const fn to_u32(self) -> u32 {
+        (self.0 as u32) << 16 | self.1 as u32
+    }
 }
 
 impl fmt::Debug for Tag {
@@ -907,6 +954,17 @@ impl From<[u16; 2]> for Tag {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Tag {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Tag(u.arbitrary()?, u.arbitrary()?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <(u16, u16) as arbitrary::Arbitrary>::size_hint(depth)
+    }
+}
+
 /// A type for representing data set content length, in bytes.
 /// An internal value of `0xFFFF_FFFF` represents an undefined
 /// (unspecified) length, which would have to be determined
@@ -946,6 +1004,7 @@ impl From<[u16; 2]> for Tag {
 /// ```
 ///
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Length(pub u32);
 
 const UNDEFINED_LEN: u32 = 0xFFFF_FFFF;
@@ -980,6 +1039,20 @@ impl From<u32> for Length {
     }
 }
 
+/// An arbitrary length is undefined once in a while,
+/// and otherwise no longer than 64 KiB,
+/// so that values of that length can be produced.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Length {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        if u.ratio(1, 8)? {
+            Ok(Length::UNDEFINED)
+        } else {
+            Ok(Length(u.arbitrary::<u16>()?.into()))
+        }
+    }
+}
+
 impl PartialEq<Length> for Length {
     fn eq(&self, rhs: &Length) -> bool {
         match (self.0, rhs.0) {
@@ -1108,6 +1181,29 @@ impl Length {
         }
     }
 
+    /// Fetch the concrete length value as a number of bytes
+    /// which can index into memory.
+    /// Returns `None` if it represents an undefined length,
+    /// or if the length does not fit in a `usize`.
+    #[inline]
+    pub fn as_usize(self) -> Option<usize> {
+        self.get().and_then(|len| usize::try_from(len).ok())
+    }
+
+    /// Add two lengths together,
+    /// returning `None` if either of them is undefined
+    /// or if the sum cannot be represented as a defined length.
+    ///
+    /// Unlike the `+` operator,
+    /// this never yields an undefined length nor overflows.
+    #[inline]
+    pub fn checked_add(self, rhs: Length) -> Option<Length> {
+        self.get()?
+            .checked_add(rhs.get()?)
+            .filter(|&len| len != UNDEFINED_LEN)
+            .map(Length)
+    }
+
     /// Check whether the length is equally specified as another length.
     /// Unlike the implemented `PartialEq`, two undefined lengths are
     /// considered equivalent by this method.
@@ -1153,6 +1249,39 @@ mod tests {
         assert_eq!(0x0020u16, t.element());
     }
 
+    #[test]
+    fn tag_packed_round_trip() {
+        const PIXEL_DATA: Tag = Tag::from_u32(0x7FE0_0010);
+        assert_eq!(PIXEL_DATA, Tag(0x7FE0, 0x0010));
+        assert_eq!(PIXEL_DATA.to_u32(), 0x7FE0_0010);
+
+        let t = Tag(0xFFFE, 0xE00D);
+        assert_eq!(Tag::from_u32(t.to_u32()), t);
+        assert!(Tag(0x0008, 0xFFFF).to_u32() < Tag(0x0010, 0x0000).to_u32());
+    }
+
+    #[test]
+    fn length_checked_arithmetic() {
+        assert_eq!(Length(2).checked_add(Length(6)), Some(Length(8)));
+        assert_eq!(Length(8).as_usize(), Some(8));
+        assert_eq!(Length(0).as_usize(), Some(0));
+
+        // undefined lengths are never taken as a size
+        assert!(Length::UNDEFINED.checked_add(Length(2)).is_none());
+        assert!(Length(2).checked_add(Length::UNDEFINED).is_none());
+        assert_eq!(Length::UNDEFINED.as_usize(), None);
+        assert_eq!(Length::UNDEFINED.get(), None);
+
+        // a sum past the greatest length does not wrap around
+        // nor land on the undefined length
+        assert!(Length(0xFFFF_FFFE).checked_add(Length(1)).is_none());
+        assert!(Length(0xFFFF_FFFE).checked_add(Length(2)).is_none());
+        assert_eq!(
+            Length(0xFFFF_FFFD).checked_add(Length(1)),
+            Some(Length(0xFFFF_FFFE))
+        );
+    }
+
     #[test]
     fn get_date_value() {
         let data_element: DataElement<_, _> = DataElement::new(
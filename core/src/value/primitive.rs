@@ -170,6 +170,7 @@ pub type C<T> = SmallVec<[T; 2]>;
 /// [`C`]: ./type.C.html
 /// [`dicom_value!`]: ../macro.dicom_value.html
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PrimitiveValue {
     /// No data. Usually employed for zero-lengthed values.
     Empty,
@@ -288,6 +289,76 @@ impl From<&[u8]> for PrimitiveValue {
     }
 }
 
+/// An arbitrary value may be of any variant,
+/// regardless of the value representation it would be written with.
+/// Dates and times are always valid.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for PrimitiveValue {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        fn values<'a, T: arbitrary::Arbitrary<'a>>(
+            u: &mut arbitrary::Unstructured<'a>,
+        ) -> arbitrary::Result<C<T>> {
+            u.arbitrary_iter()?.collect()
+        }
+        fn string(u: &mut arbitrary::Unstructured) -> arbitrary::Result<String> {
+            // character by character, to keep strings short
+            u.arbitrary_iter::<char>()?.collect()
+        }
+        fn values_with<'a, T>(
+            u: &mut arbitrary::Unstructured<'a>,
+            value: fn(&mut arbitrary::Unstructured<'a>) -> arbitrary::Result<T>,
+        ) -> arbitrary::Result<C<T>> {
+            let mut out = C::new();
+            while u.arbitrary()? {
+                out.push(value(u)?);
+            }
+            Ok(out)
+        }
+        fn date(u: &mut arbitrary::Unstructured) -> arbitrary::Result<NaiveDate> {
+            let date = NaiveDate::from_ymd_opt(
+                u.int_in_range(1..=9999)?,
+                u.int_in_range(1..=12)?,
+                u.int_in_range(1..=28)?,
+            );
+            Ok(date.expect("valid date"))
+        }
+        fn time(u: &mut arbitrary::Unstructured) -> arbitrary::Result<NaiveTime> {
+            let time = NaiveTime::from_hms_micro_opt(
+                u.int_in_range(0..=23)?,
+                u.int_in_range(0..=59)?,
+                u.int_in_range(0..=59)?,
+                u.int_in_range(0..=999_999)?,
+            );
+            Ok(time.expect("valid time"))
+        }
+        fn datetime(u: &mut arbitrary::Unstructured) -> arbitrary::Result<DateTime<FixedOffset>> {
+            let offset = FixedOffset::east_opt(u.int_in_range(-12 * 3600..=14 * 3600)?)
+                .expect("valid offset");
+            let naive = date(u)?.and_time(time(u)?);
+            Ok(chrono::TimeZone::from_utc_datetime(&offset, &naive))
+        }
+
+        Ok(match u.int_in_range(0..=15)? {
+            0 => PrimitiveValue::Empty,
+            1 => PrimitiveValue::Strs(values_with(u, string)?),
+            2 => PrimitiveValue::Str(string(u)?),
+            3 => PrimitiveValue::Tags(values(u)?),
+            4 => PrimitiveValue::U8(values(u)?),
+            5 => PrimitiveValue::I16(values(u)?),
+            6 => PrimitiveValue::U16(values(u)?),
+            7 => PrimitiveValue::I32(values(u)?),
+            8 => PrimitiveValue::U32(values(u)?),
+            9 => PrimitiveValue::I64(values(u)?),
+            10 => PrimitiveValue::U64(values(u)?),
+            11 => PrimitiveValue::F32(values(u)?),
+            12 => PrimitiveValue::F64(values(u)?),
+            13 => PrimitiveValue::Date(values_with(u, date)?),
+            14 => PrimitiveValue::Time(values_with(u, time)?),
+            _ => PrimitiveValue::DateTime(values_with(u, datetime)?),
+        })
+    }
+}
+
 macro_rules! impl_from_array_for_primitive {
     ($typ: ty, $variant: ident) => {
         impl From<$typ> for PrimitiveValue {
@@ -0,0 +1,2271 @@
+//! Library for generating DICOM data dictionaries
+//! from the XML documentation of the standard (PS3.6).
+//!
+//! This is used by the `dicom-dictionary-builder` application,
+//! but may also be used programmatically,
+//! such as from a build script.
+//! [`regenerate_into`] covers the full process
+//! of fetching, parsing, and emitting the dictionary.
+//!
+//! [`regenerate_into`]: ./fn.regenerate_into.html
+
+use chrono::Utc;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use futures::{Future, Stream};
+use hyper::client::Client;
+use hyper::client::ResponseFuture;
+use hyper::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
+use hyper::{Body, Chunk, Request, Uri};
+use serde::{Deserialize, Serialize};
+use serde_json::{to_writer, to_writer_pretty};
+use snafu::{ResultExt, Snafu};
+use tokio_core::reactor::Core;
+
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::Error as XmlError;
+use quick_xml::Reader;
+use regex::Regex;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{create_dir_all, File};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// url to PS3.6 XML file
+pub const DEFAULT_LOCATION: &str =
+    "http://dicom.nema.org/medical/dicom/current/source/docbook/part06/part06.xml";
+
+/// Build the URL to the PS3.6 XML file of a specific edition of the standard,
+/// such as `2021e` or `2019`, in place of the current edition
+/// at [`DEFAULT_LOCATION`].
+///
+/// Only the format of the edition is checked here:
+/// a four-digit year, optionally followed by a release letter from `a` to `e`.
+/// Whether that edition was ever published is only known once it is downloaded.
+///
+/// [`DEFAULT_LOCATION`]: ./constant.DEFAULT_LOCATION.html
+pub fn edition_location(edition: &str) -> Result<String, BuilderError> {
+    let valid = match (edition.get(..4), edition.get(4..)) {
+        (Some(year), Some(release)) => {
+            year.bytes().all(|b| b.is_ascii_digit())
+                && matches!(release.as_bytes(), [] | [b'a'..=b'e'])
+        }
+        _ => false,
+    };
+    if !valid {
+        return InvalidEdition { edition }.fail();
+    }
+    Ok(DEFAULT_LOCATION.replace("/current/", &format!("/{}/", edition)))
+}
+
+/// How long a downloaded copy of the source XML is considered fresh.
+const CACHE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The suffix appended to the keyword of a retired attribute
+/// which collides with the keyword of a current attribute.
+pub const RETIRED_ALIAS_SUFFIX: &str = "_Retired";
+
+type XmlResult<T> = Result<T, XmlError>;
+
+/// An error which may occur when building a dictionary.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum BuilderError {
+    #[snafu(display("Invalid source URL: {}", source))]
+    InvalidUrl {
+        source: hyper::http::uri::InvalidUri,
+    },
+    #[snafu(display("Could not start the HTTP client: {}", source))]
+    Runtime { source: std::io::Error },
+    #[snafu(display("Could not download the source XML: {}", source))]
+    Download { source: hyper::Error },
+    #[snafu(display("Could not download `{}`: the server responded with {}", url, status))]
+    HttpStatus {
+        url: String,
+        status: hyper::StatusCode,
+    },
+    #[snafu(display(
+        "Invalid edition `{}`, expected a year with an optional release letter (e.g. `2021e`)",
+        edition
+    ))]
+    InvalidEdition { edition: String },
+    #[snafu(display("Unsupported content encoding `{}`", encoding))]
+    UnsupportedEncoding { encoding: String },
+    #[snafu(display("Could not decode the `{}` encoded source XML: {}", encoding, source))]
+    DecodeContent {
+        encoding: String,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not read the source XML `{}`: {}", path.display(), source))]
+    ReadSource {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not parse the source XML: {}", error))]
+    ParseXml { error: XmlError },
+    #[snafu(display(
+        "Could not parse the private tag CSV `{}` at line {}: {}",
+        path.display(),
+        line,
+        message
+    ))]
+    ParsePrivateCsv {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    #[snafu(display("Could not write to `{}`: {}", path.display(), source))]
+    WriteOutput {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// The kind of dictionary output.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// Rust source code with a constant array of entries
+    Rs,
+    /// JSON document with build information and a map of entries
+    Json,
+    /// JSON document like `Json`,
+    /// but following the [`JsonSchema::V2`] entry schema
+    ///
+    /// [`JsonSchema::V2`]: ./enum.JsonSchema.html#variant.V2
+    JsonV2,
+}
+
+/// The schema of entries in the JSON output.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum JsonSchema {
+    /// Each entry holds the columns of the table as text,
+    /// including the VR column (e.g. `"US or SS"`).
+    V1,
+    /// The VR column is split into a list of alternatives
+    /// (e.g. `["US", "SS"]`),
+    /// with `vr_note` describing what the choice depends on
+    /// whenever it is known.
+    /// The keyword of the attribute is under `keyword`
+    /// instead of `alias`, as in PS3.6 and DICOMweb.
+    V2,
+}
+
+/// The layout of entries in the JSON output.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum JsonLayout {
+    /// The entries are in a map keyed by tag.
+    ByTag,
+    /// The entries are grouped in lists keyed by their primary VR,
+    /// the first of its alternatives (e.g. `"US"` for `US or SS`),
+    /// each list ordered by tag.
+    /// Entries without a VR, such as those of VR `See Note`,
+    /// are under the empty key.
+    ByVr,
+}
+
+/// A summary of a complete dictionary build.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Stats {
+    /// the number of entries written to the output
+    pub entries: usize,
+    /// whether the source XML was taken from a local copy
+    /// instead of being downloaded
+    pub cached: bool,
+}
+
+/// Fetch the dictionary from the given source,
+/// parse it, and write it to the file at `out`.
+///
+/// `src` may be a URL or a local file path.
+/// A downloaded copy of the XML is kept next to the output file,
+/// at [`cache_location`],
+/// and reused for subsequent builds from the same URL
+/// for as long as it is fresh.
+///
+/// [`cache_location`]: ./fn.cache_location.html
+pub fn regenerate_into(
+    src: &str,
+    out: &Path,
+    format: Format,
+    include_retired: bool,
+) -> Result<Stats, BuilderError> {
+    let (xml, cached) = if is_url(src) {
+        let cache_path = cache_location(src, out);
+        if is_fresh(&cache_path) {
+            let xml = std::fs::read(&cache_path).context(ReadSource { path: &cache_path })?;
+            (xml, true)
+        } else {
+            let xml = download(src)?;
+            if let Some(p_dir) = cache_path.parent() {
+                create_dir_all(p_dir).context(WriteOutput { path: &cache_path })?;
+            }
+            std::fs::write(&cache_path, &xml).context(WriteOutput { path: &cache_path })?;
+            (xml, false)
+        }
+    } else {
+        let xml = std::fs::read(src).context(ReadSource { path: src })?;
+        (xml, true)
+    };
+
+    let entries = XmlEntryIterator::with_trim(&xml[..], true)
+        .collect::<XmlResult<Vec<_>>>()
+        .map_err(|error| BuilderError::ParseXml { error })?;
+    let info = BuildInfo::new(src);
+    let entries = match format {
+        Format::Rs => to_code_file(out, entries, include_retired, &info),
+        Format::Json => to_json_file(out, entries, include_retired, &info),
+        Format::JsonV2 => {
+            to_json_file_with_schema(out, entries, include_retired, &info, JsonSchema::V2, false)
+        }
+    }
+    .context(WriteOutput { path: out })?;
+
+    Ok(Stats { entries, cached })
+}
+
+/// Obtain the path where [`regenerate_into`] keeps
+/// the copy of the XML downloaded from `src`
+/// for a dictionary written to `out`.
+///
+/// The file is next to the output file,
+/// named after it and after the source URL,
+/// so that sources of different editions are kept apart.
+///
+/// [`regenerate_into`]: ./fn.regenerate_into.html
+pub fn cache_location(src: &str, out: &Path) -> PathBuf {
+    let source: String = src
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let stem = out
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    out.with_file_name(format!("{}.{}.xml", stem, source))
+}
+
+/// Check whether the given source is a URL to be downloaded.
+pub fn is_url(src: &str) -> bool {
+    src.starts_with("http:") || src.starts_with("https:")
+}
+
+/// Check whether a previously downloaded file exists and is recent enough.
+fn is_fresh(path: &Path) -> bool {
+    path.metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .map(|age| age < CACHE_MAX_AGE)
+        .unwrap_or(false)
+}
+
+/// Download the full contents of the file at the given URL.
+///
+/// The server may compress the file with `gzip` or `deflate`,
+/// as advertised in the `Content-Encoding` header,
+/// in which case the contents are decompressed.
+/// A response with an unsuccessful status,
+/// such as `404 Not Found` for an edition which does not exist,
+/// is reported as [`BuilderError::HttpStatus`].
+///
+/// [`BuilderError::HttpStatus`]: ./enum.BuilderError.html#variant.HttpStatus
+pub fn download(url: &str) -> Result<Vec<u8>, BuilderError> {
+    let uri = Uri::from_str(url).context(InvalidUrl)?;
+    let mut core = Core::new().context(Runtime)?;
+    let req = xml_from_site(uri).and_then(|resp| {
+        let status = resp.status();
+        let encoding = resp
+            .headers()
+            .get(CONTENT_ENCODING)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+        resp.into_body()
+            .concat2()
+            .map(move |body| (status, encoding, body))
+    });
+    let (status, encoding, body): (_, Option<String>, Chunk) = core.run(req).context(Download)?;
+    if !status.is_success() {
+        return HttpStatus { url, status }.fail();
+    }
+    match encoding {
+        Some(encoding) => decode_content(&encoding, body.to_vec()),
+        None => Ok(body.to_vec()),
+    }
+}
+
+fn xml_from_site(url: Uri) -> ResponseFuture {
+    let client = Client::new();
+    let req = Request::get(url)
+        .header(ACCEPT_ENCODING, "gzip, deflate")
+        .body(Body::empty())
+        .expect("valid request");
+    client.request(req)
+}
+
+/// Decode a body with the given `Content-Encoding` header value.
+///
+/// Multiple encodings are undone in reverse order of application.
+pub fn decode_content(encoding: &str, mut body: Vec<u8>) -> Result<Vec<u8>, BuilderError> {
+    for coding in encoding.rsplit(',').map(str::trim) {
+        let mut decoded = Vec::new();
+        let res = match coding.to_ascii_lowercase().as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => GzDecoder::new(&body[..]).read_to_end(&mut decoded),
+            "deflate" => ZlibDecoder::new(&body[..]).read_to_end(&mut decoded),
+            _ => {
+                return UnsupportedEncoding { encoding: coding }.fail();
+            }
+        };
+        res.context(DecodeContent { encoding: coding })?;
+        body = decoded;
+    }
+    Ok(body)
+}
+
+/// Information about how a dictionary was generated,
+/// recorded in the output for traceability.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct BuildInfo {
+    /// the URL or file path of the source XML
+    source: String,
+    /// the date and time of generation, in RFC 3339 format
+    generated: String,
+}
+
+impl BuildInfo {
+    /// Create the build information for a dictionary
+    /// generated from the given source at this moment.
+    pub fn new(source: impl Into<String>) -> Self {
+        BuildInfo {
+            source: source.into(),
+            generated: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// An attribute entry of the dictionary,
+/// as read from the XML or from a private tag CSV.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    tag: String,
+    name: Option<String>,
+    alias: Option<String>,
+    vr: Option<String>,
+    vm: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    obs: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    private: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl Entry {
+    /// Whether the attribute is a private attribute,
+    /// merged from a private tag CSV.
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
+
+    /// Whether the attribute is marked as retired.
+    pub fn is_retired(&self) -> bool {
+        self.observation() == Observation::Retired
+    }
+
+    /// Interpret the contents of the attribute's observation column.
+    pub fn observation(&self) -> Observation {
+        Observation::from(self.obs.as_deref())
+    }
+
+    /// Obtain the alternative value representations of the attribute,
+    /// as listed in the VR column (e.g. `US or SS`).
+    ///
+    /// The list is empty if the VR is absent
+    /// or only described in a note.
+    pub fn vr_alternatives(&self) -> Vec<&str> {
+        match self.vr.as_deref().map(str::trim) {
+            None | Some("") | Some("See Note") => Vec::new(),
+            Some(vr) => vr.split(" or ").map(str::trim).collect(),
+        }
+    }
+
+    /// Describe what the choice of value representation depends on,
+    /// if the attribute has multiple alternatives
+    /// and the dependency is documented by the standard.
+    pub fn vr_note(&self) -> Option<&'static str> {
+        if self.vr.as_deref().map(str::trim) == Some("See Note") {
+            return Some("see note in PS3.6");
+        }
+        let vrs = self.vr_alternatives();
+        if vrs.contains(&"US") && vrs.contains(&"SS") {
+            Some("depends on (0028,0103)")
+        } else if vrs.contains(&"OB") && vrs.contains(&"OW") {
+            Some("depends on the transfer syntax")
+        } else {
+            None
+        }
+    }
+}
+
+/// Resolve keyword collisions between retired and current attributes,
+/// returning the colliding keywords in alphabetical order.
+///
+/// The current attribute keeps the keyword,
+/// so that looking up an attribute by keyword is not ambiguous,
+/// whereas the keyword of each colliding retired attribute
+/// gains the suffix [`RETIRED_ALIAS_SUFFIX`].
+///
+/// [`RETIRED_ALIAS_SUFFIX`]: ./constant.RETIRED_ALIAS_SUFFIX.html
+pub fn resolve_alias_collisions(entries: &mut [Entry]) -> Vec<String> {
+    let current: BTreeSet<String> = entries
+        .iter()
+        .filter(|e| !e.is_retired())
+        .filter_map(|e| e.alias.clone())
+        .collect();
+
+    let mut collisions = BTreeSet::new();
+    for e in entries.iter_mut().filter(|e| e.is_retired()) {
+        if let Some(alias) = &mut e.alias {
+            if current.contains(alias.as_str()) {
+                collisions.insert(alias.clone());
+                alias.push_str(RETIRED_ALIAS_SUFFIX);
+            }
+        }
+    }
+    collisions.into_iter().collect()
+}
+
+/// Read private attribute entries from a CSV file,
+/// with the columns `group, element, creator, keyword, VR, VM`.
+///
+/// A header line, empty lines, and lines starting with `#` are skipped.
+/// Fields may be enclosed in double quotes.
+/// The group and element are given as four hexadecimal digits each,
+/// the group number being odd,
+/// and each tag may only appear once for the same private creator.
+/// The entries are marked as private,
+/// and sorted by tag, then by creator, for [`merge_private_entries`].
+///
+/// [`merge_private_entries`]: ./fn.merge_private_entries.html
+pub fn read_private_csv<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>, BuilderError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).context(ReadSource { path })?;
+    let regex_hex = Regex::new(r"^(0[xX])?([0-9A-Fa-f]{4})$").expect("valid regex");
+
+    let mut entries: BTreeMap<(String, Option<String>), Entry> = BTreeMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let fail = |message: &'static str| ParsePrivateCsv {
+            path,
+            line: i + 1,
+            message,
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        if fields.len() != 6 {
+            return fail("expected 6 fields").fail();
+        }
+        let (group, element) = match (
+            regex_hex.captures(&fields[0]),
+            regex_hex.captures(&fields[1]),
+        ) {
+            (Some(group), Some(element)) => (group[2].to_uppercase(), element[2].to_uppercase()),
+            _ if i == 0 => continue, // header line
+            _ => return fail("invalid group or element").fail(),
+        };
+        if u16::from_str_radix(&group, 16).unwrap() % 2 == 0 {
+            return fail("not a private group").fail();
+        }
+        if fields[4].len() != 2 || !fields[4].chars().all(|c| c.is_ascii_uppercase()) {
+            return fail("invalid VR").fail();
+        }
+
+        let non_empty = |field: &str| Some(field.to_string()).filter(|f| !f.is_empty());
+        let key = (format!("({},{})", group, element), non_empty(&fields[2]));
+        if entries.contains_key(&key) {
+            return fail("duplicate tag of the same creator").fail();
+        }
+        let entry = Entry {
+            tag: key.0.clone(),
+            name: None,
+            alias: non_empty(&fields[3]),
+            vr: non_empty(&fields[4]),
+            vm: non_empty(&fields[5]),
+            obs: None,
+            creator: key.1.clone(),
+            private: true,
+        };
+        entries.insert(key, entry);
+    }
+    Ok(entries.into_values().collect())
+}
+
+/// Split a line of comma separated values,
+/// removing the double quotes around fields and the whitespace around them.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields.iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Merge private attribute entries sorted by tag
+/// into a sequence of standard entries,
+/// so that each private entry goes before the first entry
+/// with a higher tag.
+///
+/// The order of the standard entries is preserved.
+pub fn merge_private_entries<I>(entries: I, private: Vec<Entry>) -> Vec<Entry>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    let mut private = private.into_iter().peekable();
+    let mut out = Vec::new();
+    for e in entries {
+        while let Some(p) = private.next_if(|p| p.tag < e.tag) {
+            out.push(p);
+        }
+        out.push(e);
+    }
+    out.extend(private);
+    out
+}
+
+/// List the XML files in a directory holding the standard in parts,
+/// sorted by file name.
+///
+/// Only the files with the extension `xml` are listed,
+/// without descending into subdirectories.
+pub fn xml_parts<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>, BuilderError> {
+    let dir = dir.as_ref();
+    let mut parts = Vec::new();
+    for entry in std::fs::read_dir(dir).context(ReadSource { path: dir })? {
+        let path = entry.context(ReadSource { path: dir })?.path();
+        if path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some("xml") {
+            parts.push(path);
+        }
+    }
+    parts.sort();
+    Ok(parts)
+}
+
+/// Merge the entries read from several parts of the standard,
+/// sorted by tag.
+///
+/// When more than one entry has the same tag,
+/// a current attribute is preferred over a retired one,
+/// and otherwise the first of them is kept.
+pub fn merge_entries<I>(entries: I) -> Vec<Entry>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    let mut merged: BTreeMap<String, Entry> = BTreeMap::new();
+    for e in entries {
+        match merged.get(&e.tag) {
+            Some(kept) if !kept.is_retired() || e.is_retired() => {}
+            _ => {
+                merged.insert(e.tag.clone(), e);
+            }
+        }
+    }
+    merged.into_values().collect()
+}
+
+/// Print a warning about the given keyword collisions, if any.
+fn warn_alias_collisions(collisions: &[String]) {
+    if !collisions.is_empty() {
+        eprintln!(
+            "warning: retired attributes renamed to `<keyword>{}` \
+             for sharing a keyword with a current attribute: {}",
+            RETIRED_ALIAS_SUFFIX,
+            collisions.join(", ")
+        );
+    }
+}
+
+/// The observation of an attribute in the dictionary.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Observation {
+    /// No observation.
+    None,
+    /// The attribute is retired,
+    /// possibly with additional remarks (e.g. `RET (2004)`).
+    Retired,
+    /// Some other annotation.
+    Other(String),
+}
+
+impl From<Option<&str>> for Observation {
+    fn from(obs: Option<&str>) -> Self {
+        match obs.map(str::trim) {
+            None | Some("") => Observation::None,
+            Some(s) if s.starts_with("RET") => Observation::Retired,
+            Some(s) => Observation::Other(s.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum XmlReadingState {
+    Off,
+    InTableHead,
+    InTable,
+    InCellTag,
+    InCellName,
+    InCellKeyword,
+    InCellVR,
+    InCellVM,
+    InCellObs,
+    InCellUnknown,
+}
+
+/// The `xml:id` of the table of data elements in PS3.6.
+pub const DATA_ELEMENT_TABLE_ID: &str = "table_6-1";
+
+/// An iterator of attribute entries from the XML of the standard.
+///
+/// The entries are read from the table with the configured `xml:id`
+/// ([`DATA_ELEMENT_TABLE_ID`] by default).
+/// Tables whose id merely starts with the configured one
+/// (such as `table_6-1a`) are also considered,
+/// but a table with the exact id is always preferred,
+/// and otherwise the first of them is read.
+/// When more than one table matches,
+/// a warning names the tables which were ignored.
+/// Reading stops at the end of the table with the exact id,
+/// so the rest of the document is not read.
+///
+/// All the bodies of the table are read,
+/// and only the rows starting with a parenthesized tag become entries,
+/// so that header rows of column labels are skipped
+/// even when they are not in a `thead`.
+///
+/// [`DATA_ELEMENT_TABLE_ID`]: ./constant.DATA_ELEMENT_TABLE_ID.html
+pub struct XmlEntryIterator<R: BufRead> {
+    /// the XML reader, only absent if a reset failed
+    parser: Option<Reader<R>>,
+    /// whether the XML reader trims the whitespace around text
+    trim: bool,
+    /// the `xml:id` of the table to read
+    table_id: String,
+    /// the `xml:id` of the table entered, if any
+    entered_table: Option<String>,
+    /// whether the table entered has the exact id
+    exact_match: bool,
+    /// the ids of other matching tables, which were ignored
+    ignored_tables: Vec<String>,
+    /// entries of a table without the exact id,
+    /// held back until the end of the document
+    fallback: Vec<Entry>,
+    /// held back entries to be yielded
+    pending: std::vec::IntoIter<Entry>,
+    /// whether the end of the document was reached
+    finished: bool,
+    buf: Vec<u8>,
+    depth: u32,
+    tag: Option<String>,
+    name: Option<String>,
+    keyword: Option<String>,
+    vr: Option<String>,
+    vm: Option<String>,
+    obs: Option<String>,
+    state: XmlReadingState,
+    /// whether the parser is inside a cell paragraph
+    in_para: bool,
+}
+
+impl<R: BufRead> XmlEntryIterator<R> {
+    /// Create an entry iterator,
+    /// where `trim` defines whether the XML reader
+    /// should trim the whitespace around text.
+    ///
+    /// When this is disabled,
+    /// the values of all columns but the name column are trimmed
+    /// after they are read.
+    pub fn with_trim(xml: R, trim: bool) -> XmlEntryIterator<R> {
+        let mut reader = Reader::from_reader(xml);
+        reader.expand_empty_elements(true).trim_text(trim);
+        XmlEntryIterator {
+            parser: Some(reader),
+            trim,
+            table_id: DATA_ELEMENT_TABLE_ID.to_string(),
+            entered_table: None,
+            exact_match: false,
+            ignored_tables: Vec::new(),
+            fallback: Vec::new(),
+            pending: Vec::new().into_iter(),
+            finished: false,
+            buf: Vec::new(),
+            depth: 0,
+            tag: None,
+            name: None,
+            keyword: None,
+            vr: None,
+            vm: None,
+            obs: None,
+            state: XmlReadingState::Off,
+            in_para: false,
+        }
+    }
+
+    /// Read the entries from the table with the given `xml:id`
+    /// instead of [`DATA_ELEMENT_TABLE_ID`].
+    ///
+    /// [`DATA_ELEMENT_TABLE_ID`]: ./constant.DATA_ELEMENT_TABLE_ID.html
+    pub fn with_table_id(mut self, table_id: impl Into<String>) -> Self {
+        self.table_id = table_id.into();
+        self
+    }
+
+    /// The `xml:id` of the table which the entries are read from,
+    /// once it was entered.
+    pub fn entered_table(&self) -> Option<&str> {
+        self.entered_table.as_deref()
+    }
+
+    /// Decide whether to read the table with the given `xml:id`,
+    /// returning true if the table was entered.
+    fn enter_table(&mut self, id: String) -> bool {
+        if !id.starts_with(&self.table_id) {
+            return false;
+        }
+        let exact = id == self.table_id;
+        match self.entered_table.take() {
+            // better than the table read before, which is dropped
+            Some(previous) if exact && !self.exact_match => {
+                self.ignored_tables.push(previous);
+                self.fallback.clear();
+            }
+            Some(previous) => {
+                self.entered_table = Some(previous);
+                self.ignored_tables.push(id);
+                return false;
+            }
+            None => {}
+        }
+        self.entered_table = Some(id);
+        self.exact_match = exact;
+        true
+    }
+
+    /// Print a warning about the other tables matching the table id, if any.
+    fn warn_ignored_tables(&self) {
+        if let (Some(table), false) = (&self.entered_table, self.ignored_tables.is_empty()) {
+            eprintln!(
+                "warning: several tables match `{}`, read `{}` and ignored: {}",
+                self.table_id,
+                table,
+                self.ignored_tables.join(", ")
+            );
+        }
+    }
+}
+
+impl<R: BufRead + Seek> XmlEntryIterator<R> {
+    /// Rewind the source to the start
+    /// and reinitialize the iterator,
+    /// so that the entries can be read again
+    /// without obtaining the source a second time.
+    ///
+    /// If seeking fails, the error is returned
+    /// and the iterator yields no more entries.
+    pub fn reset(&mut self) -> std::io::Result<()> {
+        if let Some(parser) = self.parser.take() {
+            let mut xml = parser.into_underlying_reader();
+            xml.seek(SeekFrom::Start(0))?;
+            let table_id = std::mem::take(&mut self.table_id);
+            *self = XmlEntryIterator::with_trim(xml, self.trim).with_table_id(table_id);
+        }
+        Ok(())
+    }
+}
+
+/// Decode a piece of a cell's text,
+/// resolving entities (e.g. `&amp;`) and character references (e.g. `&#38;`),
+/// and removing the zero width spaces which break up keywords.
+///
+/// This applies to the text of every column alike,
+/// before it is appended to the cell.
+fn sanitize<R: BufRead>(data: &BytesText, parser: &Reader<R>) -> XmlResult<String> {
+    Ok(data.unescape_and_decode(parser)?.replace("\u{200b}", ""))
+}
+
+/// Remove surrounding whitespace from a cell's text.
+fn trimmed(text: Option<String>) -> Option<String> {
+    text.map(|s| s.trim().to_string())
+}
+
+/// Check whether the text of a row's first cell is a parenthesized tag,
+/// such as `(0010,0010)` or `(60xx,3000)`.
+fn is_tag_cell(text: &str) -> bool {
+    text.starts_with('(') && text.ends_with(')') && text.contains(',')
+}
+
+/// Obtain the `xml:id` attribute of an XML element, if any.
+fn xml_id(e: &BytesStart) -> XmlResult<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr?;
+        if attr.key == b"xml:id" {
+            return Ok(Some(String::from_utf8_lossy(&attr.value).into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Append a piece of text to a cell's contents.
+fn push_text(cell: &mut Option<String>, text: &str) {
+    cell.get_or_insert_with(String::new).push_str(text);
+}
+
+impl<R: BufRead> Iterator for XmlEntryIterator<R> {
+    type Item = XmlResult<Entry>;
+    fn next(&mut self) -> Option<XmlResult<Entry>> {
+        if self.finished {
+            return self.pending.next().map(Ok);
+        }
+        loop {
+            let parser = self.parser.as_mut()?;
+            self.buf.clear();
+            let res = parser.read_event(&mut self.buf);
+            match res {
+                Ok(Event::Start(ref e)) => {
+                    self.depth += 1;
+                    let local_name = e.local_name();
+                    if local_name == b"para" {
+                        self.in_para = true;
+                    }
+                    match self.state {
+                        XmlReadingState::Off if local_name == b"table" => {
+                            // check the table id (e.g. xml:id="table_6-1")
+                            match xml_id(e) {
+                                Ok(Some(id)) => {
+                                    if self.enter_table(id) {
+                                        // entered the table!
+                                        self.state = XmlReadingState::InTableHead;
+                                    }
+                                }
+                                Ok(None) => {}
+                                Err(err) => return Some(Err(err)),
+                            }
+                        }
+                        XmlReadingState::InTableHead if local_name == b"tbody" => {
+                            self.state = XmlReadingState::InTable;
+                        }
+                        XmlReadingState::InTable if local_name == b"para" => {
+                            self.state = XmlReadingState::InCellTag;
+                        }
+                        XmlReadingState::InCellTag if local_name == b"para" => {
+                            self.state = XmlReadingState::InCellName;
+                        }
+                        XmlReadingState::InCellName if local_name == b"para" => {
+                            self.state = XmlReadingState::InCellKeyword;
+                        }
+                        XmlReadingState::InCellKeyword if local_name == b"para" => {
+                            self.state = XmlReadingState::InCellVR;
+                        }
+                        XmlReadingState::InCellVR if local_name == b"para" => {
+                            self.state = XmlReadingState::InCellVM;
+                        }
+                        XmlReadingState::InCellVM if local_name == b"para" => {
+                            self.state = XmlReadingState::InCellObs;
+                        }
+                        XmlReadingState::InCellObs if local_name == b"para" => {
+                            self.state = XmlReadingState::InCellUnknown;
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::End(ref e)) => {
+                    self.depth -= 1;
+                    let local_name = e.local_name();
+                    if local_name == b"para" {
+                        self.in_para = false;
+                    }
+                    match self.state {
+                        XmlReadingState::Off => {
+                            // do nothing
+                        }
+                        _e => {
+                            if local_name == b"tr" && self.tag.is_some() {
+                                let tag = trimmed(self.tag.take()).unwrap();
+                                let out = Entry {
+                                    tag,
+                                    name: self.name.take(),
+                                    alias: trimmed(self.keyword.take()),
+                                    vr: trimmed(self.vr.take()),
+                                    vm: trimmed(self.vm.take()),
+                                    obs: trimmed(self.obs.take()),
+                                    creator: None,
+                                    private: false,
+                                };
+                                self.state = XmlReadingState::InTable;
+                                // not an attribute, such as a row of column labels
+                                if !is_tag_cell(&out.tag) {
+                                    continue;
+                                }
+                                if self.exact_match {
+                                    return Some(Ok(out));
+                                }
+                                self.fallback.push(out);
+                            } else if local_name == b"tbody" {
+                                // the body ended, but another one may follow
+                                self.state = XmlReadingState::InTableHead;
+                            } else if local_name == b"table" {
+                                // the table ended!
+                                if self.exact_match {
+                                    break;
+                                }
+                                // keep looking for a table with the exact id
+                                self.state = XmlReadingState::Off;
+                            }
+                        }
+                    }
+                }
+                Ok(Event::Text(data)) if self.in_para => {
+                    let data = match sanitize(&data, parser) {
+                        Ok(data) => data,
+                        Err(e) => return Some(Err(e)),
+                    };
+                    match self.state {
+                        XmlReadingState::InCellTag => push_text(&mut self.tag, &data),
+                        XmlReadingState::InCellName => push_text(&mut self.name, &data),
+                        XmlReadingState::InCellKeyword => push_text(&mut self.keyword, &data),
+                        XmlReadingState::InCellVR => push_text(&mut self.vr, &data),
+                        XmlReadingState::InCellVM => push_text(&mut self.vm, &data),
+                        XmlReadingState::InCellObs => push_text(&mut self.obs, &data),
+                        _ => {}
+                    }
+                }
+                Ok(Event::Eof { .. }) => {
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        self.finished = true;
+        self.warn_ignored_tables();
+        self.pending = std::mem::take(&mut self.fallback).into_iter();
+        self.pending.next().map(Ok)
+    }
+}
+
+/// Write the entries to a Rust source file,
+/// returning the number of entries written.
+pub fn to_code_file<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    to_code_file_with_packed_tags(dest_path, entries, include_retired, info, false)
+}
+
+/// Write the entries to a Rust source file,
+/// returning the number of entries written.
+///
+/// If `packed_tags` is true,
+/// the file also gets a `TAGS_PACKED` array
+/// with the tag of each entry packed into a `u32`
+/// (group in the upper 16 bits, element in the lower 16 bits),
+/// sorted for binary search,
+/// and a parallel `TAGS_PACKED_ENTRIES` array
+/// with the position of the corresponding entry in `ENTRIES`.
+/// Entries with a tag range are left out of these arrays.
+pub fn to_code_file_with_packed_tags<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+    packed_tags: bool,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    write_code_file(
+        dest_path,
+        entries,
+        include_retired,
+        info,
+        packed_tags,
+        false,
+    )
+}
+
+/// Write the entries to a Rust source file
+/// which only depends on `core`,
+/// returning the number of entries written.
+///
+/// Instead of using the types in `dicom_core`,
+/// the file declares its own `TagRange`, `Vm` and `Entry` types,
+/// with tags as pairs of numbers and value representations as text
+/// (e.g. `"PN"`),
+/// so that it can be built into a `#![no_std]` crate
+/// without an allocator.
+/// All items are constants.
+/// `packed_tags` works as in [`to_code_file_with_packed_tags`].
+///
+/// [`to_code_file_with_packed_tags`]: ./fn.to_code_file_with_packed_tags.html
+pub fn to_no_std_code_file<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+    packed_tags: bool,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    write_code_file(dest_path, entries, include_retired, info, packed_tags, true)
+}
+
+/// The declarations at the top of a Rust output
+/// which only depends on `core`.
+const NO_STD_PRELUDE: &[u8] = b"//!
+//! This file only depends on `core`.
+
+/// The tag or range of tags of a dictionary entry,
+/// as (group, element) pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagRange {
+    /// A single tag.
+    Single(u16, u16),
+    /// A range of tags over groups, `(ggxx,eeee)`, given by its first tag.
+    Group100(u16, u16),
+    /// A range of tags over elements, `(gggg,eexx)`, given by its first tag.
+    Element100(u16, u16),
+}
+
+/// The value multiplicity of a dictionary entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vm {
+    /// From the first to the second number of values, inclusive.
+    Bounded(u32, u32),
+    /// At least the given number of values.
+    Unbounded(u32),
+    /// A non-zero multiple of the given number of values.
+    Multiple(u32),
+}
+
+/// An entry of the data dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// The tag or range of tags of the attribute.
+    pub tag: TagRange,
+    /// The keyword of the attribute.
+    pub alias: &'static str,
+    /// The value representation (e.g. `\"PN\"`).
+    pub vr: &'static str,
+    /// The alternative value representation, if any.
+    pub vr2: Option<&'static str>,
+    /// The value multiplicity, if known.
+    pub vm: Option<Vm>,
+    /// The display name (e.g. `\"Patient's Name\"`), if known.
+    pub name: Option<&'static str>,
+}
+
+use self::TagRange::*;
+
+type E = Entry;
+";
+
+/// The declarations at the top of a Rust output using `dicom_core`.
+const STD_PRELUDE: &[u8] = b"
+use dicom_core::dictionary::{DictionaryEntryRef, TagRange::*, Vm};
+use dicom_core::Tag;
+use dicom_core::VR::*;
+
+type E = DictionaryEntryRef<'static>;
+";
+
+/// The same as `STD_PRELUDE`,
+/// for entries without any value multiplicity.
+const STD_PRELUDE_WITHOUT_VM: &[u8] = b"
+use dicom_core::dictionary::{DictionaryEntryRef, TagRange::*};
+use dicom_core::Tag;
+use dicom_core::VR::*;
+
+type E = DictionaryEntryRef<'static>;
+";
+
+/// The entries of a Rust output, as lines of code.
+struct CodeEntries {
+    /// the group portion of the tag of each entry (e.g. `0008` or `60xx`)
+    /// and the code of the entry
+    lines: Vec<(String, String)>,
+    /// (packed tag, position in ENTRIES) of each single tag entry
+    packed: Vec<(String, usize)>,
+    /// whether any entry has a value multiplicity
+    has_vm: bool,
+}
+
+impl CodeEntries {
+    /// The declarations to write before the entries,
+    /// so that the output builds without warnings.
+    fn prelude(&self, no_std: bool) -> &'static [u8] {
+        match (no_std, self.has_vm) {
+            (true, _) => NO_STD_PRELUDE,
+            (false, true) => STD_PRELUDE,
+            (false, false) => STD_PRELUDE_WITHOUT_VM,
+        }
+    }
+}
+
+fn write_code_file<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+    packed_tags: bool,
+    no_std: bool,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    if let Some(p_dir) = dest_path.as_ref().parent() {
+        create_dir_all(p_dir)?;
+    }
+    let mut f = File::create(&dest_path)?;
+
+    let code = code_entries(entries, include_retired, no_std);
+    write_code_header(&mut f, include_retired, info)?;
+    f.write_all(code.prelude(no_std))?;
+    f.write_all(b"\n#[rustfmt::skip]\npub const ENTRIES: &[E] = &[\n")?;
+    for (_, line) in &code.lines {
+        writeln!(f, "{}", line)?;
+    }
+    f.write_all(b"];\n")?;
+    write_sort_assertion(&mut f, no_std)?;
+
+    if packed_tags {
+        write_packed_tags(&mut f, code.packed)?;
+    }
+    Ok(code.lines.len())
+}
+
+/// Write the entries to Rust source files in the given directory,
+/// split into `parts` files (`entries_0.rs` and so on)
+/// and a `mod.rs` file declaring the same `ENTRIES` as a single file would,
+/// returning the number of entries written.
+///
+/// The entries keep the order of a single file.
+/// The groups of the entries are divided between the parts,
+/// each part holding about the same number of groups,
+/// so that adding or changing entries in a group
+/// only touches the part holding that group.
+/// A range over groups (e.g. `60xx`) counts as the group it starts at.
+/// The parts are joined into `ENTRIES` with macros,
+/// one for each part,
+/// which are only meant to be used by the `mod.rs` file.
+/// `packed_tags` works as in [`to_code_file_with_packed_tags`],
+/// and `no_std` chooses the output of [`to_no_std_code_file`].
+///
+/// # Panics
+///
+/// Panics if `parts` is zero.
+///
+/// [`to_code_file_with_packed_tags`]: ./fn.to_code_file_with_packed_tags.html
+/// [`to_no_std_code_file`]: ./fn.to_no_std_code_file.html
+pub fn to_split_code_files<P: AsRef<Path>, I>(
+    dest_dir: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+    packed_tags: bool,
+    no_std: bool,
+    parts: usize,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    assert!(
+        parts > 0,
+        "the entries must be split into at least one part"
+    );
+    let dest_dir = dest_dir.as_ref();
+    create_dir_all(dest_dir)?;
+
+    let code = code_entries(entries, include_retired, no_std);
+
+    // the entries are ordered by group,
+    // so each group takes the next position
+    let first_group = |group: &str| group.replace('x', "0");
+    let mut groups: Vec<String> = code.lines.iter().map(|(g, _)| first_group(g)).collect();
+    groups.dedup();
+
+    // the entries of each part, by the position of their group
+    let mut part_lines: Vec<Vec<&str>> = vec![Vec::new(); parts];
+    let mut position = 0;
+    for (group, line) in &code.lines {
+        if groups[position] != first_group(group) {
+            position += 1;
+        }
+        part_lines[position * parts / groups.len()].push(line);
+    }
+
+    for (part, lines) in part_lines.iter().enumerate() {
+        let mut f = File::create(dest_dir.join(format!("entries_{}.rs", part)))?;
+        writeln!(f, "//! Automatically generated. Edit at your own risk.")?;
+        writeln!(f, "//!")?;
+        writeln!(
+            f,
+            "//! Part {} of {} of the dictionary entries.",
+            part, parts
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "/// Append the entries of this part to the given entries,"
+        )?;
+        writeln!(f, "/// then pass them on to the next part.")?;
+        writeln!(f, "#[rustfmt::skip]")?;
+        writeln!(f, "macro_rules! entries_{} {{", part)?;
+        writeln!(f, "    ($($entries:tt)*) => {{")?;
+        if part + 1 < parts {
+            writeln!(f, "        entries_{}! {{", part + 1)?;
+        } else {
+            writeln!(f, "        entries_end! {{")?;
+        }
+        writeln!(f, "            $($entries)*")?;
+        for line in lines {
+            writeln!(f, "        {}", line)?;
+        }
+        writeln!(f, "        }}")?;
+        writeln!(f, "    }};")?;
+        writeln!(f, "}}")?;
+    }
+
+    let mut f = File::create(dest_dir.join("mod.rs"))?;
+    write_code_header(&mut f, include_retired, info)?;
+    f.write_all(code.prelude(no_std))?;
+    writeln!(f)?;
+    for part in 0..parts {
+        writeln!(f, "#[macro_use]")?;
+        writeln!(f, "mod entries_{};", part)?;
+    }
+    f.write_all(
+        b"\n\
+    /// Declare `ENTRIES` with the entries of all parts.\n\
+    macro_rules! entries_end {\n\
+    \x20   ($($entries:tt)*) => {\n\
+    \x20       #[rustfmt::skip]\n\
+    \x20       pub const ENTRIES: &[E] = &[$($entries)*];\n\
+    \x20   };\n\
+    }\n\n\
+    entries_0! {}\n",
+    )?;
+    write_sort_assertion(&mut f, no_std)?;
+
+    if packed_tags {
+        write_packed_tags(&mut f, code.packed)?;
+    }
+    Ok(code.lines.len())
+}
+
+/// Write the comments at the top of a Rust output.
+fn write_code_header(f: &mut File, include_retired: bool, info: &BuildInfo) -> std::io::Result<()> {
+    writeln!(f, "//! Automatically generated. Edit at your own risk.")?;
+    writeln!(f, "//!")?;
+    writeln!(f, "//! - Source: {}", info.source)?;
+    writeln!(f, "//! - Generated: {}", info.generated)?;
+    writeln!(
+        f,
+        "//! - Retired attributes: {}",
+        if include_retired {
+            "included"
+        } else {
+            "excluded"
+        }
+    )
+}
+
+/// Write a constant item to a Rust output
+/// which fails to build unless `ENTRIES` is sorted
+/// as by [`TagPatterns::sort_key`].
+fn write_sort_assertion(f: &mut File, no_std: bool) -> std::io::Result<()> {
+    let (single, group100, element100) = if no_std {
+        (
+            "Single(g, e) => (g, e, 0)",
+            "Group100(g, e) => (g, e, 1)",
+            "Element100(g, e) => (g, e, 2)",
+        )
+    } else {
+        (
+            "Single(Tag(g, e)) => (g, e, 0)",
+            "Group100(Tag(g, e)) => (g, e, 1)",
+            "Element100(Tag(g, e)) => (g, e, 2)",
+        )
+    };
+    write!(
+        f,
+        "\n\
+    // `ENTRIES` is sorted by the first tag of each entry,\n\
+    // with a single tag before the ranges starting at it\n\
+    const _: () = {{\n\
+    \x20   const fn key(entry: &E) -> u64 {{\n\
+    \x20       let (group, elem, kind) = match entry.tag {{\n\
+    \x20           {},\n\
+    \x20           {},\n\
+    \x20           {},\n\
+    \x20       }};\n\
+    \x20       ((group as u64) << 18) | ((elem as u64) << 2) | kind\n\
+    \x20   }}\n\
+    \x20   let mut i = 1;\n\
+    \x20   while i < ENTRIES.len() {{\n\
+    \x20       assert!(key(&ENTRIES[i - 1]) <= key(&ENTRIES[i]), \"ENTRIES must be sorted by tag\");\n\
+    \x20       i += 1;\n\
+    \x20   }}\n\
+    }};\n",
+        single, group100, element100
+    )
+}
+
+/// Write the `TAGS_PACKED` and `TAGS_PACKED_ENTRIES` arrays
+/// of a Rust output.
+fn write_packed_tags(f: &mut File, mut packed: Vec<(String, usize)>) -> std::io::Result<()> {
+    // hexadecimal numbers of the same width sort like the numbers
+    packed.sort();
+    f.write_all(
+        b"\n\
+    /// The tags of the entries with a single tag,\n\
+    /// packed as with `Tag::to_u32` and sorted.\n\
+    #[rustfmt::skip]\n\
+    pub const TAGS_PACKED: &[u32] = &[\n",
+    )?;
+    for chunk in packed.chunks(8) {
+        let line: Vec<_> = chunk.iter().map(|(tag, _)| tag.as_str()).collect();
+        writeln!(f, "    {},", line.join(", "))?;
+    }
+    f.write_all(
+        b"];\n\n\
+    /// The position in `ENTRIES` of the entry of each tag in `TAGS_PACKED`.\n\
+    #[rustfmt::skip]\n\
+    pub const TAGS_PACKED_ENTRIES: &[usize] = &[\n",
+    )?;
+    for chunk in packed.chunks(8) {
+        let line: Vec<_> = chunk.iter().map(|(_, i)| i.to_string()).collect();
+        writeln!(f, "    {},", line.join(", "))?;
+    }
+    f.write_all(b"];\n")
+}
+
+/// Turn the entries into lines of Rust code,
+/// leaving out those which cannot be written.
+fn code_entries<I>(entries: I, include_retired: bool, no_std: bool) -> CodeEntries
+where
+    I: IntoIterator<Item = Entry>,
+{
+    // tags are spelled out as `Tag` values, unless only `core` is available
+    let tag_code = |group: &str, elem: &str| {
+        if no_std {
+            format!("0x{}, 0x{}", group, elem)
+        } else {
+            format!("Tag(0x{}, 0x{})", group, elem)
+        }
+    };
+
+    let patterns = TagPatterns::new();
+
+    let mut entries: Vec<Entry> = entries
+        .into_iter()
+        .filter(|e| include_retired || !e.is_retired())
+        .collect();
+    warn_alias_collisions(&resolve_alias_collisions(&mut entries));
+    // stable, so that entries with the same tag keep their order
+    entries.sort_by_key(|e| patterns.sort_key(&e.tag));
+
+    let mut lines = Vec::new();
+    // (packed tag, position in ENTRIES) of each single tag entry
+    let mut packed = Vec::new();
+    let mut has_vm = false;
+    for e in entries {
+        let Entry {
+            tag,
+            name,
+            alias,
+            vr,
+            vm,
+            obs,
+            creator,
+            ..
+        } = e;
+
+        // sanitize components
+
+        let alias = if let Some(v) = alias {
+            v
+        } else {
+            continue;
+        };
+
+        let cap = patterns.single.captures(tag.as_str());
+        let tag_txt = if let Some(cap) = cap {
+            // single tag
+            let group = cap.get(1).expect("capture group 1: group").as_str();
+            let elem = cap.get(2).expect("capture group 2: element").as_str();
+            packed.push((format!("0x{}{}", group, elem), lines.len()));
+            format!("Single({})", tag_code(group, elem))
+        } else if let Some(cap) = patterns.group100.captures(tag.as_str()) {
+            // tag range over groups: (ggxx, eeee)
+            let group = cap.get(1).expect("capture group 1: group portion").as_str();
+            let elem = cap.get(2).expect("capture group 2: element").as_str();
+            format!("Group100({})", tag_code(&format!("{}00", group), elem))
+        } else if let Some(cap) = patterns.element100.captures(tag.as_str()) {
+            // tag range over elements: (gggg, eexx)
+            let group = cap.get(1).expect("capture group 1: group").as_str();
+            let elem = cap
+                .get(2)
+                .expect("capture group 2: element portion")
+                .as_str();
+            format!("Element100({})", tag_code(group, &format!("{}00", elem)))
+        } else {
+            continue;
+        };
+
+        let mut vr = vr.unwrap_or_else(|| "".into());
+        if vr == "See Note" {
+            vr = "UN See Note".to_string();
+        }
+
+        let (vr1, vr_rest) = match split_vr(&vr) {
+            Some(split) => split,
+            None => {
+                eprintln!(
+                    "warning: attribute {} ({}) has an invalid VR `{}`, using UN",
+                    tag, alias, vr
+                );
+                ("UN", "")
+            }
+        };
+
+        // the first alternative VR becomes data,
+        // other remarks are kept as a comment
+        let (vr2, remark) = match vr_rest.strip_prefix(" or ").and_then(split_vr) {
+            Some((vr2, others)) => (format!("Some({})", vr_code(vr2, no_std)), others),
+            None => ("None".to_string(), vr_rest),
+        };
+        let vr1 = vr_code(vr1, no_std);
+        let remark = if !remark.is_empty() {
+            format!(" /*{} */", remark)
+        } else {
+            String::new()
+        };
+
+        let vm = match vm.as_deref().map(|v| (v, vm_code(v))) {
+            Some((_, Some(code))) => {
+                has_vm = true;
+                format!("Some({})", code)
+            }
+            Some((v, None)) => {
+                eprintln!(
+                    "warning: attribute {} ({}) has an invalid VM `{}`",
+                    tag, alias, v
+                );
+                "None".to_string()
+            }
+            None => "None".to_string(),
+        };
+
+        let name = match name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => format!("Some({})", string_code(name)),
+            _ => "None".to_string(),
+        };
+
+        let mut obs = obs.unwrap_or_else(String::new);
+        if !obs.is_empty() {
+            obs = format!(" // {}", obs.as_str());
+        } else if let Some(creator) = creator {
+            obs = format!(" // private: {}", creator);
+        }
+
+        lines.push((
+            tag[1..5].to_string(),
+            format!(
+                "    E {{ tag: {}, alias: \"{}\", vr: {}, vr2: {}{}, vm: {}, name: {} }},{}",
+                tag_txt, alias, vr1, vr2, remark, vm, name, obs
+            ),
+        ));
+    }
+    CodeEntries {
+        lines,
+        packed,
+        has_vm,
+    }
+}
+
+/// The patterns of the tags which can be written to the Rust output.
+struct TagPatterns {
+    /// a single tag: `(gggg,eeee)`
+    single: Regex,
+    /// a range of tags over groups: `(ggxx,eeee)`
+    group100: Regex,
+    /// a range of tags over elements: `(gggg,eexx)`
+    element100: Regex,
+}
+
+impl TagPatterns {
+    fn new() -> Self {
+        TagPatterns {
+            single: Regex::new(r"^\(([0-9A-F]{4}),([0-9A-F]{4})\)$").expect("valid regex"),
+            group100: Regex::new(r"^\(([0-9A-F]{2})xx,([0-9A-F]{4})\)$").expect("valid regex"),
+            element100: Regex::new(r"^\(([0-9A-F]{4}),([0-9A-F]{2})xx\)$").expect("valid regex"),
+        }
+    }
+
+    /// Whether the tag can be written to the Rust output.
+    fn is_match(&self, tag: &str) -> bool {
+        self.single.is_match(tag) || self.group100.is_match(tag) || self.element100.is_match(tag)
+    }
+
+    /// The position of an entry with the given tag in the Rust output:
+    /// ordered by the first tag of the range,
+    /// then single tags before ranges over groups
+    /// and ranges over elements,
+    /// or `None` for tags which cannot be written.
+    fn sort_key(&self, tag: &str) -> Option<(u16, u16, u8)> {
+        let number = |text: &str| u16::from_str_radix(text, 16).ok();
+        if let Some(cap) = self.single.captures(tag) {
+            Some((number(&cap[1])?, number(&cap[2])?, 0))
+        } else if let Some(cap) = self.group100.captures(tag) {
+            Some((number(&format!("{}00", &cap[1]))?, number(&cap[2])?, 1))
+        } else if let Some(cap) = self.element100.captures(tag) {
+            Some((number(&cap[1])?, number(&format!("{}00", &cap[2]))?, 2))
+        } else {
+            None
+        }
+    }
+}
+
+/// The code of a value representation in the Rust output:
+/// a `VR` variant, or a string if only `core` is available.
+fn vr_code(vr: &str, no_std: bool) -> String {
+    if no_std {
+        format!("\"{}\"", vr)
+    } else {
+        vr.to_string()
+    }
+}
+
+/// Split the leading value representation from the rest of a VR cell,
+/// if it starts with one.
+fn split_vr(text: &str) -> Option<(&str, &str)> {
+    match text.get(..2) {
+        Some(vr) if vr.bytes().all(|b| b.is_ascii_uppercase()) => Some((vr, &text[2..])),
+        _ => None,
+    }
+}
+
+/// Write text as a Rust string literal,
+/// escaping quotes, backslashes and special characters.
+fn string_code(text: &str) -> String {
+    format!("{:?}", text)
+}
+
+/// Convert a value multiplicity cell (e.g. `1-n`)
+/// to the corresponding `Vm` expression, if it is a valid one.
+fn vm_code(vm: &str) -> Option<String> {
+    let number = |n: &str| n.parse::<u32>().ok();
+    let mut parts = vm.trim().splitn(2, '-');
+    let min = number(parts.next()?)?;
+    match parts.next() {
+        None => Some(format!("Vm::Bounded({}, {})", min, min)),
+        Some("n") => Some(format!("Vm::Unbounded({})", min)),
+        Some(max) => match max.strip_suffix('n') {
+            Some(k) if number(k) == Some(min) && min > 0 => Some(format!("Vm::Multiple({})", min)),
+            Some(_) => None,
+            None => number(max)
+                .filter(|&max| max >= min)
+                .map(|max| format!("Vm::Bounded({}, {})", min, max)),
+        },
+    }
+}
+
+/// Convert an attribute keyword to the name of its tag constant,
+/// in SCREAMING_SNAKE_CASE (e.g. `SOPInstanceUID` to `SOP_INSTANCE_UID`).
+///
+/// Words start at each uppercase letter following a lowercase letter or digit,
+/// and at the last uppercase letter of an acronym followed by lowercase letters,
+/// unless that is a plural `s` (as in `OtherPatientIDs`).
+pub fn tag_constant_name(keyword: &str) -> String {
+    let chars: Vec<char> = keyword.chars().collect();
+    let mut name = String::with_capacity(keyword.len() + 8);
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            let prev = chars[i - 1];
+            let next_lower = matches!(chars.get(i + 1), Some(c) if c.is_ascii_lowercase());
+            let plural = chars.get(i + 1) == Some(&'s')
+                && !matches!(chars.get(i + 2), Some(c) if c.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower && !plural)
+            {
+                name.push('_');
+            }
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+/// Write a Rust source file with a tag constant for each attribute,
+/// returning the number of constants written.
+///
+/// Retired attributes and tag ranges are left out.
+/// Constants are named after the attribute keyword
+/// with [`tag_constant_name`].
+/// When multiple keywords lead to the same constant name,
+/// only the first attribute gets a constant,
+/// and a warning lists the colliding names.
+///
+/// [`tag_constant_name`]: ./fn.tag_constant_name.html
+pub fn to_tags_file<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    info: &BuildInfo,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    if let Some(p_dir) = dest_path.as_ref().parent() {
+        create_dir_all(p_dir)?;
+    }
+    let mut f = File::create(&dest_path)?;
+
+    writeln!(f, "//! Automatically generated. Edit at your own risk.")?;
+    writeln!(f, "//!")?;
+    writeln!(f, "//! - Source: {}", info.source)?;
+    writeln!(f, "//! - Generated: {}", info.generated)?;
+    f.write_all(b"\nuse dicom_core::Tag;\n")?;
+
+    let regex_tag = Regex::new(r"^\(([0-9A-F]{4}),([0-9A-F]{4})\)$").expect("valid regex");
+
+    let mut keywords: BTreeMap<String, String> = BTreeMap::new();
+    let mut collisions = BTreeSet::new();
+    for e in entries.into_iter().filter(|e| !e.is_retired()) {
+        let (alias, cap) = match (&e.alias, regex_tag.captures(&e.tag)) {
+            (Some(alias), Some(cap)) => (alias, cap),
+            _ => continue,
+        };
+        let const_name = tag_constant_name(alias);
+        if let Some(other) = keywords.get(&const_name) {
+            collisions.insert(format!("{} ({} and {})", const_name, other, alias));
+            continue;
+        }
+
+        let group = cap.get(1).expect("capture group 1: group").as_str();
+        let elem = cap.get(2).expect("capture group 2: element").as_str();
+        writeln!(f)?;
+        writeln!(
+            f,
+            "/// {} {}",
+            e.name.as_deref().unwrap_or(alias.as_str()),
+            e.tag
+        )?;
+        writeln!(
+            f,
+            "pub const {}: Tag = Tag(0x{}, 0x{});",
+            const_name, group, elem
+        )?;
+        keywords.insert(const_name, alias.clone());
+    }
+
+    if !collisions.is_empty() {
+        eprintln!(
+            "warning: tag constants skipped for colliding names: {}",
+            collisions.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+    Ok(keywords.len())
+}
+
+/// Write an enum of the current attributes with a single tag
+/// to a Rust source file,
+/// returning the number of attributes written.
+///
+/// Each variant is named after the attribute's keyword,
+/// and the enum gains methods for retrieving the tag and the VR
+/// of each attribute.
+pub fn to_enum_file<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    info: &BuildInfo,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    if let Some(p_dir) = dest_path.as_ref().parent() {
+        create_dir_all(p_dir)?;
+    }
+    let mut f = File::create(&dest_path)?;
+
+    writeln!(f, "//! Automatically generated. Edit at your own risk.")?;
+    writeln!(f, "//!")?;
+    writeln!(f, "//! - Source: {}", info.source)?;
+    writeln!(f, "//! - Generated: {}", info.generated)?;
+    f.write_all(b"\nuse dicom_core::{Tag, VR};\n")?;
+
+    let regex_tag = Regex::new(r"^\(([0-9A-F]{4}),([0-9A-F]{4})\)$").expect("valid regex");
+
+    // (variant, description, tag, VR)
+    let mut variants: Vec<(String, String, String, String)> = Vec::new();
+    let mut keywords: BTreeMap<String, String> = BTreeMap::new();
+    let mut collisions = BTreeSet::new();
+    for e in entries.into_iter().filter(|e| !e.is_retired()) {
+        let (alias, cap) = match (&e.alias, regex_tag.captures(&e.tag)) {
+            (Some(alias), Some(cap)) => (alias, cap),
+            _ => continue,
+        };
+        let variant = match variant_name(alias) {
+            Some(variant) => variant,
+            None => continue,
+        };
+        if let Some(other) = keywords.get(&variant) {
+            collisions.insert(format!("{} ({} and {})", variant, other, alias));
+            continue;
+        }
+
+        let group = cap.get(1).expect("capture group 1: group").as_str();
+        let elem = cap.get(2).expect("capture group 2: element").as_str();
+        let vr =
+            e.vr.as_deref()
+                .and_then(split_vr)
+                .map(|(vr, _)| vr)
+                .unwrap_or("UN");
+        variants.push((
+            variant.clone(),
+            format!("{} {}", e.name.as_deref().unwrap_or(alias.as_str()), e.tag),
+            format!("Tag(0x{}, 0x{})", group, elem),
+            vr.to_string(),
+        ));
+        keywords.insert(variant, alias.clone());
+    }
+
+    writeln!(f)?;
+    writeln!(f, "/// An attribute of the standard with a single tag.")?;
+    writeln!(f, "#[non_exhaustive]")?;
+    writeln!(f, "#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]")?;
+    writeln!(f, "pub enum StandardAttribute {{")?;
+    for (variant, description, _, _) in &variants {
+        writeln!(f, "    /// {}", description)?;
+        writeln!(f, "    {},", variant)?;
+    }
+    writeln!(f, "}}")?;
+
+    writeln!(f)?;
+    writeln!(f, "impl StandardAttribute {{")?;
+    writeln!(f, "    /// The tag of the attribute.")?;
+    writeln!(f, "    pub fn tag(self) -> Tag {{")?;
+    writeln!(f, "        match self {{")?;
+    for (variant, _, tag, _) in &variants {
+        writeln!(f, "            StandardAttribute::{} => {},", variant, tag)?;
+    }
+    writeln!(f, "        }}")?;
+    writeln!(f, "    }}")?;
+    writeln!(f)?;
+    writeln!(
+        f,
+        "    /// The _typical_ value representation of the attribute."
+    )?;
+    writeln!(f, "    pub fn vr(self) -> VR {{")?;
+    writeln!(f, "        match self {{")?;
+    for (variant, _, _, vr) in &variants {
+        writeln!(
+            f,
+            "            StandardAttribute::{} => VR::{},",
+            variant, vr
+        )?;
+    }
+    writeln!(f, "        }}")?;
+    writeln!(f, "    }}")?;
+    writeln!(f, "}}")?;
+
+    if !collisions.is_empty() {
+        eprintln!(
+            "warning: enum variants skipped for colliding names: {}",
+            collisions.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+    Ok(variants.len())
+}
+
+/// Turn an attribute keyword into a valid enum variant name,
+/// dropping any character which cannot be part of an identifier.
+fn variant_name(keyword: &str) -> Option<String> {
+    let name: String = keyword
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    match name.chars().next() {
+        None => None,
+        Some(c) if c.is_ascii_digit() => Some(format!("_{}", name)),
+        Some(_) => Some(name),
+    }
+}
+
+/// The root object of the JSON output.
+#[derive(Debug, Serialize)]
+struct JsonDictionary<'a, E> {
+    meta: &'a BuildInfo,
+    entries: BTreeMap<String, E>,
+}
+
+/// The part of a JSON output read back when appending to it.
+#[derive(Debug, Deserialize)]
+struct StoredDictionary<E> {
+    entries: BTreeMap<String, E>,
+}
+
+/// An entry of a JSON output in the second schema version,
+/// as read back when appending to it.
+#[derive(Debug, Deserialize)]
+struct StoredEntryV2 {
+    tag: String,
+    name: Option<String>,
+    #[serde(rename = "keyword")]
+    alias: Option<String>,
+    vr: Vec<String>,
+    #[serde(default)]
+    vr_note: Option<String>,
+    vm: Option<String>,
+    #[serde(default)]
+    obs: Option<String>,
+    #[serde(default)]
+    creator: Option<String>,
+    #[serde(default)]
+    private: bool,
+}
+
+impl From<StoredEntryV2> for Entry {
+    fn from(e: StoredEntryV2) -> Self {
+        let vr = if !e.vr.is_empty() {
+            Some(e.vr.join(" or "))
+        } else if e.vr_note.as_deref() == Some("see note in PS3.6") {
+            Some("See Note".to_string())
+        } else {
+            None
+        };
+        Entry {
+            tag: e.tag,
+            name: e.name,
+            alias: e.alias,
+            vr,
+            vm: e.vm,
+            obs: e.obs,
+            creator: e.creator,
+            private: e.private,
+        }
+    }
+}
+
+/// An entry of the JSON output in the second schema version.
+#[derive(Debug, Serialize)]
+struct JsonEntryV2<'a> {
+    tag: &'a str,
+    /// the display name of the attribute (e.g. "Patient's Name")
+    name: Option<&'a str>,
+    /// the keyword of the attribute (e.g. "PatientName")
+    #[serde(rename = "keyword")]
+    alias: Option<&'a str>,
+    vr: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vr_note: Option<&'static str>,
+    vm: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    obs: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    creator: Option<&'a str>,
+    #[serde(skip_serializing_if = "is_false")]
+    private: bool,
+}
+
+impl<'a> From<&'a Entry> for JsonEntryV2<'a> {
+    fn from(e: &'a Entry) -> Self {
+        JsonEntryV2 {
+            tag: &e.tag,
+            name: e.name.as_deref(),
+            alias: e.alias.as_deref(),
+            vr: e.vr_alternatives(),
+            vr_note: e.vr_note(),
+            vm: e.vm.as_deref(),
+            obs: e.obs.as_deref(),
+            creator: e.creator.as_deref(),
+            private: e.private,
+        }
+    }
+}
+
+/// Write the entries to a JSON file,
+/// returning the number of entries written.
+///
+/// The entries follow the [`JsonSchema::V1`] schema.
+///
+/// [`JsonSchema::V1`]: ./enum.JsonSchema.html#variant.V1
+pub fn to_json_file<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    to_json_file_with_schema(
+        dest_path,
+        entries,
+        include_retired,
+        info,
+        JsonSchema::V1,
+        false,
+    )
+}
+
+/// Write the entries to a JSON file in the given entry schema,
+/// returning the number of entries written.
+///
+/// If `pretty` is true, the JSON document is indented,
+/// one member per line.
+/// Entries are always ordered by tag.
+pub fn to_json_file_with_schema<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+    schema: JsonSchema,
+    pretty: bool,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    to_json_file_with_layout(
+        dest_path,
+        entries,
+        include_retired,
+        info,
+        schema,
+        JsonLayout::ByTag,
+        pretty,
+    )
+}
+
+/// Write the entries to a JSON file in the given entry schema and layout,
+/// returning the number of entries written.
+///
+/// This is the same as [`to_json_file_with_schema`],
+/// except that the entries may be grouped by VR
+/// instead of keyed by tag.
+///
+/// [`to_json_file_with_schema`]: ./fn.to_json_file_with_schema.html
+pub fn to_json_file_with_layout<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+    schema: JsonSchema,
+    layout: JsonLayout,
+    pretty: bool,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    if let Some(p_dir) = dest_path.as_ref().parent() {
+        create_dir_all(p_dir)?;
+    }
+    let f = File::create(&dest_path)?;
+
+    let entries = json_entries(entries, include_retired);
+    let count = entries.len();
+    write_json_entries(f, entries, info, schema, layout, pretty)?;
+    Ok(count)
+}
+
+/// Merge the entries into an existing JSON file in the given entry schema,
+/// returning the number of entries in the file afterwards.
+///
+/// The entries already in the file are read back,
+/// and the new entries are added to them,
+/// replacing those with the same tag.
+/// A warning lists the tags of the entries replaced by a different entry.
+/// The build information of the file is replaced by `info`.
+/// If the file does not exist yet,
+/// this is the same as [`to_json_file_with_schema`].
+///
+/// [`to_json_file_with_schema`]: ./fn.to_json_file_with_schema.html
+pub fn append_to_json_file<P: AsRef<Path>, I>(
+    dest_path: P,
+    entries: I,
+    include_retired: bool,
+    info: &BuildInfo,
+    schema: JsonSchema,
+    pretty: bool,
+) -> std::io::Result<usize>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    let mut stored = match File::open(&dest_path) {
+        Ok(f) => read_json_entries(BufReader::new(f), schema)?,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+        Err(e) => return Err(e),
+    };
+
+    let mut replaced = Vec::new();
+    for (tag, entry) in json_entries(entries, include_retired) {
+        if matches!(stored.get(&tag), Some(old) if *old != entry) {
+            replaced.push(tag.clone());
+        }
+        stored.insert(tag, entry);
+    }
+    if !replaced.is_empty() {
+        eprintln!(
+            "warning: entries replaced in {}: {}",
+            dest_path.as_ref().display(),
+            replaced.join(", ")
+        );
+    }
+
+    if let Some(p_dir) = dest_path.as_ref().parent() {
+        create_dir_all(p_dir)?;
+    }
+    let f = File::create(&dest_path)?;
+    let count = stored.len();
+    write_json_entries(f, stored, info, schema, JsonLayout::ByTag, pretty)?;
+    Ok(count)
+}
+
+/// Collect the entries to write to a JSON output, by tag.
+fn json_entries<I>(entries: I, include_retired: bool) -> BTreeMap<String, Entry>
+where
+    I: IntoIterator<Item = Entry>,
+{
+    let mut entries: Vec<Entry> = entries
+        .into_iter()
+        .filter(|e| include_retired || !e.is_retired())
+        .collect();
+    warn_alias_collisions(&resolve_alias_collisions(&mut entries));
+
+    entries.into_iter().map(|v| (v.tag.clone(), v)).collect()
+}
+
+/// Read back the entries of a JSON output in the given entry schema.
+fn read_json_entries<R: Read>(
+    reader: R,
+    schema: JsonSchema,
+) -> serde_json::Result<BTreeMap<String, Entry>> {
+    match schema {
+        JsonSchema::V1 => {
+            let dict: StoredDictionary<Entry> = serde_json::from_reader(reader)?;
+            Ok(dict.entries)
+        }
+        JsonSchema::V2 => {
+            let dict: StoredDictionary<StoredEntryV2> = serde_json::from_reader(reader)?;
+            Ok(dict
+                .entries
+                .into_iter()
+                .map(|(tag, e)| (tag, Entry::from(e)))
+                .collect())
+        }
+    }
+}
+
+/// Write the entries as a JSON output in the given entry schema and layout.
+fn write_json_entries(
+    f: File,
+    entries: BTreeMap<String, Entry>,
+    info: &BuildInfo,
+    schema: JsonSchema,
+    layout: JsonLayout,
+    pretty: bool,
+) -> serde_json::Result<()> {
+    match (schema, layout) {
+        (JsonSchema::V1, JsonLayout::ByVr) => write_json(
+            f,
+            &JsonDictionary {
+                meta: info,
+                entries: group_by_vr::<&Entry>(&entries),
+            },
+            pretty,
+        ),
+        (JsonSchema::V2, JsonLayout::ByVr) => write_json(
+            f,
+            &JsonDictionary {
+                meta: info,
+                entries: group_by_vr::<JsonEntryV2>(&entries),
+            },
+            pretty,
+        ),
+        (JsonSchema::V1, JsonLayout::ByTag) => write_json(
+            f,
+            &JsonDictionary {
+                meta: info,
+                entries,
+            },
+            pretty,
+        ),
+        (JsonSchema::V2, JsonLayout::ByTag) => write_json(
+            f,
+            &JsonDictionary {
+                meta: info,
+                entries: entries
+                    .iter()
+                    .map(|(tag, e)| (tag.clone(), JsonEntryV2::from(e)))
+                    .collect(),
+            },
+            pretty,
+        ),
+    }
+}
+
+/// Group the entries by their primary VR, keeping them ordered by tag.
+fn group_by_vr<'a, E>(entries: &'a BTreeMap<String, Entry>) -> BTreeMap<String, Vec<E>>
+where
+    E: From<&'a Entry>,
+{
+    let mut groups: BTreeMap<String, Vec<E>> = BTreeMap::new();
+    for entry in entries.values() {
+        let vr = entry.vr_alternatives().first().copied().unwrap_or("");
+        groups
+            .entry(vr.to_string())
+            .or_default()
+            .push(E::from(entry));
+    }
+    groups
+}
+
+/// Why an entry was left out of the dictionary output.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The attribute is retired and retired attributes were excluded.
+    Retired,
+    /// The attribute has no keyword.
+    /// Only applies to the Rust output.
+    NoAlias,
+    /// The tag is not in a form which can be written as code.
+    /// Only applies to the Rust output.
+    UnparseableTag,
+}
+
+/// An entry left out of the dictionary output,
+/// as listed in the report of skipped entries.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct SkippedEntry {
+    /// the tag of the entry, as written in the source
+    pub tag: String,
+    /// the reason for leaving the entry out
+    pub reason: SkipReason,
+}
+
+/// Determine which of the given entries
+/// would be left out of the dictionary output in the given format, and why.
+///
+/// This follows the same rules as [`to_code_file`] and [`to_json_file`].
+///
+/// [`to_code_file`]: ./fn.to_code_file.html
+/// [`to_json_file`]: ./fn.to_json_file.html
+pub fn skipped_entries<'a, I>(
+    entries: I,
+    include_retired: bool,
+    format: Format,
+) -> Vec<SkippedEntry>
+where
+    I: IntoIterator<Item = &'a Entry>,
+{
+    let patterns = TagPatterns::new();
+    entries
+        .into_iter()
+        .filter_map(|e| {
+            let reason = if !include_retired && e.is_retired() {
+                SkipReason::Retired
+            } else if format != Format::Rs {
+                return None;
+            } else if e.alias.is_none() {
+                SkipReason::NoAlias
+            } else if !patterns.is_match(&e.tag) {
+                SkipReason::UnparseableTag
+            } else {
+                return None;
+            };
+            Some(SkippedEntry {
+                tag: e.tag.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Write a report of skipped entries to a JSON file,
+/// as an array of `{ "tag", "reason" }` records.
+pub fn to_report_file<P: AsRef<Path>>(
+    dest_path: P,
+    skipped: &[SkippedEntry],
+) -> std::io::Result<()> {
+    if let Some(p_dir) = dest_path.as_ref().parent() {
+        create_dir_all(p_dir)?;
+    }
+    let f = File::create(&dest_path)?;
+    write_json(f, &skipped, true)?;
+    Ok(())
+}
+
+/// Run the given jobs, such as the writing of several outputs
+/// from the same entries, on up to the given number of threads.
+///
+/// Each free thread takes the next job in order.
+/// With a single thread, the jobs run in order on the current thread.
+///
+/// # Panics
+///
+/// Panics if any of the jobs panics, once the other threads are done.
+pub fn run_jobs<J>(jobs: Vec<J>, threads: usize)
+where
+    J: FnOnce() + Send,
+{
+    let threads = threads.min(jobs.len());
+    if threads <= 1 {
+        jobs.into_iter().for_each(|job| job());
+        return;
+    }
+    let queue = Mutex::new(jobs.into_iter());
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                // release the queue before running the job
+                let job = queue.lock().unwrap().next();
+                match job {
+                    Some(job) => job(),
+                    None => break,
+                }
+            });
+        }
+    });
+}
+
+/// Serialize the value as JSON, either compact or indented.
+fn write_json<T: Serialize>(f: File, value: &T, pretty: bool) -> serde_json::Result<()> {
+    if pretty {
+        to_writer_pretty(f, value)
+    } else {
+        to_writer(f, value)
+    }
+}
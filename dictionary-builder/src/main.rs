@@ -11,30 +11,18 @@
 //! Please use the `--help` flag for the full usage information.
 
 use clap::{App, Arg};
-use futures::{Future, Stream};
-use hyper::client::Client;
-use hyper::client::ResponseFuture;
-use hyper::{Chunk, Uri};
-use serde::Serialize;
-use serde_json::to_writer;
-use tokio_core::reactor::Core;
+use dicom_dictionary_builder::{
+    append_to_json_file, download, edition_location, is_url, merge_entries, merge_private_entries,
+    read_private_csv, run_jobs, skipped_entries, to_code_file_with_packed_tags, to_enum_file,
+    to_json_file_with_layout, to_no_std_code_file, to_report_file, to_split_code_files,
+    to_tags_file, xml_parts, BuildInfo, Entry, Format, JsonLayout, JsonSchema, XmlEntryIterator,
+    DEFAULT_LOCATION,
+};
 
-use quick_xml::events::attributes::Attribute;
-use quick_xml::events::Event;
 use quick_xml::Error as XmlError;
-use quick_xml::Reader;
-use regex::Regex;
-
-use std::borrow::Cow;
-use std::collections::BTreeMap;
-use std::fs::{create_dir_all, File};
-use std::io::{BufRead, BufReader, Write};
+use std::fs::File;
+use std::io::BufReader;
 use std::path::Path;
-use std::str::FromStr;
-
-/// url to PS3.6 XML file
-const DEFAULT_LOCATION: &str =
-    "http://dicom.nema.org/medical/dicom/current/source/docbook/part06/part06.xml";
 
 fn main() {
     let matches = App::new("DICOM Dictionary Builder")
@@ -44,6 +32,15 @@ fn main() {
                 .default_value(DEFAULT_LOCATION)
                 .help("Where to fetch the dictionary from"),
         )
+        .arg(
+            Arg::with_name("edition")
+                .long("edition")
+                .value_name("YEAR")
+                .help("Fetch the dictionary of a specific edition of the standard (e.g. 2021e)")
+                .takes_value(true)
+                .conflicts_with("FROM")
+                .validator(|v| edition_location(&v).map(|_| ()).map_err(|e| e.to_string())),
+        )
         .arg(
             Arg::with_name("OUTPUT")
                 .short("o")
@@ -61,392 +58,314 @@ fn main() {
                 .possible_value("rs")
                 .possible_value("json"),
         )
+        .arg(
+            Arg::with_name("json-schema")
+                .long("json-schema")
+                .value_name("VERSION")
+                .help("The schema of entries in the JSON output")
+                .takes_value(true)
+                .default_value("v1")
+                .possible_value("v1")
+                .possible_value("v2"),
+        )
+        .arg(
+            Arg::with_name("json-layout")
+                .long("json-layout")
+                .value_name("LAYOUT")
+                .help("Key the JSON entries by tag, or group them by VR")
+                .takes_value(true)
+                .default_value("by-tag")
+                .possible_value("by-tag")
+                .possible_value("by-vr"),
+        )
+        .arg(
+            Arg::with_name("append")
+                .long("append")
+                .help(
+                    "Merge the entries into the existing JSON output; \
+                     with --private and no source, only the private tags are merged",
+                )
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("pretty")
+                .long("pretty")
+                .help("Indent the JSON output")
+                .takes_value(false),
+        )
         .arg(
             Arg::with_name("no-retired")
+                .long("no-retired")
                 .help("Whether to ignore retired tags")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("emit-tags")
+                .long("emit-tags")
+                .help("Also write tag constants to tags.rs, next to the output file")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("emit-enum")
+                .long("emit-enum")
+                .help("Also write an enum of attributes to attributes.rs, next to the output file")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("packed-tags")
+                .long("packed-tags")
+                .help("Also write a sorted array of packed tags to the Rust output")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("no-std")
+                .long("no-std")
+                .help("Write Rust output which only depends on core, for no_std crates")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("split")
+                .long("split")
+                .value_name("N")
+                .help("Split the Rust output into N files in the output directory")
+                .takes_value(true)
+                .validator(|v| match v.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(()),
+                    _ => Err("must be a positive integer".to_string()),
+                }),
+        )
+        .arg(
+            Arg::with_name("no-trim")
+                .long("no-trim")
+                .help("Do not trim whitespace from attribute names")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("private")
+                .long("private")
+                .value_name("CSV")
+                .help("Merge private tags from a CSV file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("report")
+                .long("report")
+                .value_name("PATH")
+                .help("Write a JSON report of the entries left out of the output")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("limit")
+                .long("limit")
+                .value_name("N")
+                .help("Only emit the first N entries")
+                .takes_value(true)
+                .validator(|v| {
+                    v.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|_| "must be a non-negative integer".to_string())
+                }),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .long("threads")
+                .value_name("N")
+                .help("Write the requested outputs on up to N threads")
+                .takes_value(true)
+                .default_value("1")
+                .validator(|v| match v.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(()),
+                    _ => Err("must be a positive integer".to_string()),
+                }),
+        )
         .get_matches();
 
     let format = matches.value_of("FORMAT").unwrap();
+    let schema = match matches.value_of("json-schema").unwrap() {
+        "v2" => JsonSchema::V2,
+        _ => JsonSchema::V1,
+    };
+    let layout = match matches.value_of("json-layout").unwrap() {
+        "by-vr" => JsonLayout::ByVr,
+        _ => JsonLayout::ByTag,
+    };
+    let pretty = matches.is_present("pretty");
+    let append = matches.is_present("append");
+    if append && format != "json" {
+        panic!("--append only applies to the JSON output");
+    }
+    if append && layout != JsonLayout::ByTag {
+        panic!("--append only applies to the JSON output keyed by tag");
+    }
     let ignore_retired = matches.is_present("no-retired");
+    let emit_tags = matches.is_present("emit-tags");
+    let emit_enum = matches.is_present("emit-enum");
+    let packed_tags = matches.is_present("packed-tags");
+    let no_std = matches.is_present("no-std");
+    let split = matches
+        .value_of("split")
+        .map(|v| v.parse::<usize>().unwrap());
+    let trim = !matches.is_present("no-trim");
+    let limit = matches
+        .value_of("limit")
+        .map(|v| v.parse::<usize>().unwrap())
+        .unwrap_or(usize::MAX);
+    let threads = matches.value_of("threads").unwrap().parse().unwrap();
 
     let out_file = matches.value_of("OUTPUT").unwrap_or_else(|| match format {
+        "rs" if split.is_some() => "entries",
         "rs" => "entries.rs",
         "json" => "entries.json",
         _ => "entries",
     });
     let dst = Path::new(out_file);
 
-    let mut core = Core::new().unwrap();
-
-    let src = matches.value_of("FROM").unwrap();
-    if src.starts_with("http:") || src.starts_with("https:") {
-        let src = Uri::from_str(src).unwrap();
-        println!("Downloading DICOM dictionary ...");
-        let req = xml_from_site(src).and_then(|resp| {
-            resp.into_body().concat2().and_then(|body: Chunk| {
-                let xml_entries = XmlEntryIterator::new(&*body).map(|item| item.unwrap());
-                println!("Writing to file ...");
-                match format {
-                    "rs" => to_code_file(dst, xml_entries, !ignore_retired),
-                    "json" => to_json_file(dst, xml_entries),
-                    _ => unreachable!(),
-                }
-                .expect("Failed to write file");
-                Ok(())
-            })
-        });
-        core.run(req).unwrap();
+    let private = matches
+        .value_of("private")
+        .map(|path| read_private_csv(path).expect("Failed to read private tags"));
+
+    let edition_src = matches
+        .value_of("edition")
+        .map(|edition| edition_location(edition).unwrap());
+    let src = edition_src
+        .as_deref()
+        .unwrap_or_else(|| matches.value_of("FROM").unwrap());
+    // with nothing else to read, layer the private tags on the existing output
+    let private_only = append
+        && matches.occurrences_of("FROM") == 0
+        && edition_src.is_none()
+        && matches.is_present("private");
+    let info = if private_only {
+        BuildInfo::new(matches.value_of("private").unwrap())
     } else {
-        // read from File
-        let file = File::open(src).unwrap();
-        let file = BufReader::new(file);
-        let xml_entries = XmlEntryIterator::new(file).map(|item| item.unwrap());
-
-        match format {
-            "rs" => to_code_file(dst, xml_entries, true),
-            "json" => to_json_file(dst, xml_entries),
-            _ => unreachable!(),
+        BuildInfo::new(src)
+    };
+    let report = matches.value_of("report");
+    let write_output = |xml_entries: Vec<Entry>| {
+        let entries: Vec<Entry> = xml_entries
+            .iter()
+            .filter(|e| !ignore_retired || !e.is_retired())
+            .take(limit)
+            .cloned()
+            .collect();
+        let entries = match private {
+            Some(private) => merge_private_entries(entries, private),
+            None => entries,
+        };
+        // the entries are parsed once,
+        // then each output is written from its own copy of them
+        let entries = &entries;
+        let info = &info;
+        let mut jobs: Vec<Box<dyn FnOnce() + Send + '_>> = Vec::new();
+        if let Some(report) = report {
+            let xml_entries = &xml_entries;
+            jobs.push(Box::new(move || {
+                let report_format = match format {
+                    "rs" => Format::Rs,
+                    _ => Format::Json,
+                };
+                let skipped = skipped_entries(xml_entries, !ignore_retired, report_format);
+                to_report_file(report, &skipped).expect("Failed to write report file");
+            }));
         }
-        .expect("Failed to write file");
-    }
-}
-
-type XmlResult<T> = Result<T, XmlError>;
-type DynResult<T> = Result<T, Box<dyn std::error::Error>>;
-
-fn xml_from_site(url: Uri) -> ResponseFuture {
-    let client = Client::new();
-    client.get(url)
-}
-
-#[derive(Debug, PartialEq, Eq, PartialOrd, Clone, Serialize)]
-struct Entry {
-    tag: String,
-    name: Option<String>,
-    alias: Option<String>,
-    vr: Option<String>,
-    vm: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    obs: Option<String>,
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum XmlReadingState {
-    Off,
-    InTableHead,
-    InTable,
-    InCellTag,
-    InCellName,
-    InCellKeyword,
-    InCellVR,
-    InCellVM,
-    InCellObs,
-    InCellUnknown,
-}
-
-struct XmlEntryIterator<R: BufRead> {
-    parser: Reader<R>,
-    buf: Vec<u8>,
-    depth: u32,
-    tag: Option<String>,
-    name: Option<String>,
-    keyword: Option<String>,
-    vr: Option<String>,
-    vm: Option<String>,
-    obs: Option<String>,
-    state: XmlReadingState,
-}
-
-impl<R: BufRead> XmlEntryIterator<R> {
-    pub fn new(xml: R) -> XmlEntryIterator<R> {
-        let mut reader = Reader::from_reader(xml);
-        reader.expand_empty_elements(true).trim_text(true);
-        XmlEntryIterator {
-            parser: reader,
-            buf: Vec::new(),
-            depth: 0,
-            tag: None,
-            name: None,
-            keyword: None,
-            vr: None,
-            vm: None,
-            obs: None,
-            state: XmlReadingState::Off,
+        if emit_tags {
+            jobs.push(Box::new(move || {
+                let tags_dst = dst.with_file_name("tags.rs");
+                to_tags_file(&tags_dst, entries.iter().cloned(), info)
+                    .expect("Failed to write tags file");
+            }));
         }
-    }
-}
-
-impl<R: BufRead> Iterator for XmlEntryIterator<R> {
-    type Item = XmlResult<Entry>;
-    fn next(&mut self) -> Option<XmlResult<Entry>> {
-        loop {
-            self.buf.clear();
-            let res = self.parser.read_event(&mut self.buf);
-            match res {
-                Ok(Event::Start(ref e)) => {
-                    self.depth += 1;
-                    let local_name = e.local_name();
-                    match self.state {
-                        XmlReadingState::Off => {
-                            if local_name == b"table" {
-                                // check for attribute xml:id="table_6-1"
-                                match e.attributes().find(|attr| {
-                                    attr.is_err()
-                                        || attr.as_ref().unwrap()
-                                            == &Attribute {
-                                                key: b"xml:id",
-                                                value: Cow::Borrowed(b"table_6-1"),
-                                            }
-                                }) {
-                                    Some(Ok(_)) => {
-                                        // entered the table!
-                                        self.state = XmlReadingState::InTableHead;
-                                    }
-                                    Some(Err(err)) => return Some(Err(err)),
-                                    None => {}
-                                }
-                            }
-                        }
-                        XmlReadingState::InTableHead => {
-                            if local_name == b"tbody" {
-                                self.state = XmlReadingState::InTable;
-                            }
-                        }
-                        XmlReadingState::InTable => {
-                            if local_name == b"para" {
-                                self.state = XmlReadingState::InCellTag;
-                            }
-                        }
-                        XmlReadingState::InCellTag => {
-                            if local_name == b"para" {
-                                self.state = XmlReadingState::InCellName;
-                            }
-                        }
-                        XmlReadingState::InCellName => {
-                            if local_name == b"para" {
-                                self.state = XmlReadingState::InCellKeyword;
-                            }
-                        }
-                        XmlReadingState::InCellKeyword => {
-                            if local_name == b"para" {
-                                self.state = XmlReadingState::InCellVR;
-                            }
-                        }
-                        XmlReadingState::InCellVR => {
-                            if local_name == b"para" {
-                                self.state = XmlReadingState::InCellVM;
-                            }
-                        }
-                        XmlReadingState::InCellVM => {
-                            if local_name == b"para" {
-                                self.state = XmlReadingState::InCellObs;
-                            }
-                        }
-                        XmlReadingState::InCellObs => {
-                            if local_name == b"para" {
-                                self.state = XmlReadingState::InCellUnknown;
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-                Ok(Event::End(ref e)) => {
-                    self.depth -= 1;
-                    let local_name = e.local_name();
-                    match self.state {
-                        XmlReadingState::Off => {
-                            // do nothing
-                        }
-                        _e => {
-                            if local_name == b"tr" && self.tag.is_some() {
-                                let tag = self.tag.take().unwrap();
-                                let out = Entry {
-                                    tag,
-                                    name: self.name.take(),
-                                    alias: self.keyword.take(),
-                                    vr: self.vr.take(),
-                                    vm: self.vm.take(),
-                                    obs: self.obs.take(),
-                                };
-                                self.state = XmlReadingState::InTable;
-                                return Some(Ok(out));
-                            } else if local_name == b"tbody" {
-                                // the table ended!
-                                break;
-                            }
-                        }
-                    }
+        if emit_enum {
+            jobs.push(Box::new(move || {
+                let enum_dst = dst.with_file_name("attributes.rs");
+                to_enum_file(&enum_dst, entries.iter().cloned(), info)
+                    .expect("Failed to write attributes file");
+            }));
+        }
+        jobs.push(Box::new(move || {
+            let entries = entries.iter().cloned();
+            match format {
+                "rs" if split.is_some() => to_split_code_files(
+                    dst,
+                    entries,
+                    !ignore_retired,
+                    info,
+                    packed_tags,
+                    no_std,
+                    split.unwrap(),
+                ),
+                "rs" if no_std => {
+                    to_no_std_code_file(dst, entries, !ignore_retired, info, packed_tags)
                 }
-                Ok(Event::Text(data)) => match self.state {
-                    XmlReadingState::InCellTag => {
-                        let data = data
-                            .unescape_and_decode(&self.parser)
-                            .unwrap()
-                            .replace("\u{200b}", "");
-                        self.tag = Some(data);
-                    }
-                    XmlReadingState::InCellName => {
-                        let data = data
-                            .unescape_and_decode(&self.parser)
-                            .unwrap()
-                            .replace("\u{200b}", "");
-                        self.name = Some(data);
-                    }
-                    XmlReadingState::InCellKeyword => {
-                        let data = data
-                            .unescape_and_decode(&self.parser)
-                            .unwrap()
-                            .replace("\u{200b}", "");
-                        self.keyword = Some(data);
-                    }
-                    XmlReadingState::InCellVR => {
-                        let data = data
-                            .unescape_and_decode(&self.parser)
-                            .unwrap()
-                            .replace("\u{200b}", "");
-                        self.vr = Some(data);
-                    }
-                    XmlReadingState::InCellVM => {
-                        let data = data
-                            .unescape_and_decode(&self.parser)
-                            .unwrap()
-                            .replace("\u{200b}", "");
-                        self.vm = Some(data);
-                    }
-                    XmlReadingState::InCellObs => {
-                        let data = data
-                            .unescape_and_decode(&self.parser)
-                            .unwrap()
-                            .replace("\u{200b}", "");
-                        self.obs = Some(data);
-                    }
-                    _ => {}
-                },
-                Ok(Event::Eof { .. }) => {
-                    break;
+                "rs" => {
+                    to_code_file_with_packed_tags(dst, entries, !ignore_retired, info, packed_tags)
                 }
-                Ok(_) => {}
-                Err(e) => {
-                    return Some(Err(e));
+                "json" if append => {
+                    append_to_json_file(dst, entries, !ignore_retired, info, schema, pretty)
                 }
+                "json" => to_json_file_with_layout(
+                    dst,
+                    entries,
+                    !ignore_retired,
+                    info,
+                    schema,
+                    layout,
+                    pretty,
+                ),
+                _ => unreachable!(),
             }
+            .expect("Failed to write file");
+        }));
+        run_jobs(jobs, threads);
+    };
+
+    if private_only {
+        write_output(Vec::new());
+    } else if is_url(src) {
+        println!("Downloading DICOM dictionary ...");
+        let body =
+            download(src).unwrap_or_else(|e| panic!("Failed to download the dictionary: {}", e));
+        let xml_entries = XmlEntryIterator::with_trim(&body[..], trim)
+            .filter_map(skip_malformed_text)
+            .collect();
+        println!("Writing to file ...");
+        write_output(xml_entries);
+    } else if Path::new(src).is_dir() {
+        // read from a directory of XML parts
+        let parts = xml_parts(src).unwrap();
+        let mut xml_entries = Vec::new();
+        for part in parts {
+            let file = BufReader::new(File::open(part).unwrap());
+            xml_entries
+                .extend(XmlEntryIterator::with_trim(file, trim).filter_map(skip_malformed_text));
         }
-
-        None
+        write_output(merge_entries(xml_entries));
+    } else {
+        // read from File
+        let file = File::open(src).unwrap();
+        let file = BufReader::new(file);
+        let xml_entries = XmlEntryIterator::with_trim(file, trim)
+            .filter_map(skip_malformed_text)
+            .collect();
+        write_output(xml_entries);
     }
 }
 
-fn to_code_file<P: AsRef<Path>, I>(dest_path: P, entries: I, include_retired: bool) -> DynResult<()>
-where
-    I: IntoIterator<Item = Entry>,
-{
-    if let Some(p_dir) = dest_path.as_ref().parent() {
-        create_dir_all(&p_dir)?;
-    }
-    let mut f = File::create(&dest_path)?;
-
-    f.write_all(
-        b"//! Automatically generated. Edit at your own risk.\n\n\
-    use dicom_core::dictionary::{DictionaryEntryRef, TagRange::*};\n\
-    use dicom_core::Tag;\n\
-    use dicom_core::VR::*;\n\n\
-    type E = DictionaryEntryRef<'static>;\n\n\
-    #[rustfmt::skip]\n\
-    pub const ENTRIES: &[E] = &[\n",
-    )?;
-
-    let regex_tag = Regex::new(r"^\(([0-9A-F]{4}),([0-9A-F]{4})\)$")?;
-    let regex_tag_group100 = Regex::new(r"^\(([0-9A-F]{2})xx,([0-9A-F]{4})\)$")?;
-    let regex_tag_element100 = Regex::new(r"^\(([0-9A-F]{4}),([0-9A-F]{2})xx\)$")?;
-
-    for e in entries {
-        let Entry {
-            tag,
-            alias,
-            vr,
-            obs,
-            ..
-        } = e;
-
-        // sanitize components
-
-        let alias = if let Some(v) = alias {
-            v
-        } else {
-            continue;
-        };
-
-        if let Some(ref s) = obs {
-            if s == "RET" && !include_retired {
-                // don't include retired attributes
-                continue;
-            }
+/// Keep the entries read from the XML,
+/// warning about text which could not be decoded instead of failing.
+///
+/// The entry holding the malformed text is still kept, without that text.
+///
+/// # Panics
+///
+/// Panics on any other XML error.
+fn skip_malformed_text(item: Result<Entry, XmlError>) -> Option<Entry> {
+    match item {
+        Ok(entry) => Some(entry),
+        Err(e @ XmlError::EscapeError(_)) => {
+            eprintln!("warning: ignoring malformed text: {}", e);
+            None
         }
-        let cap = regex_tag.captures(tag.as_str());
-        let tag_txt = if let Some(cap) = cap {
-            // single tag
-            let group = cap.get(1).expect("capture group 1: group").as_str();
-            let elem = cap.get(2).expect("capture group 2: element").as_str();
-            format!("Single(Tag(0x{}, 0x{}))", group, elem)
-        } else if let Some(cap) = regex_tag_group100.captures(tag.as_str()) {
-            // tag range over groups: (ggxx, eeee)
-            let group = cap.get(1).expect("capture group 1: group portion").as_str();
-            let elem = cap.get(2).expect("capture group 2: element").as_str();
-            format!("Group100(Tag(0x{}00, 0x{}))", group, elem)
-        } else if let Some(cap) = regex_tag_element100.captures(tag.as_str()) {
-            // tag range over elements: (gggg, eexx)
-            let group = cap.get(1).expect("capture group 1: group").as_str();
-            let elem = cap
-                .get(2)
-                .expect("capture group 2: element portion")
-                .as_str();
-            format!("Element100(Tag(0x{}, 0x{}00))", group, elem)
-        } else {
-            continue;
-        };
-
-        let mut vr = vr.unwrap_or_else(|| "".into());
-        if vr == "See Note" {
-            vr = "UN See Note".to_string();
-        }
-
-        let (vr1, vr2) = vr.split_at(2);
-
-        let second_vr = if vr2 != "" {
-            format!(" /*{} */", vr2)
-        } else {
-            vr2.to_string()
-        };
-
-        let mut obs = obs.unwrap_or_else(String::new);
-        if obs != "" {
-            obs = format!(" // {}", obs.as_str());
-        }
-
-        writeln!(
-            f,
-            "    E {{ tag: {}, alias: \"{}\", vr: {}{} }},{}",
-            tag_txt, alias, vr1, second_vr, obs
-        )?;
-    }
-    f.write_all(b"];\n")?;
-    Ok(())
-}
-
-fn to_json_file<P: AsRef<Path>, I>(dest_path: P, entries: I) -> DynResult<()>
-where
-    I: IntoIterator<Item = Entry>,
-{
-    if let Some(p_dir) = dest_path.as_ref().parent() {
-        create_dir_all(&p_dir)?;
+        Err(e) => panic!("Failed to read the dictionary: {}", e),
     }
-    let f = File::create(&dest_path)?;
-
-    let entries: BTreeMap<String, Entry> =
-        entries.into_iter().map(|v| (v.tag.clone(), v)).collect();
-
-    to_writer(f, &entries)?;
-    Ok(())
 }
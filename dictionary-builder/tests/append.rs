@@ -0,0 +1,88 @@
+//! Checks that appending to a JSON output merges the entries.
+mod common;
+
+use common::{entries, row, TempDir};
+use dicom_dictionary_builder::{
+    append_to_json_file, to_json_file_with_schema, BuildInfo, JsonSchema,
+};
+use serde_json::Value;
+use std::fs::read_to_string;
+
+fn merge_in_schema(schema: JsonSchema) -> Value {
+    let dir = TempDir::new(&format!("append-{:?}", schema));
+    let out = dir.join("entries.json");
+
+    let base = entries(&[
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+        row("(0010,0020)", "Patient ID", "PatientID", "LO"),
+        row(
+            "(0028,0106)",
+            "Smallest Image Pixel Value",
+            "SmallestImagePixelValue",
+            "US or SS",
+        ),
+    ]);
+    let count =
+        to_json_file_with_schema(&out, base, true, &BuildInfo::new("base"), schema, false).unwrap();
+    assert_eq!(count, 3);
+
+    let extension = entries(&[
+        row("(0009,1001)", "Acme Flags", "AcmeFlags", "US"),
+        // replaces the entry of the base
+        row("(0010,0020)", "Patient Identifier", "PatientID", "LO"),
+    ]);
+    let count = append_to_json_file(
+        &out,
+        extension,
+        true,
+        &BuildInfo::new("extension"),
+        schema,
+        true,
+    )
+    .unwrap();
+    assert_eq!(count, 4);
+
+    let json = read_to_string(&out).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn tags(doc: &Value) -> Vec<&str> {
+    doc["entries"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect()
+}
+
+#[test]
+fn append_merges_v1_entries() {
+    let doc = merge_in_schema(JsonSchema::V1);
+    assert_eq!(doc["meta"]["source"], "extension");
+    assert_eq!(
+        tags(&doc),
+        vec!["(0009,1001)", "(0010,0010)", "(0010,0020)", "(0028,0106)"]
+    );
+    let entries = &doc["entries"];
+    assert_eq!(entries["(0010,0020)"]["name"], "Patient Identifier");
+    assert_eq!(entries["(0010,0010)"]["alias"], "PatientName");
+    assert_eq!(entries["(0028,0106)"]["vr"], "US or SS");
+}
+
+#[test]
+fn append_merges_v2_entries() {
+    let doc = merge_in_schema(JsonSchema::V2);
+    assert_eq!(
+        tags(&doc),
+        vec!["(0009,1001)", "(0010,0010)", "(0010,0020)", "(0028,0106)"]
+    );
+    let entries = &doc["entries"];
+    assert_eq!(entries["(0010,0020)"]["name"], "Patient Identifier");
+    assert_eq!(entries["(0010,0010)"]["keyword"], "PatientName");
+    // the entries read back keep their VR alternatives
+    assert_eq!(
+        entries["(0028,0106)"]["vr"],
+        serde_json::json!(["US", "SS"])
+    );
+    assert_eq!(entries["(0028,0106)"]["vr_note"], "depends on (0028,0103)");
+}
@@ -0,0 +1,88 @@
+//! Checks the options of the command line application
+//! against a registry read from a file.
+mod common;
+
+use common::{registry, row, Row, TempDir};
+use serde_json::Value;
+use std::fs::{read_to_string, write};
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn rows() -> Vec<Row<'static>> {
+    vec![
+        row("(0008,0001)", "Length to End", "LengthToEnd", "UL").obs("RET"),
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+    ]
+}
+
+/// Run the application in the given directory,
+/// reading the registry from `part06.xml`.
+fn run(dir: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_dicom-dictionary-builder"))
+        .current_dir(dir)
+        .arg("part06.xml")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "the application failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn json_tags(path: &Path) -> Vec<String> {
+    let doc: Value = serde_json::from_str(&read_to_string(path).unwrap()).unwrap();
+    doc["entries"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn no_retired_switch() {
+    let dir = TempDir::new("cli-no-retired");
+    write(dir.join("part06.xml"), registry(&rows())).unwrap();
+
+    run(dir.path(), &["-f", "json", "-o", "all.json"]);
+    assert_eq!(
+        json_tags(&dir.join("all.json")),
+        vec!["(0008,0001)", "(0010,0010)"]
+    );
+
+    // the switch takes no value, so the output path still follows it
+    run(
+        dir.path(),
+        &["-f", "json", "--no-retired", "-o", "current.json"],
+    );
+    assert_eq!(json_tags(&dir.join("current.json")), vec!["(0010,0010)"]);
+
+    run(dir.path(), &["--no-retired", "-o", "current.rs"]);
+    let code = read_to_string(dir.join("current.rs")).unwrap();
+    assert!(!code.contains("LengthToEnd"));
+    assert!(code.contains("\"PatientName\""));
+}
+
+#[test]
+fn malformed_text_is_skipped_with_a_warning() {
+    let dir = TempDir::new("cli-malformed");
+    let xml = registry(&[row(
+        "(0010,0010)",
+        "Patient's &bogus; Name",
+        "PatientName",
+        "PN",
+    )]);
+    write(dir.join("part06.xml"), xml).unwrap();
+
+    let output = run(dir.path(), &["-f", "json", "-o", "entries.json"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("warning: ignoring malformed text"),
+        "{}",
+        stderr
+    );
+    assert_eq!(json_tags(&dir.join("entries.json")), vec!["(0010,0010)"]);
+}
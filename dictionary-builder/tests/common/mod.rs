@@ -0,0 +1,172 @@
+//! Fixtures shared by the integration tests:
+//! attribute registry documents in the format of PS3.6,
+//! temporary directories, and building generated code.
+#![allow(dead_code)]
+
+use dicom_dictionary_builder::{Entry, XmlEntryIterator, DATA_ELEMENT_TABLE_ID};
+use std::fs::{create_dir_all, remove_dir_all};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A row of an attribute table.
+///
+/// The cells are written to the document as they are,
+/// so they may hold entities and character references.
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+    pub tag: &'a str,
+    pub name: &'a str,
+    pub keyword: &'a str,
+    pub vr: &'a str,
+    pub vm: &'a str,
+    pub obs: &'a str,
+}
+
+/// A row of the given attribute,
+/// with a VM of `1` and no observation.
+pub fn row<'a>(tag: &'a str, name: &'a str, keyword: &'a str, vr: &'a str) -> Row<'a> {
+    Row {
+        tag,
+        name,
+        keyword,
+        vr,
+        vm: "1",
+        obs: "",
+    }
+}
+
+impl<'a> Row<'a> {
+    /// Replace the value multiplicity.
+    pub fn vm(self, vm: &'a str) -> Self {
+        Row { vm, ..self }
+    }
+
+    /// Replace the observation, such as `RET`.
+    pub fn obs(self, obs: &'a str) -> Self {
+        Row { obs, ..self }
+    }
+
+    fn to_xml(self) -> String {
+        let cells: String = [
+            self.tag,
+            self.name,
+            self.keyword,
+            self.vr,
+            self.vm,
+            self.obs,
+        ]
+        .iter()
+        .map(|cell| match *cell {
+            "" => "<td><para/></td>".to_string(),
+            cell => format!("<td><para>{}</para></td>", cell),
+        })
+        .collect();
+        format!("<tr>{}</tr>\n", cells)
+    }
+}
+
+/// A table body holding the given rows.
+pub fn tbody(rows: &[Row]) -> String {
+    let rows: String = rows.iter().map(|row| row.to_xml()).collect();
+    format!("<tbody>\n{}</tbody>\n", rows)
+}
+
+/// A table with the given ID and bodies.
+pub fn table(id: &str, bodies: &[String]) -> String {
+    format!("<table xml:id=\"{}\">\n{}</table>\n", id, bodies.concat())
+}
+
+/// A document holding the given tables.
+pub fn book(tables: &[String]) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <book xmlns=\"http://docbook.org/ns/docbook\">\n<chapter>\n{}</chapter>\n</book>\n",
+        tables.concat()
+    )
+}
+
+/// A document holding the registry of data elements with the given rows.
+pub fn registry(rows: &[Row]) -> String {
+    book(&[table(DATA_ELEMENT_TABLE_ID, &[tbody(rows)])])
+}
+
+/// Read all entries of the given document, with names trimmed.
+pub fn read_entries(xml: &str) -> Vec<Entry> {
+    read_entries_with_trim(xml, true)
+}
+
+/// Read all entries of the given document,
+/// with names trimmed or not.
+pub fn read_entries_with_trim(xml: &str, trim: bool) -> Vec<Entry> {
+    XmlEntryIterator::with_trim(xml.as_bytes(), trim)
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+/// The entries of the registry of data elements with the given rows.
+pub fn entries(rows: &[Row]) -> Vec<Entry> {
+    read_entries(&registry(rows))
+}
+
+/// A directory of the system's temporary directory,
+/// unique to the test process, removed when dropped.
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Create the directory with the given name.
+    pub fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("dicom-dictionary-{}-{}", name, std::process::id()));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.0);
+    }
+}
+
+/// Build the crate at `lib.rs` in the given directory as a library,
+/// returning the compiler output.
+pub fn build_lib(dir: &Path, crate_name: &str) -> Output {
+    rustc(dir, crate_name, "rlib", "lib.rs")
+}
+
+/// Build the program at `main.rs` in the given directory,
+/// returning the compiler output.
+pub fn build_bin(dir: &Path, crate_name: &str) -> Output {
+    rustc(dir, crate_name, "bin", "main.rs")
+}
+
+fn rustc(dir: &Path, crate_name: &str, crate_type: &str, root: &str) -> Output {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    Command::new(rustc)
+        .args(["--edition", "2018", "--crate-type", crate_type])
+        .args(["--crate-name", crate_name])
+        .arg("--out-dir")
+        .arg(dir)
+        .arg(dir.join(root))
+        .output()
+        .unwrap()
+}
+
+/// Assert that a build was successful, showing the compiler errors if not.
+pub fn assert_built(output: &Output) {
+    assert!(
+        output.status.success(),
+        "the output failed to build:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,45 @@
+//! Checks that downloaded bodies are decoded
+//! according to their `Content-Encoding`.
+use dicom_dictionary_builder::{decode_content, BuilderError};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use std::io::Write;
+
+const XML: &[u8] = b"<?xml version=\"1.0\" encoding=\"utf-8\"?><book/>";
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn decodes_each_content_encoding() {
+    assert_eq!(decode_content("", XML.to_vec()).unwrap(), XML);
+    assert_eq!(decode_content("identity", XML.to_vec()).unwrap(), XML);
+    assert_eq!(decode_content("gzip", gzip(XML)).unwrap(), XML);
+    assert_eq!(decode_content("x-gzip", gzip(XML)).unwrap(), XML);
+    assert_eq!(decode_content("Deflate", deflate(XML)).unwrap(), XML);
+
+    // applied in order, so undone in reverse order
+    let body = gzip(&deflate(XML));
+    assert_eq!(decode_content("deflate, gzip", body).unwrap(), XML);
+}
+
+#[test]
+fn fails_on_unsupported_or_malformed_content() {
+    match decode_content("br", XML.to_vec()) {
+        Err(BuilderError::UnsupportedEncoding { encoding }) => assert_eq!(encoding, "br"),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+    match decode_content("gzip", XML.to_vec()) {
+        Err(BuilderError::DecodeContent { encoding, .. }) => assert_eq!(encoding, "gzip"),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
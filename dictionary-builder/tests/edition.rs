@@ -0,0 +1,35 @@
+//! Checks the URL of the dictionary of a specific edition of the standard.
+use dicom_dictionary_builder::{edition_location, BuilderError};
+
+#[test]
+fn location_of_an_edition() {
+    assert_eq!(
+        edition_location("2021e").unwrap(),
+        "http://dicom.nema.org/medical/dicom/2021e/source/docbook/part06/part06.xml"
+    );
+    assert_eq!(
+        edition_location("2019").unwrap(),
+        "http://dicom.nema.org/medical/dicom/2019/source/docbook/part06/part06.xml"
+    );
+}
+
+#[test]
+fn invalid_editions() {
+    for &edition in &[
+        "",
+        "202",
+        "21e",
+        "2021f",
+        "2021ee",
+        "2021E",
+        "e2021",
+        "202é",
+        "2021é",
+        "２０２１",
+    ] {
+        match edition_location(edition) {
+            Err(BuilderError::InvalidEdition { edition: e }) => assert_eq!(e, edition),
+            other => panic!("unexpected result for `{}`: {:?}", edition, other),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Checks that entities and character references
+//! are decoded in the same way in every column.
+mod common;
+
+use common::{read_entries_with_trim, registry, row};
+use serde_json::json;
+
+fn entries(trim: bool) -> Vec<serde_json::Value> {
+    let xml = registry(&[
+        row(
+            "(0008,0016)",
+            "SOP Class &amp; Instance &lt;UID&gt;",
+            "SOP&#8203;Class&#8203;UID",
+            "UI",
+        ),
+        row(
+            "&#40;0008,0054&#41;",
+            "Retrieve &#65;E Title",
+            "Retrieve&#8203;&#65;E&#8203;Title",
+            "&#65;E",
+        )
+        .vm("1&#x2D;n")
+        .obs("&#82;ET"),
+    ]);
+    read_entries_with_trim(&xml, trim)
+        .iter()
+        .map(|e| serde_json::to_value(e).unwrap())
+        .collect()
+}
+
+#[test]
+fn name_with_entities() {
+    let entries = entries(true);
+    assert_eq!(entries[0]["name"], "SOP Class & Instance <UID>");
+    assert_eq!(entries[0]["alias"], "SOPClassUID");
+}
+
+#[test]
+fn character_references_in_all_columns() {
+    for &trim in &[true, false] {
+        let entries = entries(trim);
+        assert_eq!(
+            entries[1],
+            json!({
+                "tag": "(0008,0054)",
+                "name": "Retrieve AE Title",
+                "alias": "RetrieveAETitle",
+                "vr": "AE",
+                "vm": "1-n",
+                "obs": "RET",
+            }),
+            "with trim: {}",
+            trim
+        );
+    }
+}
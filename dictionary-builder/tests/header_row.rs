@@ -0,0 +1,48 @@
+//! Checks that a row of column labels in the body of the table
+//! is not read as an entry.
+mod common;
+
+use common::{book, read_entries, row, table, tbody};
+use dicom_dictionary_builder::DATA_ELEMENT_TABLE_ID;
+
+#[test]
+fn skips_a_header_row_in_the_first_body() {
+    let xml = book(&[
+        table(
+            DATA_ELEMENT_TABLE_ID,
+            &[
+                tbody(&[row("Tag", "Name", "Keyword", "VR").vm("VM")]),
+                tbody(&[
+                    row(
+                        "(0008,0016)",
+                        "SOP Class UID",
+                        "SOP&#8203;Class&#8203;UID",
+                        "UI",
+                    ),
+                    row(
+                        "(60xx,3000)",
+                        "Overlay Data",
+                        "Overlay&#8203;Data",
+                        "OB or OW",
+                    ),
+                ]),
+            ],
+        ),
+        table(
+            "table_7-1",
+            &[tbody(&[row(
+                "(0002,0010)",
+                "Transfer Syntax UID",
+                "Transfer&#8203;Syntax&#8203;UID",
+                "UI",
+            )])],
+        ),
+    ]);
+    let entries = read_entries(&xml);
+
+    let tags: Vec<_> = entries
+        .iter()
+        .map(|e| serde_json::to_value(e).unwrap()["tag"].clone())
+        .collect();
+    assert_eq!(tags, vec!["(0008,0016)", "(60xx,3000)"]);
+}
@@ -0,0 +1,37 @@
+//! Checks that the outputs written from the same entries
+//! can run in order or concurrently.
+use dicom_dictionary_builder::run_jobs;
+use std::sync::{Barrier, Mutex};
+
+#[test]
+fn jobs_on_one_thread_run_in_order() {
+    let done = Mutex::new(Vec::new());
+    let jobs: Vec<_> = (0..4)
+        .map(|i| {
+            let done = &done;
+            move || done.lock().unwrap().push(i)
+        })
+        .collect();
+    run_jobs(jobs, 1);
+    assert_eq!(done.into_inner().unwrap(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn jobs_on_several_threads_run_concurrently() {
+    // every job waits for the others, so they must all run at once
+    let barrier = Barrier::new(3);
+    let done = Mutex::new(Vec::new());
+    let jobs: Vec<_> = (0..3)
+        .map(|i| {
+            let (barrier, done) = (&barrier, &done);
+            move || {
+                barrier.wait();
+                done.lock().unwrap().push(i)
+            }
+        })
+        .collect();
+    run_jobs(jobs, 8);
+    let mut done = done.into_inner().unwrap();
+    done.sort_unstable();
+    assert_eq!(done, vec![0, 1, 2]);
+}
@@ -0,0 +1,72 @@
+//! Checks that the JSON output grouped by VR
+//! holds each entry under its primary VR.
+mod common;
+
+use common::{entries, row, TempDir};
+use dicom_dictionary_builder::{to_json_file_with_layout, BuildInfo, JsonLayout, JsonSchema};
+use serde_json::Value;
+use std::fs::read_to_string;
+
+/// Write the entries grouped by VR in the given schema
+/// and read back the groups.
+fn groups_in_schema(schema: JsonSchema) -> Value {
+    let entries = entries(&[
+        row("(0008,0018)", "SOP Instance UID", "SOPInstanceUID", "UI"),
+        row("(0008,0016)", "SOP Class UID", "SOPClassUID", "UI"),
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+        row(
+            "(0028,0106)",
+            "Smallest Image Pixel Value",
+            "SmallestImagePixelValue",
+            "US or SS",
+        ),
+    ]);
+
+    let dir = TempDir::new(&format!("by-vr-{:?}", schema));
+    let out = dir.join("entries.json");
+    let info = BuildInfo::new("json_layout.rs");
+    let count =
+        to_json_file_with_layout(&out, entries, true, &info, schema, JsonLayout::ByVr, false)
+            .unwrap();
+    assert_eq!(count, 4);
+    let json = read_to_string(&out).unwrap();
+
+    let mut doc: Value = serde_json::from_str(&json).unwrap();
+    doc["entries"].take()
+}
+
+/// The tags of the entries in the given group.
+fn tags(group: &Value) -> Vec<&str> {
+    group
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["tag"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn entries_are_grouped_by_primary_vr() {
+    for &schema in &[JsonSchema::V1, JsonSchema::V2] {
+        let groups = groups_in_schema(schema);
+        let mut keys: Vec<_> = groups.as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["PN", "UI", "US"], "in schema {:?}", schema);
+
+        // ordered by tag within each group
+        assert_eq!(tags(&groups["UI"]), vec!["(0008,0016)", "(0008,0018)"]);
+        assert_eq!(tags(&groups["PN"]), vec!["(0010,0010)"]);
+        assert_eq!(tags(&groups["US"]), vec!["(0028,0106)"]);
+    }
+}
+
+#[test]
+fn grouped_entries_follow_the_schema() {
+    let groups = groups_in_schema(JsonSchema::V1);
+    assert_eq!(groups["US"][0]["vr"], "US or SS");
+    assert_eq!(groups["PN"][0]["alias"], "PatientName");
+
+    let groups = groups_in_schema(JsonSchema::V2);
+    assert_eq!(groups["US"][0]["vr"], serde_json::json!(["US", "SS"]));
+    assert_eq!(groups["PN"][0]["keyword"], "PatientName");
+}
@@ -0,0 +1,37 @@
+//! Checks the keys of the entries in each JSON schema.
+mod common;
+
+use common::{entries, row, TempDir};
+use dicom_dictionary_builder::{to_json_file_with_schema, BuildInfo, JsonSchema};
+use serde_json::Value;
+use std::fs::read_to_string;
+
+/// Write the entry in the given schema and read back its JSON object.
+fn entry_in_schema(schema: JsonSchema) -> Value {
+    let entries = entries(&[row("(0010,0010)", "Patient's Name", "PatientName", "PN")]);
+
+    let dir = TempDir::new(&format!("schema-{:?}", schema));
+    let out = dir.join("entries.json");
+    let info = BuildInfo::new("json_schema.rs");
+    to_json_file_with_schema(&out, entries, true, &info, schema, false).unwrap();
+    let json = read_to_string(&out).unwrap();
+
+    let mut doc: Value = serde_json::from_str(&json).unwrap();
+    doc["entries"]["(0010,0010)"].take()
+}
+
+#[test]
+fn v1_entries_have_an_alias() {
+    let entry = entry_in_schema(JsonSchema::V1);
+    assert_eq!(entry["name"], "Patient's Name");
+    assert_eq!(entry["alias"], "PatientName");
+    assert!(entry.get("keyword").is_none());
+}
+
+#[test]
+fn v2_entries_have_a_keyword() {
+    let entry = entry_in_schema(JsonSchema::V2);
+    assert_eq!(entry["name"], "Patient's Name");
+    assert_eq!(entry["keyword"], "PatientName");
+    assert!(entry.get("alias").is_none());
+}
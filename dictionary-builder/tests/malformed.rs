@@ -0,0 +1,29 @@
+//! Checks that text which cannot be decoded
+//! is reported as an error instead of a panic.
+mod common;
+
+use common::{registry, row};
+use dicom_dictionary_builder::XmlEntryIterator;
+use quick_xml::Error as XmlError;
+
+#[test]
+fn malformed_text_is_an_error() {
+    let xml = registry(&[
+        row("(0010,0010)", "Patient's &bogus; Name", "PatientName", "PN"),
+        row("(0010,0020)", "Patient ID", "PatientID", "LO"),
+    ]);
+    let items: Vec<_> = XmlEntryIterator::with_trim(xml.as_bytes(), true).collect();
+    assert_eq!(items.len(), 3, "{:?}", items);
+    assert!(matches!(items[0], Err(XmlError::EscapeError(_))));
+
+    // reading goes on after the error,
+    // and the entry is kept without the malformed text
+    let entries: Vec<_> = items[1..]
+        .iter()
+        .map(|item| serde_json::to_value(item.as_ref().unwrap()).unwrap())
+        .collect();
+    assert_eq!(entries[0]["tag"], "(0010,0010)");
+    assert_eq!(entries[0]["alias"], "PatientName");
+    assert!(entries[0]["name"].is_null());
+    assert_eq!(entries[1]["tag"], "(0010,0020)");
+}
@@ -0,0 +1,60 @@
+//! Checks that the Rust output exposes the display name of each entry,
+//! with quotes and backslashes escaped.
+mod common;
+
+use common::{assert_built, build_bin, row, TempDir};
+use dicom_dictionary_builder::{to_code_file, to_no_std_code_file, BuildInfo, Entry};
+use std::fs::{read_to_string, write};
+use std::process::Command;
+
+/// A program printing the display name of each generated entry.
+const MAIN: &str = r#"#![deny(warnings)]
+
+#[allow(dead_code)]
+mod entries;
+
+fn main() {
+    for e in entries::ENTRIES {
+        println!("{}", e.name.unwrap_or("?"));
+    }
+}
+"#;
+
+fn entries() -> Vec<Entry> {
+    common::entries(&[
+        row("(0010,0010)", "Patient's Name", "Patient&#8203;Name", "PN"),
+        row(
+            "(0018,9346)",
+            "CTDI Phantom Type \"Code\" \\ Sequence",
+            "CTDI&#8203;Phantom&#8203;Type&#8203;Code&#8203;Sequence",
+            "SQ",
+        ),
+    ])
+}
+
+#[test]
+fn code_output_has_escaped_names() {
+    let dir = TempDir::new("names");
+    let info = BuildInfo::new("names.rs");
+    to_code_file(dir.join("entries.rs"), entries(), true, &info).unwrap();
+    let code = read_to_string(dir.join("entries.rs")).unwrap();
+
+    assert!(code.contains(r#"alias: "PatientName", vr: PN, vr2: None, vm: Some(Vm::Bounded(1, 1)), name: Some("Patient's Name") },"#));
+    assert!(code.contains(r#"name: Some("CTDI Phantom Type \"Code\" \\ Sequence") },"#));
+}
+
+#[test]
+fn generated_entries_expose_their_names() {
+    let dir = TempDir::new("names-run");
+    let info = BuildInfo::new("names.rs");
+    to_no_std_code_file(dir.join("entries.rs"), entries(), true, &info, false).unwrap();
+    write(dir.join("main.rs"), MAIN).unwrap();
+    assert_built(&build_bin(dir.path(), "names"));
+
+    let run = Command::new(dir.join("names")).output().unwrap();
+    assert!(run.status.success());
+    assert_eq!(
+        String::from_utf8(run.stdout).unwrap(),
+        "Patient's Name\nCTDI Phantom Type \"Code\" \\ Sequence\n"
+    );
+}
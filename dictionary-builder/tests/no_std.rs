@@ -0,0 +1,42 @@
+//! Checks that the Rust output for `no_std` crates
+//! builds in a `#![no_std]` crate.
+mod common;
+
+use common::{assert_built, build_lib, entries, row, TempDir};
+use dicom_dictionary_builder::{to_no_std_code_file, BuildInfo};
+use std::fs::write;
+
+/// A `no_std` crate using the generated dictionary in constant expressions.
+const LIB: &str = r#"#![no_std]
+#![deny(warnings)]
+
+pub mod entries;
+
+pub const FIRST_ALIAS: &str = entries::ENTRIES[0].alias;
+pub const NUM_PACKED: usize = entries::TAGS_PACKED.len();
+"#;
+
+#[test]
+fn no_std_output_builds_without_std() {
+    let entries = entries(&[
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+        row(
+            "(0028,0106)",
+            "Smallest Image Pixel Value",
+            "SmallestImagePixelValue",
+            "US or SS",
+        ),
+        row("(0020,31xx)", "Source Image IDs", "SourceImageIDs", "CS")
+            .vm("1-n")
+            .obs("RET"),
+        row("(60xx,3000)", "Overlay Data", "OverlayData", "OB or OW"),
+    ]);
+
+    let dir = TempDir::new("no-std");
+    let info = BuildInfo::new("no_std.rs");
+    let count = to_no_std_code_file(dir.join("entries.rs"), entries, true, &info, true).unwrap();
+    assert_eq!(count, 4);
+    write(dir.join("lib.rs"), LIB).unwrap();
+
+    assert_built(&build_lib(dir.path(), "entries"));
+}
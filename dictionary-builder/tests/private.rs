@@ -0,0 +1,81 @@
+//! Checks reading private attributes from a CSV file.
+mod common;
+
+use common::TempDir;
+use dicom_dictionary_builder::{read_private_csv, BuilderError, Entry};
+use serde_json::{json, Value};
+use std::fs::write;
+
+fn read_csv(dir: &TempDir, csv: &str) -> Result<Vec<Entry>, BuilderError> {
+    let path = dir.join("private.csv");
+    write(&path, csv).unwrap();
+    read_private_csv(path)
+}
+
+/// The tag and creator of each entry.
+fn tags_and_creators(entries: &[Entry]) -> Vec<(Value, Value)> {
+    entries
+        .iter()
+        .map(|e| {
+            let e = serde_json::to_value(e).unwrap();
+            (e["tag"].clone(), e["creator"].clone())
+        })
+        .collect()
+}
+
+#[test]
+fn same_tag_of_different_creators() {
+    let dir = TempDir::new("private-creators");
+    let entries = read_csv(
+        &dir,
+        "group,element,creator,keyword,VR,VM\n\
+         0009,1010,\"OTHER, INC\",OtherFlags,UL,1\n\
+         0009,1001,ACME,AcmeFlags,US,1\n\
+         0009,1001,OTHER,OtherName,LO,1\n",
+    )
+    .unwrap();
+    assert_eq!(
+        tags_and_creators(&entries),
+        vec![
+            (json!("(0009,1001)"), json!("ACME")),
+            (json!("(0009,1001)"), json!("OTHER")),
+            (json!("(0009,1010)"), json!("OTHER, INC")),
+        ]
+    );
+    assert!(entries.iter().all(Entry::is_private));
+}
+
+#[test]
+fn duplicate_tag_of_the_same_creator() {
+    let dir = TempDir::new("private-duplicates");
+    let err = read_csv(
+        &dir,
+        "0009,1001,ACME,AcmeFlags,US,1\n\
+         # the same attribute, listed again\n\
+         0009,1001,ACME,AcmeName,LO,1\n",
+    )
+    .unwrap_err();
+    match err {
+        BuilderError::ParsePrivateCsv { line, message, .. } => {
+            assert_eq!(line, 3);
+            assert_eq!(message, "duplicate tag of the same creator");
+        }
+        e => panic!("unexpected error: {}", e),
+    }
+
+    // entries without a creator are also told apart from the others
+    let entries = read_csv(
+        &dir,
+        "0009,1001,,Flags,US,1\n\
+         0009,1001,ACME,AcmeFlags,US,1\n",
+    )
+    .unwrap();
+    assert_eq!(
+        tags_and_creators(&entries),
+        vec![
+            (json!("(0009,1001)"), Value::Null),
+            (json!("(0009,1001)"), json!("ACME")),
+        ]
+    );
+    assert!(read_csv(&dir, "0009,1001,,Flags,US,1\n0009,1001,,Name,LO,1\n").is_err());
+}
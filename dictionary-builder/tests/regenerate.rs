@@ -0,0 +1,38 @@
+//! Checks that a dictionary regenerated from a URL
+//! only reuses the copy of the XML downloaded from that URL.
+mod common;
+
+use common::{registry, row, TempDir};
+use dicom_dictionary_builder::{cache_location, regenerate_into, Format, Stats};
+use std::fs::{read_to_string, write};
+
+/// Nothing listens on this port, so downloading from it fails right away.
+const CURRENT: &str = "http://127.0.0.1:1/medical/dicom/current/part06.xml";
+const EDITION: &str = "http://127.0.0.1:1/medical/dicom/2021e/part06.xml";
+
+#[test]
+fn cache_is_kept_per_source() {
+    let dir = TempDir::new("regenerate");
+    let out = dir.join("entries.rs");
+    assert_ne!(cache_location(CURRENT, &out), cache_location(EDITION, &out));
+    assert_eq!(cache_location(CURRENT, &out).parent(), Some(dir.path()));
+
+    write(
+        cache_location(CURRENT, &out),
+        registry(&[row("(0010,0010)", "Patient's Name", "PatientName", "PN")]),
+    )
+    .unwrap();
+
+    let stats = regenerate_into(CURRENT, &out, Format::Rs, true).unwrap();
+    assert_eq!(
+        stats,
+        Stats {
+            entries: 1,
+            cached: true
+        }
+    );
+    assert!(read_to_string(&out).unwrap().contains("\"PatientName\""));
+
+    // another edition is downloaded instead
+    assert!(regenerate_into(EDITION, &out, Format::Rs, true).is_err());
+}
@@ -0,0 +1,76 @@
+//! Checks that retired attributes are left out of every output on request.
+mod common;
+
+use common::{entries, row, TempDir};
+use dicom_dictionary_builder::{
+    to_code_file, to_json_file, to_json_file_with_schema, BuildInfo, Entry, JsonSchema, Observation,
+};
+use serde_json::Value;
+use std::fs::read_to_string;
+
+fn registry() -> Vec<Entry> {
+    entries(&[
+        row("(0008,0001)", "Length to End", "LengthToEnd", "UL").obs("RET"),
+        row(
+            "(0008,0005)",
+            "Specific Character Set",
+            "SpecificCharacterSet",
+            "CS",
+        )
+        .vm("1-n"),
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+    ])
+}
+
+/// The tags of the entries of a JSON output.
+fn json_tags(path: &std::path::Path) -> Vec<String> {
+    let doc: Value = serde_json::from_str(&read_to_string(path).unwrap()).unwrap();
+    doc["entries"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn json_output_without_retired_entries() {
+    let dir = TempDir::new("retired-json");
+    let info = BuildInfo::new("retired.rs");
+
+    let out = dir.join("all.json");
+    assert_eq!(to_json_file(&out, registry(), true, &info).unwrap(), 3);
+    assert_eq!(
+        json_tags(&out),
+        vec!["(0008,0001)", "(0008,0005)", "(0010,0010)"]
+    );
+
+    for &schema in &[JsonSchema::V1, JsonSchema::V2] {
+        let out = dir.join(format!("current-{:?}.json", schema));
+        let count =
+            to_json_file_with_schema(&out, registry(), false, &info, schema, false).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(json_tags(&out), vec!["(0008,0005)", "(0010,0010)"]);
+    }
+}
+
+#[test]
+fn retired_with_remarks_left_out() {
+    let dir = TempDir::new("retired-remarks");
+    let info = BuildInfo::new("retired.rs");
+    let registry = entries(&[
+        row("(0008,0010)", "Recognition Code", "RecognitionCode", "SH").obs("RET (see note)"),
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+    ]);
+    assert_eq!(registry[0].observation(), Observation::Retired);
+
+    let out = dir.join("entries.rs");
+    to_code_file(&out, registry.clone(), false, &info).unwrap();
+    let code = read_to_string(&out).unwrap();
+    assert!(!code.contains("RecognitionCode"));
+    assert!(code.contains("\"PatientName\""));
+
+    let out = dir.join("entries.json");
+    assert_eq!(to_json_file(&out, registry, false, &info).unwrap(), 1);
+    assert_eq!(json_tags(&out), vec!["(0010,0010)"]);
+}
@@ -0,0 +1,93 @@
+//! Checks that the entries of the Rust output are sorted by tag,
+//! and that the output fails to build if they are not.
+mod common;
+
+use common::{assert_built, build_lib, row, TempDir};
+use dicom_dictionary_builder::{to_code_file, to_no_std_code_file, BuildInfo, Entry};
+use std::fs::{read_to_string, write};
+
+/// A `no_std` crate holding the generated dictionary.
+const LIB: &str = r#"#![no_std]
+
+#[allow(dead_code)]
+mod entries;
+"#;
+
+/// Entries out of order, including tag ranges.
+fn entries() -> Vec<Entry> {
+    common::entries(&[
+        row("(60xx,3000)", "Overlay Data", "OverlayData", "OB or OW"),
+        row(
+            "(0028,0106)",
+            "Smallest Image Pixel Value",
+            "SmallestImagePixelValue",
+            "US or SS",
+        ),
+        row("(0020,31xx)", "Source Image IDs", "SourceImageIDs", "CS")
+            .vm("1-n")
+            .obs("RET"),
+        row(
+            "(0020,3100)",
+            "First Source Image ID",
+            "FirstSourceImageID",
+            "CS",
+        ),
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+        row(
+            "(6000,3000)",
+            "First Overlay Data",
+            "FirstOverlayData",
+            "OW",
+        ),
+    ])
+}
+
+#[test]
+fn code_output_is_sorted_by_tag() {
+    let dir = TempDir::new("sorted");
+    let info = BuildInfo::new("sorted.rs");
+    to_code_file(dir.join("entries.rs"), entries(), true, &info).unwrap();
+    let code = read_to_string(dir.join("entries.rs")).unwrap();
+
+    let aliases: Vec<_> = code
+        .lines()
+        .filter_map(|line| line.split("alias: \"").nth(1))
+        .filter_map(|rest| rest.split('"').next())
+        .collect();
+    assert_eq!(
+        aliases,
+        vec![
+            "PatientName",
+            "FirstSourceImageID",
+            "SourceImageIDs",
+            "SmallestImagePixelValue",
+            "FirstOverlayData",
+            "OverlayData",
+        ]
+    );
+    assert!(code.contains("\"ENTRIES must be sorted by tag\""));
+}
+
+#[test]
+fn unsorted_output_fails_to_build() {
+    let dir = TempDir::new("unsorted");
+    let info = BuildInfo::new("sorted.rs");
+    to_no_std_code_file(dir.join("entries.rs"), entries(), true, &info, false).unwrap();
+    write(dir.join("lib.rs"), LIB).unwrap();
+
+    assert_built(&build_lib(dir.path(), "sorted"));
+
+    // swap the first two entries
+    let code = read_to_string(dir.join("entries.rs")).unwrap();
+    let mut lines: Vec<_> = code.lines().collect();
+    let first = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("E { "))
+        .unwrap();
+    lines.swap(first, first + 1);
+    write(dir.join("entries.rs"), lines.join("\n")).unwrap();
+
+    let unsorted = build_lib(dir.path(), "sorted");
+    assert!(!unsorted.status.success());
+    assert!(String::from_utf8_lossy(&unsorted.stderr).contains("ENTRIES must be sorted by tag"));
+}
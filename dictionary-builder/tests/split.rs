@@ -0,0 +1,111 @@
+//! Checks that the Rust output split into multiple files
+//! holds the same entries as a single file, and builds.
+mod common;
+
+use common::{assert_built, build_lib, entries, row, TempDir};
+use dicom_dictionary_builder::{to_no_std_code_file, to_split_code_files, BuildInfo};
+use std::fs::{read_to_string, write};
+
+/// A `no_std` crate using the generated dictionary in constant expressions.
+const LIB: &str = r#"#![no_std]
+#![deny(warnings)]
+
+pub mod entries;
+
+pub const FIRST_ALIAS: &str = entries::ENTRIES[0].alias;
+pub const LAST_ALIAS: &str = entries::ENTRIES[entries::ENTRIES.len() - 1].alias;
+pub const NUM_PACKED: usize = entries::TAGS_PACKED.len();
+"#;
+
+/// The entry lines of a Rust output, in order.
+fn entry_lines(code: &str) -> Vec<&str> {
+    code.lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("E {"))
+        .collect()
+}
+
+#[test]
+fn split_output_has_the_entries_of_a_single_file() {
+    let entries = entries(&[
+        row("(0008,0016)", "SOP Class UID", "SOPClassUID", "UI"),
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+        row("(0010,0020)", "Patient ID", "PatientID", "LO"),
+        row(
+            "(0028,0106)",
+            "Smallest Image Pixel Value",
+            "SmallestImagePixelValue",
+            "US or SS",
+        ),
+        row("(60xx,3000)", "Overlay Data", "OverlayData", "OB or OW"),
+    ]);
+
+    let dir = TempDir::new("split");
+    let info = BuildInfo::new("split.rs");
+    to_no_std_code_file(dir.join("single.rs"), entries.clone(), true, &info, true).unwrap();
+    let count =
+        to_split_code_files(dir.join("entries"), entries, true, &info, true, true, 2).unwrap();
+    assert_eq!(count, 5);
+
+    let single = read_to_string(dir.join("single.rs")).unwrap();
+    let part_0 = read_to_string(dir.join("entries").join("entries_0.rs")).unwrap();
+    let part_1 = read_to_string(dir.join("entries").join("entries_1.rs")).unwrap();
+    // a part does not end in the middle of a group
+    assert_eq!(entry_lines(&part_0).len(), 3);
+    assert_eq!(entry_lines(&part_1).len(), 2);
+    let mut split_lines = entry_lines(&part_0);
+    split_lines.extend(entry_lines(&part_1));
+    assert_eq!(split_lines, entry_lines(&single));
+
+    write(dir.join("lib.rs"), LIB).unwrap();
+    assert_built(&build_lib(dir.path(), "entries"));
+}
+
+#[test]
+fn new_entry_only_changes_the_part_of_its_group() {
+    let rows = vec![
+        row("(0008,0016)", "SOP Class UID", "SOPClassUID", "UI"),
+        row("(0008,0018)", "SOP Instance UID", "SOPInstanceUID", "UI"),
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+        row("(0018,0050)", "Slice Thickness", "SliceThickness", "DS"),
+        row(
+            "(0020,000D)",
+            "Study Instance UID",
+            "StudyInstanceUID",
+            "UI",
+        ),
+        row("(0028,0010)", "Rows", "Rows", "US"),
+        row(
+            "(0040,0244)",
+            "Performed Procedure Step Start Date",
+            "PerformedProcedureStepStartDate",
+            "DA",
+        ),
+        row("(6000,0010)", "Overlay Rows", "OverlayRows", "US"),
+        row("(60xx,3000)", "Overlay Data", "OverlayData", "OB or OW"),
+    ];
+    let mut more_rows = rows.clone();
+    more_rows.insert(
+        1,
+        row("(0008,0017)", "Acquisition UID", "AcquisitionUID", "UI"),
+    );
+
+    let dir = TempDir::new("split-stable");
+    let info = BuildInfo::new("split.rs");
+    for (name, rows) in &[("before", &rows), ("after", &more_rows)] {
+        to_split_code_files(dir.join(name), entries(rows), true, &info, false, false, 4).unwrap();
+    }
+
+    let part = |name: &str, part: usize| {
+        read_to_string(dir.join(name).join(format!("entries_{}.rs", part))).unwrap()
+    };
+    assert_ne!(part("before", 0), part("after", 0));
+    assert!(entry_lines(&part("after", 0))
+        .iter()
+        .any(|line| line.contains("AcquisitionUID")));
+    for i in 1..4 {
+        assert_eq!(part("before", i), part("after", i), "part {} changed", i);
+    }
+    // seven groups, with the overlay entries in the last part
+    assert_eq!(entry_lines(&part("before", 3)).len(), 2);
+}
@@ -0,0 +1,74 @@
+//! Checks which table of the document the entries are read from.
+mod common;
+
+use common::{book, row, table, tbody, Row};
+use dicom_dictionary_builder::{Entry, XmlEntryIterator, DATA_ELEMENT_TABLE_ID};
+
+fn patient_name() -> Row<'static> {
+    row("(0010,0010)", "Patient's Name", "PatientName", "PN")
+}
+
+fn transfer_syntax() -> Row<'static> {
+    row(
+        "(0002,0010)",
+        "Transfer Syntax UID",
+        "TransferSyntaxUID",
+        "UI",
+    )
+}
+
+fn tags(entries: &[Entry]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|e| {
+            serde_json::to_value(e).unwrap()["tag"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn exact_table_preferred() {
+    let xml = book(&[
+        table("table_6-1a", &[tbody(&[transfer_syntax()])]),
+        table(DATA_ELEMENT_TABLE_ID, &[tbody(&[patient_name()])]),
+    ]);
+    let mut iter = XmlEntryIterator::with_trim(xml.as_bytes(), true);
+    let entries: Vec<_> = iter.by_ref().collect::<Result<_, _>>().unwrap();
+    assert_eq!(tags(&entries), vec!["(0010,0010)"]);
+    assert_eq!(iter.entered_table(), Some(DATA_ELEMENT_TABLE_ID));
+
+    // without the exact table, the first matching one is read
+    let xml = book(&[
+        table("table_6-1a", &[tbody(&[transfer_syntax()])]),
+        table("table_6-1b", &[tbody(&[patient_name()])]),
+    ]);
+    let mut iter = XmlEntryIterator::with_trim(xml.as_bytes(), true);
+    let entries: Vec<_> = iter.by_ref().collect::<Result<_, _>>().unwrap();
+    assert_eq!(tags(&entries), vec!["(0002,0010)"]);
+    assert_eq!(iter.entered_table(), Some("table_6-1a"));
+}
+
+#[test]
+fn stops_at_the_end_of_the_table() {
+    // the text of the second table cannot be decoded,
+    // which would be an error if it were read
+    let xml = book(&[
+        table(DATA_ELEMENT_TABLE_ID, &[tbody(&[patient_name()])]),
+        table(
+            "table_7-1",
+            &[tbody(&[row(
+                "(0002,0010)",
+                "Transfer &bogus; UID",
+                "TransferSyntaxUID",
+                "UI",
+            )])],
+        ),
+    ]);
+    let entries: Vec<_> = XmlEntryIterator::with_trim(xml.as_bytes(), true)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(tags(&entries), vec!["(0010,0010)"]);
+}
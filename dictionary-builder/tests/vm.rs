@@ -0,0 +1,79 @@
+//! Checks how the VM column is written to the Rust output.
+mod common;
+
+use common::{entries, row, TempDir};
+use dicom_dictionary_builder::{to_code_file, BuildInfo};
+use std::fs::read_to_string;
+
+#[test]
+fn code_output_has_value_multiplicities() {
+    let dir = TempDir::new("vm");
+    let info = BuildInfo::new("vm.rs");
+    let registry = entries(&[
+        row(
+            "(0008,0005)",
+            "Specific Character Set",
+            "SpecificCharacterSet",
+            "CS",
+        )
+        .vm("1-n"),
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN"),
+        row(
+            "(0018,1149)",
+            "Field of View Dimension(s)",
+            "FieldOfViewDimensions",
+            "IS",
+        )
+        .vm("1-2"),
+        row(
+            "(0020,0032)",
+            "Image Position (Patient)",
+            "ImagePositionPatient",
+            "DS",
+        )
+        .vm("3"),
+        row(
+            "(0028,0009)",
+            "Frame Increment Pointer",
+            "FrameIncrementPointer",
+            "AT",
+        )
+        .vm("2-2n"),
+        row("(0028,0010)", "Rows", "Rows", "US").vm("see note"),
+    ]);
+    to_code_file(dir.join("entries.rs"), registry, true, &info).unwrap();
+    let code = read_to_string(dir.join("entries.rs")).unwrap();
+
+    assert!(code.contains("use dicom_core::dictionary::{DictionaryEntryRef, TagRange::*, Vm};"));
+    for (alias, vm) in &[
+        ("SpecificCharacterSet", "Some(Vm::Unbounded(1))"),
+        ("PatientName", "Some(Vm::Bounded(1, 1))"),
+        ("FieldOfViewDimensions", "Some(Vm::Bounded(1, 2))"),
+        ("ImagePositionPatient", "Some(Vm::Bounded(3, 3))"),
+        ("FrameIncrementPointer", "Some(Vm::Multiple(2))"),
+        ("Rows", "None"),
+    ] {
+        let line = code
+            .lines()
+            .find(|line| line.contains(&format!("alias: \"{}\"", alias)))
+            .unwrap();
+        assert!(line.contains(&format!("vm: {},", vm)), "{}", line);
+    }
+}
+
+#[test]
+fn code_output_without_value_multiplicities() {
+    let dir = TempDir::new("vm-none");
+    let info = BuildInfo::new("vm.rs");
+    let registry = entries(&[
+        row("(0010,0010)", "Patient's Name", "PatientName", "PN").vm(""),
+        row("(0010,0020)", "Patient ID", "PatientID", "LO").vm(""),
+    ]);
+    to_code_file(dir.join("entries.rs"), registry, true, &info).unwrap();
+    let code = read_to_string(dir.join("entries.rs")).unwrap();
+
+    // `Vm` is not imported, so that the output builds without warnings
+    assert!(code.contains("use dicom_core::dictionary::{DictionaryEntryRef, TagRange::*};"));
+    assert!(!code.contains("Vm::"));
+    assert!(code.contains("alias: \"PatientName\", vr: PN, vr2: None, vm: None,"));
+}
@@ -0,0 +1,60 @@
+//! Checks how the VR column is written to the Rust output,
+//! including alternative VRs and invalid VRs.
+mod common;
+
+use common::{entries, row, TempDir};
+use dicom_dictionary_builder::{to_code_file, BuildInfo};
+use std::fs::read_to_string;
+
+#[test]
+fn code_output_has_alternative_vrs() {
+    let dir = TempDir::new("vr-alternatives");
+    let info = BuildInfo::new("vr.rs");
+    let registry = entries(&[
+        row(
+            "(0028,0106)",
+            "Smallest Image Pixel Value",
+            "SmallestImagePixelValue",
+            "US or SS",
+        ),
+        row("(60xx,3000)", "Overlay Data", "OverlayData", "OB or OW"),
+        row(
+            "(0028,1101)",
+            "Palette Data",
+            "PaletteData",
+            "US or SS or OW",
+        ),
+        row("(0028,1201)", "Palette Note", "PaletteNote", "See Note"),
+    ]);
+    to_code_file(dir.join("entries.rs"), registry, true, &info).unwrap();
+    let code = read_to_string(dir.join("entries.rs")).unwrap();
+
+    assert!(code.contains(r#"alias: "SmallestImagePixelValue", vr: US, vr2: Some(SS), vm:"#));
+    assert!(code.contains(r#"alias: "OverlayData", vr: OB, vr2: Some(OW), vm:"#));
+    assert!(code.contains(r#"alias: "PaletteData", vr: US, vr2: Some(SS) /* or OW */, vm:"#));
+    assert!(code.contains(r#"alias: "PaletteNote", vr: UN, vr2: None /* See Note */, vm:"#));
+}
+
+#[test]
+fn code_output_falls_back_to_un_for_invalid_vrs() {
+    let dir = TempDir::new("vr-invalid");
+    let info = BuildInfo::new("vr.rs");
+    let registry = entries(&[
+        row("(0009,0001)", "Lower Case", "LowerCase", "us"),
+        row("(0009,0002)", "Too Short", "TooShort", "U"),
+        row("(0009,0003)", "Empty", "Empty", ""),
+        row("(0009,0004)", "Not ASCII", "NotAscii", "Ü"),
+        // only the first alternative is invalid
+        row("(0009,0005)", "Invalid First", "InvalidFirst", "1S or SS"),
+        // only the second alternative is invalid, so it is kept as a remark
+        row("(0009,0006)", "Invalid Second", "InvalidSecond", "US or ss"),
+    ]);
+    to_code_file(dir.join("entries.rs"), registry, true, &info).unwrap();
+    let code = read_to_string(dir.join("entries.rs")).unwrap();
+
+    for alias in &["LowerCase", "TooShort", "Empty", "NotAscii", "InvalidFirst"] {
+        let line = format!("alias: \"{}\", vr: UN, vr2: None, vm:", alias);
+        assert!(code.contains(&line), "missing `{}`", line);
+    }
+    assert!(code.contains(r#"alias: "InvalidSecond", vr: US, vr2: None /* or ss */, vm:"#));
+}
@@ -0,0 +1,70 @@
+//! Checks that a directory of XML parts is read as a single dictionary.
+mod common;
+
+use common::{book, read_entries, registry, row, table, tbody, TempDir};
+use dicom_dictionary_builder::{merge_entries, xml_parts, Entry, XmlEntryIterator};
+use std::fs::{create_dir_all, write, File};
+use std::io::BufReader;
+
+/// A part with the registry of data elements,
+/// with the given observation on _Patient ID_.
+fn table_part(patient_id_obs: &str) -> String {
+    registry(&[
+        row("(0010,0020)", "Patient ID", "PatientID", "LO").obs(patient_id_obs),
+        row("(0008,0016)", "SOP Class UID", "SOPClassUID", "UI"),
+    ])
+}
+
+/// A part with another table.
+fn other_part() -> String {
+    book(&[table(
+        "table_7-1",
+        &[tbody(&[row(
+            "(0002,0010)",
+            "Transfer Syntax UID",
+            "TransferSyntaxUID",
+            "UI",
+        )])],
+    )])
+}
+
+/// Read the entries of all the XML parts in the directory.
+fn read_parts(dir: &std::path::Path) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for part in xml_parts(dir).unwrap() {
+        let file = BufReader::new(File::open(part).unwrap());
+        entries.extend(XmlEntryIterator::with_trim(file, true).map(Result::unwrap));
+    }
+    merge_entries(entries)
+}
+
+#[test]
+fn reads_the_table_from_any_part() {
+    let dir = TempDir::new("parts");
+    create_dir_all(dir.join("nested.xml")).unwrap();
+    write(dir.join("part06_1.xml"), other_part()).unwrap();
+    write(dir.join("part06_2.xml"), table_part("")).unwrap();
+    write(dir.join("notes.txt"), table_part("")).unwrap();
+
+    let parts = xml_parts(dir.path()).unwrap();
+    let entries = read_parts(dir.path());
+
+    assert_eq!(parts.len(), 2);
+    let single = read_entries(&table_part(""));
+    // the same entries, in tag order
+    assert_eq!(entries, vec![single[1].clone(), single[0].clone()]);
+}
+
+#[test]
+fn prefers_current_entries_over_retired_ones() {
+    let retired_entries = read_entries(&table_part("RET"));
+    let current_entries = read_entries(&table_part(""));
+    assert!(retired_entries[0].is_retired());
+
+    // whichever comes first
+    let merged = merge_entries(retired_entries.iter().chain(&current_entries).cloned());
+    assert_eq!(merged.len(), 2);
+    assert!(merged.iter().all(|e| !e.is_retired()));
+    let merged = merge_entries(current_entries.iter().chain(&retired_entries).cloned());
+    assert!(merged.iter().all(|e| !e.is_retired()));
+}
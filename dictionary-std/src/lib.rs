@@ -9,7 +9,7 @@
 mod entries;
 
 use crate::entries::ENTRIES;
-use dicom_core::dictionary::{DataDictionary, DictionaryEntryRef, TagRange::*};
+use dicom_core::dictionary::{DataDictionary, DictionaryEntryRef, TagRange::*, Vm};
 use dicom_core::header::{Tag, VR};
 use lazy_static::lazy_static;
 use std::collections::{HashMap, HashSet};
@@ -131,6 +131,9 @@ fn init_dictionary() -> StandardDictionaryRegistry {
     for entry in META_ENTRIES {
         d.index(&entry);
     }
+    for entry in COMMAND_ENTRIES {
+        d.index(entry);
+    }
     d
 }
 
@@ -141,68 +144,300 @@ const META_ENTRIES: &[E<'static>] = &[
         tag: Single(Tag(0x0002, 0x0000)),
         alias: "FileMetaInformationGroupLength",
         vr: VR::UL,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("File Meta Information Group Length"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0001)),
         alias: "FileMetaInformationVersion",
         vr: VR::OB,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("File Meta Information Version"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0002)),
         alias: "MediaStorageSOPClassUID",
         vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Media Storage SOP Class UID"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0003)),
         alias: "MediaStorageSOPInstanceUID",
         vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Media Storage SOP Instance UID"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0010)),
         alias: "TransferSyntaxUID",
         vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Transfer Syntax UID"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0012)),
         alias: "ImplementationClassUID",
         vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Implementation Class UID"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0013)),
         alias: "ImplentationVersionName",
         vr: VR::SH,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Implementation Version Name"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0016)),
         alias: "SourceApplicationEntityTitle",
         vr: VR::AE,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Source Application Entity Title"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0017)),
         alias: "SendingApplicationEntityTitle",
         vr: VR::AE,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Sending Application Entity Title"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0018)),
         alias: "ReceivingApplicationEntityTitle",
         vr: VR::AE,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Receiving Application Entity Title"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0100)),
         alias: "PrivateInformationCreatorUID",
         vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Private Information Creator UID"),
     },
     E {
         tag: Single(Tag(0x0002, 0x0102)),
         alias: "PrivateInformation",
         vr: VR::OB,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Private Information"),
+    },
+];
+
+// command elements (PS3.7), which are not part of the main dictionary
+const COMMAND_ENTRIES: &[E<'static>] = &[
+    E {
+        tag: Single(Tag(0x0000, 0x0000)),
+        alias: "CommandGroupLength",
+        vr: VR::UL,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Command Group Length"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0002)),
+        alias: "AffectedSOPClassUID",
+        vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Affected SOP Class UID"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0003)),
+        alias: "RequestedSOPClassUID",
+        vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Requested SOP Class UID"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0100)),
+        alias: "CommandField",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Command Field"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0110)),
+        alias: "MessageID",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Message ID"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0120)),
+        alias: "MessageIDBeingRespondedTo",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Message ID Being Responded To"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0600)),
+        alias: "MoveDestination",
+        vr: VR::AE,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Move Destination"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0700)),
+        alias: "Priority",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Priority"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0800)),
+        alias: "CommandDataSetType",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Command Data Set Type"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0900)),
+        alias: "Status",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Status"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0901)),
+        alias: "OffendingElement",
+        vr: VR::AT,
+        vr2: None,
+        vm: Some(Vm::Unbounded(1)),
+        name: Some("Offending Element"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0902)),
+        alias: "ErrorComment",
+        vr: VR::LO,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Error Comment"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x0903)),
+        alias: "ErrorID",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Error ID"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1000)),
+        alias: "AffectedSOPInstanceUID",
+        vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Affected SOP Instance UID"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1001)),
+        alias: "RequestedSOPInstanceUID",
+        vr: VR::UI,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Requested SOP Instance UID"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1002)),
+        alias: "EventTypeID",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Event Type ID"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1005)),
+        alias: "AttributeIdentifierList",
+        vr: VR::AT,
+        vr2: None,
+        vm: Some(Vm::Unbounded(1)),
+        name: Some("Attribute Identifier List"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1008)),
+        alias: "ActionTypeID",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Action Type ID"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1020)),
+        alias: "NumberOfRemainingSuboperations",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Number of Remaining Sub-operations"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1021)),
+        alias: "NumberOfCompletedSuboperations",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Number of Completed Sub-operations"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1022)),
+        alias: "NumberOfFailedSuboperations",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Number of Failed Sub-operations"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1023)),
+        alias: "NumberOfWarningSuboperations",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Number of Warning Sub-operations"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1030)),
+        alias: "MoveOriginatorApplicationEntityTitle",
+        vr: VR::AE,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Move Originator Application Entity Title"),
+    },
+    E {
+        tag: Single(Tag(0x0000, 0x1031)),
+        alias: "MoveOriginatorMessageID",
+        vr: VR::US,
+        vr2: None,
+        vm: Some(Vm::Bounded(1, 1)),
+        name: Some("Move Originator Message ID"),
     },
 ];
 
 #[cfg(test)]
 mod tests {
     use super::StandardDataDictionary;
-    use dicom_core::dictionary::{DataDictionary, DictionaryEntryRef, TagRange::*};
+    use dicom_core::dictionary::{DataDictionary, DictionaryEntryRef, TagRange::*, Vm};
     use dicom_core::header::{Tag, VR};
 
     // tests for just a few attributes to make sure that the entries
@@ -211,23 +446,27 @@ mod tests {
     fn smoke_test() {
         let dict = StandardDataDictionary::default();
 
-        assert_eq!(
-            dict.by_name("PatientName"),
-            Some(&DictionaryEntryRef {
-                tag: Single(Tag(0x0010, 0x0010)),
-                alias: "PatientName",
-                vr: VR::PN,
-            })
-        );
+        // the value multiplicity and name are only known
+        // once the entries are generated from a source which has them
+        let patient_name = dict
+            .by_name("PatientName")
+            .expect("Patient's Name attribute should exist");
+        assert_eq!(patient_name.tag, Single(Tag(0x0010, 0x0010)));
+        assert_eq!(patient_name.alias, "PatientName");
+        assert_eq!(patient_name.vr, VR::PN);
+        assert_eq!(patient_name.vr2, None);
+        assert!(matches!(patient_name.vm, None | Some(Vm::Bounded(1, 1))));
+        assert!(matches!(patient_name.name, None | Some("Patient's Name")));
 
-        assert_eq!(
-            dict.by_name("Modality"),
-            Some(&DictionaryEntryRef {
-                tag: Single(Tag(0x0008, 0x0060)),
-                alias: "Modality",
-                vr: VR::CS,
-            })
-        );
+        let modality = dict
+            .by_name("Modality")
+            .expect("Modality attribute should exist");
+        assert_eq!(modality.tag, Single(Tag(0x0008, 0x0060)));
+        assert_eq!(modality.alias, "Modality");
+        assert_eq!(modality.vr, VR::CS);
+        assert_eq!(modality.vr2, None);
+        assert!(matches!(modality.vm, None | Some(Vm::Bounded(1, 1))));
+        assert!(matches!(modality.name, None | Some("Modality")));
 
         let pixel_data = dict
             .by_tag(Tag(0x7FE0, 0x0010))
@@ -252,4 +491,100 @@ mod tests {
         assert_eq!(overlay_data.alias, "OverlayData");
         assert!(overlay_data.vr == VR::OB || overlay_data.vr == VR::OW);
     }
+
+    #[test]
+    fn command_entries() {
+        let dict = StandardDataDictionary;
+
+        assert_eq!(
+            dict.by_name("CommandField"),
+            Some(&DictionaryEntryRef {
+                tag: Single(Tag(0x0000, 0x0100)),
+                alias: "CommandField",
+                vr: VR::US,
+                vr2: None,
+                vm: Some(Vm::Bounded(1, 1)),
+                name: Some("Command Field"),
+            })
+        );
+
+        let group_length = dict
+            .by_tag(Tag(0x0000, 0x0000))
+            .expect("Command Group Length attribute should exist");
+        assert_eq!(group_length.alias, "CommandGroupLength");
+        assert_eq!(group_length.vr, VR::UL);
+
+        let status = dict
+            .by_tag(Tag(0x0000, 0x0900))
+            .expect("Status attribute should exist");
+        assert_eq!(status.alias, "Status");
+        assert_eq!(status.vr, VR::US);
+    }
+
+    #[test]
+    fn conditional_vr_entries() {
+        use dicom_core::dictionary::DictionaryEntry;
+
+        let dict = StandardDataDictionary;
+
+        let smallest = dict
+            .by_name("SmallestImagePixelValue")
+            .expect("Smallest Image Pixel Value attribute should exist");
+        assert_eq!(smallest.vr(), VR::US);
+        assert_eq!(smallest.alternative_vr(), Some(VR::SS));
+
+        let pixel_data = dict
+            .by_tag(Tag(0x7FE0, 0x0010))
+            .expect("Pixel Data attribute should exist");
+        assert_eq!(pixel_data.vr(), VR::OB);
+        assert_eq!(pixel_data.alternative_vr(), Some(VR::OW));
+
+        // attributes with a single VR have no alternative
+        let modality = dict
+            .by_name("Modality")
+            .expect("Modality attribute should exist");
+        assert_eq!(modality.alternative_vr(), None);
+    }
+
+    #[test]
+    fn value_multiplicity_entries() {
+        use dicom_core::dictionary::DictionaryEntry;
+
+        let dict = StandardDataDictionary;
+
+        let identifiers = dict
+            .by_tag(Tag(0x0000, 0x1005))
+            .expect("Attribute Identifier List attribute should exist");
+        assert_eq!(identifiers.vm(), Some(Vm::Unbounded(1)));
+        assert!(identifiers.vm().unwrap().contains(3));
+        assert!(!identifiers.vm().unwrap().contains(0));
+
+        let transfer_syntax = dict
+            .by_name("TransferSyntaxUID")
+            .expect("Transfer Syntax UID attribute should exist");
+        assert_eq!(transfer_syntax.vm(), Some(Vm::Bounded(1, 1)));
+    }
+
+    #[test]
+    fn display_name_entries() {
+        use dicom_core::dictionary::DictionaryEntry;
+
+        let dict = StandardDataDictionary;
+
+        let transfer_syntax = dict
+            .by_name("TransferSyntaxUID")
+            .expect("Transfer Syntax UID attribute should exist");
+        assert_eq!(transfer_syntax.name(), Some("Transfer Syntax UID"));
+
+        let remaining = dict
+            .by_tag(Tag(0x0000, 0x1020))
+            .expect("Number of Remaining Sub-operations attribute should exist");
+        assert_eq!(remaining.name(), Some("Number of Remaining Sub-operations"));
+
+        // standard entries have the name of PS3.6 once regenerated
+        let patient_name = dict
+            .by_tag(Tag(0x0010, 0x0010))
+            .expect("Patient's Name attribute should exist");
+        assert!(matches!(patient_name.name(), None | Some("Patient's Name")));
+    }
 }
@@ -16,7 +16,18 @@ pub struct ExplicitVRBigEndianDecoder {
 }
 
 impl Decode for ExplicitVRBigEndianDecoder {
-    fn decode_header<S>(&self, mut source: &mut S) -> Result<(DataElementHeader, usize)>
+    fn decode_header<S>(&self, source: &mut S) -> Result<(DataElementHeader, usize)>
+    where
+        S: ?Sized + Read,
+    {
+        let (header, bytes_read, _) = Decode::decode_header_with_raw_vr(self, source)?;
+        Ok((header, bytes_read))
+    }
+
+    fn decode_header_with_raw_vr<S>(
+        &self,
+        mut source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
     where
         S: ?Sized + Read,
     {
@@ -31,12 +42,14 @@ impl Decode for ExplicitVRBigEndianDecoder {
             return Ok((
                 DataElementHeader::new((group, element), VR::UN, Length(len)),
                 8, // tag + len
+                None,
             ));
         }
 
         // retrieve explicit VR
         source.read_exact(&mut buf[0..2]).context(ReadVr)?;
-        let vr = VR::from_binary([buf[0], buf[1]]).unwrap_or(VR::UN);
+        let raw_vr = [buf[0], buf[1]];
+        let vr = VR::from_binary(raw_vr).unwrap_or(VR::UN);
 
         let bytes_read;
 
@@ -69,6 +82,7 @@ impl Decode for ExplicitVRBigEndianDecoder {
         Ok((
             DataElementHeader::new((group, element), vr, Length(len)),
             bytes_read,
+            Some(raw_vr),
         ))
     }
 
@@ -117,6 +131,14 @@ where
     fn decode_tag(&self, source: &mut S) -> Result<Tag> {
         Decode::decode_tag(self, source)
     }
+
+    #[inline]
+    fn decode_header_with_raw_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        Decode::decode_header_with_raw_vr(self, source)
+    }
 }
 
 #[cfg(test)]
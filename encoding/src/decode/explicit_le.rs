@@ -15,7 +15,18 @@ pub struct ExplicitVRLittleEndianDecoder {
 }
 
 impl Decode for ExplicitVRLittleEndianDecoder {
-    fn decode_header<S>(&self, mut source: &mut S) -> Result<(DataElementHeader, usize)>
+    fn decode_header<S>(&self, source: &mut S) -> Result<(DataElementHeader, usize)>
+    where
+        S: ?Sized + Read,
+    {
+        let (header, bytes_read, _) = Decode::decode_header_with_raw_vr(self, source)?;
+        Ok((header, bytes_read))
+    }
+
+    fn decode_header_with_raw_vr<S>(
+        &self,
+        mut source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
     where
         S: ?Sized + Read,
     {
@@ -30,12 +41,14 @@ impl Decode for ExplicitVRLittleEndianDecoder {
             return Ok((
                 DataElementHeader::new((group, element), VR::UN, Length(len)),
                 8, // tag + len
+                None,
             ));
         }
 
         // retrieve explicit VR
         source.read_exact(&mut buf[0..2]).context(ReadVr)?;
-        let vr = VR::from_binary([buf[0], buf[1]]).unwrap_or(VR::UN);
+        let raw_vr = [buf[0], buf[1]];
+        let vr = VR::from_binary(raw_vr).unwrap_or(VR::UN);
         let bytes_read;
 
         // retrieve data length
@@ -67,6 +80,7 @@ impl Decode for ExplicitVRLittleEndianDecoder {
         Ok((
             DataElementHeader::new((group, element), vr, Length(len)),
             bytes_read,
+            Some(raw_vr),
         ))
     }
 
@@ -115,6 +129,14 @@ where
     fn decode_tag(&self, source: &mut S) -> Result<Tag> {
         Decode::decode_tag(self, source)
     }
+
+    #[inline]
+    fn decode_header_with_raw_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        Decode::decode_header_with_raw_vr(self, source)
+    }
 }
 
 #[cfg(test)]
@@ -202,6 +224,35 @@ mod tests {
         0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
     ];
 
+    #[test]
+    fn decode_header_with_raw_vr() {
+        #[rustfmt::skip]
+        let raw: &[u8] = &[
+            // (0009,1001), nonstandard VR "XY", reserved, length 4
+            0x09, 0x00, 0x01, 0x10, b'X', b'Y', 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            // item delimiter
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let dec = ExplicitVRLittleEndianDecoder::default();
+        let mut cursor = Cursor::new(raw);
+
+        let (elem, bytes_read, raw_vr) = dec
+            .decode_header_with_raw_vr(&mut cursor)
+            .expect("should find an element");
+        assert_eq!(elem.tag(), Tag(0x0009, 0x1001));
+        assert_eq!(elem.vr(), VR::UN);
+        assert_eq!(elem.length(), Length(4));
+        assert_eq!(bytes_read, 12);
+        assert_eq!(raw_vr, Some(*b"XY"));
+
+        // delimiters have no VR
+        let (elem, _, raw_vr) = dec
+            .decode_header_with_raw_vr(&mut cursor)
+            .expect("should find a delimiter");
+        assert_eq!(elem.tag(), Tag(0xFFFE, 0xE00D));
+        assert_eq!(raw_vr, None);
+    }
+
     #[test]
     fn decode_items() {
         let dec = ExplicitVRLittleEndianDecoder::default();
@@ -319,6 +319,27 @@ pub trait Decode {
     fn decode_tag<S>(&self, source: &mut S) -> Result<Tag>
     where
         S: ?Sized + Read;
+
+    /// Fetch and decode the next data element header from the given source,
+    /// as in [`decode_header`],
+    /// also returning the two bytes of the value representation
+    /// exactly as found in the source.
+    ///
+    /// The VR bytes are only available in explicit VR encodings,
+    /// and not for item or sequence delimiters.
+    /// The default implementation never provides them.
+    ///
+    /// [`decode_header`]: #tymethod.decode_header
+    fn decode_header_with_raw_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + Read,
+    {
+        let (header, bytes_read) = self.decode_header(source)?;
+        Ok((header, bytes_read, None))
+    }
 }
 
 impl<T: ?Sized> Decode for Box<T>
@@ -345,6 +366,16 @@ where
     {
         (**self).decode_tag(source)
     }
+
+    fn decode_header_with_raw_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + Read,
+    {
+        (**self).decode_header_with_raw_vr(source)
+    }
 }
 
 impl<'a, T: ?Sized> Decode for &'a T
@@ -371,6 +402,16 @@ where
     {
         (**self).decode_tag(source)
     }
+
+    fn decode_header_with_raw_vr<S>(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)>
+    where
+        S: ?Sized + Read,
+    {
+        (**self).decode_header_with_raw_vr(source)
+    }
 }
 
 /** Type trait for reading and decoding DICOM data elements from a specific source
@@ -400,6 +441,24 @@ pub trait DecodeFrom<S: ?Sized + Read> {
 
     /// Decode a DICOM attribute tag from the given source.
     fn decode_tag(&self, source: &mut S) -> Result<Tag>;
+
+    /// Fetch and decode the next data element header from the given source,
+    /// as in [`decode_header`],
+    /// also returning the two bytes of the value representation
+    /// exactly as found in the source.
+    ///
+    /// The VR bytes are only available in explicit VR encodings,
+    /// and not for item or sequence delimiters.
+    /// The default implementation never provides them.
+    ///
+    /// [`decode_header`]: #tymethod.decode_header
+    fn decode_header_with_raw_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        let (header, bytes_read) = self.decode_header(source)?;
+        Ok((header, bytes_read, None))
+    }
 }
 
 impl<S: ?Sized, T: ?Sized> DecodeFrom<S> for &T
@@ -418,6 +477,13 @@ where
     fn decode_tag(&self, source: &mut S) -> Result<Tag> {
         (**self).decode_tag(source)
     }
+
+    fn decode_header_with_raw_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        (**self).decode_header_with_raw_vr(source)
+    }
 }
 
 impl<S: ?Sized, T: ?Sized> DecodeFrom<S> for Box<T>
@@ -436,6 +502,13 @@ where
     fn decode_tag(&self, source: &mut S) -> Result<Tag> {
         (**self).decode_tag(source)
     }
+
+    fn decode_header_with_raw_vr(
+        &self,
+        source: &mut S,
+    ) -> Result<(DataElementHeader, usize, Option<[u8; 2]>)> {
+        (**self).decode_header_with_raw_vr(source)
+    }
 }
 
 #[cfg(test)]
@@ -15,10 +15,16 @@
 //! At the moment, text encoding support is limited.
 //! Please see [`SpecificCharacterSet`] for a complete enumeration
 //! of all supported text encodings.
+//! Character sets with code extensions,
+//! declared by a multi-valued Specific Character Set,
+//! are handled by [`Iso2022CharacterSetCodec`].
 //!
 //! [`SpecificCharacterSet`]: ./enum.SpecificCharacterSet.html
+//! [`Iso2022CharacterSetCodec`]: ./struct.Iso2022CharacterSetCodec.html
 
-use encoding::all::{GB18030, ISO_8859_1, ISO_8859_2, ISO_8859_3, ISO_8859_4, ISO_8859_5, UTF_8};
+use encoding::all::{
+    EUC_JP, GB18030, ISO_8859_1, ISO_8859_2, ISO_8859_3, ISO_8859_4, ISO_8859_5, UTF_8, WINDOWS_949,
+};
 use encoding::{DecoderTrap, EncoderTrap, Encoding, RawDecoder, StringWriter};
 use snafu::{Backtrace, Snafu};
 use std::borrow::Cow;
@@ -80,6 +86,16 @@ pub trait TextCodec {
     /// feature multiple text values by using the backslash character ('\')
     /// as the value delimiter.
     fn encode(&self, text: &str) -> EncodeResult<Vec<u8>>;
+
+    /// Split the given byte buffer into the encoded text values
+    /// delimited by backslash characters ('\\').
+    ///
+    /// The default implementation splits at every backslash byte.
+    /// Codecs in which that byte may also be part of a multi-byte character
+    /// should only split at actual value delimiters.
+    fn split_values<'a>(&self, text: &'a [u8]) -> Vec<&'a [u8]> {
+        text.split(|b| *b == b'\\').collect()
+    }
 }
 
 impl<T: ?Sized> TextCodec for Box<T>
@@ -97,6 +113,10 @@ where
     fn encode(&self, text: &str) -> EncodeResult<Vec<u8>> {
         self.as_ref().encode(text)
     }
+
+    fn split_values<'b>(&self, text: &'b [u8]) -> Vec<&'b [u8]> {
+        self.as_ref().split_values(text)
+    }
 }
 
 impl<'a, T: ?Sized> TextCodec for &'a T
@@ -114,6 +134,10 @@ where
     fn encode(&self, text: &str) -> EncodeResult<Vec<u8>> {
         (**self).encode(text)
     }
+
+    fn split_values<'b>(&self, text: &'b [u8]) -> Vec<&'b [u8]> {
+        (**self).split_values(text)
+    }
 }
 
 /// Type alias for a type erased text codec.
@@ -203,7 +227,13 @@ fn decode_text_trap(
     input: &[u8],
     output: &mut dyn StringWriter,
 ) -> bool {
-    let c = input[0];
+    write_octal_escape(input[0], output);
+    true
+}
+
+/// Write the given byte to the output as a backslash
+/// followed by its three octal digits.
+fn write_octal_escape(c: u8, output: &mut dyn StringWriter) {
     let o0 = c & 7;
     let o1 = (c & 56) >> 3;
     let o2 = (c & 192) >> 6;
@@ -211,7 +241,6 @@ fn decode_text_trap(
     output.write_char((o2 + b'0') as char);
     output.write_char((o1 + b'0') as char);
     output.write_char((o0 + b'0') as char);
-    true
 }
 
 /// Create and implement a character set type using the `encoding` crate.
@@ -275,6 +304,457 @@ decl_character_set!(IsoIr144CharacterSetCodec, "ISO_IR 144", ISO_8859_5);
 decl_character_set!(Utf8CharacterSetCodec, "ISO_IR 192", UTF_8);
 decl_character_set!(Gb18030CharacterSetCodec, "GB18030", GB18030);
 
+/// The escape character, introducing ISO 2022 escape sequences.
+const ESC: u8 = 0x1b;
+
+/// A character set designated to the G0 code element (bytes `0x21`–`0x7E`)
+/// through ISO 2022 code extensions.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+enum G0Set {
+    /// ISO-IR 6 (ASCII)
+    Ascii,
+    /// ISO-IR 14, the romaji part of JIS X 0201
+    Romaji,
+    /// ISO-IR 87 (JIS X 0208), two bytes per character
+    JisX0208,
+    /// ISO-IR 159 (JIS X 0212), two bytes per character
+    JisX0212,
+}
+
+impl G0Set {
+    fn escape(self) -> &'static [u8] {
+        match self {
+            G0Set::Ascii => b"\x1b(B",
+            G0Set::Romaji => b"\x1b(J",
+            G0Set::JisX0208 => b"\x1b$B",
+            G0Set::JisX0212 => b"\x1b$(D",
+        }
+    }
+
+    fn is_multi_byte(self) -> bool {
+        match self {
+            G0Set::Ascii | G0Set::Romaji => false,
+            G0Set::JisX0208 | G0Set::JisX0212 => true,
+        }
+    }
+
+    /// Encode a single character into `out`,
+    /// returning false if it is not in this character set.
+    fn encode_char(self, c: char, out: &mut Vec<u8>) -> bool {
+        match self {
+            G0Set::Ascii if c.is_ascii() => out.push(c as u8),
+            G0Set::Romaji if c.is_ascii() && c != '~' => out.push(c as u8),
+            G0Set::Romaji if c == '\u{203e}' => out.push(0x7e),
+            G0Set::JisX0208 => match encode_char_with(EUC_JP, c) {
+                Some(b) if b.len() == 2 && b[0] >= 0xa1 && b[1] >= 0xa1 => {
+                    out.extend(b.iter().map(|b| b & 0x7f))
+                }
+                _ => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// A character set designated to the G1 code element (bytes `0xA1`–`0xFE`)
+/// through ISO 2022 code extensions.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+enum G1Set {
+    /// no character set was designated
+    Unset,
+    /// ISO-IR 100 (ISO-8859-1)
+    IsoIr100,
+    /// ISO-IR 101 (ISO-8859-2)
+    IsoIr101,
+    /// ISO-IR 109 (ISO-8859-3)
+    IsoIr109,
+    /// ISO-IR 110 (ISO-8859-4)
+    IsoIr110,
+    /// ISO-IR 144 (ISO-8859-5)
+    IsoIr144,
+    /// ISO-IR 13, the katakana part of JIS X 0201
+    Katakana,
+    /// ISO-IR 149 (KS X 1001), two bytes per character
+    KsX1001,
+}
+
+impl G1Set {
+    fn escape(self) -> &'static [u8] {
+        match self {
+            G1Set::Unset => b"",
+            G1Set::IsoIr100 => b"\x1b-A",
+            G1Set::IsoIr101 => b"\x1b-B",
+            G1Set::IsoIr109 => b"\x1b-C",
+            G1Set::IsoIr110 => b"\x1b-D",
+            G1Set::IsoIr144 => b"\x1b-L",
+            G1Set::Katakana => b"\x1b)I",
+            G1Set::KsX1001 => b"\x1b$)C",
+        }
+    }
+
+    /// The single byte encoding of this character set, if any.
+    fn single_byte_encoding(self) -> Option<&'static dyn Encoding> {
+        match self {
+            G1Set::IsoIr100 => Some(ISO_8859_1),
+            G1Set::IsoIr101 => Some(ISO_8859_2),
+            G1Set::IsoIr109 => Some(ISO_8859_3),
+            G1Set::IsoIr110 => Some(ISO_8859_4),
+            G1Set::IsoIr144 => Some(ISO_8859_5),
+            G1Set::Unset | G1Set::Katakana | G1Set::KsX1001 => None,
+        }
+    }
+
+    /// Encode a single character into `out`,
+    /// returning false if it is not in this character set.
+    fn encode_char(self, c: char, out: &mut Vec<u8>) -> bool {
+        match self {
+            G1Set::Unset => return false,
+            G1Set::Katakana => match c {
+                '\u{ff61}'..='\u{ff9f}' => out.push((c as u32 - 0xff61 + 0xa1) as u8),
+                _ => return false,
+            },
+            G1Set::KsX1001 => match encode_char_with(WINDOWS_949, c) {
+                Some(b) if b.len() == 2 && b[0] >= 0xa1 && b[1] >= 0xa1 => out.extend(b),
+                _ => return false,
+            },
+            _ => {
+                let encoding = self.single_byte_encoding().unwrap();
+                match encode_char_with(encoding, c) {
+                    Some(b) if b.len() == 1 && b[0] >= 0xa0 => out.extend(b),
+                    _ => return false,
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Encode a single character with the given encoding.
+fn encode_char_with(encoding: &dyn Encoding, c: char) -> Option<Vec<u8>> {
+    let mut buf = [0; 4];
+    encoding
+        .encode(c.encode_utf8(&mut buf), EncoderTrap::Strict)
+        .ok()
+}
+
+/// Parse the escape sequence at the start of `text`,
+/// returning its length and the character set designated.
+fn parse_escape(text: &[u8]) -> Option<(usize, Result<G0Set, G1Set>)> {
+    let designation = match text.get(1..3)? {
+        b"(B" => Ok(G0Set::Ascii),
+        b"(J" => Ok(G0Set::Romaji),
+        b"$B" | b"$@" => Ok(G0Set::JisX0208),
+        b")I" => Err(G1Set::Katakana),
+        b"-A" => Err(G1Set::IsoIr100),
+        b"-B" => Err(G1Set::IsoIr101),
+        b"-C" => Err(G1Set::IsoIr109),
+        b"-D" => Err(G1Set::IsoIr110),
+        b"-L" => Err(G1Set::IsoIr144),
+        b"$(" if text.get(3) == Some(&b'D') => return Some((4, Ok(G0Set::JisX0212))),
+        b"$)" if text.get(3) == Some(&b'C') => return Some((4, Err(G1Set::KsX1001))),
+        _ => return None,
+    };
+    Some((3, designation))
+}
+
+/// Check whether the given byte is a delimiter or control character
+/// before which the initial character sets are active again.
+fn is_reset_byte(b: u8) -> bool {
+    matches!(b, b'\\' | b'^' | b'=' | b'\r' | b'\n' | b'\t' | 0x0c)
+}
+
+/// A character set which may be declared in a multi-valued
+/// Specific Character Set (0008, 0005) with code extensions.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+enum Iso2022Term {
+    IsoIr6,
+    IsoIr100,
+    IsoIr101,
+    IsoIr109,
+    IsoIr110,
+    IsoIr144,
+    IsoIr13,
+    IsoIr87,
+    IsoIr159,
+    IsoIr149,
+}
+
+impl Iso2022Term {
+    fn from_code(code: &str) -> Option<Self> {
+        use self::Iso2022Term::*;
+        match code.trim() {
+            "" | "ISO_IR 6" | "ISO 2022 IR 6" => Some(IsoIr6),
+            "ISO_IR 100" | "ISO 2022 IR 100" => Some(IsoIr100),
+            "ISO_IR 101" | "ISO 2022 IR 101" => Some(IsoIr101),
+            "ISO_IR 109" | "ISO 2022 IR 109" => Some(IsoIr109),
+            "ISO_IR 110" | "ISO 2022 IR 110" => Some(IsoIr110),
+            "ISO_IR 144" | "ISO 2022 IR 144" => Some(IsoIr144),
+            "ISO_IR 13" | "ISO 2022 IR 13" => Some(IsoIr13),
+            "ISO 2022 IR 87" => Some(IsoIr87),
+            "ISO 2022 IR 159" => Some(IsoIr159),
+            "ISO 2022 IR 149" => Some(IsoIr149),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        use self::Iso2022Term::*;
+        match self {
+            IsoIr6 => "ISO 2022 IR 6",
+            IsoIr100 => "ISO 2022 IR 100",
+            IsoIr101 => "ISO 2022 IR 101",
+            IsoIr109 => "ISO 2022 IR 109",
+            IsoIr110 => "ISO 2022 IR 110",
+            IsoIr144 => "ISO 2022 IR 144",
+            IsoIr13 => "ISO 2022 IR 13",
+            IsoIr87 => "ISO 2022 IR 87",
+            IsoIr159 => "ISO 2022 IR 159",
+            IsoIr149 => "ISO 2022 IR 149",
+        }
+    }
+
+    fn g0(self) -> Option<G0Set> {
+        use self::Iso2022Term::*;
+        match self {
+            IsoIr6 | IsoIr100 | IsoIr101 | IsoIr109 | IsoIr110 | IsoIr144 => Some(G0Set::Ascii),
+            IsoIr13 => Some(G0Set::Romaji),
+            IsoIr87 => Some(G0Set::JisX0208),
+            IsoIr159 => Some(G0Set::JisX0212),
+            IsoIr149 => None,
+        }
+    }
+
+    fn g1(self) -> Option<G1Set> {
+        use self::Iso2022Term::*;
+        match self {
+            IsoIr100 => Some(G1Set::IsoIr100),
+            IsoIr101 => Some(G1Set::IsoIr101),
+            IsoIr109 => Some(G1Set::IsoIr109),
+            IsoIr110 => Some(G1Set::IsoIr110),
+            IsoIr144 => Some(G1Set::IsoIr144),
+            IsoIr13 => Some(G1Set::Katakana),
+            IsoIr149 => Some(G1Set::KsX1001),
+            IsoIr6 | IsoIr87 | IsoIr159 => None,
+        }
+    }
+}
+
+/// Data type for text with ISO 2022 code extensions,
+/// as declared by a multi-valued Specific Character Set (0008, 0005)
+/// such as `ISO 2022 IR 6\ISO 2022 IR 87`.
+///
+/// The character sets of the first value are active
+/// at the start of each value,
+/// and escape sequences in the text switch to other character sets.
+/// The initial character sets are active again
+/// after value delimiters (`\`), person name delimiters (`^` and `=`)
+/// and control characters.
+///
+/// Decoding accepts the escape sequences of all supported character sets,
+/// whereas encoding only switches to the declared ones.
+/// ISO-IR 159 (JIS X 0212) is only supported for decoding.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct Iso2022CharacterSetCodec {
+    /// the declared character sets, starting with the initial one
+    terms: Vec<Iso2022Term>,
+    /// the initial G0 character set
+    g0: G0Set,
+    /// the initial G1 character set
+    g1: G1Set,
+}
+
+impl Iso2022CharacterSetCodec {
+    /// Create a codec from the values of a Specific Character Set element.
+    ///
+    /// Returns `None` if any of the character sets is not supported,
+    /// or if the first one cannot be the initial character set.
+    /// An empty first value stands for the default character repertoire.
+    pub fn from_codes<'a, I>(codes: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let terms = codes
+            .into_iter()
+            .map(Iso2022Term::from_code)
+            .collect::<Option<Vec<_>>>()?;
+        let initial = *terms.first()?;
+        let g0 = initial.g0().unwrap_or(G0Set::Ascii);
+        if g0.is_multi_byte() {
+            return None;
+        }
+        let g1 = initial.g1().unwrap_or(G1Set::Unset);
+        Some(Iso2022CharacterSetCodec { terms, g0, g1 })
+    }
+
+    /// Find a declared character set containing the given character,
+    /// writing the escape sequence and the encoded character to `out`.
+    fn encode_extended(&self, c: char, g0: &mut G0Set, g1: &mut G1Set, out: &mut Vec<u8>) -> bool {
+        let mut buf = Vec::with_capacity(2);
+        for term in &self.terms {
+            if let Some(set) = term.g0().filter(|set| set != g0) {
+                if set.encode_char(c, &mut buf) {
+                    out.extend(set.escape());
+                    out.extend(buf);
+                    *g0 = set;
+                    return true;
+                }
+            }
+            if let Some(set) = term.g1().filter(|set| set != g1) {
+                if set.encode_char(c, &mut buf) {
+                    out.extend(set.escape());
+                    out.extend(buf);
+                    *g1 = set;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+impl TextCodec for Iso2022CharacterSetCodec {
+    fn name(&self) -> &'static str {
+        self.terms[0].name()
+    }
+
+    fn decode(&self, text: &[u8]) -> DecodeResult<String> {
+        let mut out = String::with_capacity(text.len());
+        let (mut g0, mut g1) = (self.g0, self.g1);
+        let trap = DecoderTrap::Call(decode_text_trap);
+        let mut i = 0;
+        while i < text.len() {
+            let b = text[i];
+            if b == ESC {
+                let (len, designation) = parse_escape(&text[i..]).ok_or_else(|| {
+                    DecodeCustom {
+                        message: "unsupported ISO 2022 escape sequence",
+                    }
+                    .build()
+                })?;
+                match designation {
+                    Ok(set) => g0 = set,
+                    Err(set) => g1 = set,
+                }
+                i += len;
+                continue;
+            }
+
+            let len = if b < 0x80 {
+                if g0.is_multi_byte() && b > 0x20 && b < 0x7f && i + 1 < text.len() {
+                    let c = [0x8f, b | 0x80, text[i + 1] | 0x80];
+                    let c = if g0 == G0Set::JisX0212 {
+                        &c[..]
+                    } else {
+                        &c[1..]
+                    };
+                    EUC_JP
+                        .decode_to(c, trap, &mut out)
+                        .map_err(|message| DecodeCustom { message }.build())?;
+                    2
+                } else {
+                    if is_reset_byte(b) {
+                        g0 = self.g0;
+                        g1 = self.g1;
+                    }
+                    match (g0, b) {
+                        (G0Set::Romaji, 0x7e) => out.push('\u{203e}'),
+                        _ => out.push(b as char),
+                    }
+                    1
+                }
+            } else {
+                match g1 {
+                    G1Set::KsX1001 if i + 1 < text.len() => {
+                        WINDOWS_949
+                            .decode_to(&text[i..i + 2], trap, &mut out)
+                            .map_err(|message| DecodeCustom { message }.build())?;
+                        2
+                    }
+                    G1Set::Katakana if (0xa1..=0xdf).contains(&b) => {
+                        out.push(std::char::from_u32(b as u32 - 0xa1 + 0xff61).unwrap());
+                        1
+                    }
+                    set => {
+                        match set.single_byte_encoding() {
+                            Some(encoding) => encoding
+                                .decode_to(&text[i..=i], trap, &mut out)
+                                .map_err(|message| DecodeCustom { message }.build())?,
+                            None => write_octal_escape(b, &mut out),
+                        }
+                        1
+                    }
+                }
+            };
+            i += len;
+        }
+        Ok(out)
+    }
+
+    fn encode(&self, text: &str) -> EncodeResult<Vec<u8>> {
+        let mut out = Vec::with_capacity(text.len());
+        let (mut g0, mut g1) = (self.g0, self.g1);
+        for c in text.chars() {
+            if c.is_ascii() && is_reset_byte(c as u8) {
+                if g0 != self.g0 {
+                    out.extend(self.g0.escape());
+                }
+                g0 = self.g0;
+                g1 = self.g1;
+                out.push(c as u8);
+                continue;
+            }
+            if g0.encode_char(c, &mut out)
+                || g1.encode_char(c, &mut out)
+                || self.encode_extended(c, &mut g0, &mut g1, &mut out)
+            {
+                continue;
+            }
+            return EncodeCustom {
+                message: format!("character {:?} is not in any declared character set", c),
+            }
+            .fail();
+        }
+        if g0 != self.g0 {
+            out.extend(self.g0.escape());
+        }
+        Ok(out)
+    }
+
+    fn split_values<'a>(&self, text: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut values = Vec::new();
+        let mut g0 = self.g0;
+        let (mut start, mut i) = (0, 0);
+        while i < text.len() {
+            match text[i] {
+                ESC => {
+                    match parse_escape(&text[i..]) {
+                        Some((len, designation)) => {
+                            if let Ok(set) = designation {
+                                g0 = set;
+                            }
+                            i += len;
+                        }
+                        None => i += 1,
+                    }
+                    continue;
+                }
+                b if g0.is_multi_byte() && b > 0x20 && b < 0x7f => i += 1,
+                b'\\' => {
+                    values.push(&text[start..i]);
+                    start = i + 1;
+                    g0 = self.g0;
+                }
+                b if is_reset_byte(b) => g0 = self.g0,
+                _ => {}
+            }
+            i += 1;
+        }
+        values.push(&text[start..]);
+        values
+    }
+}
+
 /// The result of a text validation procedure (please see [`validate_iso_8859`]).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TextValidationOutcome {
@@ -404,4 +884,87 @@ mod tests {
             b"\xb8\xd2\xd0\xdd\xda\xde\xd2^\xb0\xdd\xd4\xe0\xd5\xd9",
         );
     }
+
+    #[test]
+    fn iso_2022_ir_87_person_name() {
+        // PS3.5 Annex H, example 1
+        let codec = Iso2022CharacterSetCodec::from_codes(vec!["", "ISO 2022 IR 87"])
+            .expect("Should be fully supported");
+        assert_eq!(codec.name(), "ISO 2022 IR 6");
+        test_codec(
+            &codec,
+            "Yamada^Tarou=山田^太郎=やまだ^たろう",
+            b"Yamada^Tarou=\
+              \x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B=\
+              \x1b$B$d$^$@\x1b(B^\x1b$B$?$m$&\x1b(B",
+        );
+    }
+
+    #[test]
+    fn iso_2022_ir_13_ir_87_person_name() {
+        // PS3.5 Annex H, example 2
+        let codec = Iso2022CharacterSetCodec::from_codes(vec!["ISO 2022 IR 13", "ISO 2022 IR 87"])
+            .expect("Should be fully supported");
+        test_codec(
+            &codec,
+            "ﾔﾏﾀﾞ^ﾀﾛｳ=山田^太郎=やまだ^たろう",
+            b"\xd4\xcf\xc0\xde^\xc0\xdb\xb3=\
+              \x1b$B;3ED\x1b(J^\x1b$BB@O:\x1b(J=\
+              \x1b$B$d$^$@\x1b(J^\x1b$B$?$m$&\x1b(J",
+        );
+    }
+
+    #[test]
+    fn iso_2022_ir_149_person_name() {
+        // PS3.5 Annex I
+        let codec = Iso2022CharacterSetCodec::from_codes(vec!["", "ISO 2022 IR 149"])
+            .expect("Should be fully supported");
+        test_codec(
+            &codec,
+            "Hong^Gildong=洪^吉洞=홍^길동",
+            b"Hong^Gildong=\
+              \x1b$)C\xfb\xf3^\x1b$)C\xd1\xce\xd4\xd7=\
+              \x1b$)C\xc8\xab^\x1b$)C\xb1\xe6\xb5\xbf",
+        );
+    }
+
+    #[test]
+    fn iso_2022_decode_only_extensions() {
+        let codec = Iso2022CharacterSetCodec::from_codes(vec!["ISO 2022 IR 100"])
+            .expect("Should be fully supported");
+        // Latin-1 in G1 is initially active, Cyrillic and JIS X 0212 are designated
+        assert_eq!(
+            codec
+                .decode(b"Sim\xf5es^\x1b-L\xb8\xd2\xd0\xdd^\x1b$(D0!\x1b(B=Jo\xe3o")
+                .expect("decoding"),
+            "Simões^Иван^丂=João",
+        );
+        // undeclared character sets are not used for encoding
+        assert!(codec.encode("山田").is_err());
+        assert!(codec.decode(b"\x1b%Gbad").is_err());
+    }
+
+    #[test]
+    fn iso_2022_split_values() {
+        let codec = Iso2022CharacterSetCodec::from_codes(vec!["", "ISO 2022 IR 87"])
+            .expect("Should be fully supported");
+        // the second byte of "ぼ" is a backslash
+        let text = b"\x1b$B$\\\x1b(B\\ABC";
+        assert_eq!(
+            codec.split_values(text),
+            vec![&b"\x1b$B$\\\x1b(B"[..], &b"ABC"[..]]
+        );
+        assert_eq!(codec.decode(codec.split_values(text)[0]).unwrap(), "ぼ");
+        assert_eq!(
+            DefaultCharacterSetCodec.split_values(text),
+            vec![&b"\x1b$B$"[..], &b"\x1b(B"[..], &b"ABC"[..]]
+        );
+    }
+
+    #[test]
+    fn iso_2022_unsupported_codes() {
+        assert!(Iso2022CharacterSetCodec::from_codes(vec!["", "ISO 2022 IR 58"]).is_none());
+        assert!(Iso2022CharacterSetCodec::from_codes(vec!["ISO 2022 IR 87"]).is_none());
+        assert!(Iso2022CharacterSetCodec::from_codes(Vec::new()).is_none());
+    }
 }
@@ -142,6 +142,37 @@ pub enum Codec<A> {
     Dataset(A),
 }
 
+/// The kind of encoding applied to the pixel data
+/// under a given transfer syntax.
+///
+/// This identifies the family of codecs required
+/// to decode the fragments of encapsulated pixel data,
+/// regardless of whether this implementation supports them.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum PixelEncoding {
+    /// Native pixel data, not encapsulated.
+    Native,
+    /// JPEG (ISO/IEC 10918-1), lossy or lossless.
+    Jpeg,
+    /// JPEG-LS (ISO/IEC 14495-1).
+    JpegLs,
+    /// JPEG 2000 (ISO/IEC 15444-1 or 15444-2).
+    Jpeg2000,
+    /// JPIP, where the pixel data is referenced rather than contained.
+    Jpip,
+    /// MPEG-2 video.
+    Mpeg2,
+    /// MPEG-4 AVC/H.264 video.
+    Mpeg4,
+    /// HEVC/H.265 video.
+    Hevc,
+    /// Run-length encoding, as defined in the standard.
+    Rle,
+    /// Any other or unrecognized encoding.
+    Other,
+}
+
 /// An alias for a transfer syntax specifier with no pixel data encapsulation
 /// nor data set deflating.
 pub type AdapterFreeTransferSyntax = TransferSyntax<NeverAdapter>;
@@ -270,6 +301,51 @@ impl<A> TransferSyntax<A> {
         &self.codec
     }
 
+    /// Identify the kind of encoding applied to the pixel data
+    /// under this transfer syntax,
+    /// as determined by its unique identifier.
+    pub fn pixel_encoding(&self) -> PixelEncoding {
+        match self.uid.trim_end_matches('\0') {
+            "1.2.840.10008.1.2"
+            | "1.2.840.10008.1.2.1"
+            | "1.2.840.10008.1.2.1.99"
+            | "1.2.840.10008.1.2.2" => PixelEncoding::Native,
+            "1.2.840.10008.1.2.4.50"
+            | "1.2.840.10008.1.2.4.51"
+            | "1.2.840.10008.1.2.4.52"
+            | "1.2.840.10008.1.2.4.53"
+            | "1.2.840.10008.1.2.4.54"
+            | "1.2.840.10008.1.2.4.55"
+            | "1.2.840.10008.1.2.4.56"
+            | "1.2.840.10008.1.2.4.57"
+            | "1.2.840.10008.1.2.4.58"
+            | "1.2.840.10008.1.2.4.59"
+            | "1.2.840.10008.1.2.4.60"
+            | "1.2.840.10008.1.2.4.61"
+            | "1.2.840.10008.1.2.4.62"
+            | "1.2.840.10008.1.2.4.63"
+            | "1.2.840.10008.1.2.4.64"
+            | "1.2.840.10008.1.2.4.65"
+            | "1.2.840.10008.1.2.4.66"
+            | "1.2.840.10008.1.2.4.70" => PixelEncoding::Jpeg,
+            "1.2.840.10008.1.2.4.80" | "1.2.840.10008.1.2.4.81" => PixelEncoding::JpegLs,
+            "1.2.840.10008.1.2.4.90"
+            | "1.2.840.10008.1.2.4.91"
+            | "1.2.840.10008.1.2.4.92"
+            | "1.2.840.10008.1.2.4.93" => PixelEncoding::Jpeg2000,
+            "1.2.840.10008.1.2.4.94" | "1.2.840.10008.1.2.4.95" => PixelEncoding::Jpip,
+            "1.2.840.10008.1.2.4.100" | "1.2.840.10008.1.2.4.101" => PixelEncoding::Mpeg2,
+            "1.2.840.10008.1.2.4.102"
+            | "1.2.840.10008.1.2.4.103"
+            | "1.2.840.10008.1.2.4.104"
+            | "1.2.840.10008.1.2.4.105"
+            | "1.2.840.10008.1.2.4.106" => PixelEncoding::Mpeg4,
+            "1.2.840.10008.1.2.4.107" | "1.2.840.10008.1.2.4.108" => PixelEncoding::Hevc,
+            "1.2.840.10008.1.2.5" => PixelEncoding::Rle,
+            _ => PixelEncoding::Other,
+        }
+    }
+
     /// Check whether this transfer syntax specifier provides a complete
     /// implementation.
     pub fn fully_supported(&self) -> bool {
@@ -56,6 +56,18 @@ pub use crate::meta::FileMetaTable;
 pub use dicom_core::Tag;
 pub use dicom_dictionary_std::StandardDataDictionary;
 
+/// The implementation class UID of this crate,
+/// written to the file meta group of new files
+/// unless another one is given.
+pub const IMPLEMENTATION_CLASS_UID: &str = "2.25.262086406829110419931297894772577063974";
+
+/// The implementation version name of this crate,
+/// written to the file meta group of new files
+/// along with [`IMPLEMENTATION_CLASS_UID`].
+///
+/// [`IMPLEMENTATION_CLASS_UID`]: constant.IMPLEMENTATION_CLASS_UID.html
+pub const IMPLEMENTATION_VERSION_NAME: &str = "DICOM-rs 0.3";
+
 /// The default implementation of a root DICOM object.
 pub type DefaultDicomObject = RootDicomObject<mem::InMemDicomObject<StandardDataDictionary>>;
 
@@ -17,7 +17,7 @@ use crate::{
 use dicom_core::dictionary::{DataDictionary, DictionaryEntry};
 use dicom_core::header::{HasLength, Header};
 use dicom_core::value::{Value, C};
-use dicom_core::{DataElement, Length, Tag, VR};
+use dicom_core::{DataElement, Length, PrimitiveValue, Tag, VR};
 use dicom_dictionary_std::StandardDataDictionary;
 use dicom_encoding::text::SpecificCharacterSet;
 use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
@@ -123,6 +123,19 @@ impl InMemDicomObject<StandardDataDictionary> {
     {
         Self::from_iter_with_dict(iter, StandardDataDictionary)
     }
+
+    /// Build the items of a sequence from a data set reader,
+    /// right after its `SequenceStart` token.
+    ///
+    /// See [`read_sequence_items_with_dict`] for the details.
+    ///
+    /// [`read_sequence_items_with_dict`]: #method.read_sequence_items_with_dict
+    pub fn read_sequence_items<I>(dataset: &mut I) -> Result<Vec<Self>>
+    where
+        I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
+    {
+        Self::read_sequence_items_with_dict(dataset, StandardDataDictionary)
+    }
 }
 
 impl<D> RootDicomObject<InMemDicomObject<D>>
@@ -302,6 +315,46 @@ where
         }
     }
 
+    /// Build the items of a sequence from a data set reader,
+    /// right after its `SequenceStart` token,
+    /// using the given dictionary for name lookup.
+    ///
+    /// The tokens of the sequence are consumed
+    /// up to and including its `SequenceEnd` token,
+    /// so that the reader can carry on with the next element.
+    /// Sequences nested in the items are built as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+    /// # use dicom_object::mem::InMemDicomObject;
+    /// # use dicom_parser::dataset::DataToken;
+    /// let tokens = vec![
+    ///     DataToken::SequenceStart { tag: Tag(0x0008, 0x1140), len: Length::UNDEFINED },
+    ///     DataToken::ItemStart { len: Length::UNDEFINED },
+    ///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0008, 0x1155), VR::UI, Length(6))),
+    ///     DataToken::PrimitiveValue(PrimitiveValue::from("1.2.34")),
+    ///     DataToken::ItemEnd,
+    ///     DataToken::SequenceEnd,
+    /// ];
+    /// let mut dataset = tokens.into_iter().map(Ok);
+    ///
+    /// if let Some(Ok(DataToken::SequenceStart { .. })) = dataset.next() {
+    ///     let items = InMemDicomObject::read_sequence_items(&mut dataset)?;
+    ///     assert_eq!(items.len(), 1);
+    ///     assert_eq!(items[0].element(Tag(0x0008, 0x1155))?.to_str()?, "1.2.34");
+    /// }
+    /// assert!(dataset.next().is_none());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn read_sequence_items_with_dict<I>(dataset: &mut I, dict: D) -> Result<Vec<Self>>
+    where
+        I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
+    {
+        Self::build_sequence(dataset, &dict).map(|items| items.into_vec())
+    }
+
     // Standard methods follow. They are not placed as a trait implementation
     // because they may require outputs to reference the lifetime of self,
     // which is not possible without GATs.
@@ -376,6 +429,12 @@ where
                         DataToken::PrimitiveValue(v) => {
                             InMemElement::new(header.tag, header.vr, Value::Primitive(v))
                         }
+                        // a value preserved as raw bytes
+                        DataToken::ItemValue(data) => InMemElement::new(
+                            header.tag,
+                            header.vr,
+                            Value::Primitive(PrimitiveValue::U8(data.into())),
+                        ),
                         token => {
                             return UnexpectedToken { token }.fail();
                         }
@@ -383,7 +442,7 @@ where
                 }
                 DataToken::SequenceStart { tag, len } => {
                     // delegate sequence building to another function
-                    let items = Self::build_sequence(tag, len, &mut *dataset, &dict)?;
+                    let items = Self::build_sequence(&mut *dataset, &dict)?;
                     DataElement::new(tag, VR::SQ, Value::Sequence { items, size: len })
                 }
                 DataToken::ItemEnd if in_item => {
@@ -418,9 +477,14 @@ where
 
         let mut fragments = C::new();
 
+        // whether the current item had a value token,
+        // empty items have none
+        let mut item_has_value = false;
+
         while let Some(token) = dataset.next() {
             match token.context(ReadToken)? {
                 DataToken::ItemValue(data) => {
+                    item_has_value = true;
                     if offset_table.is_none() {
                         offset_table = Some(data.into());
                     } else {
@@ -433,18 +497,20 @@ where
                     // are seen as compressed fragments
                     if offset_table.is_none() {
                         offset_table = Some(C::new())
+                    } else if !item_has_value {
+                        // keep empty fragments as well
+                        fragments.push(Vec::new());
                     }
                 }
-                DataToken::ItemStart { len: _ } => { /* no-op */ }
+                DataToken::ItemStart { len: _ } => {
+                    item_has_value = false;
+                }
                 DataToken::SequenceEnd => {
                     // end of pixel data
                     break;
                 }
-                // the following variants are unexpected
-                token @ DataToken::ElementHeader(_)
-                | token @ DataToken::PixelSequenceStart
-                | token @ DataToken::SequenceStart { .. }
-                | token @ DataToken::PrimitiveValue(_) => {
+                // any other kind of token is unexpected
+                token => {
                     return UnexpectedToken { token }.fail();
                 }
             }
@@ -457,14 +523,9 @@ where
     }
 
     /// Build a DICOM sequence by consuming a data set parser.
-    fn build_sequence<I: ?Sized>(
-        _tag: Tag,
-        _len: Length,
-        dataset: &mut I,
-        dict: &D,
-    ) -> Result<C<InMemDicomObject<D>>>
+    fn build_sequence<I>(dataset: &mut I, dict: &D) -> Result<C<InMemDicomObject<D>>>
     where
-        I: Iterator<Item = ParserResult<DataToken>>,
+        I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
     {
         let mut items: C<_> = SmallVec::new();
         while let Some(token) = dataset.next() {
@@ -792,6 +853,187 @@ mod tests {
         assert_obj_eq(&obj, &gt_obj);
     }
 
+    #[test]
+    fn inmem_sequence_items_from_tokens() {
+        use smallvec::smallvec;
+
+        let obj_1 = InMemDicomObject::from_element_iter(vec![DataElement::new(
+            Tag(0x0008, 0x1155),
+            VR::UI,
+            Value::Primitive("1.2.3\0".into()),
+        )]);
+        let nested = InMemDicomObject::from_element_iter(vec![DataElement::new(
+            Tag(0x0008, 0x0100),
+            VR::SH,
+            Value::Primitive("T-A0100 ".into()),
+        )]);
+        let obj_2 = InMemDicomObject::from_element_iter(vec![
+            DataElement::new(
+                Tag(0x0008, 0x1155),
+                VR::UI,
+                Value::Primitive("1.2.4\0".into()),
+            ),
+            DataElement::new(
+                Tag(0x0040, 0xA043),
+                VR::SQ,
+                Value::Sequence {
+                    items: smallvec![nested],
+                    size: Length::UNDEFINED,
+                },
+            ),
+        ]);
+
+        let uid = |value: &str| {
+            vec![
+                DataToken::ElementHeader(DataElementHeader {
+                    tag: Tag(0x0008, 0x1155),
+                    vr: VR::UI,
+                    len: Length(6),
+                }),
+                DataToken::PrimitiveValue(PrimitiveValue::from(value)),
+            ]
+        };
+        let trailing = DataToken::ElementHeader(DataElementHeader {
+            tag: Tag(0x0020, 0x4000),
+            vr: VR::LT,
+            len: Length(4),
+        });
+        let mut tokens = vec![DataToken::ItemStart {
+            len: Length::UNDEFINED,
+        }];
+        tokens.extend(uid("1.2.3\0"));
+        tokens.extend(vec![
+            DataToken::ItemEnd,
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ]);
+        tokens.extend(uid("1.2.4\0"));
+        tokens.extend(vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0040, 0xA043),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0008, 0x0100),
+                vr: VR::SH,
+                len: Length(8),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::from("T-A0100 ")),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            trailing.clone(),
+        ]);
+
+        let mut dataset = tokens.into_iter().map(Result::Ok);
+        let items = InMemDicomObject::read_sequence_items(&mut dataset).unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_obj_eq(&items[0], &obj_1);
+        assert_obj_eq(&items[1], &obj_2);
+        // the reader is left right after the end of the sequence
+        assert_eq!(dataset.next().unwrap().unwrap(), trailing);
+
+        // a sequence without its end is an error
+        let mut dataset = vec![
+            Ok(DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            }),
+            Ok(DataToken::ItemEnd),
+        ]
+        .into_iter();
+        let result = InMemDicomObject::read_sequence_items(&mut dataset);
+        assert!(matches!(result, Err(Error::PrematureEnd { .. })));
+    }
+
+    #[test]
+    fn inmem_object_with_preserved_values() {
+        let preserved = |tag, vr, data: &[u8]| {
+            vec![
+                DataToken::ElementHeader(DataElementHeader {
+                    tag,
+                    vr,
+                    len: Length(data.len() as u32),
+                }),
+                DataToken::ItemValue(data.to_vec()),
+            ]
+        };
+        let mut tokens = vec![DataToken::ItemStart {
+            len: Length::UNDEFINED,
+        }];
+        tokens.extend(preserved(Tag(0x0009, 0x1001), VR::UN, &[1, 2]));
+        tokens.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+
+        // the raw bytes are kept under the VR of the header
+        let mut dataset = tokens.into_iter().map(Result::Ok);
+        let items = InMemDicomObject::read_sequence_items(&mut dataset).unwrap();
+        let elem = items[0].element(Tag(0x0009, 0x1001)).unwrap();
+        assert_eq!(elem.vr(), VR::UN);
+        assert_eq!(
+            elem.value(),
+            &Value::Primitive(PrimitiveValue::U8([1, 2].as_ref().into()))
+        );
+
+        let obj = InMemDicomObject::build_object(
+            &mut preserved(Tag(0x0010, 0x0020), VR::LO, b"ID1")
+                .into_iter()
+                .map(Result::Ok),
+            StandardDataDictionary,
+            false,
+            Length::UNDEFINED,
+        )
+        .unwrap();
+        let elem = obj.element(Tag(0x0010, 0x0020)).unwrap();
+        assert_eq!(elem.vr(), VR::LO);
+        assert_eq!(
+            elem.value(),
+            &Value::Primitive(PrimitiveValue::U8(b"ID1".as_ref().into()))
+        );
+    }
+
+    #[test]
+    fn inmem_defined_length_sequence_roundtrip() {
+        let tokens: Vec<_> = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length(18),
+            },
+            DataToken::ItemStart { len: Length(10) },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0018, 0x6012),
+                vr: VR::US,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([1].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        let obj = InMemDicomObject::build_object(
+            &mut tokens.clone().into_iter().map(Result::Ok),
+            StandardDataDictionary,
+            false,
+            Length::UNDEFINED,
+        )
+        .unwrap();
+
+        let out: Vec<_> = obj.into_tokens().collect();
+        // the sequence and its item are emitted with the lengths read
+        assert_eq!(
+            out[0],
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length(18),
+            }
+        );
+        assert_eq!(out, tokens);
+    }
+
     #[test]
     fn inmem_deep_object_into_tokens() {
         use smallvec::smallvec;
@@ -865,6 +1107,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn inmem_sequence_item_lengths_roundtrip() {
+        let tokens: Vec<_> = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length(36),
+            },
+            DataToken::ItemStart { len: Length(10) },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0018, 0x6012),
+                vr: VR::US,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([1].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0018, 0x6012),
+                vr: VR::US,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([4].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        let obj = InMemDicomObject::build_object(
+            &mut tokens.clone().into_iter().map(Result::Ok),
+            StandardDataDictionary,
+            false,
+            Length::UNDEFINED,
+        )
+        .unwrap();
+
+        let out_tokens: Vec<_> = obj.into_tokens().collect();
+        assert_eq!(out_tokens, tokens);
+    }
+
+    #[test]
+    fn inmem_empty_sequence_and_item_roundtrip() {
+        let tokens: Vec<_> = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length(0),
+            },
+            DataToken::SequenceEnd,
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1140),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        let obj = InMemDicomObject::build_object(
+            &mut tokens.clone().into_iter().map(Result::Ok),
+            StandardDataDictionary,
+            false,
+            Length::UNDEFINED,
+        )
+        .unwrap();
+
+        let empty_seq = obj.element(Tag(0x0008, 0x1115)).unwrap();
+        assert_eq!(empty_seq.value().items().map(|items| items.len()), Some(0));
+        let seq_of_empty = obj.element(Tag(0x0008, 0x1140)).unwrap();
+        let items = seq_of_empty.value().items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!((&items[0]).into_iter().count(), 0);
+
+        let out_tokens: Vec<_> = obj.into_tokens().collect();
+        assert_eq!(out_tokens, tokens);
+    }
+
     #[test]
     fn inmem_encapsulated_pixel_data_from_tokens() {
         use smallvec::smallvec;
@@ -927,4 +1247,65 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn inmem_encapsulated_pixel_data_roundtrip() {
+        use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
+        use dicom_encoding::encode::EncoderFor;
+        use dicom_encoding::text::DefaultCharacterSetCodec;
+        use dicom_encoding::transfer_syntax::explicit_le::{
+            ExplicitVRLittleEndianDecoder, ExplicitVRLittleEndianEncoder,
+        };
+        use dicom_parser::dataset::DataSetWriter;
+        use dicom_parser::StatefulDecoder;
+
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0xe0, 0x7f, 0x10, 0x00, // (7FE0,0010) PixelData
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 -- basic offset table
+            0xfe, 0xff, 0x00, 0xe0, 0x08, 0x00, 0x00, 0x00, // item start, len = 8
+            0x00, 0x00, 0x00, 0x00, // first frame at 0
+            0x12, 0x00, 0x00, 0x00, // second frame at 18
+            // -- 28 -- first fragment
+            0xfe, 0xff, 0x00, 0xe0, 0x0a, 0x00, 0x00, 0x00, // item start, len = 10
+            0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, b'J', b'F', b'I', b'F',
+            // -- 46 -- second fragment, padded to even length
+            0xfe, 0xff, 0x00, 0xe0, 0x06, 0x00, 0x00, 0x00, // item start, len = 6
+            0x01, 0x02, 0x03, 0xff, 0xd9, 0x00,
+            // -- 60 -- empty fragment
+            0xfe, 0xff, 0x00, 0xe0, 0x00, 0x00, 0x00, 0x00, // item start, len = 0
+            // -- 68 --
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let mut tokens = DataSetReader::new(parser, Default::default());
+        let obj = InMemDicomObject::build_object(
+            &mut tokens,
+            StandardDataDictionary,
+            false,
+            Length::UNDEFINED,
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        DataSetWriter::new(
+            &mut out,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec,
+        )
+        .write_sequence(obj.into_tokens())
+        .unwrap();
+
+        assert_eq!(out, DATA);
+    }
 }
@@ -1,4 +1,5 @@
 //! Module containing data structures and readers of DICOM file meta information tables.
+use crate::{IMPLEMENTATION_CLASS_UID, IMPLEMENTATION_VERSION_NAME};
 use byteordered::byteorder::{ByteOrder, LittleEndian};
 use dicom_core::dicom_value;
 use dicom_core::header::{DataElement, EmptyObject, HasLength, Header};
@@ -606,6 +607,14 @@ impl FileMetaTableBuilder {
     }
 
     /// Build the table.
+    ///
+    /// If no implementation class UID was defined,
+    /// this crate's [`IMPLEMENTATION_CLASS_UID`] is used,
+    /// along with its [`IMPLEMENTATION_VERSION_NAME`]
+    /// if no implementation version name was defined either.
+    ///
+    /// [`IMPLEMENTATION_CLASS_UID`]: ../constant.IMPLEMENTATION_CLASS_UID.html
+    /// [`IMPLEMENTATION_VERSION_NAME`]: ../constant.IMPLEMENTATION_VERSION_NAME.html
     pub fn build(self) -> Result<FileMetaTable> {
         let information_version = self.information_version.unwrap_or_else(|| {
             // Missing information version, will assume (00H, 01H). See #28
@@ -623,9 +632,16 @@ impl FileMetaTableBuilder {
         let transfer_syntax = self.transfer_syntax.context(MissingElement {
             alias: "TransferSyntax",
         })?;
-        let implementation_class_uid = self.implementation_class_uid.context(MissingElement {
-            alias: "ImplementationClassUID",
-        })?;
+        let (implementation_class_uid, implementation_version_name) =
+            match self.implementation_class_uid {
+                Some(uid) => (uid, self.implementation_version_name),
+                // the version name only makes sense with its own class UID
+                None => (
+                    ui_padded(IMPLEMENTATION_CLASS_UID),
+                    self.implementation_version_name
+                        .or_else(|| Some(txt_padded(IMPLEMENTATION_VERSION_NAME))),
+                ),
+            };
 
         fn dicom_len<T: AsRef<str>>(x: T) -> u32 {
             let o = x.as_ref().len() as u32;
@@ -650,8 +666,7 @@ impl FileMetaTableBuilder {
                     + dicom_len(&transfer_syntax)
                     + 8
                     + dicom_len(&implementation_class_uid)
-                    + self
-                        .implementation_version_name
+                    + implementation_version_name
                         .as_ref()
                         .map(|s| 8 + s.len() as u32)
                         .unwrap_or(0)
@@ -690,7 +705,7 @@ impl FileMetaTableBuilder {
             media_storage_sop_instance_uid,
             transfer_syntax,
             implementation_class_uid,
-            implementation_version_name: self.implementation_version_name,
+            implementation_version_name,
             source_application_entity_title: self.source_application_entity_title,
             sending_application_entity_title: self.sending_application_entity_title,
             receiving_application_entity_title: self.receiving_application_entity_title,
@@ -703,6 +718,7 @@ impl FileMetaTableBuilder {
 #[cfg(test)]
 mod tests {
     use super::{FileMetaTable, FileMetaTableBuilder};
+    use crate::{IMPLEMENTATION_CLASS_UID, IMPLEMENTATION_VERSION_NAME};
     use dicom_core::value::Value;
     use dicom_core::{dicom_value, DataElement, Tag, VR};
 
@@ -820,6 +836,51 @@ mod tests {
         assert_eq!(table, gt);
     }
 
+    #[test]
+    fn create_meta_table_with_default_implementation() {
+        let builder = FileMetaTableBuilder::new()
+            .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1")
+            .media_storage_sop_instance_uid("1.2.3.456")
+            .transfer_syntax("1.2.840.10008.1.2.1");
+
+        let table = builder.clone().build().unwrap();
+        assert_eq!(table.implementation_class_uid, IMPLEMENTATION_CLASS_UID);
+        assert_eq!(
+            table.implementation_version_name.as_deref(),
+            Some(IMPLEMENTATION_VERSION_NAME)
+        );
+        // 4 elements (including information version) + implementation
+        assert_eq!(
+            table.information_group_length,
+            14 + 8 + 26 + 8 + 10 + 8 + 20 + 8 + 44 + 8 + 12
+        );
+
+        let elems: Vec<_> = table.into_element_iter().collect();
+        assert_eq!(
+            &elems[5..],
+            &[
+                DataElement::new(
+                    Tag(0x0002, 0x0012),
+                    VR::UI,
+                    Value::Primitive(IMPLEMENTATION_CLASS_UID.into()),
+                ),
+                DataElement::new(
+                    Tag(0x0002, 0x0013),
+                    VR::SH,
+                    Value::Primitive(IMPLEMENTATION_VERSION_NAME.into()),
+                ),
+            ][..]
+        );
+
+        // a version name of this crate is not paired with another class UID
+        let table = builder
+            .implementation_class_uid("1.2.345.6.7890.1.234")
+            .build()
+            .unwrap();
+        assert_eq!(table.implementation_class_uid, "1.2.345.6.7890.1.234");
+        assert_eq!(table.implementation_version_name, None);
+    }
+
     #[test]
     fn read_meta_table_into_iter() {
         let table = FileMetaTable {
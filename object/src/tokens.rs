@@ -1,6 +1,5 @@
 //! Convertion of DICOM objects into tokens.
 use crate::mem::InMemDicomObject;
-use dicom_core::DataElement;
 use dicom_parser::dataset::{DataToken, IntoTokens};
 use std::collections::VecDeque;
 
@@ -30,9 +29,9 @@ where
     }
 }
 
-impl<P, I, E> Iterator for InMemObjectTokens<E>
+impl<E> Iterator for InMemObjectTokens<E>
 where
-    E: Iterator<Item = DataElement<I, P>>,
+    E: Iterator,
     E::Item: IntoTokens,
 {
     type Item = DataToken;
@@ -74,14 +73,12 @@ impl<D> IntoTokens for InMemDicomObject<D> {
     }
 }
 
-impl<'a, D> IntoTokens for &'a InMemDicomObject<D>
-where
-    D: Clone,
-{
-    type Iter =
-        InMemObjectTokens<std::iter::Cloned<<&'a InMemDicomObject<D> as IntoIterator>::IntoIter>>;
+/// Borrowed objects are converted into tokens
+/// without cloning their elements.
+impl<'a, D> IntoTokens for &'a InMemDicomObject<D> {
+    type Iter = InMemObjectTokens<<&'a InMemDicomObject<D> as IntoIterator>::IntoIter>;
 
     fn into_tokens(self) -> Self::Iter {
-        InMemObjectTokens::new(self.into_iter().cloned())
+        InMemObjectTokens::new(self)
     }
 }
@@ -0,0 +1,262 @@
+//! Adaptor for declaring the character set of Unicode text values.
+use crate::dataset::adaptor::WithDepth;
+use crate::dataset::DataToken;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::value::PrimitiveValue;
+use dicom_core::{Tag, VR};
+use std::collections::VecDeque;
+
+/// The tag of the _Specific Character Set_ attribute.
+const SPECIFIC_CHARACTER_SET: Tag = Tag(0x0008, 0x0005);
+
+/// The defined term of _Specific Character Set_ for UTF-8.
+const ISO_IR_192: &str = "ISO_IR 192";
+
+/// A token stream adaptor which adds a _Specific Character Set_
+/// `(0008,0005)` element of `ISO_IR 192` (UTF-8) to the root data set
+/// when a text value holds characters outside of the default repertoire
+/// and the data set does not declare its character set.
+///
+/// The element is inserted before the first root element
+/// with a greater tag,
+/// so the stream is expected to have its root elements sorted by tag.
+/// Only the values of the VRs affected by the character set
+/// (`SH`, `LO`, `ST`, `PN`, `LT`, `UC` and `UT`) are checked,
+/// in the root data set as well as in nested data sets.
+/// Values given as raw bytes are taken as already encoded.
+///
+/// Since the text values needing the element usually come after it,
+/// the root tokens from that position on are held back
+/// until such a value is found, or until the end of the stream.
+/// Tokens pass through unchanged once the element is found or inserted.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+/// # use dicom_parser::dataset::adaptor::DeclareCharacterSet;
+/// # use dicom_parser::dataset::DataToken;
+/// let tokens = vec![
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0010, 0x0010), VR::PN, Length(10))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("Müller^Jo")),
+/// ];
+///
+/// let out: Vec<_> = DeclareCharacterSet::new(tokens).collect();
+/// assert_eq!(out.len(), 4);
+/// assert_eq!(out[1], DataToken::PrimitiveValue(PrimitiveValue::from("ISO_IR 192")));
+/// ```
+#[derive(Debug)]
+pub struct DeclareCharacterSet<I> {
+    /// the inner token stream, with the depth of each token
+    tokens: WithDepth<I>,
+    /// the tokens held back or about to be emitted
+    held: VecDeque<DataToken>,
+    /// the VR of the element header last seen
+    last_vr: Option<VR>,
+    /// whether a text value requiring the element was seen
+    needed: bool,
+    /// how far the root data set was read
+    state: State,
+}
+
+/// The progress of the adaptor through the root data set.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum State {
+    /// before the position of the element
+    Before,
+    /// past the position of the element,
+    /// holding back tokens until a text value requires it
+    Holding,
+    /// the element was found or inserted, or the stream ended
+    Done,
+}
+
+impl<I> DeclareCharacterSet<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        DeclareCharacterSet {
+            tokens: WithDepth::new(tokens),
+            held: VecDeque::new(),
+            last_vr: None,
+            needed: false,
+            state: State::Before,
+        }
+    }
+
+    /// Check whether the given token is a text value
+    /// which cannot be encoded in the default character set.
+    fn requires_character_set(&mut self, token: &DataToken) -> bool {
+        match token {
+            DataToken::ElementHeader(header) => {
+                self.last_vr = Some(header.vr);
+                false
+            }
+            DataToken::PrimitiveValue(value) => {
+                let is_text = matches!(
+                    self.last_vr.take(),
+                    Some(VR::SH | VR::LO | VR::ST | VR::PN | VR::LT | VR::UC | VR::UT)
+                );
+                is_text
+                    && match value {
+                        PrimitiveValue::Str(s) => !s.is_ascii(),
+                        PrimitiveValue::Strs(values) => values.iter().any(|s| !s.is_ascii()),
+                        _ => false,
+                    }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<I> Iterator for DeclareCharacterSet<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.state == State::Done {
+                if let Some(token) = self.held.pop_front() {
+                    return Some(token);
+                }
+                return self.tokens.next().map(|(_, token)| token);
+            }
+
+            let (depth, token) = match self.tokens.next() {
+                Some(next) => next,
+                None => {
+                    // the element goes at the end, if still needed
+                    if self.state == State::Before && self.needed {
+                        self.held.extend(character_set_tokens());
+                    }
+                    self.state = State::Done;
+                    continue;
+                }
+            };
+
+            if self.requires_character_set(&token) {
+                self.needed = true;
+            }
+            if depth == 0 {
+                match token.element_tag() {
+                    Some(tag) if tag == SPECIFIC_CHARACTER_SET => {
+                        // already declared
+                        self.state = State::Done;
+                        self.held.push_back(token);
+                        continue;
+                    }
+                    Some(tag) if tag > SPECIFIC_CHARACTER_SET && self.state == State::Before => {
+                        self.state = State::Holding;
+                    }
+                    _ => {}
+                }
+            }
+
+            if self.state == State::Before {
+                return Some(token);
+            }
+            self.held.push_back(token);
+            if self.needed {
+                let [header, value] = character_set_tokens();
+                self.held.push_front(value);
+                self.held.push_front(header);
+                self.state = State::Done;
+            }
+        }
+    }
+}
+
+/// The tokens of a _Specific Character Set_ element declaring UTF-8.
+fn character_set_tokens() -> [DataToken; 2] {
+    [
+        DataToken::ElementHeader(DataElementHeader::new(
+            SPECIFIC_CHARACTER_SET,
+            VR::CS,
+            Length(ISO_IR_192.len() as u32),
+        )),
+        DataToken::PrimitiveValue(PrimitiveValue::from(ISO_IR_192)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeclareCharacterSet;
+    use crate::dataset::adaptor::testing::text_element;
+    use crate::dataset::DataToken;
+    use dicom_core::header::Length;
+    use dicom_core::{Tag, VR};
+
+    /// A data set with the given patient name.
+    fn tokens(name: &str) -> Vec<DataToken> {
+        let mut tokens = text_element(Tag(0x0002, 0x0010), VR::UI, "1.2.840.10008.1.2.1\0");
+        tokens.extend(text_element(Tag(0x0008, 0x0016), VR::UI, "1.2.3\0"));
+        tokens.extend(text_element(Tag(0x0008, 0x0060), VR::CS, "MR"));
+        tokens.extend(text_element(Tag(0x0010, 0x0010), VR::PN, name));
+        tokens
+    }
+
+    #[test]
+    fn declares_utf8_for_unicode_names() {
+        let out: Vec<_> = DeclareCharacterSet::new(tokens("Ærøskøbing^Ånd")).collect();
+
+        let mut expected = tokens("Ærøskøbing^Ånd");
+        expected.splice(
+            2..2,
+            text_element(Tag(0x0008, 0x0005), VR::CS, "ISO_IR 192"),
+        );
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn declares_utf8_for_unicode_text_in_items() {
+        let mut input = text_element(Tag(0x0008, 0x0016), VR::UI, "1.2.3\0");
+        input.extend(vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ]);
+        input.extend(text_element(Tag(0x0008, 0x103E), VR::LO, "Schädel"));
+        input.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+
+        let out: Vec<_> = DeclareCharacterSet::new(input.clone()).collect();
+
+        let mut expected = input;
+        expected.splice(
+            0..0,
+            text_element(Tag(0x0008, 0x0005), VR::CS, "ISO_IR 192"),
+        );
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn leaves_ascii_and_declared_data_sets_alone() {
+        let out: Vec<_> = DeclareCharacterSet::new(tokens("Doe^John")).collect();
+        assert_eq!(out, tokens("Doe^John"));
+
+        // not in the default repertoire, but not affected by the character set either
+        let mut input = tokens("Doe^John");
+        input.extend(text_element(Tag(0x0020, 0x000D), VR::UI, "1.2.é"));
+        let out: Vec<_> = DeclareCharacterSet::new(input.clone()).collect();
+        assert_eq!(out, input);
+
+        // an existing character set is not duplicated
+        let mut input = tokens("Müller");
+        input.splice(
+            2..2,
+            text_element(Tag(0x0008, 0x0005), VR::CS, "ISO_IR 100"),
+        );
+        let out: Vec<_> = DeclareCharacterSet::new(input.clone()).collect();
+        assert_eq!(out, input);
+    }
+}
@@ -0,0 +1,216 @@
+//! Token source for streaming primitive values in pieces.
+use crate::dataset::DataToken;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::VR;
+use std::io::{Error, ErrorKind, Read, Result};
+
+/// A token source which yields an element header
+/// followed by its value in chunks of bounded size,
+/// read from the given source of value data.
+///
+/// Each chunk is yielded as a [`DataToken::ItemValue`],
+/// which the data set writer prints as is.
+/// The chunks add up to the declared value length,
+/// and the last chunk is padded to make the length even,
+/// in which case the yielded header's length accounts for the padding.
+/// This makes it possible to relay large values
+/// without holding them in memory in full.
+///
+/// [`DataToken::ItemValue`]: ../../enum.DataToken.html#variant.ItemValue
+#[derive(Debug)]
+pub struct ValueChunks<R> {
+    /// the element header, taken once yielded
+    header: Option<DataElementHeader>,
+    /// the source of value data
+    source: R,
+    /// the number of value bytes still to be read from the source
+    remaining: u32,
+    /// the byte to append to the last chunk, if the length is odd
+    padding: Option<u8>,
+    /// the maximum number of bytes per chunk
+    chunk_size: usize,
+    /// fuse the iteration process if true
+    hard_break: bool,
+}
+
+impl<R> ValueChunks<R>
+where
+    R: Read,
+{
+    /// Create a new token source for the element with the given header,
+    /// reading its value from `source` in chunks of up to `chunk_size` bytes.
+    ///
+    /// An error is yielded if the header's length is undefined,
+    /// or if the source ends before the declared length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn new(header: DataElementHeader, source: R, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must not be zero");
+        let remaining = header.len.get().unwrap_or(0);
+        ValueChunks {
+            header: Some(header),
+            source,
+            remaining,
+            padding: if remaining % 2 == 1 {
+                Some(padding_of(header.vr))
+            } else {
+                None
+            },
+            chunk_size,
+            hard_break: false,
+        }
+    }
+}
+
+impl<R> Iterator for ValueChunks<R>
+where
+    R: Read,
+{
+    type Item = Result<DataToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.hard_break {
+            return None;
+        }
+
+        if let Some(header) = self.header.take() {
+            if header.len.is_undefined() {
+                self.hard_break = true;
+                return Some(Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "value length must be defined",
+                )));
+            }
+            let len = self.remaining + self.padding.is_some() as u32;
+            return Some(Ok(DataToken::ElementHeader(DataElementHeader::new(
+                header.tag,
+                header.vr,
+                Length(len),
+            ))));
+        }
+
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let len = usize::min(self.remaining as usize, self.chunk_size);
+        let mut chunk = vec![0; len];
+        if let Err(e) = self.source.read_exact(&mut chunk) {
+            self.hard_break = true;
+            return Some(Err(e));
+        }
+        self.remaining -= len as u32;
+
+        if self.remaining == 0 {
+            chunk.extend(self.padding);
+        }
+        Some(Ok(DataToken::ItemValue(chunk)))
+    }
+}
+
+/// Obtain the byte for padding a value of the given VR to even length.
+pub(crate) fn padding_of(vr: VR) -> u8 {
+    match vr {
+        VR::AE
+        | VR::AS
+        | VR::CS
+        | VR::DA
+        | VR::DS
+        | VR::DT
+        | VR::IS
+        | VR::LO
+        | VR::LT
+        | VR::PN
+        | VR::SH
+        | VR::ST
+        | VR::TM
+        | VR::UC
+        | VR::UR
+        | VR::UT => b' ',
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueChunks;
+    use crate::dataset::{DataSetWriter, DataToken};
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+    use dicom_encoding::encode::EncoderFor;
+    use dicom_encoding::text::DefaultCharacterSetCodec;
+    use dicom_encoding::transfer_syntax::explicit_le::ExplicitVRLittleEndianEncoder;
+
+    #[test]
+    fn value_chunks_with_padding() {
+        let header = DataElementHeader::new(Tag(0x0042, 0x0011), VR::OB, Length(5));
+        let data: &[u8] = &[1, 2, 3, 4, 5];
+
+        let tokens: Vec<_> = ValueChunks::new(header, data, 2)
+            .collect::<Result<_, _>>()
+            .expect("should read all chunks");
+
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0042, 0x0011),
+                    VR::OB,
+                    Length(6),
+                )),
+                DataToken::ItemValue(vec![1, 2]),
+                DataToken::ItemValue(vec![3, 4]),
+                DataToken::ItemValue(vec![5, 0]),
+            ]
+        );
+
+        // writes the same bytes as the whole value
+        let mut chunked = Vec::new();
+        DataSetWriter::new(
+            &mut chunked,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec::default(),
+        )
+        .write_sequence(tokens)
+        .unwrap();
+
+        let mut whole = Vec::new();
+        DataSetWriter::new(
+            &mut whole,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec::default(),
+        )
+        .write_sequence(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0042, 0x0011),
+                VR::OB,
+                Length(6),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(&[1, 2, 3, 4, 5, 0][..])),
+        ])
+        .unwrap();
+
+        assert_eq!(chunked, whole);
+    }
+
+    #[test]
+    fn value_chunks_source_too_short() {
+        let header = DataElementHeader::new(Tag(0x0042, 0x0011), VR::OB, Length(8));
+        let data: &[u8] = &[1, 2, 3, 4, 5];
+
+        let mut chunks = ValueChunks::new(header, data, 4);
+        assert!(matches!(
+            chunks.next(),
+            Some(Ok(DataToken::ElementHeader(_)))
+        ));
+        assert_eq!(
+            chunks.next().unwrap().unwrap(),
+            DataToken::ItemValue(vec![1, 2, 3, 4])
+        );
+        assert!(chunks.next().unwrap().is_err());
+        assert!(chunks.next().is_none());
+    }
+}
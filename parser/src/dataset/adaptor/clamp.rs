@@ -0,0 +1,278 @@
+//! Adaptor for truncating values to a maximum length.
+use crate::dataset::DataToken;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::value::PrimitiveValue;
+use dicom_core::VR;
+use std::collections::HashMap;
+
+/// A token stream adaptor which truncates values
+/// exceeding a maximum length for their value representation,
+/// according to a set of limits `(VR, maximum length)`.
+///
+/// For textual values, the limit is a number of characters
+/// and applies to each of the backslash-separated values of the element,
+/// as the maximum lengths in the standard do
+/// (e.g. 64 characters per value for `LO`).
+/// For binary values (`OB`, `UN`), the limit is a number of bytes
+/// and applies to the whole value.
+/// The length in the element header is fixed up
+/// whenever the value is truncated.
+/// As the new length may be odd,
+/// this adaptor should be followed by [`PadToEvenLength`]
+/// when writing the data set.
+///
+/// The limits apply to the root data set and to nested data sets alike.
+/// Values of any other kind (numbers, dates and times),
+/// values split into [`DataToken::ItemValue`] chunks,
+/// and elements without a limit for their VR
+/// are passed through unchanged.
+/// The explicit lengths of sequences and items are not recomputed.
+///
+/// [`PadToEvenLength`]: ../pad/struct.PadToEvenLength.html
+/// [`DataToken::ItemValue`]: ../../enum.DataToken.html#variant.ItemValue
+#[derive(Debug)]
+pub struct ClampValueLengths<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the maximum length of each VR
+    limits: HashMap<VR, u32>,
+    /// a token to be emitted before consuming the inner stream
+    queued: Option<DataToken>,
+}
+
+impl<I> ClampValueLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens, without any limits.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        ClampValueLengths {
+            tokens: tokens.into_iter(),
+            limits: HashMap::new(),
+            queued: None,
+        }
+    }
+
+    /// Add a limit to the length of values with the given representation.
+    pub fn max_length(mut self, vr: VR, max: u32) -> Self {
+        self.limits.insert(vr, max);
+        self
+    }
+
+    /// Truncate the value of the element with the given header,
+    /// taking it from the inner stream.
+    fn clamp(&mut self, header: DataElementHeader, max: u32) -> DataToken {
+        let value = match self.tokens.next() {
+            Some(DataToken::PrimitiveValue(value)) => value,
+            token => {
+                // a value in some other form, leave it as is
+                self.queued = token;
+                return DataToken::ElementHeader(header);
+            }
+        };
+
+        let (value, len) = match clamp_value(value, max as usize) {
+            (value, Some(len)) => (value, Length(len as u32)),
+            (value, None) => (value, header.len),
+        };
+        self.queued = Some(DataToken::PrimitiveValue(value));
+        DataToken::ElementHeader(DataElementHeader::new(header.tag, header.vr, len))
+    }
+}
+
+impl<I> Iterator for ClampValueLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // a queued header is checked against the limits all the same
+        let token = match self.queued.take() {
+            Some(token) => token,
+            None => self.tokens.next()?,
+        };
+        match token {
+            DataToken::ElementHeader(header) => match self.limits.get(&header.vr) {
+                Some(&max) => Some(self.clamp(header, max)),
+                None => Some(DataToken::ElementHeader(header)),
+            },
+            token => Some(token),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.tokens.size_hint();
+        let queued = self.queued.is_some() as usize;
+        (
+            min.saturating_add(queued),
+            max.and_then(|max| max.checked_add(queued)),
+        )
+    }
+}
+
+/// Truncate a textual or binary value to the given maximum length,
+/// returning the new value length in bytes if anything was truncated.
+fn clamp_value(value: PrimitiveValue, max: usize) -> (PrimitiveValue, Option<usize>) {
+    match value {
+        PrimitiveValue::Str(mut s) => {
+            if truncate_chars(&mut s, max) {
+                let len = s.len();
+                (PrimitiveValue::Str(s), Some(len))
+            } else {
+                (PrimitiveValue::Str(s), None)
+            }
+        }
+        PrimitiveValue::Strs(mut strs) => {
+            let mut truncated = false;
+            for s in strs.iter_mut() {
+                truncated |= truncate_chars(s, max);
+            }
+            if truncated {
+                // values are separated by a backslash
+                let len = strs
+                    .iter()
+                    .map(|s| s.len() + 1)
+                    .sum::<usize>()
+                    .saturating_sub(1);
+                (PrimitiveValue::Strs(strs), Some(len))
+            } else {
+                (PrimitiveValue::Strs(strs), None)
+            }
+        }
+        PrimitiveValue::U8(mut bytes) if bytes.len() > max => {
+            bytes.truncate(max);
+            (PrimitiveValue::U8(bytes), Some(max))
+        }
+        value => (value, None),
+    }
+}
+
+/// Truncate the string to the given number of characters,
+/// returning whether it was longer.
+fn truncate_chars(s: &mut String, max: usize) -> bool {
+    match s.char_indices().nth(max) {
+        Some((end, _)) => {
+            s.truncate(end);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClampValueLengths;
+    use crate::dataset::adaptor::testing::header;
+    use crate::dataset::DataToken;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{dicom_value, Tag, VR};
+
+    #[test]
+    fn truncates_each_string_value() {
+        let tokens = vec![
+            header(Tag(0x0008, 0x1030), VR::LO, 22),
+            DataToken::PrimitiveValue(PrimitiveValue::from("CHEST PA AND LATERAL  ")),
+            header(Tag(0x0018, 0x0015), VR::CS, 10),
+            DataToken::PrimitiveValue(dicom_value!(Strs, ["CHEST", "ABDOM"])),
+            // short enough
+            header(Tag(0x0008, 0x0070), VR::LO, 4),
+            DataToken::PrimitiveValue(PrimitiveValue::from("ACME")),
+            // no limit for this VR
+            header(Tag(0x0010, 0x0010), VR::PN, 8),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+        ];
+
+        let out: Vec<_> = ClampValueLengths::new(tokens)
+            .max_length(VR::LO, 5)
+            .max_length(VR::CS, 4)
+            .collect();
+
+        assert_eq!(
+            out,
+            vec![
+                header(Tag(0x0008, 0x1030), VR::LO, 5),
+                DataToken::PrimitiveValue(PrimitiveValue::from("CHEST")),
+                header(Tag(0x0018, 0x0015), VR::CS, 9),
+                DataToken::PrimitiveValue(dicom_value!(Strs, ["CHES", "ABDO"])),
+                header(Tag(0x0008, 0x0070), VR::LO, 4),
+                DataToken::PrimitiveValue(PrimitiveValue::from("ACME")),
+                header(Tag(0x0010, 0x0010), VR::PN, 8),
+                DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncates_strings_by_character() {
+        let tokens = vec![
+            header(Tag(0x0010, 0x4000), VR::LT, 8),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Müller")),
+        ];
+
+        let out: Vec<_> = ClampValueLengths::new(tokens)
+            .max_length(VR::LT, 2)
+            .collect();
+
+        assert_eq!(
+            out,
+            vec![
+                header(Tag(0x0010, 0x4000), VR::LT, 3),
+                DataToken::PrimitiveValue(PrimitiveValue::from("Mü")),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncates_binary_values() {
+        let tokens = vec![
+            header(Tag(0x0009, 0x1001), VR::OB, 6),
+            DataToken::PrimitiveValue(PrimitiveValue::from(vec![1_u8, 2, 3, 4, 5, 6])),
+            // numbers are left alone
+            header(Tag(0x0028, 0x0010), VR::US, 2),
+            DataToken::PrimitiveValue(PrimitiveValue::from(512_u16)),
+            // as are values in chunks
+            header(Tag(0x0009, 0x1002), VR::OB, 6),
+            DataToken::ItemValue(vec![1, 2, 3]),
+            DataToken::ItemValue(vec![4, 5, 6]),
+        ];
+
+        let out: Vec<_> = ClampValueLengths::new(tokens.clone())
+            .max_length(VR::OB, 4)
+            .max_length(VR::US, 0)
+            .collect();
+
+        let mut expected = vec![
+            header(Tag(0x0009, 0x1001), VR::OB, 4),
+            DataToken::PrimitiveValue(PrimitiveValue::from(vec![1_u8, 2, 3, 4])),
+        ];
+        expected.extend(tokens[2..].iter().cloned());
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn truncates_value_after_empty_element() {
+        let tokens = vec![
+            // no value token
+            header(Tag(0x0008, 0x1030), VR::LO, 0),
+            header(Tag(0x0008, 0x103E), VR::LO, 8),
+            DataToken::PrimitiveValue(PrimitiveValue::from("ABDOMEN ")),
+        ];
+
+        let out: Vec<_> = ClampValueLengths::new(tokens)
+            .max_length(VR::LO, 4)
+            .collect();
+
+        assert_eq!(
+            out,
+            vec![
+                header(Tag(0x0008, 0x1030), VR::LO, 0),
+                header(Tag(0x0008, 0x103E), VR::LO, 4),
+                DataToken::PrimitiveValue(PrimitiveValue::from("ABDO")),
+            ]
+        );
+    }
+}
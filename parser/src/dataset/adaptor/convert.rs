@@ -0,0 +1,383 @@
+//! Adaptor for converting the value representation of numeric elements.
+use crate::dataset::DataToken;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::value::{PrimitiveValue, C};
+use dicom_core::{Tag, VR};
+use snafu::{ensure, Backtrace, Snafu};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum Error {
+    #[snafu(display(
+        "Value of element {} cannot be converted from {:?} to {:?} without loss",
+        tag,
+        from,
+        to
+    ))]
+    LossyConversion {
+        tag: Tag,
+        from: VR,
+        to: VR,
+        backtrace: Backtrace,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A token stream adaptor which converts the values of numeric elements
+/// to another numeric value representation,
+/// according to a set of rules `(tag, target VR)`.
+///
+/// The element header gains the target VR and the new value length,
+/// and the value is converted number by number
+/// (e.g. sign-extended from `SS` to `SL`).
+/// A conversion which does not preserve every number
+/// yields an error,
+/// unless lossy conversions are enabled with [`lossy`],
+/// in which case integers are clamped to the range of the target
+/// and floating point numbers are truncated.
+///
+/// The rules apply to the root data set and to nested data sets alike.
+/// Elements without a rule,
+/// elements with a non-numeric value representation,
+/// and rules with a non-numeric target are passed through unchanged.
+///
+/// [`lossy`]: #method.lossy
+#[derive(Debug)]
+pub struct ConvertVr<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the target VR of each tag
+    rules: HashMap<Tag, VR>,
+    /// whether to clamp values instead of failing on lossy conversions
+    lossy: bool,
+    /// a token to be emitted before consuming the inner stream
+    queued: Option<DataToken>,
+    /// fuse the iteration process if true
+    hard_break: bool,
+}
+
+impl<I> ConvertVr<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens, without any rules.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        ConvertVr {
+            tokens: tokens.into_iter(),
+            rules: HashMap::new(),
+            lossy: false,
+            queued: None,
+            hard_break: false,
+        }
+    }
+
+    /// Add a rule converting the values of elements with the given tag
+    /// to the given value representation.
+    pub fn rule(mut self, tag: Tag, vr: VR) -> Self {
+        self.rules.insert(tag, vr);
+        self
+    }
+
+    /// Define whether lossy conversions are allowed.
+    pub fn lossy(mut self, lossy: bool) -> Self {
+        self.lossy = lossy;
+        self
+    }
+
+    /// Convert the element with the given header,
+    /// taking its value from the inner stream.
+    fn convert(&mut self, header: DataElementHeader, to: VR) -> Result<DataToken> {
+        let value = match self.tokens.next() {
+            Some(DataToken::PrimitiveValue(value)) => value,
+            token => {
+                // a value in some other form, leave it as is
+                self.queued = token;
+                return Ok(DataToken::ElementHeader(header));
+            }
+        };
+
+        match (convert_value(&value, to), byte_size_of(to)) {
+            (Some((converted, exact)), Some(size)) => {
+                ensure!(
+                    exact || self.lossy,
+                    LossyConversion {
+                        tag: header.tag,
+                        from: header.vr,
+                        to,
+                    }
+                );
+                let len = Length(converted.multiplicity() * size);
+                self.queued = Some(DataToken::PrimitiveValue(converted));
+                Ok(DataToken::ElementHeader(DataElementHeader::new(
+                    header.tag, to, len,
+                )))
+            }
+            _ => {
+                self.queued = Some(DataToken::PrimitiveValue(value));
+                Ok(DataToken::ElementHeader(header))
+            }
+        }
+    }
+}
+
+impl<I> Iterator for ConvertVr<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = Result<DataToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.hard_break {
+            return None;
+        }
+        if let Some(token) = self.queued.take() {
+            return Some(Ok(token));
+        }
+
+        match self.tokens.next()? {
+            DataToken::ElementHeader(header) if byte_size_of(header.vr).is_some() => {
+                match self.rules.get(&header.tag) {
+                    Some(&to) if to != header.vr => {
+                        let out = self.convert(header, to);
+                        if out.is_err() {
+                            self.hard_break = true;
+                        }
+                        Some(out)
+                    }
+                    _ => Some(Ok(DataToken::ElementHeader(header))),
+                }
+            }
+            token => Some(Ok(token)),
+        }
+    }
+}
+
+/// Obtain the size in bytes of each number
+/// of the given numeric value representation.
+fn byte_size_of(vr: VR) -> Option<u32> {
+    match vr {
+        VR::US | VR::SS => Some(2),
+        VR::UL | VR::SL | VR::FL => Some(4),
+        VR::UV | VR::SV | VR::FD => Some(8),
+        _ => None,
+    }
+}
+
+/// The numbers of a primitive value, widened for conversion.
+enum Numbers {
+    Ints(Vec<i128>),
+    Floats(Vec<f64>),
+}
+
+impl Numbers {
+    fn of(value: &PrimitiveValue) -> Option<Self> {
+        use self::Numbers::*;
+        match value {
+            PrimitiveValue::Empty => Some(Ints(Vec::new())),
+            PrimitiveValue::U16(v) => Some(Ints(v.iter().map(|&x| x.into()).collect())),
+            PrimitiveValue::I16(v) => Some(Ints(v.iter().map(|&x| x.into()).collect())),
+            PrimitiveValue::U32(v) => Some(Ints(v.iter().map(|&x| x.into()).collect())),
+            PrimitiveValue::I32(v) => Some(Ints(v.iter().map(|&x| x.into()).collect())),
+            PrimitiveValue::U64(v) => Some(Ints(v.iter().map(|&x| x.into()).collect())),
+            PrimitiveValue::I64(v) => Some(Ints(v.iter().map(|&x| x.into()).collect())),
+            PrimitiveValue::F32(v) => Some(Floats(v.iter().map(|&x| x.into()).collect())),
+            PrimitiveValue::F64(v) => Some(Floats(v.iter().cloned().collect())),
+            _ => None,
+        }
+    }
+}
+
+/// Convert the numbers to the given integer type,
+/// clamping or truncating those which do not fit.
+macro_rules! convert_ints {
+    ($numbers: expr, $typ: ty, $variant: ident) => {{
+        let mut exact = true;
+        let values: C<$typ> = match $numbers {
+            Numbers::Ints(v) => v
+                .into_iter()
+                .map(|x| {
+                    <$typ>::try_from(x).unwrap_or_else(|_| {
+                        exact = false;
+                        if x < 0 {
+                            <$typ>::MIN
+                        } else {
+                            <$typ>::MAX
+                        }
+                    })
+                })
+                .collect(),
+            Numbers::Floats(v) => v
+                .into_iter()
+                .map(|x| {
+                    let y = x as $typ;
+                    exact &= y as f64 == x;
+                    y
+                })
+                .collect(),
+        };
+        (PrimitiveValue::$variant(values), exact)
+    }};
+}
+
+/// Convert the numbers to the given floating point type.
+macro_rules! convert_floats {
+    ($numbers: expr, $typ: ty, $variant: ident) => {{
+        let mut exact = true;
+        let values: C<$typ> = match $numbers {
+            Numbers::Ints(v) => v
+                .into_iter()
+                .map(|x| {
+                    let y = x as $typ;
+                    exact &= y as i128 == x;
+                    y
+                })
+                .collect(),
+            Numbers::Floats(v) => v
+                .into_iter()
+                .map(|x| {
+                    let y = x as $typ;
+                    exact &= y as f64 == x || x.is_nan();
+                    y
+                })
+                .collect(),
+        };
+        (PrimitiveValue::$variant(values), exact)
+    }};
+}
+
+/// Convert a numeric value to the given value representation,
+/// also returning whether all numbers were preserved.
+///
+/// Returns `None` if the value is not numeric
+/// or the value representation is not a numeric one.
+fn convert_value(value: &PrimitiveValue, vr: VR) -> Option<(PrimitiveValue, bool)> {
+    let numbers = Numbers::of(value)?;
+    let out = match vr {
+        VR::US => convert_ints!(numbers, u16, U16),
+        VR::SS => convert_ints!(numbers, i16, I16),
+        VR::UL => convert_ints!(numbers, u32, U32),
+        VR::SL => convert_ints!(numbers, i32, I32),
+        VR::UV => convert_ints!(numbers, u64, U64),
+        VR::SV => convert_ints!(numbers, i64, I64),
+        VR::FL => convert_floats!(numbers, f32, F32),
+        VR::FD => convert_floats!(numbers, f64, F64),
+        _ => return None,
+    };
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConvertVr, Error};
+    use crate::dataset::adaptor::testing::element;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    #[test]
+    fn converts_matching_numeric_elements() {
+        let mut tokens = element(
+            Tag(0x0028, 0x0106),
+            VR::SS,
+            4,
+            PrimitiveValue::I16([-5, 7].as_ref().into()),
+        );
+        tokens.extend(element(
+            Tag(0x0028, 0x0107),
+            VR::SS,
+            2,
+            PrimitiveValue::I16([-1].as_ref().into()),
+        ));
+        // string VRs are left alone
+        tokens.extend(element(
+            Tag(0x0028, 0x1050),
+            VR::DS,
+            2,
+            PrimitiveValue::from("40"),
+        ));
+
+        let out: Vec<_> = ConvertVr::new(tokens.clone())
+            .rule(Tag(0x0028, 0x0106), VR::SL)
+            .rule(Tag(0x0028, 0x1050), VR::US)
+            .collect::<Result<_, _>>()
+            .expect("conversion should be lossless");
+
+        let mut expected = element(
+            Tag(0x0028, 0x0106),
+            VR::SL,
+            8,
+            PrimitiveValue::I32([-5, 7].as_ref().into()),
+        );
+        expected.extend(tokens[2..].iter().cloned());
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn lossy_conversions_fail_unless_allowed() {
+        let tokens = element(
+            Tag(0x0028, 0x0106),
+            VR::US,
+            4,
+            PrimitiveValue::U16([40000, 12].as_ref().into()),
+        );
+
+        let mut strict = ConvertVr::new(tokens.clone()).rule(Tag(0x0028, 0x0106), VR::SS);
+        assert!(matches!(
+            strict.next(),
+            Some(Err(Error::LossyConversion {
+                tag: Tag(0x0028, 0x0106),
+                from: VR::US,
+                to: VR::SS,
+                ..
+            }))
+        ));
+        assert!(strict.next().is_none());
+
+        let out: Vec<_> = ConvertVr::new(tokens)
+            .rule(Tag(0x0028, 0x0106), VR::SS)
+            .lossy(true)
+            .collect::<Result<_, _>>()
+            .expect("lossy conversion should be allowed");
+        assert_eq!(
+            out,
+            element(
+                Tag(0x0028, 0x0106),
+                VR::SS,
+                4,
+                PrimitiveValue::I16([i16::MAX, 12].as_ref().into()),
+            )
+        );
+    }
+
+    #[test]
+    fn converts_between_integers_and_floats() {
+        let tokens = element(
+            Tag(0x0018, 0x9089),
+            VR::FD,
+            16,
+            PrimitiveValue::F64([1.0, 2.5].as_ref().into()),
+        );
+
+        let mut strict = ConvertVr::new(tokens.clone()).rule(Tag(0x0018, 0x9089), VR::UL);
+        assert!(strict.next().unwrap().is_err());
+
+        let out: Vec<_> = ConvertVr::new(tokens)
+            .rule(Tag(0x0018, 0x9089), VR::FL)
+            .collect::<Result<_, _>>()
+            .expect("conversion should be lossless");
+        assert_eq!(
+            out,
+            element(
+                Tag(0x0018, 0x9089),
+                VR::FL,
+                8,
+                PrimitiveValue::F32([1.0, 2.5].as_ref().into()),
+            )
+        );
+    }
+}
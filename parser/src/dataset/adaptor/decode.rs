@@ -0,0 +1,782 @@
+//! Adaptor for decoding encapsulated pixel data into native pixel data.
+use crate::dataset::DataToken;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::value::PrimitiveValue;
+use dicom_core::{Tag, VR};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+/// The tag of the _Pixel Data_ attribute.
+const PIXEL_DATA: Tag = Tag(0x7FE0, 0x0010);
+
+/// The tag of the _File Meta Information Group Length_ attribute.
+const META_GROUP_LENGTH: Tag = Tag(0x0002, 0x0000);
+
+/// The tag of the _Transfer Syntax UID_ attribute of the file meta group.
+const TRANSFER_SYNTAX_UID: Tag = Tag(0x0002, 0x0010);
+
+/// The attributes which only apply to encapsulated pixel data:
+/// _Extended Offset Table_ and _Extended Offset Table Lengths_.
+const ENCAPSULATED_ONLY: [Tag; 2] = [Tag(0x7FE0, 0x0001), Tag(0x7FE0, 0x0002)];
+
+/// The UID of the _Explicit VR Little Endian_ transfer syntax.
+pub const EXPLICIT_VR_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2.1";
+
+/// A decoder of the frames of encapsulated pixel data,
+/// as used by [`DecodePixelData`].
+///
+/// This is the point where a codec for the transfer syntax
+/// of the data set is plugged in.
+///
+/// [`DecodePixelData`]: ./struct.DecodePixelData.html
+pub trait FrameDecoder {
+    /// The error type of a frame which could not be decoded.
+    type Error;
+
+    /// Decode a single frame into native pixel data,
+    /// given the concatenated fragments of that frame.
+    fn decode_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
+impl<F: FrameDecoder + ?Sized> FrameDecoder for &mut F {
+    type Error = F::Error;
+
+    fn decode_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        (**self).decode_frame(frame)
+    }
+}
+
+/// A frame decoder which takes the fragments as native pixel data,
+/// without decoding them.
+///
+/// This fits encapsulated pixel data which is not actually compressed,
+/// and serves as a placeholder until a real decoder is plugged in.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PassthroughDecoder;
+
+impl FrameDecoder for PassthroughDecoder {
+    type Error = Infallible;
+
+    fn decode_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        Ok(frame.to_vec())
+    }
+}
+
+/// The position of the adaptor in the token stream.
+#[derive(Debug)]
+enum State {
+    /// outside of any pixel data
+    Pass,
+    /// after the header of an element to be dropped
+    Drop,
+    /// after the header of the file meta group length,
+    /// holding back the tokens taken until the transfer syntax UID
+    MetaGroup(Vec<DataToken>),
+    /// after the header of the transfer syntax UID,
+    /// before its value is replaced
+    TransferSyntax,
+    /// inside encapsulated pixel data of the root data set
+    Encapsulated(PixelSequence),
+}
+
+/// The tokens held back from the first attribute rewritten
+/// until the pixel data is known to decode.
+#[derive(Debug, Default)]
+struct Held {
+    /// the tokens as taken from the inner stream,
+    /// to be emitted if decoding fails
+    original: Vec<DataToken>,
+    /// the tokens with the attributes rewritten,
+    /// to be emitted if decoding succeeds
+    rewritten: Vec<DataToken>,
+}
+
+/// The encapsulated pixel data taken from the inner stream so far.
+#[derive(Debug, Default)]
+struct PixelSequence {
+    /// the tokens taken, to be emitted as they were if decoding fails
+    tokens: Vec<DataToken>,
+    /// the number of items started
+    items: u32,
+    /// the basic offset table
+    offset_table: Vec<u8>,
+    /// the fragments after the offset table
+    fragments: Vec<Vec<u8>>,
+}
+
+impl PixelSequence {
+    /// Take in the given token of the pixel sequence.
+    fn push(&mut self, token: DataToken) {
+        match &token {
+            DataToken::ItemStart { .. } => {
+                self.items += 1;
+                if self.items > 1 {
+                    self.fragments.push(Vec::new());
+                }
+            }
+            DataToken::ItemValue(data) if self.items == 1 => {
+                self.offset_table.extend_from_slice(data)
+            }
+            DataToken::ItemValue(data) => {
+                if let Some(fragment) = self.fragments.last_mut() {
+                    fragment.extend_from_slice(data);
+                }
+            }
+            _ => {}
+        }
+        self.tokens.push(token);
+    }
+
+    /// Group the fragments into frames:
+    /// by the basic offset table if present,
+    /// one frame per fragment if there are as many as the number of frames,
+    /// and a single frame otherwise.
+    fn frames(&self, number_of_frames: u32) -> Vec<Vec<u8>> {
+        let offsets: Vec<u32> = self
+            .offset_table
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        if offsets.len() > 1 {
+            let mut frames: Vec<Vec<u8>> = Vec::new();
+            // the offset of each fragment item from the first one
+            let mut position = 0_u32;
+            for fragment in &self.fragments {
+                if frames.is_empty() || offsets[frames.len()..].contains(&position) {
+                    frames.push(Vec::new());
+                }
+                if let Some(frame) = frames.last_mut() {
+                    frame.extend_from_slice(fragment);
+                }
+                position = position.saturating_add(8 + fragment.len() as u32);
+            }
+            frames
+        } else if self.fragments.len() == number_of_frames as usize {
+            self.fragments.clone()
+        } else {
+            vec![self.fragments.concat()]
+        }
+    }
+}
+
+/// A token stream adaptor which decodes the encapsulated _Pixel Data_
+/// `(7FE0,0010)` of a data set into native pixel data,
+/// using the given [`FrameDecoder`],
+/// so that the data set can be written
+/// in a native transfer syntax such as _Explicit VR Little Endian_.
+///
+/// The pixel sequence is taken in full from the inner stream.
+/// Its fragments are grouped into frames
+/// according to the basic offset table, if present,
+/// or else to the _Number of Frames_ attribute.
+/// Each frame is decoded,
+/// and the frames are joined into a single primitive value,
+/// emitted after an element header with the length of the value,
+/// padded to even length with a zero,
+/// and the VR `OB` if _Bits Allocated_ is 8 or less, `OW` otherwise.
+///
+/// The data set is also brought in line with the new encoding:
+/// the _Transfer Syntax UID_ of the file meta group, if present,
+/// is replaced with the given one
+/// ([`EXPLICIT_VR_LITTLE_ENDIAN`] by default),
+/// the _File Meta Information Group Length_ before it
+/// is adjusted to the length of the new UID,
+/// and the _Extended Offset Table_ and _Extended Offset Table Lengths_
+/// of the root data set are dropped.
+/// Attributes which describe the decoded pixels,
+/// such as _Photometric Interpretation_,
+/// depend on the decoder and are left as they are;
+/// they can be replaced with [`UpsertElement`].
+///
+/// Only the pixel data of the root data set is decoded.
+/// If a frame fails to decode,
+/// the error is recorded for [`errors`]
+/// and the data set is passed through unchanged,
+/// attributes above included.
+/// To this end, the tokens from the first attribute to rewrite
+/// until the end of the pixel data are held back,
+/// so the whole data set may be kept in memory.
+///
+/// [`FrameDecoder`]: ./trait.FrameDecoder.html
+/// [`EXPLICIT_VR_LITTLE_ENDIAN`]: ./constant.EXPLICIT_VR_LITTLE_ENDIAN.html
+/// [`UpsertElement`]: ../upsert/struct.UpsertElement.html
+/// [`errors`]: #method.errors
+#[derive(Debug)]
+pub struct DecodePixelData<I, D>
+where
+    D: FrameDecoder,
+{
+    /// the inner token stream
+    tokens: I,
+    /// the decoder of each frame
+    decoder: D,
+    /// the transfer syntax UID to write in the file meta group
+    transfer_syntax_uid: String,
+    /// the position in the token stream
+    state: State,
+    /// the current sequence nesting depth
+    depth: u32,
+    /// the tag of the last element header in the root data set
+    last_tag: Option<Tag>,
+    /// the value of _Bits Allocated_ in the root data set
+    bits_allocated: Option<u32>,
+    /// the value of _Number of Frames_ in the root data set
+    number_of_frames: Option<u32>,
+    /// the frames which could not be decoded
+    errors: Vec<D::Error>,
+    /// tokens to be emitted before consuming the inner stream
+    queued: VecDeque<DataToken>,
+    /// the tokens held back until the pixel data is known to decode
+    held: Option<Held>,
+}
+
+impl<I, D> DecodePixelData<I, D>
+where
+    I: Iterator<Item = DataToken>,
+    D: FrameDecoder,
+{
+    /// Create a new adaptor over the given tokens,
+    /// decoding each frame with the given decoder.
+    pub fn new<T>(tokens: T, decoder: D) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        DecodePixelData {
+            tokens: tokens.into_iter(),
+            decoder,
+            transfer_syntax_uid: EXPLICIT_VR_LITTLE_ENDIAN.to_string(),
+            state: State::Pass,
+            depth: 0,
+            last_tag: None,
+            bits_allocated: None,
+            number_of_frames: None,
+            errors: Vec::new(),
+            queued: VecDeque::new(),
+            held: None,
+        }
+    }
+
+    /// Replace the _Transfer Syntax UID_ of the file meta group
+    /// with the given UID instead of _Explicit VR Little Endian_.
+    pub fn transfer_syntax_uid(mut self, uid: impl Into<String>) -> Self {
+        self.transfer_syntax_uid = uid.into();
+        self
+    }
+
+    /// The errors of the frames which could not be decoded so far, in order.
+    pub fn errors(&self) -> &[D::Error] {
+        &self.errors
+    }
+
+    /// Recover the inner token stream and the decoder.
+    pub fn into_inner(self) -> (I, D) {
+        (self.tokens, self.decoder)
+    }
+
+    /// Start holding back the tokens from the given one,
+    /// unless already holding them back.
+    fn hold(&mut self, token: &DataToken) {
+        if self.held.is_none() {
+            self.held = Some(Held {
+                original: vec![token.clone()],
+                rewritten: Vec::new(),
+            });
+        }
+    }
+
+    /// Release the tokens held back, if any:
+    /// the rewritten ones followed by the queued ones
+    /// if the pixel data was decoded,
+    /// the original ones otherwise.
+    fn release(&mut self, decoded: bool) {
+        if let Some(held) = self.held.take() {
+            if decoded {
+                let mut tokens = held.rewritten;
+                tokens.extend(self.queued.drain(..));
+                self.queued = tokens.into();
+            } else {
+                self.queued = held.original.into();
+            }
+        }
+    }
+
+    /// Decode the frames of the given pixel sequence,
+    /// queueing up the native pixel data element,
+    /// or the pixel sequence as it was if any frame fails to decode,
+    /// and release the tokens held back accordingly.
+    fn decode(&mut self, sequence: PixelSequence) {
+        let mut data = Vec::new();
+        for frame in sequence.frames(self.number_of_frames.unwrap_or(1)) {
+            match self.decoder.decode_frame(&frame) {
+                Ok(frame) => data.extend(frame),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.queued.extend(sequence.tokens);
+                    self.release(false);
+                    return;
+                }
+            }
+        }
+        if data.len() & 1 == 1 {
+            data.push(0);
+        }
+        let vr = match self.bits_allocated {
+            Some(bits) if bits <= 8 => VR::OB,
+            _ => VR::OW,
+        };
+        let header = DataElementHeader::new(PIXEL_DATA, vr, Length(data.len() as u32));
+        self.queued.push_back(DataToken::ElementHeader(header));
+        self.queued
+            .push_back(DataToken::PrimitiveValue(PrimitiveValue::from(data)));
+        self.release(true);
+    }
+
+    /// Handle a token outside of any pixel data,
+    /// returning `None` if it should be dropped.
+    fn pass(&mut self, token: DataToken) -> Option<DataToken> {
+        match &token {
+            DataToken::SequenceStart { .. } => {
+                self.depth += 1;
+                self.last_tag = None;
+            }
+            DataToken::PixelSequenceStart if self.depth == 0 => {
+                let mut sequence = PixelSequence::default();
+                sequence.push(token);
+                self.state = State::Encapsulated(sequence);
+                return None;
+            }
+            DataToken::PixelSequenceStart => self.depth += 1,
+            DataToken::SequenceEnd => self.depth = self.depth.saturating_sub(1),
+            DataToken::ElementHeader(header) if self.depth == 0 => {
+                self.last_tag = Some(header.tag);
+                if header.tag == PIXEL_DATA && header.len.is_defined() {
+                    // native pixel data, nothing to decode
+                    self.release(true);
+                }
+                if header.tag == META_GROUP_LENGTH {
+                    self.hold(&token);
+                    self.state = State::MetaGroup(vec![token]);
+                    return None;
+                }
+                if ENCAPSULATED_ONLY.contains(&header.tag) {
+                    self.hold(&token);
+                    self.state = State::Drop;
+                    return None;
+                }
+                if header.tag == TRANSFER_SYNTAX_UID {
+                    self.hold(&token);
+                    self.state = State::TransferSyntax;
+                    let mut uid = self.transfer_syntax_uid.clone();
+                    if uid.len() & 1 == 1 {
+                        uid.push('\0');
+                    }
+                    let len = Length(uid.len() as u32);
+                    self.transfer_syntax_uid = uid;
+                    return Some(DataToken::ElementHeader(DataElementHeader::new(
+                        header.tag, header.vr, len,
+                    )));
+                }
+            }
+            DataToken::PrimitiveValue(value) if self.depth == 0 => match self.last_tag.take() {
+                Some(Tag(0x0028, 0x0100)) => self.bits_allocated = value.to_int().ok(),
+                Some(Tag(0x0028, 0x0008)) => self.number_of_frames = value.to_int().ok(),
+                _ => {}
+            },
+            _ => {}
+        }
+        Some(token)
+    }
+
+    /// Handle a token of the file meta group
+    /// after its group length,
+    /// releasing the held tokens once the transfer syntax UID is reached,
+    /// with the group length adjusted to the length of the new UID,
+    /// or as they were if the group ends without it.
+    fn meta_group(&mut self, mut held: Vec<DataToken>, token: DataToken) -> Option<DataToken> {
+        let header = match &token {
+            DataToken::ElementHeader(header) => *header,
+            _ => {
+                held.push(token);
+                self.state = State::MetaGroup(held);
+                return None;
+            }
+        };
+        if header.tag.group() != META_GROUP_LENGTH.group() || header.tag > TRANSFER_SYNTAX_UID {
+            let out = self.pass(token);
+            self.queued.extend(held);
+            self.queued.extend(out);
+            return None;
+        }
+        if header.tag != TRANSFER_SYNTAX_UID {
+            held.push(token);
+            self.state = State::MetaGroup(held);
+            return None;
+        }
+        let out = self.pass(token);
+        if let (Some(DataToken::ElementHeader(new_header)), Some(old_len)) =
+            (&out, header.len.get())
+        {
+            if let Some(DataToken::PrimitiveValue(value)) = held.get_mut(1) {
+                if let Ok(group_len) = value.to_int::<u32>() {
+                    let group_len = (group_len + new_header.len.0).saturating_sub(old_len);
+                    *value = PrimitiveValue::from(group_len);
+                }
+            }
+        }
+        self.queued.extend(held);
+        self.queued.extend(out);
+        None
+    }
+}
+
+/// Whether the token carries the value of an element, or a piece of it.
+fn is_value(token: &DataToken) -> bool {
+    matches!(
+        token,
+        DataToken::PrimitiveValue(_) | DataToken::ItemValue(_) | DataToken::LazyValue(_)
+    )
+}
+
+impl<I, D> Iterator for DecodePixelData<I, D>
+where
+    I: Iterator<Item = DataToken>,
+    D: FrameDecoder,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.queued.pop_front() {
+                return Some(token);
+            }
+
+            let token = match self.tokens.next() {
+                Some(token) => token,
+                None => {
+                    // the stream ended in the middle of the pixel data
+                    // or of the file meta group,
+                    // or without encapsulated pixel data
+                    match std::mem::replace(&mut self.state, State::Pass) {
+                        State::Encapsulated(PixelSequence { tokens, .. }) => {
+                            self.queued.extend(tokens);
+                            self.release(false);
+                        }
+                        State::MetaGroup(tokens) => {
+                            self.queued.extend(tokens);
+                            self.release(true);
+                        }
+                        _ => self.release(true),
+                    }
+                    if self.queued.is_empty() {
+                        return None;
+                    }
+                    continue;
+                }
+            };
+            if let Some(held) = &mut self.held {
+                held.original.push(token.clone());
+            }
+            let out = match std::mem::replace(&mut self.state, State::Pass) {
+                State::Pass => self.pass(token),
+                State::Drop if is_value(&token) => {
+                    self.state = State::Drop;
+                    None
+                }
+                State::Drop => self.pass(token),
+                State::MetaGroup(held) => self.meta_group(held, token),
+                State::TransferSyntax if is_value(&token) => {
+                    // any further chunks of the old value are dropped
+                    self.state = State::Drop;
+                    Some(DataToken::PrimitiveValue(PrimitiveValue::from(
+                        self.transfer_syntax_uid.as_str(),
+                    )))
+                }
+                State::TransferSyntax => self.pass(token),
+                State::Encapsulated(mut sequence) => {
+                    let end = matches!(token, DataToken::SequenceEnd);
+                    sequence.push(token);
+                    if end {
+                        self.decode(sequence);
+                    } else {
+                        self.state = State::Encapsulated(sequence);
+                    }
+                    None
+                }
+            };
+            if let Some(held) = &mut self.held {
+                held.rewritten.extend(self.queued.drain(..));
+                held.rewritten.extend(out);
+                continue;
+            }
+            if !self.queued.is_empty() {
+                // the tokens just released come first
+                self.queued.extend(out);
+                continue;
+            }
+            if out.is_some() {
+                return out;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecodePixelData, FrameDecoder, PassthroughDecoder};
+    use crate::dataset::adaptor::testing::element;
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    fn item(data: Vec<u8>) -> Vec<DataToken> {
+        vec![
+            DataToken::ItemStart {
+                len: Length(data.len() as u32),
+            },
+            DataToken::ItemValue(data),
+            DataToken::ItemEnd,
+        ]
+    }
+
+    fn header_and_attributes(number_of_frames: &str) -> Vec<DataToken> {
+        let mut tokens = element(
+            Tag(0x0002, 0x0010),
+            VR::UI,
+            22,
+            PrimitiveValue::from("1.2.840.10008.1.2.4.50"),
+        );
+        tokens.extend(element(
+            Tag(0x0028, 0x0008),
+            VR::IS,
+            2,
+            PrimitiveValue::from(number_of_frames),
+        ));
+        tokens.extend(element(
+            Tag(0x0028, 0x0100),
+            VR::US,
+            2,
+            PrimitiveValue::U16([8].as_ref().into()),
+        ));
+        tokens
+    }
+
+    fn encapsulated(offset_table: Vec<u8>, fragments: Vec<Vec<u8>>) -> Vec<DataToken> {
+        let mut tokens = vec![DataToken::PixelSequenceStart];
+        if offset_table.is_empty() {
+            tokens.extend(vec![
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+            ]);
+        } else {
+            tokens.extend(item(offset_table));
+        }
+        for fragment in fragments {
+            tokens.extend(item(fragment));
+        }
+        tokens.push(DataToken::SequenceEnd);
+        tokens
+    }
+
+    fn native_attributes() -> Vec<DataToken> {
+        let mut tokens = element(
+            Tag(0x0002, 0x0010),
+            VR::UI,
+            20,
+            PrimitiveValue::from("1.2.840.10008.1.2.1\0"),
+        );
+        tokens.extend(header_and_attributes("2 ")[2..].iter().cloned());
+        tokens
+    }
+
+    /// A decoder which reverses each frame, failing on empty frames.
+    struct ReverseDecoder(u32);
+
+    impl FrameDecoder for ReverseDecoder {
+        type Error = u32;
+
+        fn decode_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>, u32> {
+            self.0 += 1;
+            if frame.is_empty() {
+                return Err(self.0);
+            }
+            Ok(frame.iter().rev().cloned().collect())
+        }
+    }
+
+    #[test]
+    fn decodes_frames_into_native_pixel_data() {
+        let mut tokens = header_and_attributes("2 ");
+        // extended offset table
+        tokens.extend(element(
+            Tag(0x7FE0, 0x0001),
+            VR::OV,
+            16,
+            PrimitiveValue::from(vec![0; 16]),
+        ));
+        tokens.extend(encapsulated(vec![], vec![vec![1, 2], vec![3, 4, 5]]));
+
+        let mut adaptor = DecodePixelData::new(tokens, ReverseDecoder(0));
+        let out: Vec<_> = adaptor.by_ref().collect();
+
+        let mut expected = native_attributes();
+        expected.extend(element(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            6,
+            PrimitiveValue::from(vec![2, 1, 5, 4, 3, 0]),
+        ));
+        assert_eq!(out, expected);
+        assert!(adaptor.errors().is_empty());
+        assert_eq!(adaptor.into_inner().1 .0, 2);
+    }
+
+    #[test]
+    fn groups_fragments_by_offset_table() {
+        let mut tokens = header_and_attributes("2 ");
+        // the second frame starts after two items of 8 + 2 bytes
+        let offset_table = vec![0, 0, 0, 0, 20, 0, 0, 0];
+        tokens.extend(encapsulated(
+            offset_table,
+            vec![vec![1, 2], vec![3, 4], vec![5, 6]],
+        ));
+
+        let mut adaptor = DecodePixelData::new(tokens, ReverseDecoder(0));
+        let out: Vec<_> = adaptor.by_ref().collect();
+
+        let mut expected = native_attributes();
+        expected.extend(element(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            6,
+            PrimitiveValue::from(vec![4, 3, 2, 1, 6, 5]),
+        ));
+        assert_eq!(out, expected);
+        assert_eq!(adaptor.into_inner().1 .0, 2);
+    }
+
+    #[test]
+    fn passes_through_pixel_data_which_fails_to_decode() {
+        let mut tokens = header_and_attributes("2 ");
+        tokens.extend(encapsulated(vec![], vec![vec![1, 2], vec![]]));
+
+        let mut adaptor = DecodePixelData::new(tokens.clone(), ReverseDecoder(0));
+        let out: Vec<_> = adaptor.by_ref().collect();
+
+        // the transfer syntax UID is kept
+        assert_eq!(out, tokens);
+        assert_eq!(adaptor.errors(), &[2]);
+    }
+
+    #[test]
+    fn passthrough_decoder_joins_fragments() {
+        let mut tokens = header_and_attributes("1 ")[..2].to_vec();
+        tokens.extend(element(
+            Tag(0x0028, 0x0100),
+            VR::US,
+            2,
+            PrimitiveValue::U16([16].as_ref().into()),
+        ));
+        tokens.extend(encapsulated(vec![], vec![vec![1, 2], vec![3, 4]]));
+
+        let out: Vec<_> = DecodePixelData::new(tokens.clone(), PassthroughDecoder)
+            .transfer_syntax_uid("1.2.840.10008.1.2")
+            .collect();
+
+        // the odd UID is padded
+        let mut expected = element(
+            Tag(0x0002, 0x0010),
+            VR::UI,
+            18,
+            PrimitiveValue::from("1.2.840.10008.1.2\0"),
+        );
+        expected.extend(tokens[2..4].iter().cloned());
+        // a single frame
+        expected.extend(element(
+            Tag(0x7FE0, 0x0010),
+            VR::OW,
+            4,
+            PrimitiveValue::from(vec![1, 2, 3, 4]),
+        ));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn adjusts_meta_group_length_to_new_transfer_syntax_uid() {
+        let mut meta = element(
+            Tag(0x0002, 0x0001),
+            VR::OB,
+            2,
+            PrimitiveValue::from(vec![0, 1]),
+        );
+        meta.extend(header_and_attributes("1 ")[..2].iter().cloned());
+        meta.extend(element(
+            Tag(0x0002, 0x0012),
+            VR::UI,
+            4,
+            PrimitiveValue::from("1.2\0"),
+        ));
+        let group_length =
+            |len: u32| element(Tag(0x0002, 0x0000), VR::UL, 4, PrimitiveValue::from(len));
+
+        let mut tokens = group_length(10 + 30 + 12);
+        tokens.extend(meta.iter().cloned());
+        tokens.extend(element(
+            Tag(0x0008, 0x0060),
+            VR::CS,
+            2,
+            PrimitiveValue::from("MR"),
+        ));
+
+        let out: Vec<_> = DecodePixelData::new(tokens.clone(), PassthroughDecoder).collect();
+
+        // the UID goes from 22 to 20 bytes
+        let mut expected = group_length(10 + 28 + 12);
+        expected.extend(meta[..2].iter().cloned());
+        expected.extend(native_attributes()[..2].iter().cloned());
+        expected.extend(tokens[6..].iter().cloned());
+        assert_eq!(out, expected);
+
+        // without a transfer syntax UID, the group length is kept
+        let mut tokens = group_length(10);
+        tokens.extend(meta[..2].iter().cloned());
+        let out: Vec<_> = DecodePixelData::new(tokens.clone(), PassthroughDecoder).collect();
+        assert_eq!(out, tokens);
+    }
+
+    #[test]
+    fn keeps_attributes_when_decoding_fails() {
+        let mut tokens = element(Tag(0x0002, 0x0000), VR::UL, 4, PrimitiveValue::from(30));
+        tokens.extend(header_and_attributes("2 "));
+        tokens.extend(element(
+            Tag(0x7FE0, 0x0001),
+            VR::OV,
+            16,
+            PrimitiveValue::from(vec![0; 16]),
+        ));
+        tokens.extend(encapsulated(vec![], vec![vec![1, 2], vec![]]));
+
+        let mut adaptor = DecodePixelData::new(tokens.clone(), ReverseDecoder(0));
+        let out: Vec<_> = adaptor.by_ref().collect();
+
+        assert_eq!(out, tokens);
+        assert_eq!(adaptor.errors(), &[2]);
+    }
+
+    #[test]
+    fn replaces_chunked_transfer_syntax_uid_once() {
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0010),
+                VR::UI,
+                Length(22),
+            )),
+            DataToken::ItemValue(b"1.2.840.10008".to_vec()),
+            DataToken::ItemValue(b".1.2.4.50".to_vec()),
+        ];
+
+        let out: Vec<_> = DecodePixelData::new(tokens, PassthroughDecoder).collect();
+
+        assert_eq!(out, native_attributes()[..2].to_vec());
+    }
+}
@@ -0,0 +1,67 @@
+//! Adaptor for keeping track of the nesting depth of tokens.
+use crate::dataset::DataToken;
+
+/// A token stream adaptor which pairs each token
+/// with its nesting depth in the data set.
+///
+/// Tokens of the root data set are at depth 0.
+/// The start and end tokens of a sequence or item
+/// are at the same depth as the element or item containing them,
+/// whereas the tokens in between are one level deeper.
+#[derive(Debug, Clone)]
+pub struct WithDepth<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the depth of the next token
+    depth: u32,
+}
+
+impl<I> WithDepth<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        WithDepth {
+            tokens: tokens.into_iter(),
+            depth: 0,
+        }
+    }
+
+    /// Retrieve the depth of the token to be yielded next.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+}
+
+impl<I> Iterator for WithDepth<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = (u32, DataToken);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        match token {
+            DataToken::SequenceStart { .. }
+            | DataToken::PixelSequenceStart
+            | DataToken::ItemStart { .. } => {
+                let depth = self.depth;
+                self.depth += 1;
+                Some((depth, token))
+            }
+            DataToken::SequenceEnd | DataToken::ItemEnd => {
+                self.depth = self.depth.saturating_sub(1);
+                Some((self.depth, token))
+            }
+            _ => Some((self.depth, token)),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tokens.size_hint()
+    }
+}
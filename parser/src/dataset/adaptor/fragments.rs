@@ -0,0 +1,111 @@
+//! Adaptor for identifying the encoding of pixel data fragments.
+use crate::dataset::DataToken;
+use dicom_encoding::transfer_syntax::PixelEncoding;
+
+/// A token stream adaptor which pairs each token
+/// with the encoding of the pixel data it carries, if any.
+///
+/// Only the item values of encapsulated pixel data fragments
+/// are marked with the given encoding,
+/// which is usually obtained from the data set's transfer syntax
+/// via `TransferSyntax::pixel_encoding`.
+/// The basic offset table and all other tokens are paired with `None`.
+/// This lets consumers route each fragment
+/// to a suitable decoder, while keeping access to the raw data.
+#[derive(Debug, Clone)]
+pub struct WithPixelEncoding<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the encoding of pixel data fragments
+    encoding: PixelEncoding,
+    /// the number of items seen in the current pixel data element,
+    /// or `None` if not in encapsulated pixel data
+    pixel_items: Option<u32>,
+}
+
+impl<I> WithPixelEncoding<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens,
+    /// where pixel data fragments are encoded as described.
+    pub fn new<T>(tokens: T, encoding: PixelEncoding) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        WithPixelEncoding {
+            tokens: tokens.into_iter(),
+            encoding,
+            pixel_items: None,
+        }
+    }
+}
+
+impl<I> Iterator for WithPixelEncoding<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = (DataToken, Option<PixelEncoding>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        let encoding = match (&token, &mut self.pixel_items) {
+            (DataToken::PixelSequenceStart, _) => {
+                self.pixel_items = Some(0);
+                None
+            }
+            (DataToken::ItemStart { .. }, Some(items)) => {
+                *items += 1;
+                None
+            }
+            // the first item is the basic offset table
+            (DataToken::ItemValue(_), Some(items)) if *items > 1 => Some(self.encoding),
+            (DataToken::SequenceEnd, Some(_)) => {
+                // pixel data sequences do not nest
+                self.pixel_items = None;
+                None
+            }
+            _ => None,
+        };
+        Some((token, encoding))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tokens.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WithPixelEncoding;
+    use crate::dataset::DataToken;
+    use dicom_core::header::Length;
+    use dicom_encoding::transfer_syntax::PixelEncoding;
+
+    #[test]
+    fn marks_pixel_fragments_only() {
+        let tokens = vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(4) },
+            DataToken::ItemValue(vec![0; 4]),
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(2) },
+            DataToken::ItemValue(vec![0xFF, 0xD8]),
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(2) },
+            DataToken::ItemValue(vec![0xFF, 0xD9]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        let encodings: Vec<_> = WithPixelEncoding::new(tokens, PixelEncoding::Jpeg)
+            .map(|(_, encoding)| encoding)
+            .collect();
+
+        let jpeg = Some(PixelEncoding::Jpeg);
+        assert_eq!(
+            encodings,
+            vec![None, None, None, None, None, jpeg, None, None, jpeg, None, None]
+        );
+    }
+}
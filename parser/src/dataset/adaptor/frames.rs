@@ -0,0 +1,281 @@
+//! Adaptor for splitting native pixel data into frames.
+use crate::dataset::DataToken;
+use dicom_core::value::PrimitiveValue;
+use dicom_core::Tag;
+use std::collections::VecDeque;
+
+/// A token stream adaptor which splits the value of a native
+/// (non-encapsulated) _Pixel Data_ element into one chunk per frame,
+/// so that frames can be consumed in the same way
+/// as the fragments of encapsulated pixel data.
+///
+/// The frame size is computed from the _Rows_, _Columns_,
+/// _Samples per Pixel_ and _Bits Allocated_ attributes
+/// of the root data set, which must appear before the pixel data,
+/// and the number of frames from _Number of Frames_
+/// (one frame if absent).
+/// The pixel data value is then replaced by one [`DataToken::ItemValue`]
+/// per frame, holding the bytes of the value as given by
+/// [`PrimitiveValue::to_bytes`].
+/// A trailing padding byte is kept at the end of the last frame,
+/// so that the chunks add up to the element's length.
+///
+/// The value is passed through unchanged
+/// if any of the geometry attributes is missing,
+/// if frames do not end on a byte boundary,
+/// or if the value does not have the expected length.
+///
+/// [`DataToken::ItemValue`]: ../../enum.DataToken.html#variant.ItemValue
+/// [`PrimitiveValue::to_bytes`]: ../../../../dicom_core/value/enum.PrimitiveValue.html#method.to_bytes
+#[derive(Debug)]
+pub struct SplitFrames<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the current sequence nesting depth
+    depth: u32,
+    /// the tag of the last element header in the root data set
+    last_tag: Option<Tag>,
+    /// the image geometry captured so far
+    geometry: FrameGeometry,
+    /// tokens to be emitted before consuming the inner stream
+    queued: VecDeque<DataToken>,
+}
+
+/// The attributes which determine the size of a native frame.
+#[derive(Debug, Default)]
+struct FrameGeometry {
+    rows: Option<u32>,
+    columns: Option<u32>,
+    samples_per_pixel: Option<u32>,
+    bits_allocated: Option<u32>,
+    number_of_frames: Option<u32>,
+}
+
+impl FrameGeometry {
+    /// Record the value of the given element if it is a geometry attribute.
+    fn capture(&mut self, tag: Tag, value: &PrimitiveValue) {
+        let field = match tag {
+            Tag(0x0028, 0x0010) => &mut self.rows,
+            Tag(0x0028, 0x0011) => &mut self.columns,
+            Tag(0x0028, 0x0002) => &mut self.samples_per_pixel,
+            Tag(0x0028, 0x0100) => &mut self.bits_allocated,
+            Tag(0x0028, 0x0008) => &mut self.number_of_frames,
+            _ => return,
+        };
+        *field = value.to_int().ok();
+    }
+
+    /// The size of each frame in bytes, if known.
+    fn frame_len(&self) -> Option<usize> {
+        let bits = [
+            self.rows?,
+            self.columns?,
+            self.samples_per_pixel?,
+            self.bits_allocated?,
+        ]
+        .iter()
+        .try_fold(1_usize, |acc, &n| acc.checked_mul(n as usize))?;
+        if bits % 8 == 0 {
+            Some(bits / 8)
+        } else {
+            None
+        }
+    }
+
+    /// Split the given pixel data into frames, if it has the expected length.
+    fn split(&self, data: &[u8]) -> Option<Vec<Vec<u8>>> {
+        let frame_len = self.frame_len().filter(|&len| len > 0)?;
+        let frames = self.number_of_frames.unwrap_or(1) as usize;
+        let total = frame_len.checked_mul(frames).filter(|&t| t > 0)?;
+        if data.len() != total && data.len() != total + 1 {
+            return None;
+        }
+
+        let mut chunks: Vec<Vec<u8>> = data[..total]
+            .chunks(frame_len)
+            .map(|frame| frame.to_vec())
+            .collect();
+        if let Some(last) = chunks.last_mut() {
+            last.extend_from_slice(&data[total..]);
+        }
+        Some(chunks)
+    }
+}
+
+impl<I> SplitFrames<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        SplitFrames {
+            tokens: tokens.into_iter(),
+            depth: 0,
+            last_tag: None,
+            geometry: FrameGeometry::default(),
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Take the pixel data value from the inner stream
+    /// and queue it up, split in frames if possible.
+    fn queue_pixel_data(&mut self) {
+        let token = match self.tokens.next() {
+            Some(DataToken::PrimitiveValue(value)) => {
+                match self.geometry.split(&value.to_bytes()) {
+                    Some(frames) => {
+                        self.queued
+                            .extend(frames.into_iter().map(DataToken::ItemValue));
+                        return;
+                    }
+                    None => DataToken::PrimitiveValue(value),
+                }
+            }
+            Some(token) => token,
+            None => return,
+        };
+        self.queued.push_back(token);
+    }
+}
+
+impl<I> Iterator for SplitFrames<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.queued.pop_front() {
+            return Some(token);
+        }
+
+        let token = self.tokens.next()?;
+        match &token {
+            DataToken::SequenceStart { .. } | DataToken::PixelSequenceStart => {
+                self.depth += 1;
+                self.last_tag = None;
+            }
+            DataToken::SequenceEnd => {
+                self.depth = self.depth.saturating_sub(1);
+            }
+            DataToken::ElementHeader(header) if self.depth == 0 => {
+                if header.tag == Tag(0x7FE0, 0x0010) {
+                    self.last_tag = None;
+                    self.queue_pixel_data();
+                } else {
+                    self.last_tag = Some(header.tag);
+                }
+            }
+            DataToken::PrimitiveValue(value) if self.depth == 0 => {
+                if let Some(tag) = self.last_tag.take() {
+                    self.geometry.capture(tag, value);
+                }
+            }
+            _ => {}
+        }
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplitFrames;
+    use crate::dataset::adaptor::testing::element;
+    use crate::dataset::DataToken;
+    use dicom_core::header::Length;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    fn geometry(number_of_frames: &str) -> Vec<DataToken> {
+        let mut tokens = element(
+            Tag(0x0028, 0x0002),
+            VR::US,
+            2,
+            PrimitiveValue::U16([1].as_ref().into()),
+        );
+        tokens.extend(element(
+            Tag(0x0028, 0x0008),
+            VR::IS,
+            2,
+            PrimitiveValue::from(number_of_frames),
+        ));
+        tokens.extend(element(
+            Tag(0x0028, 0x0010),
+            VR::US,
+            2,
+            PrimitiveValue::U16([2].as_ref().into()),
+        ));
+        tokens.extend(element(
+            Tag(0x0028, 0x0011),
+            VR::US,
+            2,
+            PrimitiveValue::U16([2].as_ref().into()),
+        ));
+        tokens.extend(element(
+            Tag(0x0028, 0x0100),
+            VR::US,
+            2,
+            PrimitiveValue::U16([8].as_ref().into()),
+        ));
+        tokens
+    }
+
+    #[test]
+    fn splits_native_pixel_data_into_frames() {
+        let mut tokens = geometry("3 ");
+        tokens.extend(element(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            12,
+            PrimitiveValue::from(&(0..12).collect::<Vec<u8>>()[..]),
+        ));
+
+        let out: Vec<_> = SplitFrames::new(tokens.clone()).collect();
+
+        let mut expected = tokens[..tokens.len() - 1].to_vec();
+        expected.extend(vec![
+            DataToken::ItemValue(vec![0, 1, 2, 3]),
+            DataToken::ItemValue(vec![4, 5, 6, 7]),
+            DataToken::ItemValue(vec![8, 9, 10, 11]),
+        ]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn leaves_unexpected_pixel_data_alone() {
+        // the value is shorter than the number of frames implies
+        let mut tokens = geometry("4 ");
+        tokens.extend(element(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            12,
+            PrimitiveValue::from(&[0; 12][..]),
+        ));
+        let out: Vec<_> = SplitFrames::new(tokens.clone()).collect();
+        assert_eq!(out, tokens);
+
+        // geometry attributes in a nested data set do not count
+        let mut tokens = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1140),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ];
+        tokens.extend(geometry("1 "));
+        tokens.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+        tokens.extend(element(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            4,
+            PrimitiveValue::from(&[0; 4][..]),
+        ));
+        let out: Vec<_> = SplitFrames::new(tokens.clone()).collect();
+        assert_eq!(out, tokens);
+    }
+}
@@ -0,0 +1,169 @@
+//! Adaptor for removing group length elements.
+use crate::dataset::adaptor::WithDepth;
+use crate::dataset::DataToken;
+use dicom_core::Tag;
+
+/// A token stream adaptor which removes the group length elements
+/// `(gggg,0000)` of the root data set, header and value alike.
+///
+/// Group length elements are retired,
+/// save for the file meta information group length `(0002,0000)`,
+/// which is kept unless requested otherwise
+/// with [`strip_meta_group_length`].
+/// Elements inside sequence items are passed through unchanged.
+///
+/// [`strip_meta_group_length`]: #method.strip_meta_group_length
+#[derive(Debug, Clone)]
+pub struct StripGroupLengths<I> {
+    /// the inner token stream, with the depth of each token
+    tokens: WithDepth<I>,
+    /// whether to also remove `(0002,0000)`
+    strip_meta: bool,
+}
+
+impl<I> StripGroupLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        StripGroupLengths {
+            tokens: WithDepth::new(tokens),
+            strip_meta: false,
+        }
+    }
+
+    /// Define whether the file meta information group length `(0002,0000)`
+    /// is also removed.
+    pub fn strip_meta_group_length(mut self, strip_meta: bool) -> Self {
+        self.strip_meta = strip_meta;
+        self
+    }
+
+    /// Check whether an element with the given tag at the root
+    /// should be removed.
+    fn is_stripped(&self, tag: Tag) -> bool {
+        tag.element() == 0x0000 && (self.strip_meta || tag.group() != 0x0002)
+    }
+}
+
+impl<I> Iterator for StripGroupLengths<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, token) = self.tokens.next()?;
+            match token {
+                DataToken::ElementHeader(header) if depth == 0 && self.is_stripped(header.tag) => {
+                    // skip the value as well
+                    self.tokens.next();
+                }
+                token => return Some(token),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, max) = self.tokens.size_hint();
+        (0, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StripGroupLengths;
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    fn group_length(group: u16, len: u32) -> Vec<DataToken> {
+        vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(group, 0x0000),
+                VR::UL,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(len)),
+        ]
+    }
+
+    fn tokens() -> Vec<DataToken> {
+        let mut tokens = group_length(0x0002, 26);
+        tokens.extend(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0010),
+                VR::UI,
+                Length(18),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.840.10008.1.2\0")),
+        ]);
+        tokens.extend(group_length(0x0008, 24));
+        tokens.extend(vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ]);
+        tokens.extend(group_length(0x0020, 4));
+        tokens.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+        tokens.extend(group_length(0x0010, 0));
+        tokens
+    }
+
+    #[test]
+    fn strips_root_group_lengths_but_meta() {
+        let out: Vec<_> = StripGroupLengths::new(tokens()).collect();
+
+        let mut expected = group_length(0x0002, 26);
+        expected.extend(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0010),
+                VR::UI,
+                Length(18),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.840.10008.1.2\0")),
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ]);
+        // nested group length elements are kept
+        expected.extend(group_length(0x0020, 4));
+        expected.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn strips_meta_group_length_on_request() {
+        let out: Vec<_> = StripGroupLengths::new(tokens())
+            .strip_meta_group_length(true)
+            .collect();
+
+        assert_eq!(
+            &out[..2],
+            &[
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0002, 0x0010),
+                    VR::UI,
+                    Length(18),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from("1.2.840.10008.1.2\0")),
+            ]
+        );
+        assert_eq!(out.len(), 8);
+    }
+}
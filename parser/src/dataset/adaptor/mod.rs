@@ -0,0 +1,48 @@
+//! Token stream adaptors.
+//!
+//! The types in this module wrap an iterator of [`DataToken`]s
+//! and transform the data set on the fly,
+//! without building an in-memory representation of the DICOM object.
+//!
+//! [`DataToken`]: ../enum.DataToken.html
+pub mod charset;
+pub mod chunks;
+pub mod clamp;
+pub mod convert;
+pub mod decode;
+pub mod depth;
+pub mod fragments;
+pub mod frames;
+pub mod group_length;
+pub mod offset_table;
+pub mod pad;
+pub mod private;
+pub mod redact;
+pub mod remap;
+pub mod sort;
+pub mod tee;
+#[cfg(test)]
+pub(crate) mod testing;
+pub mod upsert;
+pub mod validate;
+pub mod vr;
+
+pub use self::charset::DeclareCharacterSet;
+pub use self::chunks::ValueChunks;
+pub use self::clamp::ClampValueLengths;
+pub use self::convert::ConvertVr;
+pub use self::decode::{DecodePixelData, FrameDecoder, PassthroughDecoder};
+pub use self::depth::WithDepth;
+pub use self::fragments::WithPixelEncoding;
+pub use self::frames::SplitFrames;
+pub use self::group_length::StripGroupLengths;
+pub use self::offset_table::BuildOffsetTable;
+pub use self::pad::PadToEvenLength;
+pub use self::private::RenumberPrivateBlocks;
+pub use self::redact::{PixelDataRedaction, RedactPixelData};
+pub use self::remap::RemapTags;
+pub use self::sort::SortElements;
+pub use self::tee::Tee;
+pub use self::upsert::UpsertElement;
+pub use self::validate::{Diagnostic, ValidateAgainstDictionary};
+pub use self::vr::ResolveVrs;
@@ -0,0 +1,379 @@
+//! Adaptor for filling in the basic offset table of encapsulated pixel data.
+use crate::dataset::DataToken;
+use dicom_core::header::Length;
+use dicom_core::Tag;
+use std::collections::VecDeque;
+
+/// The tag of the _Number of Frames_ attribute.
+const NUMBER_OF_FRAMES: Tag = Tag(0x0028, 0x0008);
+
+/// A token stream adaptor which fills in an empty basic offset table
+/// in the encapsulated _Pixel Data_ of the root data set,
+/// with the offset of the first fragment of each frame.
+///
+/// Frames start at the fragments given to [`frame_boundaries`], if any.
+/// Otherwise, each fragment is taken as a frame
+/// if there are as many fragments as the _Number of Frames_ attribute
+/// of the root data set, which must appear before the pixel data,
+/// and all fragments are taken as a single frame
+/// if there is only one frame (_Number of Frames_ is absent or 1).
+///
+/// The pixel sequence is taken in full from the inner stream,
+/// so as to know the length of every fragment,
+/// and the first item is then replaced
+/// with one holding the offsets as 32-bit little endian numbers.
+/// The pixel sequence is passed through unchanged
+/// if its basic offset table is not empty,
+/// if it has no fragments,
+/// or if the frames cannot be told apart.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::header::Length;
+/// # use dicom_parser::dataset::adaptor::BuildOffsetTable;
+/// # use dicom_parser::dataset::DataToken;
+/// let tokens = vec![
+///     DataToken::PixelSequenceStart,
+///     DataToken::ItemStart { len: Length(0) },
+///     DataToken::ItemEnd,
+///     DataToken::ItemStart { len: Length(4) },
+///     DataToken::ItemValue(vec![1, 2, 3, 4]),
+///     DataToken::ItemEnd,
+///     DataToken::ItemStart { len: Length(2) },
+///     DataToken::ItemValue(vec![5, 6]),
+///     DataToken::ItemEnd,
+///     DataToken::SequenceEnd,
+/// ];
+///
+/// // a single frame in two fragments
+/// let out: Vec<_> = BuildOffsetTable::new(tokens).collect();
+/// assert_eq!(out[1], DataToken::ItemStart { len: Length(4) });
+/// assert_eq!(out[2], DataToken::ItemValue(vec![0, 0, 0, 0]));
+/// ```
+///
+/// [`frame_boundaries`]: #method.frame_boundaries
+#[derive(Debug)]
+pub struct BuildOffsetTable<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the position of the first fragment of each frame, if given
+    boundaries: Option<Vec<usize>>,
+    /// the current sequence nesting depth
+    depth: u32,
+    /// the tag of the last element header in the root data set
+    last_tag: Option<Tag>,
+    /// the value of _Number of Frames_ in the root data set
+    number_of_frames: Option<u32>,
+    /// tokens to be emitted before consuming the inner stream
+    queued: VecDeque<DataToken>,
+}
+
+impl<I> BuildOffsetTable<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        BuildOffsetTable {
+            tokens: tokens.into_iter(),
+            boundaries: None,
+            depth: 0,
+            last_tag: None,
+            number_of_frames: None,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Start each frame at the fragment in the given position,
+    /// counting from 0 for the first fragment after the offset table,
+    /// instead of telling frames apart by the number of frames.
+    ///
+    /// The positions must be in increasing order, starting with 0.
+    pub fn frame_boundaries(mut self, boundaries: impl IntoIterator<Item = usize>) -> Self {
+        self.boundaries = Some(boundaries.into_iter().collect());
+        self
+    }
+
+    /// Take the rest of the pixel sequence from the inner stream
+    /// and queue it up, with the offset table filled in if possible.
+    fn queue_pixel_sequence(&mut self, start: DataToken) {
+        let mut tokens = vec![start];
+        for token in self.tokens.by_ref() {
+            let end = token == DataToken::SequenceEnd;
+            tokens.push(token);
+            if end {
+                break;
+            }
+        }
+
+        if let Some((end, fragments)) = read_items(&tokens) {
+            if let Some(starts) = self.frame_starts(fragments.len()) {
+                let table = offset_table(&fragments, &starts);
+                tokens.splice(
+                    1..=end,
+                    vec![
+                        DataToken::ItemStart {
+                            len: Length(table.len() as u32),
+                        },
+                        DataToken::ItemValue(table),
+                        DataToken::ItemEnd,
+                    ],
+                );
+            }
+        }
+        self.queued.extend(tokens);
+    }
+
+    /// The position of the first fragment of each frame,
+    /// if the frames can be told apart.
+    fn frame_starts(&self, fragments: usize) -> Option<Vec<usize>> {
+        if let Some(boundaries) = &self.boundaries {
+            let valid = boundaries.first() == Some(&0)
+                && boundaries.windows(2).all(|w| w[0] < w[1])
+                && boundaries.last().map(|&b| b < fragments) == Some(true);
+            return if valid {
+                Some(boundaries.clone())
+            } else {
+                None
+            };
+        }
+        match self.number_of_frames.unwrap_or(1) as usize {
+            1 => Some(vec![0]),
+            frames if frames == fragments => Some((0..frames).collect()),
+            _ => None,
+        }
+    }
+}
+
+/// Read the items of a pixel sequence,
+/// returning the position of the end of the basic offset table
+/// and the length of each fragment as written,
+/// or `None` if the offset table is not empty
+/// or there are no fragments.
+///
+/// The length of a fragment is the one in its item header,
+/// or the length of its value if undefined,
+/// rounded up to even length as the fragment will be padded.
+fn read_items(tokens: &[DataToken]) -> Option<(usize, Vec<u32>)> {
+    let mut table_end = None;
+    let mut fragments = Vec::new();
+    // the declared length of the current fragment and the length of its value
+    let mut current = None;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            DataToken::ItemStart { len } if table_end.is_some() => current = Some((*len, 0_u32)),
+            DataToken::ItemValue(data) if table_end.is_none() && !data.is_empty() => return None,
+            DataToken::ItemValue(data) => {
+                if let Some((_, value_len)) = &mut current {
+                    *value_len += data.len() as u32;
+                }
+            }
+            DataToken::ItemEnd if table_end.is_none() => table_end = Some(i),
+            DataToken::ItemEnd => {
+                if let Some((len, value_len)) = current.take() {
+                    let len = len.get().unwrap_or(value_len);
+                    fragments.push(len.saturating_add(len & 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    if fragments.is_empty() {
+        return None;
+    }
+    table_end.map(|end| (end, fragments))
+}
+
+/// Build a basic offset table
+/// from the length of each fragment
+/// and the position of the first fragment of each frame.
+fn offset_table(fragments: &[u32], frame_starts: &[usize]) -> Vec<u8> {
+    let mut table = Vec::with_capacity(frame_starts.len() * 4);
+    let mut offset = 0_u32;
+    let mut starts = frame_starts.iter().peekable();
+    for (i, len) in fragments.iter().enumerate() {
+        if starts.peek() == Some(&&i) {
+            starts.next();
+            table.extend_from_slice(&offset.to_le_bytes());
+        }
+        // each item has a header of 8 bytes
+        offset = offset.saturating_add(8).saturating_add(*len);
+    }
+    table
+}
+
+impl<I> Iterator for BuildOffsetTable<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.queued.pop_front() {
+            return Some(token);
+        }
+
+        let token = self.tokens.next()?;
+        let last_tag = self.last_tag.take();
+        match &token {
+            DataToken::SequenceStart { .. } => self.depth += 1,
+            DataToken::PixelSequenceStart if self.depth == 0 => {
+                self.queue_pixel_sequence(token);
+                return self.queued.pop_front();
+            }
+            DataToken::PixelSequenceStart => self.depth += 1,
+            DataToken::SequenceEnd => self.depth = self.depth.saturating_sub(1),
+            DataToken::ElementHeader(header) if self.depth == 0 => {
+                self.last_tag = Some(header.tag);
+            }
+            DataToken::PrimitiveValue(value)
+                if self.depth == 0 && last_tag == Some(NUMBER_OF_FRAMES) =>
+            {
+                self.number_of_frames = value.to_int().ok();
+            }
+            _ => {}
+        }
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildOffsetTable;
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    fn item(data: Vec<u8>) -> Vec<DataToken> {
+        vec![
+            DataToken::ItemStart {
+                len: Length(data.len() as u32),
+            },
+            DataToken::ItemValue(data),
+            DataToken::ItemEnd,
+        ]
+    }
+
+    fn number_of_frames(frames: &str) -> Vec<DataToken> {
+        vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0028, 0x0008),
+                VR::IS,
+                Length(frames.len() as u32),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(frames)),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x7FE0, 0x0010),
+                VR::OB,
+                Length::UNDEFINED,
+            )),
+        ]
+    }
+
+    fn encapsulated(offset_table: Vec<u8>, fragments: Vec<Vec<u8>>) -> Vec<DataToken> {
+        let mut tokens = vec![DataToken::PixelSequenceStart];
+        if offset_table.is_empty() {
+            tokens.extend(vec![
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+            ]);
+        } else {
+            tokens.extend(item(offset_table));
+        }
+        for fragment in fragments {
+            tokens.extend(item(fragment));
+        }
+        tokens.push(DataToken::SequenceEnd);
+        tokens
+    }
+
+    #[test]
+    fn builds_offset_table_for_two_frames() {
+        let fragments = vec![vec![1; 10], vec![2; 6]];
+        let mut tokens = number_of_frames("2 ");
+        tokens.extend(encapsulated(vec![], fragments.clone()));
+        tokens.extend(number_of_frames("1 ")[..2].iter().cloned());
+
+        let out: Vec<_> = BuildOffsetTable::new(tokens).collect();
+
+        let mut expected = number_of_frames("2 ");
+        expected.extend(encapsulated(vec![0, 0, 0, 0, 18, 0, 0, 0], fragments));
+        expected.extend(number_of_frames("1 ")[..2].iter().cloned());
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn builds_offset_table_from_frame_boundaries() {
+        let fragments = vec![vec![1; 4], vec![1; 2], vec![2; 8]];
+        let mut tokens = number_of_frames("2 ");
+        tokens.extend(encapsulated(vec![], fragments.clone()));
+
+        let out: Vec<_> = BuildOffsetTable::new(tokens)
+            .frame_boundaries(vec![0, 2])
+            .collect();
+
+        let mut expected = number_of_frames("2 ");
+        expected.extend(encapsulated(vec![0, 0, 0, 0, 22, 0, 0, 0], fragments));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn builds_offset_table_from_item_lengths() {
+        let mut tokens = number_of_frames("2 ");
+        tokens.extend(vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            // an odd length fragment in chunks, padded when written
+            DataToken::ItemStart { len: Length(9) },
+            DataToken::ItemValue(vec![1; 5]),
+            DataToken::ItemValue(vec![1; 4]),
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(2) },
+            DataToken::ItemValue(vec![2; 2]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ]);
+
+        let out: Vec<_> = BuildOffsetTable::new(tokens.clone()).collect();
+
+        let mut expected = tokens;
+        expected.splice(
+            4..5,
+            vec![
+                DataToken::ItemStart { len: Length(8) },
+                DataToken::ItemValue(vec![0, 0, 0, 0, 18, 0, 0, 0]),
+            ],
+        );
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn leaves_unresolved_pixel_data_alone() {
+        // already has an offset table
+        let mut tokens = number_of_frames("2 ");
+        tokens.extend(encapsulated(
+            vec![0, 0, 0, 0, 9, 0, 0, 0],
+            vec![vec![1], vec![2]],
+        ));
+        let out: Vec<_> = BuildOffsetTable::new(tokens.clone()).collect();
+        assert_eq!(out, tokens);
+
+        // more fragments than frames
+        let mut tokens = number_of_frames("2 ");
+        tokens.extend(encapsulated(vec![], vec![vec![1], vec![2], vec![3]]));
+        let out: Vec<_> = BuildOffsetTable::new(tokens.clone()).collect();
+        assert_eq!(out, tokens);
+
+        // boundaries past the last fragment
+        let out: Vec<_> = BuildOffsetTable::new(tokens.clone())
+            .frame_boundaries(vec![0, 3])
+            .collect();
+        assert_eq!(out, tokens);
+    }
+}
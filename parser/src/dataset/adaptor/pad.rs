@@ -0,0 +1,397 @@
+//! Adaptor for padding values to even length.
+use super::chunks::padding_of;
+use crate::dataset::DataToken;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::value::PrimitiveValue;
+use std::collections::VecDeque;
+use std::iter::Peekable;
+
+/// A token stream adaptor which pads every value to even length,
+/// as required by the standard,
+/// and fixes up odd lengths declared in element headers
+/// and in the items of encapsulated pixel data.
+///
+/// Values are padded with the byte appropriate
+/// to the VR of the preceding element header:
+/// a space for textual VRs
+/// and a null byte for the others (`UI` included).
+/// Pixel data fragments are padded with a null byte.
+/// When a value is split across multiple [`DataToken::ItemValue`] chunks,
+/// only the last chunk is padded.
+///
+/// Values of numeric VRs are always even in length,
+/// and date/time values are left to the encoder,
+/// so these pass through unchanged.
+/// The explicit lengths of sequences
+/// and of items holding a data set
+/// grow by the padding added within them,
+/// so that they still match their content.
+/// To this end, the tokens of a sequence or item of defined length
+/// are held back until it ends.
+///
+/// [`DataToken::ItemValue`]: ../../enum.DataToken.html#variant.ItemValue
+#[derive(Debug)]
+pub struct PadToEvenLength<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// the inner token stream
+    tokens: Peekable<I>,
+    /// the padding byte for the value being read
+    padding: u8,
+    /// the number of bytes in the value chunks seen so far
+    chunked_len: usize,
+    /// whether the tokens are inside encapsulated pixel data
+    in_pixel_sequence: bool,
+    /// the sequences and items open, from the outermost one
+    open: Vec<Open>,
+    /// the tokens held back until the sequences and items
+    /// of defined length are closed
+    buffer: Vec<DataToken>,
+    /// the tokens ready to be emitted
+    ready: VecDeque<DataToken>,
+}
+
+/// A sequence or item open in the token stream.
+#[derive(Debug)]
+struct Open {
+    /// the position of its start token in the buffer,
+    /// if it declares a defined length
+    start: Option<usize>,
+    /// the number of padding bytes added to its content so far
+    added: u32,
+}
+
+impl<I> PadToEvenLength<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        PadToEvenLength {
+            tokens: tokens.into_iter().peekable(),
+            padding: 0,
+            chunked_len: 0,
+            in_pixel_sequence: false,
+            open: Vec::new(),
+            buffer: Vec::new(),
+            ready: VecDeque::new(),
+        }
+    }
+
+    /// Pad the given length to even length,
+    /// counting the padding towards the construct open.
+    fn pad_length(&mut self, len: Length) -> Length {
+        let padded = even_length(len);
+        if padded != len {
+            if let Some(open) = self.open.last_mut() {
+                open.added += 1;
+            }
+        }
+        padded
+    }
+
+    /// Pad the given token.
+    fn pad(&mut self, token: DataToken) -> DataToken {
+        match token {
+            DataToken::ElementHeader(header) => {
+                self.padding = padding_of(header.vr);
+                DataToken::ElementHeader(DataElementHeader::new(
+                    header.tag,
+                    header.vr,
+                    self.pad_length(header.len),
+                ))
+            }
+            DataToken::PixelSequenceStart => {
+                self.in_pixel_sequence = true;
+                DataToken::PixelSequenceStart
+            }
+            DataToken::SequenceEnd => {
+                // pixel sequences do not nest
+                self.in_pixel_sequence = false;
+                DataToken::SequenceEnd
+            }
+            DataToken::ItemStart { len } if self.in_pixel_sequence => {
+                self.padding = 0;
+                DataToken::ItemStart {
+                    len: self.pad_length(len),
+                }
+            }
+            DataToken::PrimitiveValue(value) => {
+                DataToken::PrimitiveValue(pad_value(value, self.padding))
+            }
+            DataToken::ItemValue(data) => DataToken::ItemValue(self.pad_chunk(data)),
+            token => token,
+        }
+    }
+
+    /// Keep track of the sequences and items opened and closed
+    /// by the given padded token,
+    /// holding it back if within a sequence or item of defined length.
+    fn track(&mut self, token: DataToken) {
+        let closes = match &token {
+            DataToken::PixelSequenceStart => {
+                self.open.push(Open {
+                    start: None,
+                    added: 0,
+                });
+                false
+            }
+            DataToken::SequenceStart { len, .. } | DataToken::ItemStart { len }
+                if !self.in_pixel_sequence =>
+            {
+                let start = Some(self.buffer.len()).filter(|_| len.is_defined());
+                self.open.push(Open { start, added: 0 });
+                false
+            }
+            DataToken::SequenceEnd => true,
+            DataToken::ItemEnd => !self.in_pixel_sequence,
+            _ => false,
+        };
+        if self.open.iter().any(|open| open.start.is_some()) {
+            self.buffer.push(token);
+        } else {
+            self.ready.push_back(token);
+        }
+        if closes {
+            self.close();
+        }
+    }
+
+    /// Close the innermost sequence or item,
+    /// adding the padding within it to its length,
+    /// and release the tokens held back once no length is left to fix.
+    fn close(&mut self) {
+        let open = match self.open.pop() {
+            Some(open) => open,
+            None => return,
+        };
+        if let Some(start) = open.start.filter(|_| open.added > 0) {
+            if let DataToken::SequenceStart { len, .. } | DataToken::ItemStart { len } =
+                &mut self.buffer[start]
+            {
+                *len = Length(len.0 + open.added);
+            }
+        }
+        if let Some(parent) = self.open.last_mut() {
+            parent.added += open.added;
+        }
+        if self.open.iter().all(|open| open.start.is_none()) {
+            self.ready.extend(self.buffer.drain(..));
+        }
+    }
+
+    /// Pad the given value chunk if it is the last one of an odd-length value.
+    fn pad_chunk(&mut self, mut data: Vec<u8>) -> Vec<u8> {
+        self.chunked_len += data.len();
+        let last = !matches!(self.tokens.peek(), Some(DataToken::ItemValue(_)));
+        if last {
+            if self.chunked_len % 2 == 1 {
+                data.push(self.padding);
+            }
+            self.chunked_len = 0;
+        }
+        data
+    }
+}
+
+impl<I> Iterator for PadToEvenLength<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.ready.pop_front() {
+                return Some(token);
+            }
+            match self.tokens.next() {
+                Some(token) => {
+                    let token = self.pad(token);
+                    self.track(token);
+                }
+                // the stream ended in a sequence or item of defined length
+                None if !self.buffer.is_empty() => self.ready.extend(self.buffer.drain(..)),
+                None => return None,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let held = self.ready.len() + self.buffer.len();
+        let (lower, upper) = self.tokens.size_hint();
+        (lower + held, upper.map(|upper| upper + held))
+    }
+}
+
+/// Round up an odd defined length to the next even number.
+fn even_length(len: Length) -> Length {
+    match len.get() {
+        Some(l) if l % 2 == 1 => Length(l + 1),
+        _ => len,
+    }
+}
+
+/// Pad a textual or binary value to even length with the given byte.
+fn pad_value(value: PrimitiveValue, padding: u8) -> PrimitiveValue {
+    match value {
+        PrimitiveValue::Str(mut s) if s.len() % 2 == 1 => {
+            s.push(char::from(padding));
+            PrimitiveValue::Str(s)
+        }
+        PrimitiveValue::Strs(mut strs) => {
+            // values are separated by a backslash
+            let len = strs
+                .iter()
+                .map(|s| s.len() + 1)
+                .sum::<usize>()
+                .saturating_sub(1);
+            if len % 2 == 1 {
+                if let Some(s) = strs.last_mut() {
+                    s.push(char::from(padding));
+                }
+            }
+            PrimitiveValue::Strs(strs)
+        }
+        PrimitiveValue::U8(mut bytes) if bytes.len() % 2 == 1 => {
+            bytes.push(padding);
+            PrimitiveValue::U8(bytes)
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PadToEvenLength;
+    use crate::dataset::adaptor::testing::header;
+    use crate::dataset::DataToken;
+    use dicom_core::header::Length;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    #[test]
+    fn pads_odd_length_values() {
+        let tokens = vec![
+            header(Tag(0x0008, 0x0018), VR::UI, 5),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.3")),
+            header(Tag(0x0008, 0x0060), VR::CS, 2),
+            DataToken::PrimitiveValue(PrimitiveValue::from("MR")),
+            header(Tag(0x0010, 0x0010), VR::PN, 7),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^Jon")),
+            header(Tag(0x0020, 0x0037), VR::DS, 5),
+            DataToken::PrimitiveValue(PrimitiveValue::Strs(
+                ["1", "0", "0"].iter().map(|s| s.to_string()).collect(),
+            )),
+            header(Tag(0x0028, 0x0010), VR::US, 2),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([512].as_ref().into())),
+            header(Tag(0x0029, 0x1010), VR::OB, 3),
+            DataToken::PrimitiveValue(PrimitiveValue::from(&[1, 2, 3][..])),
+            // a value split in chunks
+            header(Tag(0x0042, 0x0011), VR::OB, 5),
+            DataToken::ItemValue(vec![1, 2]),
+            DataToken::ItemValue(vec![3, 4, 5]),
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(3) },
+            DataToken::ItemValue(vec![0x99; 3]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        let out: Vec<_> = PadToEvenLength::new(tokens).collect();
+
+        assert_eq!(
+            out,
+            vec![
+                header(Tag(0x0008, 0x0018), VR::UI, 6),
+                DataToken::PrimitiveValue(PrimitiveValue::from("1.2.3\0")),
+                header(Tag(0x0008, 0x0060), VR::CS, 2),
+                DataToken::PrimitiveValue(PrimitiveValue::from("MR")),
+                header(Tag(0x0010, 0x0010), VR::PN, 8),
+                DataToken::PrimitiveValue(PrimitiveValue::from("Doe^Jon ")),
+                header(Tag(0x0020, 0x0037), VR::DS, 6),
+                DataToken::PrimitiveValue(PrimitiveValue::Strs(
+                    ["1", "0", "0 "].iter().map(|s| s.to_string()).collect(),
+                )),
+                header(Tag(0x0028, 0x0010), VR::US, 2),
+                DataToken::PrimitiveValue(PrimitiveValue::U16([512].as_ref().into())),
+                header(Tag(0x0029, 0x1010), VR::OB, 4),
+                DataToken::PrimitiveValue(PrimitiveValue::from(&[1, 2, 3, 0][..])),
+                header(Tag(0x0042, 0x0011), VR::OB, 6),
+                DataToken::ItemValue(vec![1, 2]),
+                DataToken::ItemValue(vec![3, 4, 5, 0]),
+                DataToken::PixelSequenceStart,
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+                DataToken::ItemStart { len: Length(4) },
+                DataToken::ItemValue(vec![0x99, 0x99, 0x99, 0]),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+            ]
+        );
+    }
+    #[test]
+    fn adjusts_lengths_of_sequences_and_items() {
+        let tokens = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length(41),
+            },
+            DataToken::ItemStart { len: Length(13) },
+            header(Tag(0x0020, 0x000E), VR::UI, 5),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.3")),
+            DataToken::ItemEnd,
+            // an item of undefined length counts towards the sequence
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            header(Tag(0x0008, 0x0060), VR::CS, 2),
+            DataToken::PrimitiveValue(PrimitiveValue::from("MR")),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(1) },
+            DataToken::ItemValue(vec![0x99]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        let out: Vec<_> = PadToEvenLength::new(tokens).collect();
+
+        assert_eq!(
+            out,
+            vec![
+                DataToken::SequenceStart {
+                    tag: Tag(0x0008, 0x1115),
+                    len: Length(42),
+                },
+                DataToken::ItemStart { len: Length(14) },
+                header(Tag(0x0020, 0x000E), VR::UI, 6),
+                DataToken::PrimitiveValue(PrimitiveValue::from("1.2.3\0")),
+                DataToken::ItemEnd,
+                DataToken::ItemStart {
+                    len: Length::UNDEFINED,
+                },
+                header(Tag(0x0008, 0x0060), VR::CS, 2),
+                DataToken::PrimitiveValue(PrimitiveValue::from("MR")),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+                DataToken::PixelSequenceStart,
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+                DataToken::ItemStart { len: Length(2) },
+                DataToken::ItemValue(vec![0x99, 0]),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+            ]
+        );
+    }
+}
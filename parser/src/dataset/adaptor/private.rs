@@ -0,0 +1,252 @@
+//! Adaptor for renumbering private data element blocks.
+use crate::dataset::DataToken;
+use dicom_core::{PrimitiveValue, Tag};
+use std::collections::{BTreeMap, HashMap};
+
+/// Check whether the given tag is of a private creator data element,
+/// in the form `(gggg,00XX)` where `gggg` is odd
+/// and `XX` is between `10` and `FF`.
+pub(crate) fn is_private_creator(tag: Tag) -> bool {
+    let Tag(group, element) = tag;
+    group % 2 == 1 && (0x0010..=0x00FF).contains(&element)
+}
+
+/// Obtain the private creator identifier from the value
+/// of a private creator data element.
+///
+/// The value is interpreted from its byte representation,
+/// since it might have been read with the VR `UN`.
+pub(crate) fn private_creator_of(value: &PrimitiveValue) -> String {
+    let bytes = value.to_bytes();
+    String::from_utf8_lossy(&bytes)
+        .trim_end_matches(&[' ', '\0'][..])
+        .to_string()
+}
+
+/// A token stream adaptor which moves private data element blocks
+/// to the block numbers assigned to their private creators.
+///
+/// Given a mapping of private creator identifiers to private blocks
+/// (the `XX` in `(gggg,00XX)`),
+/// the private creator elements of the data set are renumbered,
+/// and so are all private data elements `(gggg,XXee)` reserved by them.
+/// Private creators absent from the mapping keep their block.
+/// The mapping is applied to each data set independently,
+/// including the data sets nested in sequence items.
+///
+/// Values are passed through unchanged.
+/// Note that renumbering blocks may leave elements
+/// out of ascending tag order.
+/// It is also the caller's responsibility that the assigned blocks
+/// do not collide with other blocks in the same group.
+#[derive(Debug)]
+pub struct RenumberPrivateBlocks<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the target block of each private creator
+    assignment: HashMap<String, u8>,
+    /// a stack of block mappings `(group, source block) -> target block`,
+    /// one for each data set level
+    blocks: Vec<BTreeMap<(u16, u8), u8>>,
+    /// a token to be emitted before consuming the inner stream
+    queued: Option<DataToken>,
+}
+
+impl<I> RenumberPrivateBlocks<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens,
+    /// using the given private creator to block assignment.
+    pub fn new<T>(tokens: T, assignment: HashMap<String, u8>) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        RenumberPrivateBlocks {
+            tokens: tokens.into_iter(),
+            assignment,
+            blocks: vec![BTreeMap::new()],
+            queued: None,
+        }
+    }
+
+    /// Translate the tag of a private data element
+    /// according to the current data set's block mapping.
+    fn map_tag(&self, tag: Tag) -> Tag {
+        let Tag(group, element) = tag;
+        if group % 2 == 0 || element < 0x1000 {
+            return tag;
+        }
+        let block = (element >> 8) as u8;
+        match self
+            .blocks
+            .last()
+            .and_then(|blocks| blocks.get(&(group, block)))
+        {
+            Some(&target) => Tag(group, (u16::from(target) << 8) | (element & 0x00FF)),
+            None => tag,
+        }
+    }
+}
+
+impl<I> Iterator for RenumberPrivateBlocks<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.queued.take() {
+            return Some(token);
+        }
+
+        match self.tokens.next()? {
+            DataToken::ElementHeader(mut header) if is_private_creator(header.tag) => {
+                // the creator is only known after its value
+                let value = self.tokens.next();
+                if let Some(DataToken::PrimitiveValue(ref v)) = value {
+                    let Tag(group, element) = header.tag;
+                    let block = element as u8;
+                    let target = self
+                        .assignment
+                        .get(&private_creator_of(v))
+                        .copied()
+                        .unwrap_or(block);
+                    if let Some(blocks) = self.blocks.last_mut() {
+                        blocks.insert((group, block), target);
+                    }
+                    header.tag = Tag(group, u16::from(target));
+                }
+                self.queued = value;
+                Some(DataToken::ElementHeader(header))
+            }
+            DataToken::ElementHeader(mut header) => {
+                header.tag = self.map_tag(header.tag);
+                Some(DataToken::ElementHeader(header))
+            }
+            DataToken::SequenceStart { tag, len } => Some(DataToken::SequenceStart {
+                tag: self.map_tag(tag),
+                len,
+            }),
+            token @ DataToken::ItemStart { .. } => {
+                self.blocks.push(BTreeMap::new());
+                Some(token)
+            }
+            token @ DataToken::ItemEnd => {
+                self.blocks.pop();
+                Some(token)
+            }
+            token => Some(token),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenumberPrivateBlocks;
+    use crate::dataset::adaptor::testing::header;
+    use crate::dataset::DataToken;
+    use dicom_core::header::Length;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    fn creator(element: u16, name: &str) -> Vec<DataToken> {
+        vec![
+            header(Tag(0x0009, element), VR::LO, name.len() as u32),
+            DataToken::PrimitiveValue(PrimitiveValue::from(name)),
+        ]
+    }
+
+    fn value(group: u16, element: u16, v: u16) -> Vec<DataToken> {
+        vec![
+            header(Tag(group, element), VR::US, 2),
+            DataToken::PrimitiveValue(PrimitiveValue::from(v)),
+        ]
+    }
+
+    #[test]
+    fn renumber_private_blocks() {
+        let tokens: Vec<DataToken> = vec![
+            creator(0x0010, "ACME 1.0"),
+            creator(0x0011, "OTHER"),
+            creator(0x0012, "UNTOUCHED"),
+            value(0x0009, 0x1001, 1),
+            value(0x0009, 0x1101, 2),
+            value(0x0009, 0x1201, 3),
+            value(0x0010, 0x1001, 4),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let assignment = vec![("ACME 1.0".to_string(), 0x11), ("OTHER".to_string(), 0x10)]
+            .into_iter()
+            .collect();
+
+        let out: Vec<_> = RenumberPrivateBlocks::new(tokens, assignment).collect();
+
+        let expected: Vec<DataToken> = vec![
+            creator(0x0011, "ACME 1.0"),
+            creator(0x0010, "OTHER"),
+            creator(0x0012, "UNTOUCHED"),
+            value(0x0009, 0x1101, 1),
+            value(0x0009, 0x1001, 2),
+            value(0x0009, 0x1201, 3),
+            // not a private group
+            value(0x0010, 0x1001, 4),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn renumber_private_blocks_per_item() {
+        let tokens: Vec<DataToken> = vec![
+            creator(0x0010, "ACME 1.0"),
+            vec![
+                DataToken::SequenceStart {
+                    tag: Tag(0x0009, 0x1002),
+                    len: Length::UNDEFINED,
+                },
+                DataToken::ItemStart {
+                    len: Length::UNDEFINED,
+                },
+            ],
+            // the nested data set does not inherit the private creator
+            value(0x0009, 0x1001, 1),
+            vec![DataToken::ItemEnd, DataToken::SequenceEnd],
+            value(0x0009, 0x1003, 2),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let assignment = vec![("ACME 1.0".to_string(), 0x20)].into_iter().collect();
+
+        let out: Vec<_> = RenumberPrivateBlocks::new(tokens, assignment).collect();
+
+        let expected: Vec<DataToken> = vec![
+            creator(0x0020, "ACME 1.0"),
+            vec![
+                DataToken::SequenceStart {
+                    tag: Tag(0x0009, 0x2002),
+                    len: Length::UNDEFINED,
+                },
+                DataToken::ItemStart {
+                    len: Length::UNDEFINED,
+                },
+            ],
+            value(0x0009, 0x1001, 1),
+            vec![DataToken::ItemEnd, DataToken::SequenceEnd],
+            value(0x0009, 0x2003, 2),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        assert_eq!(out, expected);
+    }
+}
@@ -0,0 +1,398 @@
+//! Adaptor for redacting pixel data.
+use crate::dataset::DataToken;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::value::PrimitiveValue;
+use dicom_core::Tag;
+use std::collections::VecDeque;
+
+/// The tag of the _Pixel Data_ attribute.
+const PIXEL_DATA: Tag = Tag(0x7FE0, 0x0010);
+
+/// What to do with the pixel data in [`RedactPixelData`].
+///
+/// [`RedactPixelData`]: ./struct.RedactPixelData.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum PixelDataRedaction {
+    /// Remove the pixel data element altogether.
+    Remove,
+    /// Replace every byte of the pixel data with zero,
+    /// keeping its length and, if encapsulated, its fragments.
+    Blank,
+    /// Replace the pixel data with the given bytes.
+    /// Encapsulated pixel data becomes a single fragment
+    /// after an empty basic offset table.
+    /// The bytes are padded with a zero if their length is odd.
+    Replace(Vec<u8>),
+}
+
+/// The position of the adaptor in the token stream.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum State {
+    /// outside of any pixel data
+    Pass,
+    /// after a native pixel data element header with the given length
+    Native { len: Length, replaced: bool },
+    /// inside encapsulated pixel data
+    Encapsulated,
+}
+
+/// A token stream adaptor which redacts the _Pixel Data_ `(7FE0,0010)`
+/// of a data set and its nested data sets,
+/// according to a [`PixelDataRedaction`],
+/// while the rest of the data set passes through unchanged.
+///
+/// Native pixel data values are handled whether they arrive
+/// as a single primitive value, as a series of value chunks,
+/// or as a lazy value.
+/// For encapsulated pixel data, every token of the pixel sequence
+/// is consumed up to the matching sequence end.
+///
+/// [`PixelDataRedaction`]: ./enum.PixelDataRedaction.html
+#[derive(Debug)]
+pub struct RedactPixelData<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the redaction to apply
+    redaction: PixelDataRedaction,
+    /// the position in the token stream
+    state: State,
+    /// tokens to be emitted before consuming the inner stream
+    queued: VecDeque<DataToken>,
+}
+
+impl<I> RedactPixelData<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens.
+    pub fn new<T>(tokens: T, redaction: PixelDataRedaction) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        RedactPixelData {
+            tokens: tokens.into_iter(),
+            redaction,
+            state: State::Pass,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// The bytes to replace the pixel data with, padded to even length.
+    fn replacement(&self) -> Vec<u8> {
+        let mut data = match &self.redaction {
+            PixelDataRedaction::Replace(data) => data.clone(),
+            _ => Vec::new(),
+        };
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        data
+    }
+
+    /// Handle a token after a native pixel data element header,
+    /// returning `None` if it should be dropped.
+    fn redact_native(
+        &mut self,
+        token: DataToken,
+        len: Length,
+        replaced: bool,
+    ) -> Option<DataToken> {
+        let zeros = |n: usize| vec![0; n];
+        match token {
+            DataToken::PrimitiveValue(value) => {
+                self.state = State::Pass;
+                match self.redaction {
+                    PixelDataRedaction::Remove => None,
+                    PixelDataRedaction::Blank => {
+                        let n = len.get().map(|l| l as usize);
+                        let n = n.unwrap_or_else(|| value.to_bytes().len());
+                        Some(DataToken::PrimitiveValue(PrimitiveValue::from(zeros(n))))
+                    }
+                    PixelDataRedaction::Replace(_) => Some(DataToken::PrimitiveValue(
+                        PrimitiveValue::from(self.replacement()),
+                    )),
+                }
+            }
+            DataToken::LazyValue(_) => {
+                self.state = State::Pass;
+                match self.redaction {
+                    PixelDataRedaction::Remove => None,
+                    PixelDataRedaction::Blank => {
+                        let n = len.get().unwrap_or(0) as usize;
+                        Some(DataToken::PrimitiveValue(PrimitiveValue::from(zeros(n))))
+                    }
+                    PixelDataRedaction::Replace(_) => Some(DataToken::PrimitiveValue(
+                        PrimitiveValue::from(self.replacement()),
+                    )),
+                }
+            }
+            DataToken::ItemValue(chunk) => match self.redaction {
+                PixelDataRedaction::Remove => None,
+                PixelDataRedaction::Blank => Some(DataToken::ItemValue(zeros(chunk.len()))),
+                PixelDataRedaction::Replace(_) if replaced => None,
+                PixelDataRedaction::Replace(_) => {
+                    self.state = State::Native {
+                        len,
+                        replaced: true,
+                    };
+                    Some(DataToken::ItemValue(self.replacement()))
+                }
+            },
+            token => {
+                // the value has ended
+                self.state = State::Pass;
+                self.redact(token)
+            }
+        }
+    }
+
+    /// Handle a token inside encapsulated pixel data,
+    /// returning `None` if it should be dropped.
+    fn redact_encapsulated(&mut self, token: DataToken) -> Option<DataToken> {
+        match (token, &self.redaction) {
+            (DataToken::SequenceEnd, PixelDataRedaction::Remove) => {
+                self.state = State::Pass;
+                None
+            }
+            (DataToken::SequenceEnd, _) => {
+                self.state = State::Pass;
+                Some(DataToken::SequenceEnd)
+            }
+            (DataToken::ItemValue(data), PixelDataRedaction::Blank) => {
+                Some(DataToken::ItemValue(vec![0; data.len()]))
+            }
+            (token, PixelDataRedaction::Blank) => Some(token),
+            _ => None,
+        }
+    }
+
+    /// Handle a token outside of any pixel data,
+    /// returning `None` if it should be dropped.
+    fn redact(&mut self, token: DataToken) -> Option<DataToken> {
+        match token {
+            DataToken::ElementHeader(header) if header.tag == PIXEL_DATA => {
+                self.state = State::Native {
+                    len: header.len,
+                    replaced: false,
+                };
+                match self.redaction {
+                    PixelDataRedaction::Remove => None,
+                    PixelDataRedaction::Blank => Some(DataToken::ElementHeader(header)),
+                    PixelDataRedaction::Replace(_) => {
+                        let len = Length(self.replacement().len() as u32);
+                        Some(DataToken::ElementHeader(DataElementHeader::new(
+                            header.tag, header.vr, len,
+                        )))
+                    }
+                }
+            }
+            DataToken::PixelSequenceStart => {
+                self.state = State::Encapsulated;
+                match self.redaction {
+                    PixelDataRedaction::Remove => None,
+                    PixelDataRedaction::Blank => Some(DataToken::PixelSequenceStart),
+                    PixelDataRedaction::Replace(_) => {
+                        let data = self.replacement();
+                        self.queued.extend(vec![
+                            DataToken::ItemStart { len: Length(0) },
+                            DataToken::ItemEnd,
+                            DataToken::ItemStart {
+                                len: Length(data.len() as u32),
+                            },
+                            DataToken::ItemValue(data),
+                            DataToken::ItemEnd,
+                        ]);
+                        Some(DataToken::PixelSequenceStart)
+                    }
+                }
+            }
+            token => Some(token),
+        }
+    }
+}
+
+impl<I> Iterator for RedactPixelData<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.queued.pop_front() {
+            return Some(token);
+        }
+
+        loop {
+            let token = self.tokens.next()?;
+            let out = match self.state {
+                State::Pass => self.redact(token),
+                State::Native { len, replaced } => self.redact_native(token, len, replaced),
+                State::Encapsulated => self.redact_encapsulated(token),
+            };
+            if out.is_some() {
+                return out;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PixelDataRedaction, RedactPixelData};
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    fn rows() -> Vec<DataToken> {
+        vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0028, 0x0010),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([2].as_ref().into())),
+        ]
+    }
+
+    fn native_pixel_data() -> Vec<DataToken> {
+        vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x7FE0, 0x0010),
+                VR::OW,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([0x1234, 0x5678].as_ref().into())),
+        ]
+    }
+
+    fn encapsulated_pixel_data() -> Vec<DataToken> {
+        vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(4) },
+            DataToken::ItemValue(vec![0, 0, 0, 0]),
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(2) },
+            DataToken::ItemValue(vec![0x99, 0x99]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ]
+    }
+
+    fn with_trailer(mut tokens: Vec<DataToken>) -> Vec<DataToken> {
+        let mut out = rows();
+        out.append(&mut tokens);
+        out.extend(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0xFFFC, 0xFFFC),
+                VR::OB,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(&[0, 0][..])),
+        ]);
+        out
+    }
+
+    #[test]
+    fn remove_pixel_data() {
+        let expected = with_trailer(vec![]);
+
+        let tokens = with_trailer(native_pixel_data());
+        let out: Vec<_> = RedactPixelData::new(tokens, PixelDataRedaction::Remove).collect();
+        assert_eq!(out, expected);
+
+        let tokens = with_trailer(encapsulated_pixel_data());
+        let out: Vec<_> = RedactPixelData::new(tokens, PixelDataRedaction::Remove).collect();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn blank_pixel_data() {
+        let tokens = with_trailer(native_pixel_data());
+        let out: Vec<_> = RedactPixelData::new(tokens, PixelDataRedaction::Blank).collect();
+        assert_eq!(
+            out,
+            with_trailer(vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x7FE0, 0x0010),
+                    VR::OW,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from(&[0, 0, 0, 0][..])),
+            ])
+        );
+
+        let tokens = with_trailer(encapsulated_pixel_data());
+        let out: Vec<_> = RedactPixelData::new(tokens, PixelDataRedaction::Blank).collect();
+        assert_eq!(
+            out,
+            with_trailer(vec![
+                DataToken::PixelSequenceStart,
+                DataToken::ItemStart { len: Length(4) },
+                DataToken::ItemValue(vec![0, 0, 0, 0]),
+                DataToken::ItemEnd,
+                DataToken::ItemStart { len: Length(2) },
+                DataToken::ItemValue(vec![0, 0]),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+            ])
+        );
+    }
+
+    #[test]
+    fn replace_pixel_data() {
+        let redaction = PixelDataRedaction::Replace(vec![1, 2, 3]);
+
+        let tokens = with_trailer(native_pixel_data());
+        let out: Vec<_> = RedactPixelData::new(tokens, redaction.clone()).collect();
+        assert_eq!(
+            out,
+            with_trailer(vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x7FE0, 0x0010),
+                    VR::OW,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from(&[1, 2, 3, 0][..])),
+            ])
+        );
+
+        // native value in chunks
+        let tokens = with_trailer(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x7FE0, 0x0010),
+                VR::OB,
+                Length(6),
+            )),
+            DataToken::ItemValue(vec![9; 4]),
+            DataToken::ItemValue(vec![9; 2]),
+        ]);
+        let out: Vec<_> = RedactPixelData::new(tokens, redaction.clone()).collect();
+        assert_eq!(
+            out,
+            with_trailer(vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x7FE0, 0x0010),
+                    VR::OB,
+                    Length(4),
+                )),
+                DataToken::ItemValue(vec![1, 2, 3, 0]),
+            ])
+        );
+
+        let tokens = with_trailer(encapsulated_pixel_data());
+        let out: Vec<_> = RedactPixelData::new(tokens, redaction).collect();
+        assert_eq!(
+            out,
+            with_trailer(vec![
+                DataToken::PixelSequenceStart,
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+                DataToken::ItemStart { len: Length(4) },
+                DataToken::ItemValue(vec![1, 2, 3, 0]),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+            ])
+        );
+    }
+}
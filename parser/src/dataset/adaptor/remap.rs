@@ -0,0 +1,260 @@
+//! Adaptor for moving elements to other tags.
+use crate::dataset::adaptor::WithDepth;
+use crate::dataset::DataToken;
+use dicom_core::Tag;
+use snafu::{Backtrace, Snafu};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum Error {
+    #[snafu(display("Element remapped to {} collides with another element", tag))]
+    DuplicateTag { tag: Tag, backtrace: Backtrace },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A token stream adaptor which moves elements to other tags,
+/// according to a mapping of source tags to target tags.
+///
+/// The tag of each matching element header or sequence start is replaced,
+/// whereas values pass through unchanged.
+/// Only the elements of the root data set are remapped,
+/// unless requested otherwise with [`nested`].
+///
+/// A remapped element must not end up with the same tag
+/// as another element of the same data set,
+/// whether remapped or not:
+/// the adaptor yields an error on the second of them,
+/// and iteration stops there.
+/// Note that remapping tags may leave elements
+/// out of ascending tag order,
+/// which [`SortElements`] can restore.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+/// # use dicom_parser::dataset::adaptor::RemapTags;
+/// # use dicom_parser::dataset::DataToken;
+/// # use std::collections::HashMap;
+/// let tokens = vec![
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0009, 0x1010), VR::LO, Length(4))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("ABCD")),
+/// ];
+/// let mut mapping = HashMap::new();
+/// mapping.insert(Tag(0x0009, 0x1010), Tag(0x0011, 0x1010));
+///
+/// let out = RemapTags::new(tokens, mapping).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(
+///     out[0],
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0011, 0x1010), VR::LO, Length(4))),
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`nested`]: #method.nested
+/// [`SortElements`]: ../sort/struct.SortElements.html
+#[derive(Debug)]
+pub struct RemapTags<I> {
+    /// the inner token stream, with the depth of each token
+    tokens: WithDepth<I>,
+    /// the target tag of each source tag
+    mapping: HashMap<Tag, Tag>,
+    /// whether to also remap the elements of nested data sets
+    nested: bool,
+    /// a stack of the tags seen so far in each data set level,
+    /// along with the tags which elements were remapped to
+    seen: Vec<(HashSet<Tag>, HashSet<Tag>)>,
+    /// fuse the iteration process if true
+    hard_break: bool,
+}
+
+impl<I> RemapTags<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens,
+    /// using the given mapping of source tags to target tags.
+    pub fn new<T>(tokens: T, mapping: HashMap<Tag, Tag>) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        RemapTags {
+            tokens: WithDepth::new(tokens),
+            mapping,
+            nested: false,
+            seen: vec![Default::default()],
+            hard_break: false,
+        }
+    }
+
+    /// Define whether the elements of data sets nested in sequence items
+    /// are also remapped.
+    pub fn nested(mut self, nested: bool) -> Self {
+        self.nested = nested;
+        self
+    }
+
+    /// Obtain the new tag of an element at the given depth,
+    /// keeping track of the tags of its data set.
+    fn remap(&mut self, depth: u32, tag: Tag) -> Result<Tag> {
+        let target = match self.mapping.get(&tag) {
+            Some(&target) if depth == 0 || self.nested => Some(target),
+            _ => None,
+        };
+        // every level is tracked, even if not remapped,
+        // so that the stack follows the items
+        let (seen, remapped) = match self.seen.last_mut() {
+            Some(level) => level,
+            None => return Ok(tag),
+        };
+        let new_tag = target.unwrap_or(tag);
+        if !seen.insert(new_tag) && (target.is_some() || remapped.contains(&new_tag)) {
+            return DuplicateTag { tag: new_tag }.fail();
+        }
+        if target.is_some() {
+            remapped.insert(new_tag);
+        }
+        Ok(new_tag)
+    }
+}
+
+impl<I> Iterator for RemapTags<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = Result<DataToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.hard_break {
+            return None;
+        }
+        let (depth, token) = self.tokens.next()?;
+        let token = match token {
+            DataToken::ElementHeader(mut header) => self.remap(depth, header.tag).map(|tag| {
+                header.tag = tag;
+                DataToken::ElementHeader(header)
+            }),
+            DataToken::SequenceStart { tag, len } => self
+                .remap(depth, tag)
+                .map(|tag| DataToken::SequenceStart { tag, len }),
+            DataToken::ItemStart { len } => {
+                self.seen.push(Default::default());
+                Ok(DataToken::ItemStart { len })
+            }
+            DataToken::ItemEnd => {
+                self.seen.pop();
+                Ok(DataToken::ItemEnd)
+            }
+            token => Ok(token),
+        };
+        if token.is_err() {
+            self.hard_break = true;
+        }
+        Some(token)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.hard_break {
+            (0, Some(0))
+        } else {
+            self.tokens.size_hint()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, RemapTags};
+    use crate::dataset::adaptor::testing::text_element;
+    use crate::dataset::DataToken;
+    use dicom_core::header::Length;
+    use dicom_core::{Tag, VR};
+    use std::collections::HashMap;
+
+    /// A data set with an element at the root and one in a sequence item,
+    /// both with the given tag.
+    fn tokens(tag: Tag) -> Vec<DataToken> {
+        let mut tokens = text_element(tag, VR::LO, "ROOT");
+        tokens.extend(vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0040, 0x0275),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ]);
+        tokens.extend(text_element(tag, VR::LO, "ITEM"));
+        tokens.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+        tokens
+    }
+
+    fn mapping(from: Tag, to: Tag) -> HashMap<Tag, Tag> {
+        let mut mapping = HashMap::new();
+        mapping.insert(from, to);
+        mapping
+    }
+
+    #[test]
+    fn remaps_one_tag_to_another() {
+        let (from, to) = (Tag(0x0009, 0x1010), Tag(0x0011, 0x1010));
+
+        let out = RemapTags::new(tokens(from), mapping(from, to))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut expected = tokens(from);
+        expected.splice(0..2, text_element(to, VR::LO, "ROOT"));
+        assert_eq!(out, expected);
+
+        let out = RemapTags::new(tokens(from), mapping(from, to))
+            .nested(true)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(out, tokens(to));
+
+        // sequences are remapped too
+        let out = RemapTags::new(
+            tokens(from),
+            mapping(Tag(0x0040, 0x0275), Tag(0x0040, 0x0260)),
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(
+            out[2],
+            DataToken::SequenceStart {
+                tag: Tag(0x0040, 0x0260),
+                len: Length::UNDEFINED,
+            }
+        );
+    }
+
+    #[test]
+    fn fails_on_duplicate_tags() {
+        let (from, to) = (Tag(0x0009, 0x1010), Tag(0x0011, 0x1010));
+
+        // the target tag comes before
+        let mut input = text_element(to, VR::LO, "OLD");
+        input.extend(text_element(from, VR::LO, "NEW"));
+        let out: Vec<_> = RemapTags::new(input, mapping(from, to)).collect();
+        assert_eq!(out.len(), 3);
+        assert!(matches!(out[2], Err(Error::DuplicateTag { tag, .. }) if tag == to));
+
+        // the target tag comes after
+        let mut input = text_element(from, VR::LO, "NEW");
+        input.extend(text_element(to, VR::LO, "OLD"));
+        let out: Vec<_> = RemapTags::new(input, mapping(from, to)).collect();
+        assert_eq!(out.len(), 3);
+        assert!(matches!(out[2], Err(Error::DuplicateTag { tag, .. }) if tag == to));
+
+        // the same tag in different data sets is fine
+        let mut input = tokens(from);
+        input.splice(0..2, text_element(to, VR::LO, "ROOT"));
+        let out = RemapTags::new(input, mapping(from, to))
+            .nested(true)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(out, tokens(to));
+    }
+}
@@ -0,0 +1,176 @@
+//! Adaptor for sorting the elements of the root data set by tag.
+use crate::dataset::{canonicalize_tokens, DataToken};
+
+/// A token stream adaptor which reorders the elements of the root data set
+/// in ascending order of tag,
+/// as required when writing in an explicit or implicit transfer syntax.
+///
+/// Each root element is moved as a whole,
+/// including the items of a sequence or the fragments of pixel data.
+/// The contents of sequence items are left in their original order.
+/// Elements with the same tag keep their relative order.
+///
+/// Since the last element may be the first in order,
+/// the inner stream is taken in full before any token is emitted.
+/// The order is the same as that of [`canonicalize_tokens`].
+///
+/// [`canonicalize_tokens`]: ../fn.canonicalize_tokens.html
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+/// # use dicom_parser::dataset::adaptor::SortElements;
+/// # use dicom_parser::dataset::DataToken;
+/// let tokens = vec![
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0010, 0x0020), VR::LO, Length(2))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("42")),
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0010, 0x0010), VR::PN, Length(4))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("Doe^")),
+/// ];
+///
+/// let out: Vec<_> = SortElements::new(tokens).collect();
+/// assert_eq!(out[1], DataToken::PrimitiveValue(PrimitiveValue::from("Doe^")));
+/// assert_eq!(out[3], DataToken::PrimitiveValue(PrimitiveValue::from("42")));
+/// ```
+#[derive(Debug)]
+pub struct SortElements<I> {
+    /// the inner token stream
+    tokens: I,
+    /// the sorted tokens, once the inner stream is taken in full
+    sorted: Option<std::vec::IntoIter<DataToken>>,
+}
+
+impl<I> SortElements<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    /// Create a new adaptor over the given tokens.
+    pub fn new<T>(tokens: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        SortElements {
+            tokens: tokens.into_iter(),
+            sorted: None,
+        }
+    }
+
+    /// Take the inner stream in full
+    /// and sort its root elements by tag.
+    fn sort(&mut self) -> std::vec::IntoIter<DataToken> {
+        canonicalize_tokens(self.tokens.by_ref()).into_iter()
+    }
+}
+
+impl<I> Iterator for SortElements<I>
+where
+    I: Iterator<Item = DataToken>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.sorted.is_none() {
+            self.sorted = Some(self.sort());
+        }
+        self.sorted.as_mut().and_then(Iterator::next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.sorted {
+            Some(sorted) => sorted.size_hint(),
+            None => self.tokens.size_hint(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SortElements;
+    use crate::dataset::adaptor::testing::text_element;
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::{Tag, VR};
+
+    /// A sequence with one item holding the given elements.
+    fn sequence(tag: Tag, elements: Vec<Vec<DataToken>>) -> Vec<DataToken> {
+        let mut tokens = vec![
+            DataToken::SequenceStart {
+                tag,
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ];
+        tokens.extend(elements.into_iter().flatten());
+        tokens.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+        tokens
+    }
+
+    fn pixel_data() -> Vec<DataToken> {
+        vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x7FE0, 0x0010),
+                VR::OB,
+                Length::UNDEFINED,
+            )),
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(2) },
+            DataToken::ItemValue(vec![1, 2]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ]
+    }
+
+    #[test]
+    fn sorts_root_elements_in_ascending_order() {
+        // nested elements out of order, which must stay that way
+        let nested = || {
+            vec![
+                text_element(Tag(0x0008, 0x1155), VR::UI, "1.2.3\0"),
+                text_element(Tag(0x0008, 0x1150), VR::UI, "1.2.4\0"),
+            ]
+        };
+        let input: Vec<_> = vec![
+            pixel_data(),
+            text_element(Tag(0x0020, 0x000D), VR::UI, "1.2\0"),
+            text_element(Tag(0x0010, 0x0010), VR::PN, "Doe^John"),
+            sequence(Tag(0x0008, 0x1140), nested()),
+            text_element(Tag(0x0008, 0x0060), VR::CS, "MR"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let out: Vec<_> = SortElements::new(input).collect();
+
+        let expected: Vec<_> = vec![
+            text_element(Tag(0x0008, 0x0060), VR::CS, "MR"),
+            sequence(Tag(0x0008, 0x1140), nested()),
+            text_element(Tag(0x0010, 0x0010), VR::PN, "Doe^John"),
+            text_element(Tag(0x0020, 0x000D), VR::UI, "1.2\0"),
+            pixel_data(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn leaves_sorted_elements_alone() {
+        let mut input = text_element(Tag(0x0008, 0x0060), VR::CS, "MR");
+        input.extend(text_element(Tag(0x0010, 0x0010), VR::PN, "Doe^John"));
+        // a pixel sequence without an element header
+        input.extend(pixel_data().into_iter().skip(1));
+
+        let out: Vec<_> = SortElements::new(input.clone()).collect();
+        assert_eq!(out, input);
+
+        let out: Vec<_> = SortElements::new(Vec::new()).collect();
+        assert_eq!(out, vec![]);
+    }
+}
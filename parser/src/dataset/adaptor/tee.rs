@@ -0,0 +1,142 @@
+//! Adaptor for observing the tokens of a stream.
+use crate::dataset::DataToken;
+use std::fmt;
+
+/// A token stream adaptor which passes every token to a callback
+/// right before yielding it, unchanged.
+///
+/// The callback only borrows each token,
+/// so observing the stream does not copy any data.
+/// This is useful for logging or tracing what goes through a pipeline
+/// of adaptors, without interfering with it.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+/// # use dicom_parser::dataset::adaptor::Tee;
+/// # use dicom_parser::dataset::DataToken;
+/// let tokens = vec![
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0010, 0x0010), VR::PN, Length(8))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+/// ];
+///
+/// let mut seen = 0;
+/// let out: Vec<_> = Tee::new(tokens.clone(), |_token| seen += 1).collect();
+/// assert_eq!(out, tokens);
+/// assert_eq!(seen, 2);
+/// ```
+pub struct Tee<I, F> {
+    /// the inner token stream
+    tokens: I,
+    /// the callback receiving each token
+    sink: F,
+}
+
+impl<I, F> Tee<I, F>
+where
+    I: Iterator<Item = DataToken>,
+    F: FnMut(&DataToken),
+{
+    /// Create a new adaptor over the given tokens,
+    /// passing each of them to the given callback.
+    pub fn new<T>(tokens: T, sink: F) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        Tee {
+            tokens: tokens.into_iter(),
+            sink,
+        }
+    }
+
+    /// Recover the inner token stream and the callback.
+    pub fn into_inner(self) -> (I, F) {
+        (self.tokens, self.sink)
+    }
+}
+
+impl<I, F> fmt::Debug for Tee<I, F>
+where
+    I: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tee").field("tokens", &self.tokens).finish()
+    }
+}
+
+impl<I, F> Iterator for Tee<I, F>
+where
+    I: Iterator<Item = DataToken>,
+    F: FnMut(&DataToken),
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        (self.sink)(&token);
+        Some(token)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tokens.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tee;
+    use crate::dataset::adaptor::StripGroupLengths;
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    fn tokens() -> Vec<DataToken> {
+        vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0000),
+                VR::UL,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(14_u32)),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0060),
+                VR::CS,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("CT")),
+        ]
+    }
+
+    #[test]
+    fn observes_tokens_in_order() {
+        let mut seen = Vec::new();
+        let out: Vec<_> = Tee::new(tokens(), |token| seen.push(token.clone())).collect();
+
+        assert_eq!(out, tokens());
+        assert_eq!(seen, tokens());
+    }
+
+    #[test]
+    fn observes_tokens_between_adaptors() {
+        // the tee sees what the inner adaptor yields
+        let mut seen = 0;
+        let out: Vec<_> =
+            StripGroupLengths::new(Tee::new(StripGroupLengths::new(tokens()), |_| seen += 1))
+                .collect();
+
+        assert_eq!(out, tokens()[2..].to_vec());
+        assert_eq!(seen, 2);
+    }
+
+    #[test]
+    fn observes_tokens_lazily() {
+        let mut seen = 0;
+        let mut tee = Tee::new(tokens(), |_| seen += 1);
+        tee.next();
+        let (rest, _) = tee.into_inner();
+        assert_eq!(rest.count(), 3);
+        assert_eq!(seen, 1);
+    }
+}
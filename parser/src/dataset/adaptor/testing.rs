@@ -0,0 +1,20 @@
+//! Token factories shared by the tests of the adaptors.
+use crate::dataset::DataToken;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::value::PrimitiveValue;
+use dicom_core::{Tag, VR};
+
+/// The header of an element with the given length.
+pub(crate) fn header(tag: Tag, vr: VR, len: u32) -> DataToken {
+    DataToken::ElementHeader(DataElementHeader::new(tag, vr, Length(len)))
+}
+
+/// A primitive element: its header with the given length, then its value.
+pub(crate) fn element(tag: Tag, vr: VR, len: u32, value: PrimitiveValue) -> Vec<DataToken> {
+    vec![header(tag, vr, len), DataToken::PrimitiveValue(value)]
+}
+
+/// A textual element, with the length of the given text.
+pub(crate) fn text_element(tag: Tag, vr: VR, value: &str) -> Vec<DataToken> {
+    element(tag, vr, value.len() as u32, PrimitiveValue::from(value))
+}
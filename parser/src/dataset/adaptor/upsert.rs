@@ -0,0 +1,274 @@
+//! Adaptor for inserting or replacing an element of the root data set.
+use crate::dataset::adaptor::WithDepth;
+use crate::dataset::{DataToken, IntoTokens};
+use dicom_core::header::{HasLength, Header};
+use dicom_core::{DataElement, Tag};
+use std::iter::Peekable;
+
+/// A token stream adaptor which sets an element of the root data set,
+/// inserting it before the first element with a greater tag,
+/// or in place of the element with the same tag.
+///
+/// The tokens of a replaced element,
+/// including the items of a sequence or the fragments of pixel data,
+/// are left out of the output.
+/// All other tokens pass through unchanged,
+/// including elements with the same tag in nested data sets.
+/// The stream is expected to have its root elements sorted by tag,
+/// as required by the standard.
+/// If no element with a greater or equal tag is found,
+/// the element is appended at the end of the stream.
+#[derive(Debug)]
+pub struct UpsertElement<I, E>
+where
+    I: Iterator<Item = DataToken>,
+    E: IntoTokens,
+{
+    /// the inner token stream, with the depth of each token
+    tokens: Peekable<WithDepth<I>>,
+    /// the tag of the element to set
+    tag: Tag,
+    /// the element to set, until it is emitted
+    element: Option<E>,
+    /// the tokens of the element being emitted
+    inserting: Option<E::Iter>,
+    /// a token to emit once the element is emitted
+    held: Option<DataToken>,
+}
+
+impl<I, V, P> UpsertElement<I, DataElement<V, P>>
+where
+    I: Iterator<Item = DataToken>,
+    V: IntoTokens + HasLength,
+    P: AsRef<[u8]>,
+{
+    /// Create a new adaptor over the given tokens,
+    /// setting the given element in the root data set.
+    pub fn new<T>(tokens: T, element: DataElement<V, P>) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        UpsertElement {
+            tokens: WithDepth::new(tokens).peekable(),
+            tag: element.tag(),
+            element: Some(element),
+            inserting: None,
+            held: None,
+        }
+    }
+}
+
+impl<I, E> UpsertElement<I, E>
+where
+    I: Iterator<Item = DataToken>,
+    E: IntoTokens,
+{
+    /// Skip the remaining tokens of the root element started by `start`.
+    fn skip_element(&mut self, start: &DataToken) {
+        if let DataToken::ElementHeader(header) = start {
+            // the value, possibly in chunks
+            while let Some((_, token)) = self.tokens.peek() {
+                match token {
+                    DataToken::PrimitiveValue(_)
+                    | DataToken::LazyValue(_)
+                    | DataToken::ItemValue(_) => {
+                        self.tokens.next();
+                    }
+                    _ => break,
+                }
+            }
+            // or the pixel sequence of encapsulated pixel data
+            let pixel_sequence =
+                matches!(self.tokens.peek(), Some((_, DataToken::PixelSequenceStart)));
+            if !header.len.is_undefined() || !pixel_sequence {
+                return;
+            }
+            self.tokens.next();
+        }
+        // everything up to the end of the sequence
+        for (depth, token) in &mut self.tokens {
+            if depth == 0 && matches!(token, DataToken::SequenceEnd) {
+                break;
+            }
+        }
+    }
+}
+
+impl<I, E> Iterator for UpsertElement<I, E>
+where
+    I: Iterator<Item = DataToken>,
+    E: IntoTokens,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(tokens) = &mut self.inserting {
+                if let Some(token) = tokens.next() {
+                    return Some(token);
+                }
+                self.inserting = None;
+                if let Some(token) = self.held.take() {
+                    return Some(token);
+                }
+            }
+
+            let (depth, token) = match self.tokens.next() {
+                Some(next) => next,
+                None => {
+                    // the element goes at the end
+                    self.inserting = Some(self.element.take()?.into_tokens());
+                    continue;
+                }
+            };
+
+            if depth == 0 && self.element.is_some() {
+                match token.element_tag() {
+                    Some(tag) if tag == self.tag => self.skip_element(&token),
+                    Some(tag) if tag > self.tag => self.held = Some(token),
+                    _ => return Some(token),
+                }
+                self.inserting = self.element.take().map(IntoTokens::into_tokens);
+                continue;
+            }
+            return Some(token);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UpsertElement;
+    use crate::dataset::adaptor::testing::text_element;
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, EmptyObject, Length};
+    use dicom_core::value::{PrimitiveValue, Value};
+    use dicom_core::{DataElement, Tag, VR};
+
+    fn sop_instance_uid(uid: &str) -> DataElement<EmptyObject, [u8; 0]> {
+        DataElement::new(
+            Tag(0x0008, 0x0018),
+            VR::UI,
+            Value::Primitive(PrimitiveValue::from(uid)),
+        )
+    }
+
+    /// A data set with a sequence holding a SOP Instance UID.
+    fn tokens() -> Vec<DataToken> {
+        let mut tokens = text_element(Tag(0x0008, 0x0016), VR::UI, "1.2.3\0");
+        tokens.extend(vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ]);
+        tokens.extend(text_element(Tag(0x0008, 0x0018), VR::UI, "9.9\0"));
+        tokens.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+        tokens.extend(text_element(Tag(0x0010, 0x0010), VR::PN, "Doe^John"));
+        tokens
+    }
+
+    #[test]
+    fn inserts_element_in_order() {
+        let out: Vec<_> = UpsertElement::new(tokens(), sop_instance_uid("1.2.4\0")).collect();
+
+        let mut expected = tokens();
+        let new = text_element(Tag(0x0008, 0x0018), VR::UI, "1.2.4\0");
+        expected.splice(2..2, new);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn replaces_element_with_same_tag() {
+        let mut tokens = text_element(Tag(0x0008, 0x0016), VR::UI, "1.2.3\0");
+        // a value in chunks
+        tokens.extend(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0018),
+                VR::UI,
+                Length(6),
+            )),
+            DataToken::ItemValue(b"9.9".to_vec()),
+            DataToken::ItemValue(b".9\0".to_vec()),
+        ]);
+        tokens.extend(text_element(Tag(0x0010, 0x0010), VR::PN, "Doe^John"));
+
+        let out: Vec<_> = UpsertElement::new(tokens, sop_instance_uid("1.2.4\0")).collect();
+
+        let mut expected = text_element(Tag(0x0008, 0x0016), VR::UI, "1.2.3\0");
+        expected.extend(text_element(Tag(0x0008, 0x0018), VR::UI, "1.2.4\0"));
+        expected.extend(text_element(Tag(0x0010, 0x0010), VR::PN, "Doe^John"));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn replaces_sequence_with_same_tag() {
+        let new = DataElement::<EmptyObject, [u8; 0]>::new(
+            Tag(0x0008, 0x1115),
+            VR::UI,
+            Value::Primitive(PrimitiveValue::from("1.2.4\0")),
+        );
+        let out: Vec<_> = UpsertElement::new(tokens(), new).collect();
+
+        let mut expected = text_element(Tag(0x0008, 0x0016), VR::UI, "1.2.3\0");
+        expected.extend(text_element(Tag(0x0008, 0x1115), VR::UI, "1.2.4\0"));
+        expected.extend(text_element(Tag(0x0010, 0x0010), VR::PN, "Doe^John"));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn replaces_encapsulated_pixel_data() {
+        let mut tokens = text_element(Tag(0x0028, 0x0100), VR::US, "");
+        tokens.extend(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x7FE0, 0x0010),
+                VR::OB,
+                Length::UNDEFINED,
+            )),
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(2) },
+            DataToken::ItemValue(vec![1, 2]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ]);
+        tokens.extend(text_element(Tag(0xFFFA, 0xFFFA), VR::SQ, ""));
+        let new = DataElement::<EmptyObject, [u8; 0]>::new(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            Value::Primitive(PrimitiveValue::from(vec![3, 4])),
+        );
+
+        let out: Vec<_> = UpsertElement::new(tokens, new).collect();
+
+        let mut expected = text_element(Tag(0x0028, 0x0100), VR::US, "");
+        expected.extend(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x7FE0, 0x0010),
+                VR::OB,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(vec![3, 4])),
+        ]);
+        expected.extend(text_element(Tag(0xFFFA, 0xFFFA), VR::SQ, ""));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn appends_element_at_the_end() {
+        let new = DataElement::<EmptyObject, [u8; 0]>::new(
+            Tag(0x0020, 0x000D),
+            VR::UI,
+            Value::Primitive(PrimitiveValue::from("1.2.5\0")),
+        );
+        let out: Vec<_> = UpsertElement::new(tokens(), new).collect();
+
+        let mut expected = tokens();
+        expected.extend(text_element(Tag(0x0020, 0x000D), VR::UI, "1.2.5\0"));
+        assert_eq!(out, expected);
+    }
+}
@@ -0,0 +1,358 @@
+//! Adaptor for checking elements against a data dictionary.
+use crate::dataset::DataToken;
+use dicom_core::dictionary::{DataDictionary, DictionaryEntry, Vm};
+use dicom_core::{Tag, VR};
+use std::fmt;
+
+/// A discrepancy between an element of a data set
+/// and the attribute described by the data dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Diagnostic {
+    /// The element has a VR not allowed for the attribute.
+    UnexpectedVr {
+        /// the tag of the element
+        tag: Tag,
+        /// the VR of the element
+        found: VR,
+        /// the typical VR of the attribute
+        expected: VR,
+        /// the alternative VR of the attribute, if any
+        alternative: Option<VR>,
+    },
+    /// The element has a number of values
+    /// outside of the value multiplicity of the attribute.
+    UnexpectedVm {
+        /// the tag of the element
+        tag: Tag,
+        /// the number of values in the element
+        found: u32,
+        /// the value multiplicity of the attribute
+        expected: Vm,
+    },
+}
+
+impl Diagnostic {
+    /// The tag of the element concerned.
+    pub fn tag(&self) -> Tag {
+        match self {
+            Diagnostic::UnexpectedVr { tag, .. } | Diagnostic::UnexpectedVm { tag, .. } => *tag,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::UnexpectedVr {
+                tag,
+                found,
+                expected,
+                alternative: Some(alternative),
+            } => write!(
+                f,
+                "{}: VR {} is neither {} nor {}",
+                tag, found, expected, alternative
+            ),
+            Diagnostic::UnexpectedVr {
+                tag,
+                found,
+                expected,
+                alternative: None,
+            } => write!(f, "{}: VR {} is not {}", tag, found, expected),
+            Diagnostic::UnexpectedVm {
+                tag,
+                found,
+                expected,
+            } => write!(
+                f,
+                "{}: {} values do not fit the multiplicity {}",
+                tag, found, expected
+            ),
+        }
+    }
+}
+
+/// A token stream adaptor which checks the VR and the VM of each element
+/// against the attribute described by a data dictionary,
+/// collecting a [`Diagnostic`] for every discrepancy.
+///
+/// This applies to the elements of the root data set
+/// and of nested data sets alike.
+/// Attributes which may take either of two VRs (e.g. `US` or `SS`)
+/// accept both.
+/// The VM is only checked when the dictionary knows it,
+/// for values of at least one value given in full,
+/// and never for binary VRs (`OB`, `OW`, `UN`, ...),
+/// which always hold a single value.
+/// Elements absent from the dictionary are not checked.
+///
+/// The tokens are passed through unchanged.
+/// The report can be retrieved at any point,
+/// such as after consuming the stream by reference.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+/// # use dicom_dictionary_std::StandardDataDictionary;
+/// # use dicom_parser::dataset::adaptor::ValidateAgainstDictionary;
+/// # use dicom_parser::dataset::DataToken;
+/// let tokens = vec![
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0010, 0x0010), VR::LO, Length(8))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+/// ];
+///
+/// let mut validator = ValidateAgainstDictionary::new(tokens.clone(), StandardDataDictionary);
+/// let out: Vec<_> = validator.by_ref().collect();
+/// assert_eq!(out, tokens);
+/// assert_eq!(validator.report().len(), 1);
+/// ```
+///
+/// [`Diagnostic`]: ./enum.Diagnostic.html
+#[derive(Debug, Clone)]
+pub struct ValidateAgainstDictionary<I, D> {
+    /// the inner token stream
+    tokens: I,
+    /// the dictionary to look up attributes from
+    dict: D,
+    /// the discrepancies found so far
+    report: Vec<Diagnostic>,
+    /// the tag and multiplicity to check the next value against
+    expected_vm: Option<(Tag, Vm)>,
+}
+
+impl<I, D> ValidateAgainstDictionary<I, D>
+where
+    I: Iterator<Item = DataToken>,
+    D: DataDictionary,
+{
+    /// Create a new adaptor over the given tokens,
+    /// looking up attributes from the given dictionary.
+    pub fn new<T>(tokens: T, dict: D) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        ValidateAgainstDictionary {
+            tokens: tokens.into_iter(),
+            dict,
+            report: Vec::new(),
+            expected_vm: None,
+        }
+    }
+
+    /// The discrepancies found in the tokens consumed so far, in order.
+    pub fn report(&self) -> &[Diagnostic] {
+        &self.report
+    }
+
+    /// Take the discrepancies found in the tokens consumed so far, in order.
+    pub fn into_report(self) -> Vec<Diagnostic> {
+        self.report
+    }
+
+    /// Check an element with the given tag and VR,
+    /// returning the multiplicity to check its value against, if any.
+    fn check_element(&mut self, tag: Tag, vr: VR) -> Option<Vm> {
+        let entry = self.dict.by_tag(tag)?;
+        let expected = entry.vr();
+        let alternative = entry.alternative_vr();
+        // the dictionary may not know the VR
+        if expected != VR::UN && vr != expected && Some(vr) != alternative {
+            self.report.push(Diagnostic::UnexpectedVr {
+                tag,
+                found: vr,
+                expected,
+                alternative,
+            });
+        }
+        match vr {
+            VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::SQ | VR::UN => None,
+            _ => entry.vm(),
+        }
+    }
+}
+
+impl<I, D> Iterator for ValidateAgainstDictionary<I, D>
+where
+    I: Iterator<Item = DataToken>,
+    D: DataDictionary,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        match &token {
+            DataToken::ElementHeader(header) => {
+                self.expected_vm = self
+                    .check_element(header.tag, header.vr)
+                    .map(|vm| (header.tag, vm));
+            }
+            DataToken::SequenceStart { tag, .. } => {
+                self.expected_vm = None;
+                self.check_element(*tag, VR::SQ);
+            }
+            DataToken::PrimitiveValue(value) => {
+                if let Some((tag, expected)) = self.expected_vm.take() {
+                    let found = value.multiplicity();
+                    // empty values are allowed regardless of multiplicity
+                    if found > 0 && !expected.contains(found) {
+                        self.report.push(Diagnostic::UnexpectedVm {
+                            tag,
+                            found,
+                            expected,
+                        });
+                    }
+                }
+            }
+            _ => self.expected_vm = None,
+        }
+        Some(token)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tokens.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, ValidateAgainstDictionary};
+    use crate::dataset::adaptor::testing::header;
+    use crate::dataset::DataToken;
+    use dicom_core::dictionary::{DataDictionary, DictionaryEntryRef, TagRange, Vm};
+    use dicom_core::header::Length;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{dicom_value, Tag, VR};
+    use dicom_dictionary_std::StandardDataDictionary;
+
+    #[test]
+    fn reports_unexpected_vrs_at_all_levels() {
+        let tokens = vec![
+            header(Tag(0x0008, 0x0060), VR::CS, 2),
+            DataToken::PrimitiveValue(PrimitiveValue::from("CT")),
+            // either VR is fine
+            header(Tag(0x0028, 0x0106), VR::SS, 2),
+            DataToken::PrimitiveValue(PrimitiveValue::from(-5_i16)),
+            header(Tag(0x0028, 0x0107), VR::US, 2),
+            DataToken::PrimitiveValue(PrimitiveValue::from(5_u16)),
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            header(Tag(0x0020, 0x000E), VR::LO, 4),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.3 ")),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            // not a sequence in the dictionary
+            DataToken::SequenceStart {
+                tag: Tag(0x0010, 0x0010),
+                len: Length(0),
+            },
+            DataToken::SequenceEnd,
+            // not in the dictionary
+            header(Tag(0x0009, 0x1001), VR::LO, 4),
+            DataToken::PrimitiveValue(PrimitiveValue::from("ACME")),
+        ];
+
+        let mut validator = ValidateAgainstDictionary::new(tokens.clone(), StandardDataDictionary);
+        let out: Vec<_> = validator.by_ref().collect();
+
+        assert_eq!(out, tokens);
+        assert_eq!(
+            validator.into_report(),
+            vec![
+                Diagnostic::UnexpectedVr {
+                    tag: Tag(0x0020, 0x000E),
+                    found: VR::LO,
+                    expected: VR::UI,
+                    alternative: None,
+                },
+                Diagnostic::UnexpectedVr {
+                    tag: Tag(0x0010, 0x0010),
+                    found: VR::SQ,
+                    expected: VR::PN,
+                    alternative: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unexpected_vms() {
+        /// A dictionary of attributes with a known multiplicity
+        #[derive(Debug)]
+        struct VmDictionary(Vec<DictionaryEntryRef<'static>>);
+
+        impl DataDictionary for VmDictionary {
+            type Entry = DictionaryEntryRef<'static>;
+
+            fn by_name(&self, name: &str) -> Option<&Self::Entry> {
+                self.0.iter().find(|e| e.alias == name)
+            }
+
+            fn by_tag(&self, tag: Tag) -> Option<&Self::Entry> {
+                self.0.iter().find(|e| e.tag == TagRange::Single(tag))
+            }
+        }
+
+        let dict = VmDictionary(vec![
+            DictionaryEntryRef {
+                tag: TagRange::Single(Tag(0x0008, 0x0008)),
+                alias: "ImageType",
+                vr: VR::CS,
+                vr2: None,
+                vm: Some(Vm::Unbounded(2)),
+                name: Some("Image Type"),
+            },
+            DictionaryEntryRef {
+                tag: TagRange::Single(Tag(0x0028, 0x0030)),
+                alias: "PixelSpacing",
+                vr: VR::DS,
+                vr2: None,
+                vm: Some(Vm::Bounded(2, 2)),
+                name: Some("Pixel Spacing"),
+            },
+            DictionaryEntryRef {
+                tag: TagRange::Single(Tag(0x0028, 0x1201)),
+                alias: "RedPaletteColorLookupTableData",
+                vr: VR::OW,
+                vr2: None,
+                vm: Some(Vm::Bounded(1, 1)),
+                name: Some("Red Palette Color Lookup Table Data"),
+            },
+        ]);
+
+        let tokens = vec![
+            header(Tag(0x0008, 0x0008), VR::CS, 8),
+            DataToken::PrimitiveValue(PrimitiveValue::from("ORIGINAL")),
+            header(Tag(0x0028, 0x0030), VR::DS, 8),
+            DataToken::PrimitiveValue(dicom_value!(Strs, ["0.5", "0.5"])),
+            // empty values are fine
+            header(Tag(0x0028, 0x0030), VR::DS, 0),
+            DataToken::PrimitiveValue(PrimitiveValue::Empty),
+            // binary values are a single value
+            header(Tag(0x0028, 0x1201), VR::OW, 4),
+            DataToken::PrimitiveValue(PrimitiveValue::from(vec![0_u8, 1, 2, 3])),
+        ];
+
+        let mut validator = ValidateAgainstDictionary::new(tokens, dict);
+        validator.by_ref().for_each(drop);
+
+        assert_eq!(
+            validator.report(),
+            &[Diagnostic::UnexpectedVm {
+                tag: Tag(0x0008, 0x0008),
+                found: 1,
+                expected: Vm::Unbounded(2),
+            }]
+        );
+        assert_eq!(
+            validator.report()[0].to_string(),
+            "(0008,0008): 1 values do not fit the multiplicity 2-n"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Adaptor for resolving unknown value representations.
+use crate::dataset::DataToken;
+use dicom_core::dictionary::{DataDictionary, DictionaryEntry};
+use dicom_core::VR;
+
+/// A token stream adaptor which replaces the unknown VR (`UN`)
+/// of element headers with the VR found in a data dictionary.
+///
+/// This applies to the elements of the root data set
+/// and of nested data sets alike.
+/// Headers with a known VR are left alone,
+/// and so are elements absent from the dictionary
+/// or which the dictionary describes as sequences,
+/// since their value would still be read as a primitive value.
+/// This is mostly useful before writing data set tokens
+/// read in implicit VR to an explicit VR encoding.
+///
+/// Values are passed through unchanged.
+#[derive(Debug, Clone)]
+pub struct ResolveVrs<I, D> {
+    /// the inner token stream
+    tokens: I,
+    /// the dictionary to look up VRs from
+    dict: D,
+}
+
+impl<I, D> ResolveVrs<I, D>
+where
+    I: Iterator<Item = DataToken>,
+    D: DataDictionary,
+{
+    /// Create a new adaptor over the given tokens,
+    /// looking up VRs from the given dictionary.
+    pub fn new<T>(tokens: T, dict: D) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = DataToken>,
+    {
+        ResolveVrs {
+            tokens: tokens.into_iter(),
+            dict,
+        }
+    }
+}
+
+impl<I, D> Iterator for ResolveVrs<I, D>
+where
+    I: Iterator<Item = DataToken>,
+    D: DataDictionary,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.tokens.next()? {
+            DataToken::ElementHeader(mut header) if header.vr == VR::UN => {
+                match self.dict.by_tag(header.tag).map(|e| e.vr()) {
+                    Some(VR::UN) | Some(VR::SQ) | None => {}
+                    Some(vr) => header.vr = vr,
+                }
+                Some(DataToken::ElementHeader(header))
+            }
+            token => Some(token),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tokens.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResolveVrs;
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+    use dicom_dictionary_std::StandardDataDictionary;
+
+    #[test]
+    fn resolves_unknown_vrs_at_all_levels() {
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::UN,
+                Length(8),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(&b"Doe^John"[..])),
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0018, 0x6012),
+                VR::UN,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(&[0x01, 0x00][..])),
+            // known VRs are kept, even if different from the dictionary
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0018, 0x6014),
+                VR::OB,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(&[0x02, 0x00][..])),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            // sequences in the dictionary are kept as unknown
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x1115),
+                VR::UN,
+                Length(0),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::Empty),
+            // private elements are not in the dictionary
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1001),
+                VR::UN,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(&[0x03, 0x00][..])),
+        ];
+
+        let vrs: Vec<_> = ResolveVrs::new(tokens, StandardDataDictionary)
+            .filter_map(|token| match token {
+                DataToken::ElementHeader(header) => Some((header.tag, header.vr)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            vrs,
+            vec![
+                (Tag(0x0010, 0x0010), VR::PN),
+                (Tag(0x0018, 0x6012), VR::US),
+                (Tag(0x0018, 0x6014), VR::OB),
+                (Tag(0x0008, 0x1115), VR::UN),
+                (Tag(0x0009, 0x1001), VR::UN),
+            ]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Canonical ordering of token streams, for comparison purposes.
+use crate::dataset::adaptor::WithDepth;
+use crate::dataset::DataToken;
+use dicom_core::Tag;
+
+/// Arrange the given data set tokens in a canonical order,
+/// so that two data sets with the same elements in a different order
+/// produce equal token sequences.
+///
+/// The tokens of the root data set are grouped into complete elements,
+/// header, value and nested items alike,
+/// and these groups are sorted by tag.
+/// The pixel sequence of encapsulated pixel data
+/// stays with the element header before it.
+/// The order of the tokens within each element is preserved,
+/// and so is the order of elements with the same tag.
+/// This is meant for comparing decoded objects,
+/// and is also the order in which the [`SortElements`] adaptor
+/// puts elements before writing them.
+///
+/// [`SortElements`]: ./adaptor/struct.SortElements.html
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+/// # use dicom_parser::dataset::{canonicalize_tokens, DataToken};
+/// let patient_name = vec![
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0010, 0x0010), VR::PN, Length(4))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("Doe^")),
+/// ];
+/// let modality = vec![
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0008, 0x0060), VR::CS, Length(2))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("OT")),
+/// ];
+///
+/// let a: Vec<_> = patient_name.iter().chain(&modality).cloned().collect();
+/// let b: Vec<_> = modality.iter().chain(&patient_name).cloned().collect();
+/// assert_ne!(a, b);
+/// assert_eq!(canonicalize_tokens(a), canonicalize_tokens(b));
+/// ```
+pub fn canonicalize_tokens<I>(tokens: I) -> Vec<DataToken>
+where
+    I: IntoIterator<Item = DataToken>,
+{
+    let mut elements: Vec<(Tag, Vec<DataToken>)> = Vec::new();
+    for (depth, token) in WithDepth::new(tokens) {
+        let continued = match (&token, elements.last()) {
+            // the pixel sequence after the header of its element
+            (DataToken::PixelSequenceStart, Some((_, element))) => {
+                matches!(element[..], [DataToken::ElementHeader(_)])
+            }
+            _ => false,
+        };
+        match token.element_tag() {
+            Some(tag) if depth == 0 && !continued => elements.push((tag, vec![token])),
+            _ => match elements.last_mut() {
+                Some((_, element)) => element.push(token),
+                // stray token before any element, keep it in front
+                None => elements.push((Tag(0, 0), vec![token])),
+            },
+        }
+    }
+
+    // stable sort, elements with the same tag keep their order
+    elements.sort_by_key(|(tag, _)| *tag);
+    elements
+        .into_iter()
+        .flat_map(|(_, element)| element)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::canonicalize_tokens;
+    use crate::dataset::adaptor::testing::text_element;
+    use crate::dataset::DataToken;
+    use dicom_core::header::Length;
+    use dicom_core::{Tag, VR};
+
+    fn sequence() -> Vec<DataToken> {
+        // nested elements in descending order, which must be kept as is
+        let mut tokens = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ];
+        tokens.extend(text_element(Tag(0x0020, 0x000E), VR::LO, "1.2"));
+        tokens.extend(text_element(Tag(0x0008, 0x1150), VR::LO, "3.4"));
+        tokens.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+        tokens
+    }
+
+    #[test]
+    fn canonical_order_ignores_top_level_order() {
+        let mut a = text_element(Tag(0x0008, 0x0060), VR::LO, "OT");
+        a.extend(sequence());
+        a.extend(text_element(Tag(0x0010, 0x0010), VR::LO, "Doe^John"));
+
+        let mut b = text_element(Tag(0x0010, 0x0010), VR::LO, "Doe^John");
+        b.extend(sequence());
+        b.extend(text_element(Tag(0x0008, 0x0060), VR::LO, "OT"));
+
+        assert_ne!(a, b);
+        let canonical = canonicalize_tokens(a.clone());
+        assert_eq!(canonical, canonicalize_tokens(b));
+        // already in ascending order
+        assert_eq!(canonical, a);
+    }
+
+    #[test]
+    fn canonical_order_is_stable_for_repeated_tags() {
+        let mut tokens = text_element(Tag(0x0010, 0x0010), VR::LO, "Doe^John");
+        tokens.extend(text_element(Tag(0x0008, 0x0060), VR::LO, "OT"));
+        tokens.extend(text_element(Tag(0x0008, 0x0060), VR::LO, "MR"));
+
+        let mut expected = text_element(Tag(0x0008, 0x0060), VR::LO, "OT");
+        expected.extend(text_element(Tag(0x0008, 0x0060), VR::LO, "MR"));
+        expected.extend(text_element(Tag(0x0010, 0x0010), VR::LO, "Doe^John"));
+
+        assert_eq!(canonicalize_tokens(tokens), expected);
+    }
+}
@@ -0,0 +1,194 @@
+//! Human readable representations of token streams.
+use crate::dataset::adaptor::WithDepth;
+use crate::dataset::DataToken;
+use std::fmt::Write;
+
+/// The maximum number of characters shown for a primitive value.
+const MAX_VALUE_CHARS: usize = 48;
+
+/// Create a multi-line, indented representation of the given data set tokens.
+///
+/// Each element, sequence, item, or delimiter goes in its own line,
+/// indented by its nesting depth.
+/// Element headers are followed by a brief rendition of their value,
+/// which is abbreviated when too long.
+/// The output is stable,
+/// which makes it suitable for snapshot testing.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+/// # use dicom_parser::dataset::{format_tokens, DataToken};
+/// let tokens = vec![
+///     DataToken::SequenceStart { tag: Tag(0x0008, 0x1115), len: Length::UNDEFINED },
+///     DataToken::ItemStart { len: Length::UNDEFINED },
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0020, 0x000E), VR::UI, Length(6))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("1.2.3")),
+///     DataToken::ItemEnd,
+///     DataToken::SequenceEnd,
+/// ];
+///
+/// assert_eq!(
+///     format_tokens(tokens),
+///     "SequenceStart(0008,1115) U/L\n\
+///     \x20 ItemStart U/L\n\
+///     \x20   (0020,000E) UI 6: 1.2.3\n\
+///     \x20 ItemEnd\n\
+///     SequenceEnd\n",
+/// );
+/// ```
+pub fn format_tokens<I>(tokens: I) -> String
+where
+    I: IntoIterator<Item = DataToken>,
+{
+    let mut out = String::new();
+    // whether the last line is an element header awaiting its value
+    let mut header_pending = false;
+
+    for (depth, token) in WithDepth::new(tokens) {
+        if let DataToken::PrimitiveValue(value) = &token {
+            if header_pending {
+                header_pending = false;
+                let value = abbreviate(&value.to_str());
+                // the header line has no line break yet
+                if value.is_empty() {
+                    out.push('\n');
+                } else {
+                    writeln!(out, ": {}", value).unwrap();
+                }
+                continue;
+            }
+        }
+        if let DataToken::LazyValue(marker) = &token {
+            if header_pending {
+                header_pending = false;
+                writeln!(out, ": <value at byte {}>", marker.pos).unwrap();
+                continue;
+            }
+        }
+        if header_pending {
+            header_pending = false;
+            out.push('\n');
+        }
+
+        for _ in 0..depth {
+            out.push_str("  ");
+        }
+        match token {
+            DataToken::ElementHeader(header) => {
+                write!(out, "{} {} {}", header.tag, header.vr, header.len).unwrap();
+                header_pending = true;
+            }
+            DataToken::SequenceStart { tag, len } => {
+                writeln!(out, "SequenceStart{} {}", tag, len).unwrap();
+            }
+            DataToken::PixelSequenceStart => out.push_str("PixelSequenceStart\n"),
+            DataToken::SequenceEnd => out.push_str("SequenceEnd\n"),
+            DataToken::ItemStart { len } => writeln!(out, "ItemStart {}", len).unwrap(),
+            DataToken::ItemEnd => out.push_str("ItemEnd\n"),
+            DataToken::PrimitiveValue(value) => {
+                writeln!(out, "PrimitiveValue: {}", abbreviate(&value.to_str())).unwrap();
+            }
+            DataToken::ItemValue(data) => {
+                writeln!(out, "ItemValue ({} bytes)", data.len()).unwrap();
+            }
+            DataToken::LazyValue(marker) => {
+                writeln!(out, "LazyValue <value at byte {}>", marker.pos).unwrap();
+            }
+        }
+    }
+
+    if header_pending {
+        out.push('\n');
+    }
+    out
+}
+
+/// Shorten the given text to a limited number of characters.
+fn abbreviate(text: &str) -> String {
+    let text = text.trim_end_matches(&[' ', '\0'][..]);
+    if text.chars().count() > MAX_VALUE_CHARS {
+        let mut short: String = text.chars().take(MAX_VALUE_CHARS).collect();
+        short.push_str("...");
+        short
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_tokens;
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    #[test]
+    fn format_nested_tokens() {
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(8),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length(46),
+            },
+            DataToken::ItemStart { len: Length(20) },
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0018, 0x6012),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([1].as_ref().into())),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0018, 0x6014),
+                VR::OB,
+                Length(0),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::Empty),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(32) },
+            DataToken::ItemValue(vec![0x99; 32]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0020, 0x4000),
+                VR::LT,
+                Length(60),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(
+                "A very long comment which does not fit in a single line",
+            )),
+        ];
+
+        assert_eq!(
+            format_tokens(tokens),
+            "\
+(0010,0010) PN 8: Doe^John
+SequenceStart(0018,6011) 46
+  ItemStart 20
+    (0018,6012) US 2: 1
+    (0018,6014) OB 0
+  ItemEnd
+SequenceEnd
+PixelSequenceStart
+  ItemStart 0
+  ItemEnd
+  ItemStart 32
+    ItemValue (32 bytes)
+  ItemEnd
+SequenceEnd
+(0020,4000) LT 60: A very long comment which does not fit in a sing...
+"
+        );
+    }
+}
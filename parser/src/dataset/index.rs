@@ -0,0 +1,85 @@
+//! Lookup of the elements of a data set by tag.
+use dicom_core::header::{HasLength, Length};
+use dicom_core::{DataElement, Tag};
+use std::collections::hash_map::{self, HashMap};
+
+/// A data element of a [`TagIndex`],
+/// with the items of a sequence as indices of their own
+/// and encapsulated pixel data fragments as byte vectors.
+///
+/// [`TagIndex`]: ./struct.TagIndex.html
+pub type IndexedElement = DataElement<TagIndex, Vec<u8>>;
+
+/// The elements of a data set, indexed by tag for repeated lookups.
+///
+/// Only the elements of the data set itself are indexed:
+/// the contents of a sequence stay in the items of its element.
+/// An index is usually built from a data set reader
+/// with [`DataSetReader::into_index`].
+///
+/// [`DataSetReader::into_index`]: ../read/struct.DataSetReader.html#method.into_index
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagIndex {
+    entries: HashMap<Tag, IndexedElement>,
+    /// the length of the data set, as declared by its item
+    len: Length,
+}
+
+impl Default for TagIndex {
+    fn default() -> Self {
+        TagIndex::new()
+    }
+}
+
+impl HasLength for TagIndex {
+    fn length(&self) -> Length {
+        self.len
+    }
+}
+
+impl TagIndex {
+    /// Create an empty index of undefined length.
+    pub fn new() -> Self {
+        TagIndex::with_length(Length::UNDEFINED)
+    }
+
+    /// Create an empty index of a data set with the given length,
+    /// such as that of a sequence item.
+    pub fn with_length(len: Length) -> Self {
+        TagIndex {
+            entries: HashMap::new(),
+            len,
+        }
+    }
+
+    /// Retrieve the element with the given tag.
+    pub fn get(&self, tag: Tag) -> Option<&IndexedElement> {
+        self.entries.get(&tag)
+    }
+
+    /// Add an element to the index,
+    /// returning the element with the same tag which it replaces, if any.
+    pub fn insert(&mut self, element: IndexedElement) -> Option<IndexedElement> {
+        self.entries.insert(element.header().tag, element)
+    }
+
+    /// The number of elements in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the index has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the elements of the index, in no particular order.
+    pub fn iter(&self) -> hash_map::Values<'_, Tag, IndexedElement> {
+        self.entries.values()
+    }
+
+    /// Take the underlying map of elements by tag.
+    pub fn into_map(self) -> HashMap<Tag, IndexedElement> {
+        self.entries
+    }
+}
@@ -1,19 +1,33 @@
 //! Interpretation of DICOM data sets as streams of tokens.
-use dicom_core::header::{DataElementHeader, Length, VR};
+use crate::marker::DicomElementMarker;
+use dicom_core::header::{DataElementHeader, HasLength, Length, VR};
 use dicom_core::value::{DicomValueType, PrimitiveValue};
 use dicom_core::{value::Value, DataElement, Tag};
 use std::fmt;
 
+pub mod adaptor;
+pub mod canonical;
+pub mod format;
+pub mod index;
 pub mod read;
+pub mod visit;
 pub mod write;
 
+pub use self::canonical::canonicalize_tokens;
+pub use self::format::format_tokens;
+pub use self::index::TagIndex;
 pub use self::read::DataSetReader;
+pub use self::visit::{drive, TokenVisitor};
 pub use self::write::DataSetWriter;
 
 /// A token of a DICOM data set stream. This is part of the interpretation of a
 /// data set as a stream of symbols, which may either represent data headers or
 /// actual value data.
+///
+/// More kinds of tokens may be added in the future,
+/// so matching on a token must also cover the kinds not listed here.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum DataToken {
     /// A data header of a primitive value.
     ElementHeader(DataElementHeader),
@@ -34,7 +48,21 @@ pub enum DataToken {
     /// This variant is used to represent the value of an offset table or a
     /// compressed fragment. It should not be used to represent nested data
     /// sets.
+    /// It may also carry a piece of a primitive value's raw data
+    /// after an element header, as yielded by [`ValueChunks`].
+    ///
+    /// [`ValueChunks`]: adaptor/chunks/struct.ValueChunks.html
     ItemValue(Vec<u8>),
+    /// A primitive data element value which was skipped
+    /// instead of being read,
+    /// marking where the value can be found in the source.
+    ///
+    /// This variant is yielded in place of a `PrimitiveValue`
+    /// for values longer than the reader's
+    /// [`max_eager_value_len`] option.
+    ///
+    /// [`max_eager_value_len`]: read/struct.DataSetReaderOptions.html#structfield.max_eager_value_len
+    LazyValue(DicomElementMarker),
 }
 
 impl fmt::Display for DataToken {
@@ -46,6 +74,32 @@ impl fmt::Display for DataToken {
     }
 }
 
+/// Arbitrary tokens are not meant to form a well structured data set:
+/// a sequence of them may have values without headers,
+/// unbalanced sequences and items,
+/// or lengths which do not match the values.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DataToken {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=8)? {
+            0 => DataToken::ElementHeader(u.arbitrary()?),
+            1 => DataToken::SequenceStart {
+                tag: u.arbitrary()?,
+                len: u.arbitrary()?,
+            },
+            2 => DataToken::PixelSequenceStart,
+            3 => DataToken::SequenceEnd,
+            4 => DataToken::ItemStart {
+                len: u.arbitrary()?,
+            },
+            5 => DataToken::ItemEnd,
+            6 => DataToken::PrimitiveValue(u.arbitrary()?),
+            7 => DataToken::ItemValue(u.arbitrary()?),
+            _ => DataToken::LazyValue(u.arbitrary()?),
+        })
+    }
+}
+
 /// This implementation treats undefined lengths as equal.
 impl PartialEq<Self> for DataToken {
     fn eq(&self, other: &Self) -> bool {
@@ -76,6 +130,7 @@ impl PartialEq<Self> for DataToken {
             (ItemStart { len: len1 }, ItemStart { len: len2 }) => len1.inner_eq(*len2),
             (PrimitiveValue(v1), PrimitiveValue(v2)) => v1 == v2,
             (ItemValue(v1), ItemValue(v2)) => v1 == v2,
+            (LazyValue(m1), LazyValue(m2)) => m1 == m2,
             (ItemEnd, ItemEnd)
             | (SequenceEnd, SequenceEnd)
             | (PixelSequenceStart, PixelSequenceStart) => true,
@@ -117,10 +172,103 @@ impl DataToken {
             _ => false,
         }
     }
+
+    /// Check whether this token represents the start of an item.
+    pub fn is_item_start(&self) -> bool {
+        matches!(self, DataToken::ItemStart { .. })
+    }
+
+    /// Check whether this token represents the end of an item.
+    pub fn is_item_end(&self) -> bool {
+        matches!(self, DataToken::ItemEnd)
+    }
+
+    /// Check whether this token is a primitive value.
+    pub fn is_primitive_value(&self) -> bool {
+        matches!(self, DataToken::PrimitiveValue(_))
+    }
+
+    /// Obtain the header of a primitive element,
+    /// if this token is one.
+    pub fn as_header(&self) -> Option<&DataElementHeader> {
+        match self {
+            DataToken::ElementHeader(header) => Some(header),
+            _ => None,
+        }
+    }
+
+    /// Obtain the primitive value,
+    /// if this token is one.
+    pub fn as_primitive(&self) -> Option<&PrimitiveValue> {
+        match self {
+            DataToken::PrimitiveValue(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Obtain the raw bytes of an item value,
+    /// if this token is one.
+    pub fn as_item_value(&self) -> Option<&[u8]> {
+        match self {
+            DataToken::ItemValue(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Obtain the tag of the element started by this token, if any.
+    pub(crate) fn element_tag(&self) -> Option<Tag> {
+        match self {
+            DataToken::ElementHeader(header) => Some(header.tag),
+            DataToken::SequenceStart { tag, .. } => Some(*tag),
+            DataToken::PixelSequenceStart => Some(Tag(0x7FE0, 0x0010)),
+            _ => None,
+        }
+    }
+}
+
+/// A token of a DICOM data set stream
+/// which may borrow the value data from an in-memory source.
+///
+/// This is obtained with [`DataSetReader::next_borrowed`]
+/// when reading from a byte slice,
+/// so that textual values do not have to be copied
+/// into a newly allocated `PrimitiveValue`.
+///
+/// [`DataSetReader::next_borrowed`]: read/struct.DataSetReader.html#method.next_borrowed
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataTokenRef<'a> {
+    /// A token with owned data.
+    Owned(DataToken),
+    /// The raw bytes of a textual primitive value,
+    /// borrowed from the source in their original encoding,
+    /// including any trailing padding.
+    PrimitiveValue(&'a [u8]),
+}
+
+impl DataTokenRef<'_> {
+    /// Convert this token into a token which owns its data.
+    ///
+    /// Borrowed values are copied into a byte buffer value,
+    /// as if read with the raw value reading strategy.
+    pub fn into_owned(self) -> DataToken {
+        match self {
+            DataTokenRef::Owned(token) => token,
+            DataTokenRef::PrimitiveValue(data) => {
+                DataToken::PrimitiveValue(PrimitiveValue::from(data))
+            }
+        }
+    }
+}
+
+impl From<DataToken> for DataTokenRef<'_> {
+    fn from(token: DataToken) -> Self {
+        DataTokenRef::Owned(token)
+    }
 }
 
 /// The type of delimiter: sequence or item.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SeqTokenType {
     Sequence,
     Item,
@@ -139,10 +287,41 @@ impl IntoTokens for dicom_core::header::EmptyObject {
     type Iter = std::iter::Empty<DataToken>;
 
     fn into_tokens(self) -> Self::Iter {
-        unreachable!()
+        // an empty object cannot be instantiated,
+        // so sequences of it never have items to convert
+        match self {}
     }
 }
 
+impl IntoTokens for &dicom_core::header::EmptyObject {
+    type Iter = std::iter::Empty<DataToken>;
+
+    fn into_tokens(self) -> Self::Iter {
+        match *self {}
+    }
+}
+
+/// The way in which the items of a sequence are given a length
+/// when converting a data element into tokens.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum ItemLengths {
+    /// Keep the length of each item object,
+    /// which is usually the length it was read with.
+    Keep,
+    /// Give every item an undefined length, closed by an item delimiter.
+    Undefined,
+    /// Give every item, including those of nested sequences,
+    /// the defined length of its content
+    /// as encoded with the given VR encoding.
+    ///
+    /// Sequences of defined length are measured again accordingly.
+    /// The tokens of the sequence are then buffered in full upon conversion.
+    /// See [`DataElementTokens::with_item_lengths`] for the details.
+    ///
+    /// [`DataElementTokens::with_item_lengths`]: ./enum.DataElementTokens.html#method.with_item_lengths
+    Defined(write::VrEncoding),
+}
+
 /// Token generator from a DICOM data element.
 pub enum DataElementTokens<I, P>
 where
@@ -153,6 +332,7 @@ where
         // Option is used for easy taking from a &mut,
         // should always be Some in practice
         Option<DataElement<I, P>>,
+        ItemLengths,
     ),
     /// the header of a plain primitive element was read
     Header(
@@ -167,6 +347,9 @@ where
             ItemTokens<I::Iter>,
         >,
     ),
+    /// reading the tokens of all items, buffered to define their lengths,
+    /// up to the end of the sequence
+    DefinedItems(std::vec::IntoIter<DataToken>),
     /// the header of encapsulated pixel data was read, will read
     /// the offset table next
     PixelData(
@@ -187,32 +370,98 @@ where
     End,
 }
 
-impl<I, P> Iterator for DataElementTokens<I, P>
+impl<I, P> DataElementTokens<I, P>
 where
     I: IntoTokens,
+{
+    /// Start converting the given data element into tokens,
+    /// keeping the length of each item in a sequence.
+    pub fn new(elem: DataElement<I, P>) -> Self {
+        DataElementTokens::with_item_lengths(elem, ItemLengths::Keep)
+    }
+
+    /// Start converting the given data element into tokens,
+    /// giving the items of a sequence a length as requested.
+    ///
+    /// Defined item lengths are measured by writing the content of each item
+    /// with the default character set and values padded to even length,
+    /// as done by the [encoding policy] presets.
+    /// Items whose content cannot be written,
+    /// such as one with a lazy value, keep their length.
+    /// Encapsulated pixel data fragments always have a defined length.
+    ///
+    /// [encoding policy]: ./write/struct.EncodingPolicy.html
+    pub fn with_item_lengths(elem: DataElement<I, P>, item_lengths: ItemLengths) -> Self {
+        DataElementTokens::Start(Some(elem), item_lengths)
+    }
+}
+
+impl<I, P> Iterator for DataElementTokens<I, P>
+where
+    I: IntoTokens + HasLength,
     P: AsRef<[u8]>,
 {
     type Item = DataToken;
 
     fn next(&mut self) -> Option<Self::Item> {
         let (out, next_state) = match self {
-            DataElementTokens::Start(elem) => {
+            DataElementTokens::Start(elem, item_lengths) => {
+                let item_lengths = *item_lengths;
                 let elem = elem.take().unwrap();
                 // data element header token
                 let header = *elem.header();
 
-                let token = DataToken::from(header);
+                let token = match elem.value() {
+                    // a primitive value even if the header says otherwise
+                    Value::Primitive(_) => DataToken::ElementHeader(header),
+                    _ => DataToken::from(header),
+                };
                 match token {
                     DataToken::SequenceStart { .. } => {
                         // retrieve sequence value, begin item sequence
                         match elem.into_value() {
                             Value::Primitive(_) | Value::PixelSequence { .. } => unreachable!(),
-                            Value::Sequence { items, size: _ } => {
-                                let items: dicom_core::value::C<_> = items
-                                    .into_iter()
-                                    .map(|o| AsItem(Length::UNDEFINED, o))
-                                    .collect();
-                                (Some(token), DataElementTokens::Items(items.into_tokens()))
+                            Value::Sequence { items, size } => {
+                                // the sequence keeps the length it was read with,
+                                // if it was defined
+                                let token = if size.is_defined() {
+                                    DataToken::SequenceStart {
+                                        tag: header.tag,
+                                        len: size,
+                                    }
+                                } else {
+                                    token
+                                };
+                                match item_lengths {
+                                    ItemLengths::Defined(vr_encoding) => {
+                                        let items: dicom_core::value::C<_> = items
+                                            .into_iter()
+                                            .map(|o| AsItem(Length::UNDEFINED, o))
+                                            .collect();
+                                        let tokens: Vec<_> = std::iter::once(token)
+                                            .chain(items.into_tokens())
+                                            .chain(std::iter::once(DataToken::SequenceEnd))
+                                            .collect();
+                                        let mut tokens =
+                                            write::define_item_lengths(tokens, vr_encoding)
+                                                .into_iter();
+                                        (tokens.next(), DataElementTokens::DefinedItems(tokens))
+                                    }
+                                    _ => {
+                                        // items retain their length from the source
+                                        // unless asked otherwise
+                                        let items: dicom_core::value::C<_> = items
+                                            .into_iter()
+                                            .map(|o| match item_lengths {
+                                                ItemLengths::Undefined => {
+                                                    AsItem(Length::UNDEFINED, o)
+                                                }
+                                                _ => AsItem(o.length(), o),
+                                            })
+                                            .collect();
+                                        (Some(token), DataElementTokens::Items(items.into_tokens()))
+                                    }
+                                }
                             }
                         }
                     }
@@ -260,6 +509,7 @@ where
                     (Some(DataToken::SequenceEnd), DataElementTokens::End)
                 }
             }
+            DataElementTokens::DefinedItems(tokens) => return tokens.next(),
             DataElementTokens::PixelData(fragments, tokens) => {
                 if let Some(token) = tokens.next() {
                     // bypass manual state transition
@@ -269,9 +519,15 @@ where
                 let fragments = fragments.take().unwrap();
                 let tokens: dicom_core::value::C<_> =
                     fragments.into_iter().map(|o| ItemValue(o)).collect();
-                *self = DataElementTokens::PixelDataFragments(tokens.into_tokens());
-                // recursive call to ensure the retrieval of a data token
-                return self.next();
+                let mut tokens = tokens.into_tokens();
+                match tokens.next() {
+                    Some(token) => {
+                        *self = DataElementTokens::PixelDataFragments(tokens);
+                        return Some(token);
+                    }
+                    // no fragments, sequence end token, end
+                    None => (Some(DataToken::SequenceEnd), DataElementTokens::End),
+                }
             }
             DataElementTokens::PixelDataFragments(tokens) => {
                 if let Some(token) = tokens.next() {
@@ -292,13 +548,141 @@ where
 
 impl<I, P> IntoTokens for DataElement<I, P>
 where
-    I: IntoTokens,
+    I: IntoTokens + HasLength,
     P: AsRef<[u8]>,
 {
     type Iter = DataElementTokens<I, P>;
 
     fn into_tokens(self) -> Self::Iter {
-        DataElementTokens::Start(Some(self))
+        DataElementTokens::new(self)
+    }
+}
+
+/// Borrowed data elements are converted into tokens
+/// without cloning the element,
+/// leaving the original element untouched.
+/// See [`DataElementRefTokens`] for the details.
+///
+/// [`DataElementRefTokens`]: ./enum.DataElementRefTokens.html
+impl<'a, I, P> IntoTokens for &'a DataElement<I, P>
+where
+    I: HasLength,
+    &'a I: IntoTokens,
+    P: AsRef<[u8]>,
+{
+    type Iter = DataElementRefTokens<'a, I, P>;
+
+    fn into_tokens(self) -> Self::Iter {
+        DataElementRefTokens::<'a, I, P>::new(self)
+    }
+}
+
+/// Token generator from a borrowed DICOM data element.
+///
+/// Only the header and primitive value of each element
+/// are copied into the tokens, as they are requested.
+/// The items of a sequence are converted into tokens by reference,
+/// keeping their length as with [`ItemLengths::Keep`],
+/// and pixel data fragments are copied one at a time.
+///
+/// [`ItemLengths::Keep`]: ./enum.ItemLengths.html#variant.Keep
+pub enum DataElementRefTokens<'a, I, P>
+where
+    &'a I: IntoTokens,
+{
+    /// initial state, at the beginning of the element
+    Start(&'a DataElement<I, P>),
+    /// the header of a plain primitive element was read
+    Header(&'a PrimitiveValue),
+    /// reading tokens from items,
+    /// with the tokens of the current item if started
+    Items(
+        std::slice::Iter<'a, I>,
+        Option<ItemTokens<<&'a I as IntoTokens>::Iter>>,
+    ),
+    /// reading the offset table of encapsulated pixel data,
+    /// then each of the fragments
+    PixelData(ItemValueTokens<&'a [u8]>, std::slice::Iter<'a, P>),
+    /// no more elements
+    End,
+}
+
+impl<'a, I, P> DataElementRefTokens<'a, I, P>
+where
+    &'a I: IntoTokens,
+{
+    /// Start converting the given data element into tokens.
+    pub fn new(elem: &'a DataElement<I, P>) -> Self {
+        Self::Start(elem)
+    }
+}
+
+impl<'a, I, P> Iterator for DataElementRefTokens<'a, I, P>
+where
+    I: HasLength,
+    &'a I: IntoTokens,
+    P: AsRef<[u8]>,
+{
+    type Item = DataToken;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (out, next_state) = match self {
+            DataElementRefTokens::Start(elem) => {
+                let header = *elem.header();
+                match elem.value() {
+                    // a primitive value even if the header says otherwise
+                    Value::Primitive(value) => {
+                        (Some(DataToken::ElementHeader(header)), Self::Header(value))
+                    }
+                    Value::Sequence { items, size } => {
+                        // the sequence keeps the length it was read with,
+                        // if it was defined
+                        let len = if size.is_defined() { *size } else { header.len };
+                        (
+                            Some(DataToken::SequenceStart {
+                                tag: header.tag,
+                                len,
+                            }),
+                            Self::Items(items.iter(), None),
+                        )
+                    }
+                    Value::PixelSequence {
+                        offset_table,
+                        fragments,
+                    } => (
+                        Some(DataToken::PixelSequenceStart),
+                        Self::PixelData(ItemValueTokens::new(&offset_table[..]), fragments.iter()),
+                    ),
+                }
+            }
+            DataElementRefTokens::Header(value) => {
+                (Some(DataToken::PrimitiveValue((*value).clone())), Self::End)
+            }
+            DataElementRefTokens::Items(items, tokens) => loop {
+                if let Some(token) = tokens.as_mut().and_then(|t| t.next()) {
+                    return Some(token);
+                }
+                match items.next() {
+                    Some(item) => *tokens = Some(ItemTokens::new(item.length(), item)),
+                    // sequence end token, end
+                    None => break (Some(DataToken::SequenceEnd), Self::End),
+                }
+            },
+            DataElementRefTokens::PixelData(tokens, fragments) => loop {
+                if let Some(token) = tokens.next() {
+                    return Some(token);
+                }
+                match fragments.next() {
+                    Some(fragment) => *tokens = ItemValueTokens::new(fragment.as_ref()),
+                    // sequence end token, end
+                    None => break (Some(DataToken::SequenceEnd), Self::End),
+                }
+            },
+            DataElementRefTokens::End => return None,
+        };
+        *self = next_state;
+
+        out
     }
 }
 
@@ -319,24 +703,13 @@ where
     type Item = DataToken;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // ensure a token sequence
-        if self.tokens.is_none() {
-            match self.seq.next() {
-                Some(entries) => {
-                    self.tokens = Some(entries.into_tokens());
-                }
-                None => return None,
-            }
-        }
-
-        // retrieve the next token
-        match self.tokens.as_mut().map(|s| s.next()) {
-            Some(Some(token)) => Some(token),
-            Some(None) => {
-                self.tokens = None;
-                self.next()
+        // move on to the next token sequence until a token is found,
+        // without recursion, as there may be many empty sequences in a row
+        loop {
+            if let Some(token) = self.tokens.as_mut().and_then(|s| s.next()) {
+                return Some(token);
             }
-            None => unreachable!(),
+            self.tokens = Some(self.seq.next()?.into_tokens());
         }
     }
 }
@@ -355,6 +728,23 @@ where
     }
 }
 
+/// A slice of elements is converted into tokens one element at a time,
+/// so that a collection can be serialized repeatedly
+/// without being consumed.
+impl<'a, T> IntoTokens for &'a [T]
+where
+    &'a T: IntoTokens,
+{
+    type Iter = FlattenTokens<std::slice::Iter<'a, T>, <&'a T as IntoTokens>::Iter>;
+
+    fn into_tokens(self) -> Self::Iter {
+        FlattenTokens {
+            seq: self.iter(),
+            tokens: None,
+        }
+    }
+}
+
 impl<T> IntoTokens for dicom_core::value::C<T>
 where
     T: IntoTokens,
@@ -370,6 +760,45 @@ where
     }
 }
 
+/// A newtype for converting the items of any iterator into tokens,
+/// such as a stream of data elements produced on demand.
+///
+/// Unlike collections, the iterator is not consumed up front:
+/// each item is only taken from it
+/// once the tokens of the previous one were all read,
+/// so that a data set can be written from a streaming source
+/// without buffering its elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokensFromIter<I>(pub I);
+
+impl<I> IntoTokens for TokensFromIter<I>
+where
+    I: Iterator,
+    I::Item: IntoTokens,
+{
+    type Iter = FlattenTokens<I, <I::Item as IntoTokens>::Iter>;
+
+    fn into_tokens(self) -> Self::Iter {
+        FlattenTokens {
+            seq: self.0,
+            tokens: None,
+        }
+    }
+}
+
+impl<I> IntoIterator for TokensFromIter<I>
+where
+    I: Iterator,
+    I::Item: IntoTokens,
+{
+    type Item = DataToken;
+    type IntoIter = FlattenTokens<I, <I::Item as IntoTokens>::Iter>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_tokens()
+    }
+}
+
 // A stream of tokens from a DICOM item.
 #[derive(Debug)]
 pub enum ItemTokens<T> {
@@ -452,6 +881,8 @@ where
 /// When converting a value of this type into tokens, the algorithm
 /// will create an item start with an explicit length, followed by
 /// an item value token, then an item delimiter.
+/// Data of odd length is padded with a trailing null byte,
+/// so that the item length is even.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ItemValue<P>(P);
 
@@ -494,7 +925,8 @@ where
         let (out, next_state) = match self {
             ItemValueTokens::Start(value) => {
                 let value = value.take().unwrap();
-                let len = Length(value.as_ref().len() as u32);
+                let len = value.as_ref().len() as u32;
+                let len = Length(len + len % 2);
 
                 (
                     Some(DataToken::ItemStart { len }),
@@ -505,10 +937,13 @@ where
                     },
                 )
             }
-            ItemValueTokens::Value(value) => (
-                Some(DataToken::ItemValue(value.as_ref().to_owned())),
-                ItemValueTokens::Done,
-            ),
+            ItemValueTokens::Value(value) => {
+                let mut data = value.as_ref().to_owned();
+                if data.len() % 2 == 1 {
+                    data.push(0);
+                }
+                (Some(DataToken::ItemValue(data)), ItemValueTokens::Done)
+            }
             ItemValueTokens::Done => (Some(DataToken::ItemEnd), ItemValueTokens::End),
             ItemValueTokens::End => return None,
         };
@@ -517,3 +952,410 @@ where
         out
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DataToken, IntoTokens, TokensFromIter};
+    use dicom_core::header::{DataElementHeader, EmptyObject, Length};
+    use dicom_core::value::{PrimitiveValue, Value};
+    use dicom_core::{DataElement, Tag, VR};
+
+    #[test]
+    fn element_slice_into_tokens_does_not_consume() {
+        let elements: Vec<DataElement<EmptyObject, [u8; 0]>> = vec![
+            DataElement::new(
+                Tag(0x0008, 0x0060),
+                VR::CS,
+                Value::Primitive(PrimitiveValue::from("MR")),
+            ),
+            DataElement::new(
+                Tag(0x0028, 0x0010),
+                VR::US,
+                Value::Primitive(PrimitiveValue::U16([512].as_ref().into())),
+            ),
+        ];
+
+        let tokens: Vec<_> = (&elements[..]).into_tokens().collect();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0008, 0x0060),
+                    VR::CS,
+                    Length(2),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from("MR")),
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0028, 0x0010),
+                    VR::US,
+                    Length(2),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::U16([512].as_ref().into())),
+            ]
+        );
+
+        // the same elements can be serialized again
+        let tokens_again: Vec<_> = (&elements[..]).into_tokens().collect();
+        assert_eq!(tokens, tokens_again);
+        assert_eq!(elements.len(), 2);
+    }
+
+    #[test]
+    fn borrowed_element_into_tokens_without_cloning() {
+        use dicom_core::header::HasLength;
+
+        /// An item which cannot be cloned
+        #[derive(Debug)]
+        struct Item(Vec<DataElement<Item, Vec<u8>>>);
+
+        impl HasLength for Item {
+            fn length(&self) -> Length {
+                Length(10)
+            }
+        }
+
+        impl<'a> IntoTokens for &'a Item {
+            // boxed to break the recursion of nested sequences
+            type Iter = Box<dyn Iterator<Item = DataToken> + 'a>;
+
+            fn into_tokens(self) -> Self::Iter {
+                Box::new((&self.0[..]).into_tokens())
+            }
+        }
+
+        let elements = [
+            DataElement::new(
+                Tag(0x0008, 0x1115),
+                VR::SQ,
+                Value::Sequence {
+                    items: vec![Item(vec![DataElement::new(
+                        Tag(0x0008, 0x0060),
+                        VR::CS,
+                        Value::Primitive(PrimitiveValue::from("MR")),
+                    )])]
+                    .into(),
+                    size: Length::UNDEFINED,
+                },
+            ),
+            DataElement::new(
+                Tag(0x7FE0, 0x0010),
+                VR::OB,
+                Value::PixelSequence {
+                    offset_table: vec![].into(),
+                    fragments: vec![vec![1, 2, 3]].into(),
+                },
+            ),
+        ];
+
+        let tokens: Vec<_> = (&elements[..]).into_tokens().collect();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::SequenceStart {
+                    tag: Tag(0x0008, 0x1115),
+                    len: Length::UNDEFINED,
+                },
+                DataToken::ItemStart { len: Length(10) },
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0008, 0x0060),
+                    VR::CS,
+                    Length(2),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from("MR")),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+                DataToken::PixelSequenceStart,
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+                DataToken::ItemStart { len: Length(4) },
+                DataToken::ItemValue(vec![1, 2, 3, 0]),
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+            ]
+        );
+        // the same elements can be serialized again
+        let tokens_again: Vec<_> = (&elements[..]).into_tokens().collect();
+        assert_eq!(tokens, tokens_again);
+    }
+
+    #[test]
+    fn token_accessors() {
+        let header = DataElementHeader::new(Tag(0x0008, 0x0060), VR::CS, Length(2));
+        let header_token = DataToken::ElementHeader(header);
+        assert_eq!(header_token.as_header(), Some(&header));
+        assert_eq!(header_token.as_primitive(), None);
+        assert!(!header_token.is_primitive_value());
+
+        let value = DataToken::PrimitiveValue(PrimitiveValue::from("MR"));
+        assert!(value.is_primitive_value());
+        assert_eq!(value.as_primitive(), Some(&PrimitiveValue::from("MR")));
+        assert_eq!(value.as_header(), None);
+
+        let item_value = DataToken::ItemValue(vec![1, 2, 3, 4]);
+        assert_eq!(item_value.as_item_value(), Some(&[1, 2, 3, 4][..]));
+        assert_eq!(value.as_item_value(), None);
+
+        let item_start = DataToken::ItemStart {
+            len: Length::UNDEFINED,
+        };
+        assert!(item_start.is_item_start());
+        assert!(!item_start.is_item_end());
+        assert!(DataToken::ItemEnd.is_item_end());
+        assert!(!DataToken::ItemEnd.is_item_start());
+    }
+
+    #[test]
+    fn odd_length_item_values_are_padded() {
+        use super::ItemValue;
+
+        let tokens: Vec<_> = ItemValue(vec![0xff, 0xd9, 0x01]).into_tokens().collect();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ItemStart { len: Length(4) },
+                DataToken::ItemValue(vec![0xff, 0xd9, 0x01, 0x00]),
+                DataToken::ItemEnd,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokens_from_iter_are_lazy() {
+        // an endless source which must not be read past the second element
+        let elements = (0..).map(|i: u16| {
+            assert!(i < 2, "the element source was read too far");
+            DataElement::<EmptyObject, [u8; 0]>::new(
+                Tag(0x0009, 0x1000 + i),
+                VR::US,
+                Value::Primitive(PrimitiveValue::U16([i].as_ref().into())),
+            )
+        });
+
+        let tokens: Vec<_> = TokensFromIter(elements).into_iter().take(4).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0009, 0x1000),
+                    VR::US,
+                    Length(2),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::U16([0].as_ref().into())),
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0009, 0x1001),
+                    VR::US,
+                    Length(2),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::U16([1].as_ref().into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn sequence_items_take_the_requested_lengths() {
+        use super::write::VrEncoding;
+        use super::{DataElementTokens, ItemLengths};
+        use dicom_core::header::HasLength;
+
+        /// An item holding its elements in memory
+        #[derive(Debug, Clone)]
+        struct Item(Vec<DataElement<Item, [u8; 0]>>);
+
+        impl HasLength for Item {
+            fn length(&self) -> Length {
+                Length(1000)
+            }
+        }
+
+        impl IntoTokens for Item {
+            // boxed to break the recursion of nested sequences
+            type Iter = Box<dyn Iterator<Item = DataToken>>;
+
+            fn into_tokens(self) -> Self::Iter {
+                Box::new(self.0.into_tokens())
+            }
+        }
+
+        let sequence = |tag, items: Vec<Item>, size| {
+            DataElement::new(
+                tag,
+                VR::SQ,
+                Value::Sequence {
+                    items: items.into(),
+                    size,
+                },
+            )
+        };
+        let modality = DataElement::new(
+            Tag(0x0008, 0x0060),
+            VR::CS,
+            Value::Primitive(PrimitiveValue::from("MR")),
+        );
+        let rows = DataElement::new(
+            Tag(0x0028, 0x0010),
+            VR::US,
+            Value::Primitive(PrimitiveValue::U16([512].as_ref().into())),
+        );
+        // an outdated sequence length, with an undefined length sequence inside
+        let elem = sequence(
+            Tag(0x0008, 0x1115),
+            vec![
+                Item(vec![modality.clone()]),
+                Item(vec![sequence(
+                    Tag(0x0008, 0x1140),
+                    vec![Item(vec![rows.clone()])],
+                    Length::UNDEFINED,
+                )]),
+            ],
+            Length(2),
+        );
+
+        let item_lengths = |item_lengths| {
+            DataElementTokens::with_item_lengths(elem.clone(), item_lengths)
+                .filter_map(|token| match token {
+                    DataToken::SequenceStart { len, .. } => Some(('S', len.get())),
+                    DataToken::ItemStart { len } => Some(('I', len.get())),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            item_lengths(ItemLengths::Keep),
+            vec![
+                ('S', Some(2)),
+                ('I', Some(1000)),
+                ('I', Some(1000)),
+                ('S', None),
+                ('I', Some(1000)),
+            ]
+        );
+        assert_eq!(
+            item_lengths(ItemLengths::Undefined),
+            vec![
+                ('S', Some(2)),
+                ('I', None),
+                ('I', None),
+                ('S', None),
+                ('I', Some(1000)),
+            ]
+        );
+        // CS: 8 + 2; SQ: 12 + (8 + US: 8 + 2) + 8
+        assert_eq!(
+            item_lengths(ItemLengths::Defined(VrEncoding::Explicit)),
+            vec![
+                ('S', Some(64)),
+                ('I', Some(10)),
+                ('I', Some(38)),
+                ('S', None),
+                ('I', Some(10)),
+            ]
+        );
+        // CS: 8 + 2; SQ: 8 + (8 + US: 8 + 2) + 8
+        assert_eq!(
+            item_lengths(ItemLengths::Defined(VrEncoding::Implicit)),
+            vec![
+                ('S', Some(60)),
+                ('I', Some(10)),
+                ('I', Some(34)),
+                ('S', None),
+                ('I', Some(10)),
+            ]
+        );
+
+        // the defined lengths are those written
+        let tokens: Vec<_> =
+            DataElementTokens::with_item_lengths(elem, ItemLengths::Defined(VrEncoding::Explicit))
+                .collect();
+        assert_eq!(tokens.last(), Some(&DataToken::SequenceEnd));
+        let mut data = Vec::new();
+        super::DataSetWriter::with_vr_encoding(
+            &mut data,
+            VrEncoding::Explicit,
+            dicom_encoding::text::SpecificCharacterSet::Default,
+        )
+        .unwrap()
+        .write_sequence(tokens)
+        .unwrap();
+        assert_eq!(data.len(), 12 + 64);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_elements_roundtrip_through_tokens() {
+        use arbitrary::Unstructured;
+
+        for seed in 0..256_u64 {
+            // xorshift64*
+            let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+            let bytes: Vec<u8> = (0..1024)
+                .map(|_| {
+                    state ^= state >> 12;
+                    state ^= state << 25;
+                    state ^= state >> 27;
+                    (state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8
+                })
+                .collect();
+            let mut u = Unstructured::new(&bytes);
+            let mut elements: Vec<DataElement<EmptyObject, [u8; 0]>> = Vec::new();
+            while !u.is_empty() {
+                // any VR, even one which does not fit the value
+                let header: DataElementHeader = u.arbitrary().unwrap();
+                let value: PrimitiveValue = u.arbitrary().unwrap();
+                elements.push(DataElement::new(
+                    header.tag,
+                    header.vr,
+                    Value::Primitive(value),
+                ));
+            }
+
+            // assemble the elements back from the tokens
+            let mut tokens = elements.clone().into_tokens();
+            let mut assembled: Vec<DataElement<EmptyObject, [u8; 0]>> = Vec::new();
+            while let Some(token) = tokens.next() {
+                let header = match token {
+                    DataToken::ElementHeader(header) => header,
+                    token => panic!("unexpected token {:?}", token),
+                };
+                match tokens.next() {
+                    Some(DataToken::PrimitiveValue(value)) => {
+                        assert_eq!(header.len, Length(value.calculate_byte_len() as u32));
+                        assembled.push(DataElement::new(
+                            header.tag,
+                            header.vr,
+                            Value::Primitive(value),
+                        ));
+                    }
+                    token => panic!("unexpected token {:?}", token),
+                }
+            }
+            // floating point values may be NaN, which is not equal to itself
+            assert_eq!(format!("{:?}", assembled), format!("{:?}", elements));
+        }
+    }
+
+    #[test]
+    fn flatten_many_empty_sequences() {
+        // deep recursion here would overflow the stack
+        let mut elements: Vec<Vec<DataElement<EmptyObject, [u8; 0]>>> = vec![Vec::new(); 100_000];
+        elements.push(vec![DataElement::new(
+            Tag(0x0008, 0x0060),
+            VR::CS,
+            Value::Primitive(PrimitiveValue::from("MR")),
+        )]);
+        elements.extend(vec![Vec::new(); 100_000]);
+
+        let tokens: Vec<_> = elements.into_tokens().collect();
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0008, 0x0060),
+                    VR::CS,
+                    Length(2),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from("MR")),
+            ]
+        );
+    }
+}
@@ -6,22 +6,27 @@
 //! to form a syntax tree of a full data set.
 use crate::marker::DicomElementMarker;
 use crate::stateful::decode::{
-    DynStatefulDecoder, Error as DecoderError, StatefulDecode, StatefulDecoder,
+    DynSeekStatefulDecoder, DynStatefulDecoder, Error as DecoderError, StatefulDecode,
+    StatefulDecoder,
 };
 use crate::util::ReadSeek;
-use dicom_core::dictionary::DataDictionary;
+use dicom_core::dictionary::{DataDictionary, DictionaryEntry};
 use dicom_core::header::{DataElementHeader, Header, Length, SequenceItemHeader};
-use dicom_core::{PrimitiveValue, Tag, VR};
+use dicom_core::value::{Value, C};
+use dicom_core::{DataElement, PrimitiveValue, Tag, VR};
 use dicom_dictionary_std::StandardDataDictionary;
 use dicom_encoding::text::SpecificCharacterSet;
 use dicom_encoding::transfer_syntax::TransferSyntax;
-use snafu::{Backtrace, ResultExt, Snafu};
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
 use std::cmp::Ordering;
-use std::io::Read;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek};
 use std::iter::Iterator;
 use std::marker::PhantomData;
 
-use super::{DataToken, SeqTokenType};
+use super::adaptor::private::{is_private_creator, private_creator_of};
+use super::index::TagIndex;
+use super::{DataToken, DataTokenRef, SeqTokenType};
 
 fn is_stateful_decode<T>(_: &T)
 where
@@ -64,6 +69,47 @@ pub enum Error {
     },
     #[snafu()]
     UnexpectedTag { tag: Tag, backtrace: Backtrace },
+    #[snafu(display("Undefined length of value fragment in element {}", tag))]
+    UndefinedFragmentLength { tag: Tag, backtrace: Backtrace },
+    #[snafu(display(
+        "Cannot resume reading at position {} from a decoder at position {}",
+        position,
+        bytes_read
+    ))]
+    InconsistentResumePosition {
+        position: u64,
+        bytes_read: u64,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Could not switch to the character set of the resumed state"))]
+    ResumeCharacterSet {
+        #[snafu(backtrace)]
+        source: DecoderError,
+    },
+    #[snafu(display(
+        "Byte limit exceeded: reading would reach {} bytes, but the limit is {}",
+        bytes_read,
+        limit
+    ))]
+    ByteLimitExceeded {
+        limit: u64,
+        bytes_read: u64,
+        backtrace: Backtrace,
+    },
+    /// A token appeared out of place while assembling elements
+    #[snafu(display("Unexpected token {:?} while assembling an element", token))]
+    UnexpectedToken {
+        token: DataToken,
+        backtrace: Backtrace,
+    },
+    /// The data set ended in the middle of an element
+    #[snafu(display("Data set ended before the end of an element"))]
+    PrematureEnd { backtrace: Backtrace },
+    #[snafu(display("Could not move back to the start of the data set"))]
+    Restart {
+        #[snafu(backtrace)]
+        source: DecoderError,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -78,11 +124,152 @@ struct SeqToken {
     len: Length,
     /// Whether this sequence token is part of an encapsulated pixel data.
     pixel_data: bool,
+    /// Whether the contents are encoded in implicit VR little endian,
+    /// as within a `UN` element of undefined length.
+    implicit_vr: bool,
     /// The number of bytes the parser has read until it reached the
     /// beginning of the sequence or item value data.
     base_offset: u64,
 }
 
+impl From<SeqToken> for DelimiterState {
+    fn from(token: SeqToken) -> Self {
+        DelimiterState {
+            typ: token.typ,
+            len: token.len,
+            pixel_data: token.pixel_data,
+            implicit_vr: token.implicit_vr,
+            base_offset: token.base_offset,
+        }
+    }
+}
+
+impl From<DelimiterState> for SeqToken {
+    fn from(state: DelimiterState) -> Self {
+        SeqToken {
+            typ: state.typ,
+            len: state.len,
+            pixel_data: state.pixel_data,
+            implicit_vr: state.implicit_vr,
+            base_offset: state.base_offset,
+        }
+    }
+}
+
+/// A sequence or item which is open at some point of the reading process.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DelimiterState {
+    /// Whether it is a sequence or an item.
+    pub typ: SeqTokenType,
+    /// The length of the sequence or item, can be undefined.
+    pub len: Length,
+    /// Whether it is part of encapsulated pixel data.
+    pub pixel_data: bool,
+    /// Whether its contents are encoded in implicit VR little endian,
+    /// regardless of the transfer syntax,
+    /// as within a `UN` element of undefined length.
+    pub implicit_vr: bool,
+    /// The position in the source where the sequence or item value begins.
+    pub base_offset: u64,
+}
+
+/// A snapshot of the decoding state of a data set reader,
+/// taken in between two tokens.
+///
+/// This contains everything needed to resume reading a data set
+/// from a new source holding the bytes which follow `position`.
+/// See [`DataSetReader::state`] and [`DataSetReader::resume`].
+/// With the `serde` feature, the state can also be serialized,
+/// so that it can be kept outside of the program in the meantime.
+///
+/// [`DataSetReader::state`]: ./struct.DataSetReader.html#method.state
+/// [`DataSetReader::resume`]: ./struct.DataSetReader.html#method.resume
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataSetReaderState {
+    /// The number of bytes consumed from the source so far.
+    pub position: u64,
+    /// The sequences and items currently open,
+    /// from the outermost to the innermost.
+    pub delimiters: Vec<DelimiterState>,
+    /// Whether an item header or sequence delimiter is expected next.
+    pub in_sequence: bool,
+    /// Whether the reader needs to check for the end
+    /// of a sequence or item of defined length.
+    pub delimiter_check_pending: bool,
+    /// The element header whose value is to be read next, if any.
+    pub pending_header: Option<DataElementHeader>,
+    /// A value which was already read and is to be yielded next, if any.
+    pub pending_value: Option<PrimitiveValue>,
+    /// The private creators found so far,
+    /// indexed by data set depth, group and private block.
+    #[cfg_attr(feature = "serde", serde(with = "private_creator_entries"))]
+    pub private_creators: BTreeMap<(usize, u16, u8), String>,
+    /// The tags of the sequences currently open,
+    /// from the outermost to the innermost.
+    pub path: Vec<Tag>,
+    /// The codes of the _Specific Character Set_ found so far,
+    /// empty if the decoder still uses the character set it was created with.
+    pub character_set: Vec<String>,
+}
+
+/// Private creators are serialized as a sequence of entries,
+/// since their keys are not strings.
+#[cfg(feature = "serde")]
+mod private_creator_entries {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    type PrivateCreators = BTreeMap<(usize, u16, u8), String>;
+
+    pub fn serialize<S>(map: &PrivateCreators, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, D>(d: D) -> Result<PrivateCreators, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<((usize, u16, u8), String)>::deserialize(d)
+            .map(|entries| entries.into_iter().collect())
+    }
+}
+
+impl DataSetReaderState {
+    /// Retrieve the number of bytes of the current element's value
+    /// which still need to be read from the source.
+    ///
+    /// Since values are read all at once,
+    /// the source given on resumption should hold
+    /// at least this many bytes from `position`
+    /// in order to read the next token.
+    pub fn value_remaining(&self) -> u32 {
+        if self.pending_value.is_some() || self.delimiter_check_pending || self.in_sequence {
+            return 0;
+        }
+        match (self.pending_header, self.delimiters.last()) {
+            (Some(header), _) if !header.is_encapsulated_pixeldata() => {
+                header.len.get().unwrap_or(0)
+            }
+            // pixel data fragment
+            (
+                None,
+                Some(DelimiterState {
+                    typ: SeqTokenType::Item,
+                    pixel_data: true,
+                    len,
+                    ..
+                }),
+            ) => len.get().unwrap_or(0),
+            _ => 0,
+        }
+    }
+}
+
 /// The value reading strategy for the data set reader.
 ///
 /// It defines how the `PrimitiveValue`s in value tokens are constructed.
@@ -117,23 +304,150 @@ pub enum ValueReadStrategy {
     Raw,
 }
 
+/// How the data set reader chooses the value representation
+/// of an element which is read as `UN`
+/// and is missing from the data dictionary.
+///
+/// This only applies to elements decoded in implicit VR,
+/// when the [unknown VR callback] did not resolve the VR.
+/// Elements with the VR `UN` in explicit VR keep it.
+///
+/// [unknown VR callback]: ./struct.DataSetReader.html#method.on_unknown_vr
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum UnknownVrPolicy {
+    /// Keep the VR `UN`, so that values are read as raw bytes.
+    Unknown,
+    /// Use the given VR for all such elements.
+    Always(VR),
+    /// Use the given VR for elements in even (standard) groups,
+    /// keeping `UN` for elements in odd (private) groups.
+    StandardGroups(VR),
+}
+
+impl UnknownVrPolicy {
+    /// The VR to give to an element with the given tag,
+    /// if not `UN`.
+    fn vr_for(self, tag: Tag) -> Option<VR> {
+        match self {
+            UnknownVrPolicy::Unknown => None,
+            UnknownVrPolicy::Always(vr) => Some(vr),
+            UnknownVrPolicy::StandardGroups(vr) if tag.group() & 1 == 0 => Some(vr),
+            UnknownVrPolicy::StandardGroups(_) => None,
+        }
+    }
+}
+
 /// The set of options for the data set reader.
 #[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub struct DataSetReaderOptions {
     pub value_read: ValueReadStrategy,
+    /// Whether to join the item fragments of an `OB` or `OW` element
+    /// of undefined length, other than the pixel data,
+    /// into a single primitive value.
+    ///
+    /// When enabled, such an element is yielded
+    /// as an element header with the total length of the fragments,
+    /// followed by the concatenated fragment data.
+    /// Otherwise, the element is treated as a data set sequence.
+    pub coalesce_fragments: bool,
+    /// The maximum length of a primitive value to be read eagerly.
+    ///
+    /// The value of an element with a longer defined length
+    /// is skipped instead of being read into memory,
+    /// and yielded as a [`DataToken::LazyValue`]
+    /// holding the position of the value in the source,
+    /// as counted by the decoder.
+    /// For a source which does not begin at the start of the file,
+    /// create the decoder with [`StatefulDecoder::new_with_position`]
+    /// so that the position is relative to the start of the file.
+    /// Pixel data fragments are always read.
+    /// All values are read if this is `None`.
+    ///
+    /// [`DataToken::LazyValue`]: ../enum.DataToken.html#variant.LazyValue
+    /// [`StatefulDecoder::new_with_position`]: ../../stateful/decode/struct.StatefulDecoder.html#method.new_with_position
+    pub max_eager_value_len: Option<u32>,
+    /// The value representation of elements read as `UN`
+    /// which are not in the data dictionary.
+    pub unknown_vr_policy: UnknownVrPolicy,
+    /// Whether to keep the values which cannot be cleanly interpreted
+    /// exactly as they were encoded.
+    ///
+    /// When enabled, the value of an element
+    /// which is still `UN` after applying the [`unknown_vr_policy`],
+    /// or which has an odd length,
+    /// is yielded as a [`DataToken::ItemValue`]
+    /// with the bytes of the value in the source,
+    /// so that a writer can reproduce the element as it was.
+    /// Such values are not interpreted,
+    /// not even as private creators.
+    /// Objects assembled from the tokens,
+    /// such as with [`into_index`],
+    /// hold them as a `U8` primitive value
+    /// under the VR of the element header.
+    ///
+    /// [`unknown_vr_policy`]: #structfield.unknown_vr_policy
+    /// [`DataToken::ItemValue`]: ../enum.DataToken.html#variant.ItemValue
+    /// [`into_index`]: struct.DataSetReader.html#method.into_index
+    pub preserve_unknown: bool,
 }
 
 impl Default for DataSetReaderOptions {
     fn default() -> Self {
         DataSetReaderOptions {
             value_read: ValueReadStrategy::Preserved,
+            coalesce_fragments: false,
+            max_eager_value_len: None,
+            unknown_vr_policy: UnknownVrPolicy::Unknown,
+            preserve_unknown: false,
         }
     }
 }
 
+impl DataSetReaderOptions {
+    /// Replace the fragment coalescing option.
+    pub fn coalesce_fragments(mut self, coalesce_fragments: bool) -> Self {
+        self.coalesce_fragments = coalesce_fragments;
+        self
+    }
+
+    /// Replace the maximum length of values to be read eagerly.
+    pub fn max_eager_value_len(mut self, max_eager_value_len: u32) -> Self {
+        self.max_eager_value_len = Some(max_eager_value_len);
+        self
+    }
+
+    /// Replace the policy for the VR of elements missing from the dictionary.
+    pub fn unknown_vr_policy(mut self, unknown_vr_policy: UnknownVrPolicy) -> Self {
+        self.unknown_vr_policy = unknown_vr_policy;
+        self
+    }
+
+    /// Use the given VR for all elements read as `UN`
+    /// which are missing from the dictionary.
+    ///
+    /// This is the same as the policy [`UnknownVrPolicy::Always`].
+    ///
+    /// [`UnknownVrPolicy::Always`]: ./enum.UnknownVrPolicy.html#variant.Always
+    pub fn default_vr(self, vr: VR) -> Self {
+        self.unknown_vr_policy(UnknownVrPolicy::Always(vr))
+    }
+
+    /// Replace the option to keep the values of unknown
+    /// and odd length elements as raw bytes.
+    pub fn preserve_unknown(mut self, preserve_unknown: bool) -> Self {
+        self.preserve_unknown = preserve_unknown;
+        self
+    }
+}
+
 /// A higher-level reader for retrieving structure in a DICOM data set from an
 /// arbitrary data source.
+///
+/// Values are read in the byte order of the underlying decoder.
+/// In particular, values of VR `AT` are read as pairs of
+/// 16-bit group and element numbers,
+/// and yielded as a `PrimitiveValue::Tags` value.
 #[derive(Debug)]
 pub struct DataSetReader<S, D> {
     /// the stateful decoder
@@ -154,6 +468,46 @@ pub struct DataSetReader<S, D> {
     last_header: Option<DataElementHeader>,
     /// Whether to expect a raw value next, and how many bytes long
     raw_value_length: Option<u32>,
+    /// a value to be yielded next, read ahead of time
+    pending_value: Option<PrimitiveValue>,
+    /// the private creators found so far,
+    /// indexed by data set depth, group and private block
+    private_creators: BTreeMap<(usize, u16, u8), String>,
+    /// user provided callback for resolving unknown VRs
+    unknown_vr_hook: Option<UnknownVrHook>,
+    /// user provided callback for observing the VR bytes of each element
+    raw_vr_hook: Option<RawVrHook>,
+    /// the tags of the sequences currently open,
+    /// from the outermost to the innermost
+    path: Vec<Tag>,
+    /// the maximum number of bytes to read from the source, if any
+    byte_limit: Option<u64>,
+    /// whether the last item or sequence end was read from a delimiter
+    last_end_delimited: bool,
+}
+
+/// A callback for resolving the value representation
+/// of an element with the given tag and private creator.
+type UnknownVrFn = dyn FnMut(Tag, Option<&str>) -> Option<VR>;
+
+/// A boxed callback for resolving the value representation
+/// of elements which would otherwise be read as `UN`.
+struct UnknownVrHook(Box<UnknownVrFn>);
+
+impl std::fmt::Debug for UnknownVrHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("UnknownVrHook")
+    }
+}
+
+/// A boxed callback for observing the value representation bytes
+/// of each element header, as found in the source.
+struct RawVrHook(Box<dyn FnMut(Tag, [u8; 2])>);
+
+impl std::fmt::Debug for RawVrHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("RawVrHook")
+    }
 }
 
 impl<'s> DataSetReader<DynStatefulDecoder<'s>, StandardDataDictionary> {
@@ -178,6 +532,13 @@ impl<'s> DataSetReader<DynStatefulDecoder<'s>, StandardDataDictionary> {
             hard_break: false,
             last_header: None,
             raw_value_length: None,
+            pending_value: None,
+            private_creators: BTreeMap::new(),
+            unknown_vr_hook: None,
+            raw_vr_hook: None,
+            path: Vec::new(),
+            byte_limit: None,
+            last_end_delimited: false,
         })
     }
 }
@@ -185,6 +546,11 @@ impl<'s> DataSetReader<DynStatefulDecoder<'s>, StandardDataDictionary> {
 impl<'s, D> DataSetReader<DynStatefulDecoder<'s>, D> {
     /// Creates a new iterator with the given random access source and data dictionary,
     /// while considering the given transfer syntax and specific character set.
+    ///
+    /// The dictionary resolves the VR of elements
+    /// which the decoder alone would read as `UN`,
+    /// such as private or command elements in implicit VR,
+    /// so that their values are read accordingly.
     pub fn new_with_dictionary<S: 's>(
         source: S,
         dict: D,
@@ -209,6 +575,58 @@ impl<'s, D> DataSetReader<DynStatefulDecoder<'s>, D> {
             hard_break: false,
             last_header: None,
             raw_value_length: None,
+            pending_value: None,
+            private_creators: BTreeMap::new(),
+            unknown_vr_hook: None,
+            raw_vr_hook: None,
+            path: Vec::new(),
+            byte_limit: None,
+            last_end_delimited: false,
+        })
+    }
+}
+
+impl<'s, D> DataSetReader<DynSeekStatefulDecoder<'s>, D> {
+    /// Creates a new iterator with the given seekable source and data dictionary,
+    /// while considering the given transfer syntax and specific character set.
+    ///
+    /// This is the same as [`new_with_dictionary`],
+    /// except that the reader can be [restarted](#method.restart)
+    /// for another pass over the data set.
+    ///
+    /// [`new_with_dictionary`]: #method.new_with_dictionary
+    pub fn new_seekable_with_dictionary<S>(
+        source: S,
+        dict: D,
+        ts: &TransferSyntax,
+        cs: SpecificCharacterSet,
+        options: DataSetReaderOptions,
+    ) -> Result<Self>
+    where
+        S: 's + Read + Seek,
+    {
+        let parser =
+            DynSeekStatefulDecoder::new_seekable_with(source, ts, cs).context(CreateDecoder)?;
+
+        is_stateful_decode(&parser);
+
+        Ok(DataSetReader {
+            parser,
+            dict,
+            options,
+            seq_delimiters: Vec::new(),
+            delimiter_check_pending: false,
+            in_sequence: false,
+            hard_break: false,
+            last_header: None,
+            raw_value_length: None,
+            pending_value: None,
+            private_creators: BTreeMap::new(),
+            unknown_vr_hook: None,
+            raw_vr_hook: None,
+            path: Vec::new(),
+            byte_limit: None,
+            last_end_delimited: false,
         })
     }
 }
@@ -226,10 +644,251 @@ impl<S> DataSetReader<S, StandardDataDictionary> {
             hard_break: false,
             last_header: None,
             raw_value_length: None,
+            pending_value: None,
+            private_creators: BTreeMap::new(),
+            unknown_vr_hook: None,
+            raw_vr_hook: None,
+            path: Vec::new(),
+            byte_limit: None,
+            last_end_delimited: false,
+        }
+    }
+}
+
+impl<S> DataSetReader<S, StandardDataDictionary>
+where
+    S: StatefulDecode,
+{
+    /// Create a new iterator which continues reading a data set
+    /// from a previously captured state.
+    ///
+    /// The given decoder must be positioned right after the bytes
+    /// consumed so far, and report the same number of bytes read
+    /// as the state's `position`
+    /// (see [`StatefulDecoder::new_with_position`]).
+    /// The decoder is switched to the character set of the state, if any.
+    /// Callbacks are not part of the state:
+    /// they need to be registered again,
+    /// unless the reader is resumed with [`resume_with_decoder`].
+    ///
+    /// [`StatefulDecoder::new_with_position`]: ../../stateful/decode/struct.StatefulDecoder.html#method.new_with_position
+    /// [`resume_with_decoder`]: #method.resume_with_decoder
+    pub fn resume(
+        decoder: S,
+        options: DataSetReaderOptions,
+        state: DataSetReaderState,
+    ) -> Result<Self> {
+        DataSetReader::resume_with_dictionary(decoder, StandardDataDictionary, options, state)
+    }
+}
+
+impl<S, D> DataSetReader<S, D>
+where
+    S: StatefulDecode,
+{
+    /// Create a new iterator which continues reading a data set
+    /// from a previously captured state,
+    /// using the given data dictionary.
+    ///
+    /// See [`resume`](#method.resume) for the requirements on the decoder.
+    pub fn resume_with_dictionary(
+        decoder: S,
+        dict: D,
+        options: DataSetReaderOptions,
+        state: DataSetReaderState,
+    ) -> Result<Self> {
+        let bytes_read = decoder.bytes_read();
+        ensure!(
+            bytes_read == state.position,
+            InconsistentResumePosition {
+                position: state.position,
+                bytes_read,
+            }
+        );
+
+        let mut reader = DataSetReader {
+            parser: decoder,
+            dict,
+            options,
+            seq_delimiters: state.delimiters.into_iter().map(SeqToken::from).collect(),
+            delimiter_check_pending: state.delimiter_check_pending,
+            in_sequence: state.in_sequence,
+            hard_break: false,
+            last_header: state.pending_header,
+            raw_value_length: None,
+            pending_value: state.pending_value,
+            private_creators: state.private_creators,
+            unknown_vr_hook: None,
+            raw_vr_hook: None,
+            path: state.path,
+            byte_limit: None,
+            last_end_delimited: false,
+        };
+        let implicit_vr = reader.seq_delimiters.last().map(|token| token.implicit_vr);
+        reader.parser.set_implicit_vr(implicit_vr.unwrap_or(false));
+        if !state.character_set.is_empty() {
+            reader
+                .parser
+                .declare_character_set(&state.character_set)
+                .context(ResumeCharacterSet)?;
+        }
+        Ok(reader)
+    }
+
+    /// Continue reading from another decoder and a previously captured state,
+    /// keeping the options, the dictionary, the callbacks
+    /// and the byte limit of this reader.
+    ///
+    /// This is the same as [`resume_with_dictionary`],
+    /// for when the reader which captured the state is still around,
+    /// such as when its source only held part of the data set.
+    ///
+    /// [`resume_with_dictionary`]: #method.resume_with_dictionary
+    pub fn resume_with_decoder<T>(
+        self,
+        decoder: T,
+        state: DataSetReaderState,
+    ) -> Result<DataSetReader<T, D>>
+    where
+        T: StatefulDecode,
+    {
+        let mut reader =
+            DataSetReader::resume_with_dictionary(decoder, self.dict, self.options, state)?;
+        reader.unknown_vr_hook = self.unknown_vr_hook;
+        reader.raw_vr_hook = self.raw_vr_hook;
+        reader.byte_limit = self.byte_limit;
+        Ok(reader)
+    }
+
+    /// Capture the current decoding state of the reader.
+    ///
+    /// Along with the bytes of the data set after `position`,
+    /// the state can be used to resume reading at a later time,
+    /// even after the original source is gone.
+    /// This is useful for parsing data sets which arrive in chunks.
+    /// Since bytes are consumed from the source
+    /// even when a token cannot be read in full,
+    /// a state should be captured before each call to `next`,
+    /// so that the last one can be used
+    /// if the reader fails due to a lack of data.
+    pub fn state(&self) -> DataSetReaderState {
+        DataSetReaderState {
+            position: self.parser.bytes_read(),
+            delimiters: self
+                .seq_delimiters
+                .iter()
+                .copied()
+                .map(DelimiterState::from)
+                .collect(),
+            in_sequence: self.in_sequence,
+            delimiter_check_pending: self.delimiter_check_pending,
+            pending_header: self.last_header,
+            pending_value: self.pending_value.clone(),
+            private_creators: self.private_creators.clone(),
+            path: self.path.clone(),
+            character_set: self.parser.character_set().to_vec(),
         }
     }
 }
 
+impl<Dec, BD, Src, T, TC, D> DataSetReader<StatefulDecoder<Dec, BD, Src, TC>, D>
+where
+    Src: std::ops::DerefMut<Target = T>,
+    T: ?Sized + Seek,
+{
+    /// Start reading the data set again from the beginning,
+    /// keeping the options, the dictionary and the callbacks of the reader.
+    ///
+    /// The source is moved back to where the decoder began reading
+    /// (see [`StatefulDecoder::rewind`])
+    /// and the decoding state is reset,
+    /// so that the same tokens are read again.
+    /// This allows for several passes over a seekable source,
+    /// such as indexing an object before extracting some of its values.
+    ///
+    /// [`StatefulDecoder::rewind`]: ../../stateful/decode/struct.StatefulDecoder.html#method.rewind
+    pub fn restart(&mut self) -> Result<()> {
+        self.parser.rewind().context(Restart)?;
+        self.in_sequence = false;
+        self.delimiter_check_pending = false;
+        self.seq_delimiters.clear();
+        self.hard_break = false;
+        self.last_header = None;
+        self.raw_value_length = None;
+        self.pending_value = None;
+        self.private_creators.clear();
+        self.path.clear();
+        self.last_end_delimited = false;
+        Ok(())
+    }
+}
+
+impl<S, D> DataSetReader<S, D> {
+    /// Retrieve the tags of the sequences which are currently open,
+    /// from the root data set to the current nesting level.
+    ///
+    /// This is updated as sequence start and end tokens are yielded.
+    /// Items do not add to the path,
+    /// and encapsulated pixel data counts as a sequence
+    /// with the tag _Pixel Data_ `(7FE0,0010)`.
+    pub fn current_path(&self) -> &[Tag] {
+        &self.path
+    }
+
+    /// Register a callback for resolving the value representation
+    /// of elements which would otherwise be read with the VR `UN`.
+    ///
+    /// The callback receives the element's tag
+    /// and the private creator identifier of its block,
+    /// if it is a private data element
+    /// and its private creator element was found in the same data set.
+    /// When it returns a VR,
+    /// the element header is emitted with that VR
+    /// and the value is read accordingly.
+    /// It is only called for elements decoded in implicit VR,
+    /// since a `UN` in explicit VR was written as such.
+    pub fn on_unknown_vr<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(Tag, Option<&str>) -> Option<VR> + 'static,
+    {
+        self.unknown_vr_hook = Some(UnknownVrHook(Box::new(hook)));
+        self
+    }
+
+    /// Register a callback for observing the value representation
+    /// of each element header exactly as read from the source,
+    /// before it is interpreted as a [`VR`].
+    ///
+    /// The callback receives the element's tag and the two VR bytes,
+    /// which may be a code unknown to this library
+    /// (such elements are otherwise read with the VR `UN`).
+    /// It is only called for data sets encoded with explicit VR,
+    /// and not for item or sequence delimiters.
+    /// Reading is not affected in any way.
+    ///
+    /// [`VR`]: ../../../dicom_core/header/enum.VR.html
+    pub fn on_raw_vr<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(Tag, [u8; 2]) + 'static,
+    {
+        self.raw_vr_hook = Some(RawVrHook(Box::new(hook)));
+        self
+    }
+
+    /// Limit the total number of bytes which the reader may consume
+    /// from its source, counted from the start of the decoder.
+    ///
+    /// Once reading the next token would go beyond the limit,
+    /// the reader yields an error and stops.
+    /// Values with a declared length which would exceed the limit
+    /// are rejected before being read into memory,
+    /// which guards against objects declaring huge lengths.
+    pub fn with_byte_limit(mut self, max: u64) -> Self {
+        self.byte_limit = Some(max);
+        self
+    }
+}
+
 impl<S, D> Iterator for DataSetReader<S, D>
 where
     S: StatefulDecode,
@@ -241,6 +900,199 @@ where
         if self.hard_break {
             return None;
         }
+        let upcoming = self.upcoming_value_len();
+        if let Err(e) = self.check_byte_limit(upcoming) {
+            self.hard_break = true;
+            return Some(Err(e));
+        }
+
+        let token = self.read_token();
+        if let Some(Ok(_)) = token {
+            if let Err(e) = self.check_byte_limit(0) {
+                self.hard_break = true;
+                return Some(Err(e));
+            }
+        }
+        match token {
+            Some(Ok(DataToken::SequenceStart { tag, .. })) => self.path.push(tag),
+            Some(Ok(DataToken::PixelSequenceStart)) => self.path.push(Tag(0x7FE0, 0x0010)),
+            Some(Ok(DataToken::SequenceEnd)) => {
+                self.path.pop();
+            }
+            _ => {}
+        }
+        token
+    }
+}
+
+impl<S, D> DataSetReader<S, D>
+where
+    S: StatefulDecode,
+    D: DataDictionary,
+{
+    /// Obtain an iterator over the headers of the remaining elements
+    /// at the root of the data set, without their values.
+    ///
+    /// See [`Headers`] for the details.
+    ///
+    /// [`Headers`]: ./struct.Headers.html
+    pub fn headers(&mut self) -> Headers<'_, S, D> {
+        Headers { reader: self }
+    }
+
+    /// Obtain an iterator over the remaining tokens of the data set,
+    /// each paired with whether it was read from a delimiter.
+    ///
+    /// See [`DelimitedTokens`] for the details.
+    ///
+    /// [`DelimitedTokens`]: ./struct.DelimitedTokens.html
+    pub fn delimited_tokens(&mut self) -> DelimitedTokens<'_, S, D> {
+        DelimitedTokens { reader: self }
+    }
+
+    /// Read the rest of the data set,
+    /// collecting its elements into an index by tag.
+    ///
+    /// Sequences are assembled into elements holding their items,
+    /// so only the elements of the root data set are in the index.
+    /// When the data set has more than one element with the same tag,
+    /// the last one is kept.
+    /// This fails if a value is not read,
+    /// as happens with the [`max_eager_value_len`] option.
+    ///
+    /// [`max_eager_value_len`]: ./struct.DataSetReaderOptions.html#structfield.max_eager_value_len
+    pub fn into_index(mut self) -> Result<TagIndex> {
+        build_index(&mut self, false, Length::UNDEFINED)
+    }
+
+    /// Read the header of the next element at the root of the data set,
+    /// skipping over its value.
+    fn next_header(&mut self) -> Option<Result<DataElementHeader>> {
+        // finish reading the element currently open, if any
+        while !self.at_root() {
+            match self.next()? {
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        if self.hard_break {
+            return None;
+        }
+
+        let (header, explicit_vr) = match self.decode_element_header() {
+            Ok(header) => header,
+            Err(DecoderError::DecodeElementHeader {
+                source: dicom_encoding::decode::Error::ReadHeaderTag { source, .. },
+                ..
+            }) if source.kind() == std::io::ErrorKind::UnexpectedEof => {
+                // end of the data set, as in `read_token`
+                self.hard_break = true;
+                return None;
+            }
+            Err(e) => {
+                self.hard_break = true;
+                return Some(Err(e).context(ReadHeader));
+            }
+        };
+
+        if header.is_encapsulated_pixeldata() {
+            // the fragments are read through the usual token logic
+            self.path.push(header.tag);
+            self.last_header = Some(header);
+            return Some(Ok(header));
+        }
+        if let Some(len) = header.len.get() {
+            // no need to look into the value, sequences included
+            let mut header = header;
+            if header.vr == VR::UN && !explicit_vr {
+                if let Some(vr) = self.resolve_unknown_vr(header.tag) {
+                    header.vr = vr;
+                }
+            }
+            if let Err(e) = self.check_byte_limit(u64::from(len)) {
+                self.hard_break = true;
+                return Some(Err(e));
+            }
+            if let Err(e) = self.parser.skip_bytes(len) {
+                self.hard_break = true;
+                return Some(Err(e).context(ReadValue));
+            }
+            return Some(Ok(header));
+        }
+        if self.options.coalesce_fragments && (header.vr == VR::OB || header.vr == VR::OW) {
+            return Some(match self.read_fragments(header.tag) {
+                Ok(data) => Ok(DataElementHeader::new(
+                    header.tag,
+                    header.vr,
+                    Length(data.len() as u32),
+                )),
+                Err(e) => {
+                    self.hard_break = true;
+                    Err(e)
+                }
+            });
+        }
+
+        // a sequence of undefined length,
+        // which needs to be read in order to find its end
+        self.path.push(header.tag);
+        self.open_undefined_length_sequence(&header);
+        Some(Ok(header))
+    }
+
+    /// Check that the bytes read so far plus the given number of bytes
+    /// do not exceed the byte limit, if any.
+    fn check_byte_limit(&self, upcoming: u64) -> Result<()> {
+        if let Some(limit) = self.byte_limit {
+            let bytes_read = self.parser.bytes_read().saturating_add(upcoming);
+            ensure!(bytes_read <= limit, ByteLimitExceeded { limit, bytes_read });
+        }
+        Ok(())
+    }
+
+    /// The length of the value which the next call to `read_token`
+    /// is about to read, or 0 if unknown.
+    fn upcoming_value_len(&self) -> u64 {
+        if self.pending_value.is_some() || self.delimiter_check_pending {
+            return 0;
+        }
+        match (self.last_header, self.seq_delimiters.last()) {
+            (Some(header), _) if !header.is_encapsulated_pixeldata() => {
+                u64::from(header.len.get().unwrap_or(0))
+            }
+            (
+                None,
+                Some(SeqToken {
+                    typ: SeqTokenType::Item,
+                    pixel_data: true,
+                    len,
+                    ..
+                }),
+            ) if !self.in_sequence => u64::from(len.get().unwrap_or(0)),
+            _ => 0,
+        }
+    }
+
+    /// Whether the reader is positioned between two elements
+    /// of the root data set.
+    fn at_root(&self) -> bool {
+        self.seq_delimiters.is_empty()
+            && !self.in_sequence
+            && self.last_header.is_none()
+            && self.pending_value.is_none()
+    }
+
+    /// Read the next token from the source.
+    fn read_token(&mut self) -> Option<Result<DataToken>> {
+        if self.hard_break {
+            return None;
+        }
+
+        if let Some(value) = self.pending_value.take() {
+            // sequences can end after this token
+            self.delimiter_check_pending = true;
+            return Some(Ok(DataToken::PrimitiveValue(value)));
+        }
 
         // item or sequence delimitation logic for explicit lengths
         if self.delimiter_check_pending {
@@ -249,7 +1101,10 @@ where
                     self.hard_break = true;
                     return Some(Err(e));
                 }
-                Ok(Some(token)) => return Some(Ok(token)),
+                Ok(Some(token)) => {
+                    self.last_end_delimited = false;
+                    return Some(Ok(token));
+                }
                 Ok(None) => { /* no-op */ }
             }
         }
@@ -269,6 +1124,9 @@ where
                                 self.seq_delimiters.last()
                                     .expect("item header should be read only inside an existing sequence")
                                     .pixel_data);
+                            // private creators do not carry over to other items
+                            let depth = self.seq_delimiters.len();
+                            self.private_creators.retain(|&(d, _, _), _| d < depth);
                             // items can be empty
                             if len == Length(0) {
                                 self.delimiter_check_pending = true;
@@ -277,14 +1135,17 @@ where
                         }
                         SequenceItemHeader::ItemDelimiter => {
                             // closed an item
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = true;
+                            self.delimiter_check_pending = true;
+                            self.last_end_delimited = true;
                             Some(Ok(DataToken::ItemEnd))
                         }
                         SequenceItemHeader::SequenceDelimiter => {
                             // closed a sequence
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = false;
+                            self.last_end_delimited = true;
                             Some(Ok(DataToken::SequenceEnd))
                         }
                     }
@@ -334,8 +1195,9 @@ where
                         }
                         SequenceItemHeader::SequenceDelimiter => {
                             // empty pixel data
-                            self.seq_delimiters.pop();
+                            self.pop_sequence_token();
                             self.in_sequence = false;
+                            self.last_end_delimited = true;
                             Some(Ok(DataToken::SequenceEnd))
                         }
                         item => {
@@ -348,9 +1210,39 @@ where
                         Some(Err(e).context(ReadItemHeader))
                     }
                 }
-            } else {
-                // a plain element header was read, so a value is expected
-                let value = match self.read_value(&header) {
+            } else if self.is_lazy_value(&header) {
+                // a value too long to be read eagerly
+                self.last_header = None;
+                let marker = DicomElementMarker {
+                    header,
+                    pos: self.parser.bytes_read(),
+                };
+                if let Err(e) = self.parser.skip_bytes(header.len.0) {
+                    self.hard_break = true;
+                    return Some(Err(e).context(ReadValue));
+                }
+
+                // sequences can end after this token
+                self.delimiter_check_pending = true;
+
+                Some(Ok(DataToken::LazyValue(marker)))
+            } else if self.is_preserved_value(&header) {
+                // a value which cannot be cleanly interpreted,
+                // kept as it was encoded
+                self.last_header = None;
+                let mut value = vec![0; header.len.0 as usize];
+                if let Err(e) = self.parser.read_bytes(&mut value[..]) {
+                    self.hard_break = true;
+                    return Some(Err(e).context(ReadValue));
+                }
+
+                // sequences can end after this token
+                self.delimiter_check_pending = true;
+
+                Some(Ok(DataToken::ItemValue(value)))
+            } else {
+                // a plain element header was read, so a value is expected
+                let value = match self.read_value(&header) {
                     Ok(v) => v,
                     Err(e) => {
                         self.hard_break = true;
@@ -360,6 +1252,7 @@ where
                 };
 
                 self.last_header = None;
+                self.register_private_creator(&header, &value);
 
                 // sequences can end after this token
                 self.delimiter_check_pending = true;
@@ -368,12 +1261,15 @@ where
             }
         } else {
             // a data element header or item delimiter is expected
-            match self.parser.decode_header() {
-                Ok(DataElementHeader {
-                    tag,
-                    vr: VR::SQ,
-                    len,
-                }) => {
+            match self.decode_element_header() {
+                Ok((
+                    DataElementHeader {
+                        tag,
+                        vr: VR::SQ,
+                        len,
+                    },
+                    _,
+                )) => {
                     self.in_sequence = true;
                     self.push_sequence_token(SeqTokenType::Sequence, len, false);
 
@@ -384,14 +1280,23 @@ where
 
                     Some(Ok(DataToken::SequenceStart { tag, len }))
                 }
-                Ok(DataElementHeader {
-                    tag: Tag(0xFFFE, 0xE00D),
-                    ..
-                }) => {
+                Ok((
+                    DataElementHeader {
+                        tag: Tag(0xFFFE, 0xE00D),
+                        ..
+                    },
+                    _,
+                )) => {
+                    // closed an item
+                    self.pop_sequence_token();
                     self.in_sequence = true;
+                    // the enclosing sequence may have an explicit length
+                    // which ends right after this delimiter
+                    self.delimiter_check_pending = true;
+                    self.last_end_delimited = true;
                     Some(Ok(DataToken::ItemEnd))
                 }
-                Ok(header) if header.is_encapsulated_pixeldata() => {
+                Ok((header, _)) if header.is_encapsulated_pixeldata() => {
                     // encapsulated pixel data conditions:
                     // expect a sequence of pixel data fragments
 
@@ -399,18 +1304,45 @@ where
                     self.last_header = Some(header);
                     Some(Ok(DataToken::PixelSequenceStart))
                 }
-                Ok(header) if header.len.is_undefined() => {
+                Ok((header, _))
+                    if self.options.coalesce_fragments
+                        && header.len.is_undefined()
+                        && (header.vr == VR::OB || header.vr == VR::OW) =>
+                {
+                    // fragmented primitive value,
+                    // read all fragments ahead of time
+                    match self.read_fragments(header.tag) {
+                        Ok(data) => {
+                            let header = DataElementHeader::new(
+                                header.tag,
+                                header.vr,
+                                Length(data.len() as u32),
+                            );
+                            self.pending_value = Some(PrimitiveValue::from(data));
+                            Some(Ok(DataToken::ElementHeader(header)))
+                        }
+                        Err(e) => {
+                            self.hard_break = true;
+                            Some(Err(e))
+                        }
+                    }
+                }
+                Ok((header, _)) if header.len.is_undefined() => {
                     // treat other undefined length elements
                     // as data set sequences,
                     // discarding the VR in the process
-                    self.in_sequence = true;
-
-                    let DataElementHeader { tag, len, .. } = header;
-                    self.push_sequence_token(SeqTokenType::Sequence, len, false);
-
-                    Some(Ok(DataToken::SequenceStart { tag, len }))
+                    self.open_undefined_length_sequence(&header);
+                    Some(Ok(DataToken::SequenceStart {
+                        tag: header.tag,
+                        len: header.len,
+                    }))
                 }
-                Ok(header) => {
+                Ok((mut header, explicit_vr)) => {
+                    if header.vr == VR::UN && !explicit_vr {
+                        if let Some(vr) = self.resolve_unknown_vr(header.tag) {
+                            header.vr = vr;
+                        }
+                    }
                     // save it for the next step
                     self.last_header = Some(header);
                     Some(Ok(DataToken::ElementHeader(header)))
@@ -436,10 +1368,351 @@ where
     }
 }
 
+/// Assemble the elements of a data set from the given tokens
+/// until the end of its item, if `in_item` is set,
+/// or until the end of the tokens otherwise.
+fn build_index<I>(tokens: &mut I, in_item: bool, len: Length) -> Result<TagIndex>
+where
+    I: Iterator<Item = Result<DataToken>>,
+{
+    let mut index = TagIndex::with_length(len);
+    while let Some(token) = tokens.next() {
+        let element = match token? {
+            DataToken::ElementHeader(header) => match tokens.next().context(PrematureEnd)?? {
+                DataToken::PrimitiveValue(value) => {
+                    DataElement::new(header.tag, header.vr, Value::Primitive(value))
+                }
+                // a value preserved as raw bytes
+                DataToken::ItemValue(data) => DataElement::new(
+                    header.tag,
+                    header.vr,
+                    Value::Primitive(PrimitiveValue::U8(data.into())),
+                ),
+                token => return UnexpectedToken { token }.fail(),
+            },
+            DataToken::SequenceStart { tag, len } => {
+                let items = build_items(tokens)?;
+                DataElement::new(tag, VR::SQ, Value::Sequence { items, size: len })
+            }
+            DataToken::PixelSequenceStart => {
+                DataElement::new(Tag(0x7FE0, 0x0010), VR::OB, build_fragments(tokens)?)
+            }
+            DataToken::ItemEnd if in_item => return Ok(index),
+            token => return UnexpectedToken { token }.fail(),
+        };
+        index.insert(element);
+    }
+
+    ensure!(!in_item, PrematureEnd);
+    Ok(index)
+}
+
+/// Assemble the items of a sequence from the given tokens,
+/// up to the end of the sequence.
+fn build_items<I>(tokens: &mut I) -> Result<C<TagIndex>>
+where
+    I: Iterator<Item = Result<DataToken>>,
+{
+    let mut items = C::new();
+    while let Some(token) = tokens.next() {
+        match token? {
+            DataToken::ItemStart { len } => items.push(build_index(tokens, true, len)?),
+            DataToken::SequenceEnd => return Ok(items),
+            token => return UnexpectedToken { token }.fail(),
+        }
+    }
+    PrematureEnd.fail()
+}
+
+/// Assemble the offset table and fragments of encapsulated pixel data
+/// from the given tokens, up to the end of the pixel sequence.
+fn build_fragments<I>(tokens: &mut I) -> Result<Value<TagIndex, Vec<u8>>>
+where
+    I: Iterator<Item = Result<DataToken>>,
+{
+    let mut offset_table = None;
+    let mut fragments = C::new();
+    // whether the current item had a value token,
+    // empty items have none
+    let mut item_has_value = false;
+    for token in tokens {
+        match token? {
+            DataToken::ItemStart { .. } => item_has_value = false,
+            DataToken::ItemValue(data) => {
+                item_has_value = true;
+                if offset_table.is_none() {
+                    offset_table = Some(data.into());
+                } else {
+                    fragments.push(data);
+                }
+            }
+            DataToken::ItemEnd => {
+                // the first item is always the offset table, even if empty
+                if offset_table.is_none() {
+                    offset_table = Some(C::new());
+                } else if !item_has_value {
+                    fragments.push(Vec::new());
+                }
+            }
+            DataToken::SequenceEnd => {
+                return Ok(Value::PixelSequence {
+                    offset_table: offset_table.unwrap_or_default(),
+                    fragments,
+                })
+            }
+            token => return UnexpectedToken { token }.fail(),
+        }
+    }
+    PrematureEnd.fail()
+}
+
+/// An iterator over the headers of the elements
+/// at the root of a data set, obtained with [`DataSetReader::headers`].
+///
+/// Each item is the header of an element
+/// as it was encoded in the source,
+/// sequences and encapsulated pixel data included.
+/// Values and sequences with an explicit length
+/// are skipped over without being decoded.
+/// Sequences of undefined length and encapsulated pixel data
+/// are still read token by token,
+/// since that is the only way to find where they end,
+/// but their contents are discarded.
+/// As values are not read,
+/// private creators and character sets found along the way
+/// are not taken into account.
+///
+/// [`DataSetReader::headers`]: ./struct.DataSetReader.html#method.headers
+#[derive(Debug)]
+pub struct Headers<'a, S, D> {
+    reader: &'a mut DataSetReader<S, D>,
+}
+
+impl<'a, S, D> Iterator for Headers<'a, S, D>
+where
+    S: StatefulDecode,
+    D: DataDictionary,
+{
+    type Item = Result<DataElementHeader>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.next_header()
+    }
+}
+
+/// An iterator over the tokens of a data set,
+/// each paired with whether it was read from a delimiter,
+/// obtained with [`DataSetReader::delimited_tokens`].
+///
+/// The flag is set on an [`ItemEnd`] or [`SequenceEnd`] token
+/// read from an actual delimiter in the source
+/// (`(FFFE,E00D)` or `(FFFE,E0DD)`),
+/// as is the case for items and sequences of undefined length.
+/// It is not set on an end inferred
+/// from the defined length of the item or sequence,
+/// including pixel data fragments,
+/// nor on any other token.
+/// This tells a writer which ends to reproduce as delimiters.
+///
+/// [`DataSetReader::delimited_tokens`]: ./struct.DataSetReader.html#method.delimited_tokens
+/// [`ItemEnd`]: ../enum.DataToken.html#variant.ItemEnd
+/// [`SequenceEnd`]: ../enum.DataToken.html#variant.SequenceEnd
+#[derive(Debug)]
+pub struct DelimitedTokens<'a, S, D> {
+    reader: &'a mut DataSetReader<S, D>,
+}
+
+impl<'a, S, D> Iterator for DelimitedTokens<'a, S, D>
+where
+    S: StatefulDecode,
+    D: DataDictionary,
+{
+    type Item = Result<(DataToken, bool)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.reader.next()?;
+        Some(token.map(|token| {
+            let delimited = matches!(token, DataToken::ItemEnd | DataToken::SequenceEnd)
+                && self.reader.last_end_delimited;
+            (token, delimited)
+        }))
+    }
+}
+
+/// A top-level data element read from an in-memory source,
+/// along with the exact bytes it was decoded from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawElement<'a> {
+    /// the tokens of the element,
+    /// from its header or sequence start to its value or sequence end
+    pub tokens: Vec<DataToken>,
+    /// the untouched encoded bytes of the element, header included
+    pub bytes: &'a [u8],
+}
+
+impl<'a, 'r, Dec, BD, TC, D> DataSetReader<StatefulDecoder<Dec, BD, &'r mut &'a [u8], TC>, D>
+where
+    StatefulDecoder<Dec, BD, &'r mut &'a [u8], TC>: StatefulDecode,
+    D: DataDictionary,
+{
+    /// Retrieve the next token,
+    /// borrowing the values of textual data elements
+    /// directly from the in-memory source.
+    ///
+    /// This works like `next`,
+    /// except that the value of an element with a textual
+    /// value representation and a defined length
+    /// is yielded as a [`DataTokenRef::PrimitiveValue`]
+    /// containing the raw bytes of the value,
+    /// not decoded in any way.
+    /// All other tokens are yielded as [`DataTokenRef::Owned`].
+    /// The _Specific Character Set_ element is always read
+    /// the usual way, so that the decoder can keep track of it,
+    /// and so are values kept raw or lazy by the reader options.
+    /// The byte limit applies all the same.
+    ///
+    /// [`DataTokenRef::PrimitiveValue`]: ../enum.DataTokenRef.html#variant.PrimitiveValue
+    /// [`DataTokenRef::Owned`]: ../enum.DataTokenRef.html#variant.Owned
+    pub fn next_borrowed(&mut self) -> Option<Result<DataTokenRef<'a>>> {
+        match self.last_header {
+            Some(header)
+                if !self.hard_break
+                    && !self.in_sequence
+                    && !self.delimiter_check_pending
+                    && header.tag != Tag(0x0008, 0x0005)
+                    && is_textual_vr(header.vr)
+                    && header.len.is_defined()
+                    && !self.is_lazy_value(&header)
+                    && !self.is_preserved_value(&header) =>
+            {
+                if let Err(e) = self.check_byte_limit(u64::from(header.len.0)) {
+                    self.hard_break = true;
+                    return Some(Err(e));
+                }
+                self.last_header = None;
+                let len = header.len.0 as usize;
+                match self.parser.read_slice(len) {
+                    Ok(data) => {
+                        if is_private_creator(header.tag) {
+                            self.register_private_creator(&header, &PrimitiveValue::from(data));
+                        }
+                        // sequences can end after this token
+                        self.delimiter_check_pending = true;
+                        Some(Ok(DataTokenRef::PrimitiveValue(data)))
+                    }
+                    Err(e) => {
+                        self.hard_break = true;
+                        Some(Err(e).context(ReadValue))
+                    }
+                }
+            }
+            _ => self.next().map(|res| res.map(DataTokenRef::Owned)),
+        }
+    }
+
+    /// Retrieve the next data element in full,
+    /// along with the original bytes it was decoded from.
+    ///
+    /// All tokens up to the end of the element are read,
+    /// including those of nested data sets,
+    /// and the bytes consumed in the process are handed over
+    /// without being re-encoded.
+    /// This is useful for computing digital signatures and message
+    /// authentication codes over the element as it was encoded.
+    ///
+    /// This method is meant to be called at the root of the data set
+    /// or at the root of an item.
+    pub fn next_raw_element(&mut self) -> Option<Result<RawElement<'a>>> {
+        let start = self.parser.remaining();
+        let mut tokens = Vec::new();
+        let mut depth = 0_u32;
+        loop {
+            let token = match self.next() {
+                Some(Ok(token)) => token,
+                Some(Err(e)) => return Some(Err(e)),
+                None if tokens.is_empty() => return None,
+                None => break,
+            };
+            let done = match token {
+                DataToken::SequenceStart { .. }
+                | DataToken::PixelSequenceStart
+                | DataToken::ItemStart { .. } => {
+                    depth += 1;
+                    false
+                }
+                DataToken::SequenceEnd | DataToken::ItemEnd => {
+                    depth = depth.saturating_sub(1);
+                    depth == 0
+                }
+                DataToken::ElementHeader(_) => false,
+                DataToken::PrimitiveValue(_)
+                | DataToken::ItemValue(_)
+                | DataToken::LazyValue(_) => depth == 0,
+            };
+            tokens.push(token);
+            if done {
+                break;
+            }
+        }
+        let consumed = start.len() - self.parser.remaining().len();
+        Some(Ok(RawElement {
+            tokens,
+            bytes: &start[..consumed],
+        }))
+    }
+}
+
+/// Check whether values of the given VR are represented as text.
+fn is_textual_vr(vr: VR) -> bool {
+    matches!(
+        vr,
+        VR::AE
+            | VR::AS
+            | VR::CS
+            | VR::DA
+            | VR::DS
+            | VR::DT
+            | VR::IS
+            | VR::LO
+            | VR::LT
+            | VR::PN
+            | VR::SH
+            | VR::ST
+            | VR::TM
+            | VR::UC
+            | VR::UI
+            | VR::UR
+            | VR::UT
+    )
+}
+
 impl<S, D> DataSetReader<S, D>
 where
     S: StatefulDecode,
+    D: DataDictionary,
 {
+    /// Decode the next data element header or delimiter,
+    /// passing the VR bytes read to the raw VR callback, if any.
+    ///
+    /// Also returns whether the VR of the header was read from the source,
+    /// as in explicit VR,
+    /// rather than inferred by the decoder.
+    fn decode_element_header(
+        &mut self,
+    ) -> std::result::Result<(DataElementHeader, bool), DecoderError> {
+        if self.in_implicit_vr() {
+            return self
+                .parser
+                .decode_header_implicit_vr()
+                .map(|header| (header, false));
+        }
+        let (header, raw_vr) = self.parser.decode_header_with_raw_vr()?;
+        if let (Some(hook), Some(raw_vr)) = (self.raw_vr_hook.as_mut(), raw_vr) {
+            (hook.0)(header.tag, raw_vr);
+        }
+        Ok((header, raw_vr.is_some()))
+    }
+
     fn update_seq_delimiters(&mut self) -> Result<Option<DataToken>> {
         if let Some(sd) = self.seq_delimiters.last() {
             if let Some(len) = sd.len.get() {
@@ -459,7 +1732,7 @@ where
                                 token = DataToken::ItemEnd;
                             }
                         }
-                        self.seq_delimiters.pop();
+                        self.pop_sequence_token();
                         return Ok(Some(token));
                     }
                     Ordering::Less => {
@@ -477,16 +1750,113 @@ where
         Ok(None)
     }
 
+    /// Keep track of the given element if it is a private creator.
+    fn register_private_creator(&mut self, header: &DataElementHeader, value: &PrimitiveValue) {
+        if is_private_creator(header.tag) {
+            let Tag(group, element) = header.tag;
+            self.private_creators.insert(
+                (self.seq_delimiters.len(), group, element as u8),
+                private_creator_of(value),
+            );
+        }
+    }
+
+    /// Resolve the VR of an element which would otherwise be read as `UN`,
+    /// by consulting the unknown VR callback, if any,
+    /// then the data dictionary,
+    /// and then the unknown VR policy if the element is not in the dictionary.
+    ///
+    /// Sequences in the dictionary are not resolved,
+    /// since their items would be encoded in implicit VR.
+    /// This only applies to headers decoded in implicit VR:
+    /// a `UN` read from the source is kept as is.
+    fn resolve_unknown_vr(&mut self, tag: Tag) -> Option<VR> {
+        if let Some(hook) = self.unknown_vr_hook.as_mut() {
+            let Tag(group, element) = tag;
+            let creator = if group % 2 == 1 && element >= 0x1000 {
+                self.private_creators
+                    .get(&(self.seq_delimiters.len(), group, (element >> 8) as u8))
+                    .map(String::as_str)
+            } else {
+                None
+            };
+            if let Some(vr) = (hook.0)(tag, creator) {
+                return Some(vr);
+            }
+        }
+        match self.dict.by_tag(tag).map(|e| e.vr()) {
+            Some(VR::UN) | Some(VR::SQ) => None,
+            None => self.options.unknown_vr_policy.vr_for(tag),
+            vr => vr,
+        }
+    }
+
+    /// Read and concatenate all item fragments of a value
+    /// until the sequence delimiter.
+    fn read_fragments(&mut self, tag: Tag) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        loop {
+            match self.parser.decode_item_header().context(ReadItemHeader)? {
+                SequenceItemHeader::Item { len } => {
+                    let len = len.get().context(UndefinedFragmentLength { tag })? as usize;
+                    let start = data.len();
+                    data.resize(start + len, 0);
+                    self.parser
+                        .read_bytes(&mut data[start..])
+                        .context(ReadValue)?;
+                }
+                SequenceItemHeader::SequenceDelimiter => return Ok(data),
+                header @ SequenceItemHeader::ItemDelimiter => {
+                    return UnexpectedTag { tag: header.tag() }.fail();
+                }
+            }
+        }
+    }
+
     #[inline]
     fn push_sequence_token(&mut self, typ: SeqTokenType, len: Length, pixel_data: bool) {
         self.seq_delimiters.push(SeqToken {
             typ,
             pixel_data,
+            implicit_vr: self.in_implicit_vr(),
             len,
             base_offset: self.parser.bytes_read(),
         })
     }
 
+    /// Enter the sequence of undefined length
+    /// started by the given element header.
+    fn open_undefined_length_sequence(&mut self, header: &DataElementHeader) {
+        self.in_sequence = true;
+        self.push_sequence_token(SeqTokenType::Sequence, header.len, false);
+        if header.vr == VR::UN {
+            // the items of a UN sequence are in implicit VR,
+            // whatever the transfer syntax says
+            if let Some(token) = self.seq_delimiters.last_mut() {
+                token.implicit_vr = true;
+            }
+            self.parser.set_implicit_vr(true);
+        }
+    }
+
+    /// Leave the innermost sequence or item,
+    /// decoding what follows in the encoding of the enclosing content.
+    #[inline]
+    fn pop_sequence_token(&mut self) {
+        self.seq_delimiters.pop();
+        self.parser.set_implicit_vr(self.in_implicit_vr());
+    }
+
+    /// Check whether the elements to be read next
+    /// are encoded in implicit VR little endian
+    /// because they are within a `UN` element of undefined length.
+    fn in_implicit_vr(&self) -> bool {
+        self.seq_delimiters
+            .last()
+            .map(|token| token.implicit_vr)
+            .unwrap_or(false)
+    }
+
     fn read_value(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
         match self.options.value_read {
             ValueReadStrategy::Interpreted => self.parser.read_value(header),
@@ -495,6 +1865,22 @@ where
         }
         .context(ReadValue)
     }
+
+    /// Check whether the value of the element with the given header
+    /// should be skipped instead of being read eagerly.
+    fn is_lazy_value(&self, header: &DataElementHeader) -> bool {
+        match (self.options.max_eager_value_len, header.len.get()) {
+            (Some(max), Some(len)) => len > max,
+            _ => false,
+        }
+    }
+
+    /// Check whether the value of the element with the given header
+    /// should be kept as raw bytes instead of being interpreted.
+    fn is_preserved_value(&self, header: &DataElementHeader) -> bool {
+        self.options.preserve_unknown
+            && (header.vr == VR::UN || matches!(header.len.get(), Some(len) if len & 1 == 1))
+    }
 }
 
 /// An iterator for retrieving DICOM object element markers from a random
@@ -623,13 +2009,21 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{DataSetReader, DataToken, StatefulDecode, StatefulDecoder};
+    use super::{
+        DataSetReader, DataSetReaderOptions, DataToken, DataTokenRef, DicomElementMarker, Error,
+        StatefulDecode, StatefulDecoder, UnknownVrPolicy,
+    };
+    use crate::dataset::DataSetWriter;
     use dicom_core::header::{DataElementHeader, Length};
     use dicom_core::value::PrimitiveValue;
     use dicom_core::{Tag, VR};
-    use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
+    use dicom_encoding::decode::basic::{BigEndianBasicDecoder, LittleEndianBasicDecoder};
+    use dicom_encoding::decode::explicit_be::ExplicitVRBigEndianDecoder;
+    use dicom_encoding::encode::EncoderFor;
     use dicom_encoding::text::DefaultCharacterSetCodec;
-    use dicom_encoding::transfer_syntax::explicit_le::ExplicitVRLittleEndianDecoder;
+    use dicom_encoding::transfer_syntax::explicit_le::{
+        ExplicitVRLittleEndianDecoder, ExplicitVRLittleEndianEncoder,
+    };
     use dicom_encoding::transfer_syntax::implicit_le::ImplicitVRLittleEndianDecoder;
 
     fn validate_dataset_reader_implicit_vr<I>(data: &[u8], ground_truth: I)
@@ -899,6 +2293,123 @@ mod tests {
         validate_dataset_reader_explicit_vr(DATA, ground_truth);
     }
 
+    #[test]
+    fn read_un_sequence_of_undefined_length_explicit_vr() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x09, 0x00, 0x10, 0x10, // tag: (0009,1010) «private, unknown attribute»
+            b'U', b'N', // VR
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 --
+            0xfe, 0xff, 0x00, 0xe0, // item begin
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 20 --
+            // implicit VR from here on
+            0x08, 0x00, 0x60, 0x00, // (0008,0060) Modality
+            0x02, 0x00, 0x00, 0x00, // length: 2
+            b'M', b'R', // value = "MR"
+            // -- 30 --
+            0xfe, 0xff, 0x0d, 0xe0, // item end
+            0x00, 0x00, 0x00, 0x00, // length is always zero
+            // -- 38 --
+            0xfe, 0xff, 0xdd, 0xe0,
+            0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 46 --
+            // explicit VR again
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x04, 0x00, // (0010,0010) PatientName, len = 4
+            b'D', b'o', b'e', b'^', // value = "Doe^"
+        ];
+
+        let ground_truth = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0009, 0x1010),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0008, 0x0060),
+                vr: VR::CS,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::Strs(["MR".to_owned()].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0010, 0x0010),
+                vr: VR::PN,
+                len: Length(4),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::Strs(["Doe^".to_owned()].as_ref().into())),
+        ];
+
+        validate_dataset_reader_explicit_vr(DATA, ground_truth);
+    }
+
+    #[test]
+    fn read_un_sequence_of_undefined_length_explicit_vr_big_endian() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x00, 0x09, 0x10, 0x10, // tag: (0009,1010) «private, unknown attribute»
+            b'U', b'N', // VR
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 --
+            // implicit VR little endian from here on
+            0xfe, 0xff, 0x00, 0xe0, // item begin
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 20 --
+            0x28, 0x00, 0x10, 0x00, // (0028,0010) Rows
+            0x02, 0x00, 0x00, 0x00, // length: 2
+            0x00, 0x02, // value = 512
+            // -- 30 --
+            0xfe, 0xff, 0x0d, 0xe0, // item end
+            0x00, 0x00, 0x00, 0x00, // length is always zero
+            // -- 38 --
+            0xfe, 0xff, 0xdd, 0xe0,
+            0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 46 --
+            // explicit VR big endian again
+            0x00, 0x10, 0x00, 0x10, b'P', b'N', 0x00, 0x04, // (0010,0010) PatientName, len = 4
+            b'D', b'o', b'e', b'^', // value = "Doe^"
+        ];
+
+        let ground_truth = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0009, 0x1010),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0028, 0x0010),
+                vr: VR::US,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([512].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0010, 0x0010),
+                vr: VR::PN,
+                len: Length(4),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::Strs(["Doe^".to_owned()].as_ref().into())),
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRBigEndianDecoder::default(),
+            BigEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        validate_dataset_reader(DATA, parser, ground_truth);
+    }
+
     #[test]
     fn read_implicit_len_sequence_implicit_vr_unknown() {
         #[rustfmt::skip]
@@ -1032,4 +2543,1536 @@ mod tests {
 
         validate_dataset_reader_explicit_vr(DATA, ground_truth);
     }
+
+    #[test]
+    fn read_private_elements_with_unknown_vr_hook() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x09, 0x00, 0x10, 0x00, // (0009,0010) private creator
+            0x04, 0x00, 0x00, 0x00, // len: 4
+            b'A', b'C', b'M', b'E', // value = "ACME"
+            // -- 12 --
+            0x09, 0x00, 0x01, 0x10, // (0009,1001) private element of block 0x10
+            0x02, 0x00, 0x00, 0x00, // len: 2
+            0x03, 0x00, // value = 3
+            // -- 22 --
+            0x09, 0x00, 0x02, 0x10, // (0009,1002) private element of block 0x10
+            0x02, 0x00, 0x00, 0x00, // len: 2
+            0x04, 0x00, // value = [4, 0]
+        ];
+
+        let ground_truth = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x0010),
+                VR::UN,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U8(b"ACME".as_ref().into())),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1001),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([3].as_ref().into())),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1002),
+                VR::UN,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U8([4, 0].as_ref().into())),
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ImplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>, // trait object
+        );
+
+        let dset_reader =
+            DataSetReader::new(parser, Default::default()).on_unknown_vr(|tag, creator| {
+                match (tag, creator) {
+                    (Tag(0x0009, e), Some("ACME")) if e & 0xFF == 0x01 => Some(VR::US),
+                    _ => None,
+                }
+            });
+
+        let tokens: Vec<_> = dset_reader
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(tokens, ground_truth);
+    }
+
+    #[test]
+    fn classify_item_and_sequence_ends() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x18, 0x00, 0x11, 0x60, b'S', b'Q', 0x00, 0x00, // (0018,6011) SequenceOfUltrasoundRegions
+            0x22, 0x00, 0x00, 0x00, // length: 18 + 16 = 34
+            // -- 12 --
+            0xfe, 0xff, 0x00, 0xe0, 0x0a, 0x00, 0x00, 0x00, // item start, len: 10
+            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x01, 0x00, // (0018,6012), len = 2, value = 1
+            // -- 30 --
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, len: undefined
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            // -- 46 --
+            0x08, 0x00, 0x40, 0x11, b'S', b'Q', 0x00, 0x00, // (0008,1140) ReferencedImageSequence
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 66 --
+            0xe0, 0x7f, 0x10, 0x00, b'O', b'B', 0x00, 0x00, // (7FE0,0010) PixelData
+            0xff, 0xff, 0xff, 0xff, // len: undefined
+            0xfe, 0xff, 0x00, 0xe0, 0x00, 0x00, 0x00, 0x00, // offset table, len = 0
+            0xfe, 0xff, 0x00, 0xe0, 0x02, 0x00, 0x00, 0x00, // fragment, len = 2
+            0x99, 0x99,
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>,
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default());
+        let tokens: Vec<_> = dset_reader
+            .delimited_tokens()
+            .collect::<Result<_, _>>()
+            .expect("should read tokens without errors");
+
+        // only end tokens are flagged
+        assert!(tokens.iter().all(|(token, delimited)| !delimited
+            || matches!(token, DataToken::ItemEnd | DataToken::SequenceEnd)));
+        let ends: Vec<_> = tokens
+            .into_iter()
+            .filter(|(token, _)| matches!(token, DataToken::ItemEnd | DataToken::SequenceEnd))
+            .collect();
+
+        assert_eq!(
+            ends,
+            vec![
+                (DataToken::ItemEnd, false),
+                (DataToken::ItemEnd, true),
+                (DataToken::SequenceEnd, false),
+                (DataToken::SequenceEnd, true),
+                // offset table and fragment
+                (DataToken::ItemEnd, false),
+                (DataToken::ItemEnd, false),
+                (DataToken::SequenceEnd, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_into_index() {
+        use dicom_core::value::Value;
+
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x08, 0x00, 0x15, 0x11, b'S', b'Q', 0x00, 0x00, // (0008,1115) ReferencedSeriesSequence
+            0xff, 0xff, 0xff, 0xff, // len: undefined
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, len: undefined
+            0x20, 0x00, 0x0e, 0x00, b'U', b'I', 0x04, 0x00, // (0020,000E) SeriesInstanceUID, len = 4
+            b'1', b'.', b'3', 0x00,
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 48 --
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x04, 0x00, // (0010,0010) PatientName, len = 4
+            b'D', b'o', b'e', b' ',
+            // -- 60 --
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) again, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            // -- 76 --
+            0xe0, 0x7f, 0x10, 0x00, b'O', b'B', 0x00, 0x00, // (7FE0,0010) PixelData
+            0xff, 0xff, 0xff, 0xff, // len: undefined
+            0xfe, 0xff, 0x00, 0xe0, 0x00, 0x00, 0x00, 0x00, // offset table, len = 0
+            0xfe, 0xff, 0x00, 0xe0, 0x04, 0x00, 0x00, 0x00, // fragment, len = 4
+            0x99, 0x99, 0x99, 0x99,
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>,
+        );
+        let index = DataSetReader::new(parser, Default::default())
+            .into_index()
+            .expect("should read the data set into an index");
+
+        assert_eq!(index.len(), 3);
+        // the last duplicate is kept
+        assert_eq!(
+            index.get(Tag(0x0010, 0x0010)).unwrap().to_str().unwrap(),
+            "Doe^John"
+        );
+        // nested elements stay in their item
+        assert!(index.get(Tag(0x0020, 0x000E)).is_none());
+        match index.get(Tag(0x0008, 0x1115)).map(|e| e.value()) {
+            Some(Value::Sequence { items, .. }) => {
+                assert_eq!(items.len(), 1);
+                assert_eq!(
+                    items[0].get(Tag(0x0020, 0x000E)).unwrap().to_str().unwrap(),
+                    "1.3\0"
+                );
+            }
+            value => panic!("unexpected value {:?}", value),
+        }
+        match index.get(Tag(0x7FE0, 0x0010)).map(|e| e.value()) {
+            Some(Value::PixelSequence {
+                offset_table,
+                fragments,
+            }) => {
+                assert!(offset_table.is_empty());
+                assert_eq!(&fragments[..], &[vec![0x99; 4]]);
+            }
+            value => panic!("unexpected value {:?}", value),
+        }
+    }
+
+    #[test]
+    fn observe_raw_vrs_in_explicit_vr() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        /// The VR bytes observed, shared with the callback
+        type RawVrs = Rc<RefCell<Vec<(Tag, [u8; 2])>>>;
+
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x08, 0x00, 0x16, 0x00, b'U', b'I', 0x04, 0x00, // (0008,0016) SOPClassUID, len = 4
+            b'1', b'.', b'2', 0x00,
+            // -- 12 --
+            0x09, 0x00, 0x01, 0x10, b'X', b'Y', 0x00, 0x00, // (0009,1001) with a made-up VR
+            0x02, 0x00, 0x00, 0x00, // len = 2
+            0x03, 0x00,
+            // -- 26 --
+            0x08, 0x00, 0x15, 0x11, b'S', b'Q', 0x00, 0x00, // (0008,1115) ReferencedSeriesSequence
+            0xff, 0xff, 0xff, 0xff, // len: undefined
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, len: undefined
+            0x20, 0x00, 0x0e, 0x00, b'U', b'I', 0x04, 0x00, // (0020,000E) SeriesInstanceUID, len = 4
+            b'1', b'.', b'3', 0x00,
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+        ];
+
+        let read = |raw_vrs: Option<RawVrs>| -> Vec<DataToken> {
+            let mut cursor = DATA;
+            let parser = StatefulDecoder::new(
+                &mut cursor,
+                ExplicitVRLittleEndianDecoder::default(),
+                LittleEndianBasicDecoder,
+                Box::new(DefaultCharacterSetCodec) as Box<_>,
+            );
+            let mut dset_reader = DataSetReader::new(parser, Default::default());
+            if let Some(raw_vrs) = raw_vrs {
+                dset_reader =
+                    dset_reader.on_raw_vr(move |tag, vr| raw_vrs.borrow_mut().push((tag, vr)));
+            }
+            dset_reader
+                .collect::<Result<_, _>>()
+                .expect("should parse without an error")
+        };
+
+        let raw_vrs = Rc::new(RefCell::new(Vec::new()));
+        let tokens = read(Some(Rc::clone(&raw_vrs)));
+
+        assert_eq!(
+            *raw_vrs.borrow(),
+            vec![
+                (Tag(0x0008, 0x0016), *b"UI"),
+                (Tag(0x0009, 0x1001), *b"XY"),
+                (Tag(0x0008, 0x1115), *b"SQ"),
+                (Tag(0x0020, 0x000E), *b"UI"),
+            ]
+        );
+        // the unknown VR is still read as UN
+        assert_eq!(
+            tokens[2],
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1001),
+                VR::UN,
+                Length(2),
+            ))
+        );
+        // reading is not affected by the callback
+        assert_eq!(tokens, read(None));
+    }
+
+    #[test]
+    fn read_elements_missing_from_dictionary_with_policy() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x09, 0x00, 0x01, 0x10, // (0009,1001) «private attribute»
+            0x02, 0x00, 0x00, 0x00, // len: 2
+            b'X', b'Y', // value = "XY"
+            // -- 10 --
+            0x09, 0x00, 0x02, 0x10, // (0009,1002) «private attribute»
+            0x02, 0x00, 0x00, 0x00, // len: 2
+            b'Z', b'W', // value = "ZW"
+            // -- 20 --
+            0x10, 0x00, 0xee, 0xee, // (0010,EEEE) «unknown attribute»
+            0x04, 0x00, 0x00, 0x00, // len: 4
+            b'A', b'B', b'C', b'D', // value = "ABCD"
+        ];
+
+        /// Read the data with the given policy,
+        /// retrieving the VR of each element
+        fn read_vrs(policy: UnknownVrPolicy) -> Vec<VR> {
+            let mut cursor = DATA;
+            let parser = StatefulDecoder::new(
+                &mut cursor,
+                ImplicitVRLittleEndianDecoder::default(),
+                LittleEndianBasicDecoder,
+                Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+            );
+            let options = DataSetReaderOptions::default().unknown_vr_policy(policy);
+            DataSetReader::new(parser, options)
+                .on_unknown_vr(|tag, _| match tag {
+                    Tag(0x0009, 0x1001) => Some(VR::SH),
+                    _ => None,
+                })
+                .filter_map(
+                    |token| match token.expect("should parse without an error") {
+                        DataToken::ElementHeader(header) => Some(header.vr),
+                        _ => None,
+                    },
+                )
+                .collect()
+        }
+
+        // the callback takes precedence over the policy
+        assert_eq!(
+            read_vrs(UnknownVrPolicy::Unknown),
+            vec![VR::SH, VR::UN, VR::UN]
+        );
+        assert_eq!(
+            read_vrs(UnknownVrPolicy::Always(VR::LO)),
+            vec![VR::SH, VR::LO, VR::LO]
+        );
+        assert_eq!(
+            read_vrs(UnknownVrPolicy::StandardGroups(VR::LO)),
+            vec![VR::SH, VR::UN, VR::LO]
+        );
+        assert_eq!(
+            DataSetReaderOptions::default()
+                .default_vr(VR::LO)
+                .unknown_vr_policy,
+            UnknownVrPolicy::Always(VR::LO)
+        );
+
+        // values are read according to the chosen VR
+        let mut cursor = &DATA[20..];
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ImplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let options = DataSetReaderOptions::default().default_vr(VR::LO);
+        let tokens: Vec<_> = DataSetReader::new(parser, options)
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0xEEEE),
+                    VR::LO,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Strs(
+                    ["ABCD".to_owned()].as_ref().into()
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_un_elements_in_explicit_vr_as_is() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x09, 0x00, 0x01, 0x10, b'U', b'N', 0x00, 0x00, // (0009,1001) «private attribute»
+            0x02, 0x00, 0x00, 0x00, // len: 2
+            b'X', b'Y', // value = "XY"
+            // -- 14 --
+            0x10, 0x00, 0x10, 0x00, b'U', b'N', 0x00, 0x00, // (0010,0010) PatientName
+            0x04, 0x00, 0x00, 0x00, // len: 4
+            b'D', b'o', b'e', b'^', // value = "Doe^"
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        // neither the policy, the callback nor the dictionary apply
+        let options = DataSetReaderOptions::default().default_vr(VR::LO);
+        let tokens: Vec<_> = DataSetReader::new(parser, options)
+            .on_unknown_vr(|_, _| Some(VR::SH))
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0009, 0x1001),
+                    VR::UN,
+                    Length(2),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::U8(b"XY".as_ref().into())),
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0010),
+                    VR::UN,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::U8(b"Doe^".as_ref().into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_unknown_vrs_from_dictionary() {
+        use dicom_core::dictionary::{DataDictionary, DictionaryEntryBuf, TagRange};
+        use dicom_encoding::text::SpecificCharacterSet;
+        use dicom_encoding::transfer_syntax::{Codec, Endianness, TransferSyntax};
+
+        /// A dictionary of private attributes only
+        #[derive(Debug)]
+        struct PrivateDictionary(Vec<DictionaryEntryBuf>);
+
+        impl DataDictionary for PrivateDictionary {
+            type Entry = DictionaryEntryBuf;
+
+            fn by_name(&self, name: &str) -> Option<&Self::Entry> {
+                self.0.iter().find(|e| e.alias == name)
+            }
+
+            fn by_tag(&self, tag: Tag) -> Option<&Self::Entry> {
+                self.0.iter().find(|e| e.tag == TagRange::Single(tag))
+            }
+        }
+
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x09, 0x00, 0x10, 0x00, // (0009,0010) private creator
+            0x04, 0x00, 0x00, 0x00, // len: 4
+            b'A', b'C', b'M', b'E', // value = "ACME"
+            // -- 12 --
+            0x09, 0x00, 0x01, 0x10, // (0009,1001) private element of block 0x10
+            0x02, 0x00, 0x00, 0x00, // len: 2
+            0x03, 0x00, // value = 3
+            // -- 22 --
+            0x09, 0x00, 0x02, 0x10, // (0009,1002) private element of block 0x10
+            0x02, 0x00, 0x00, 0x00, // len: 2
+            0x04, 0x00, // value = [4, 0]
+        ];
+
+        let dict = PrivateDictionary(vec![
+            DictionaryEntryBuf {
+                tag: TagRange::Single(Tag(0x0009, 0x1001)),
+                alias: "AcmeCount".to_string(),
+                vr: VR::US,
+            },
+            // sequences are not resolved
+            DictionaryEntryBuf {
+                tag: TagRange::Single(Tag(0x0009, 0x1002)),
+                alias: "AcmeSequence".to_string(),
+                vr: VR::SQ,
+            },
+        ]);
+
+        let ts: TransferSyntax = TransferSyntax::new(
+            "1.2.840.10008.1.2",
+            "Implicit VR Little Endian",
+            Endianness::Little,
+            false,
+            Codec::None,
+        );
+
+        let ground_truth = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x0010),
+                VR::UN,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U8(b"ACME".as_ref().into())),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1001),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([3].as_ref().into())),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1002),
+                VR::UN,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U8([4, 0].as_ref().into())),
+        ];
+
+        let dset_reader = DataSetReader::new_with_dictionary(
+            DATA,
+            dict,
+            &ts,
+            SpecificCharacterSet::Default,
+            Default::default(),
+        )
+        .expect("should create a reader");
+
+        let tokens: Vec<_> = dset_reader
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(tokens, ground_truth);
+    }
+
+    #[test]
+    fn read_borrowed_text_values() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0a, 0x00, // (0008,0005) SpecificCharacterSet, len = 10
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'0', b'0',
+            // -- 18 --
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            // -- 34 --
+            0x18, 0x00, 0x11, 0x60, b'S', b'Q', 0x00, 0x00, // (0018,6011) SequenceOfUltrasoundRegions
+            0x1a, 0x00, 0x00, 0x00, // length: 26
+            // -- 46 --
+            0xfe, 0xff, 0x00, 0xe0, 0x12, 0x00, 0x00, 0x00, // item start, length: 18
+            // -- 54 --
+            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x01, 0x00, // (0018,6012) RegionSpatialformat, value = 1
+            // -- 64 --
+            0x08, 0x00, 0x0f, 0x30, b'S', b'H', 0x00, 0x00, // (0008,300F) empty SH value
+            // -- 72 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T',
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default());
+
+        let ground_truth = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0005),
+                VR::CS,
+                Length(10),
+            ))
+            .into(),
+            // the character set is still read as a regular value
+            DataToken::PrimitiveValue(PrimitiveValue::Strs(
+                ["ISO_IR 100".to_owned()].as_ref().into(),
+            ))
+            .into(),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(8),
+            ))
+            .into(),
+            DataTokenRef::PrimitiveValue(b"Doe^John"),
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length(26),
+            }
+            .into(),
+            DataToken::ItemStart { len: Length(18) }.into(),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0018, 0x6012),
+                VR::US,
+                Length(2),
+            ))
+            .into(),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([1].as_ref().into())).into(),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x300f),
+                VR::SH,
+                Length(0),
+            ))
+            .into(),
+            DataTokenRef::PrimitiveValue(b""),
+            DataToken::ItemEnd.into(),
+            DataToken::SequenceEnd.into(),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0020, 0x4000),
+                VR::LT,
+                Length(4),
+            ))
+            .into(),
+            DataTokenRef::PrimitiveValue(b"TEST"),
+        ];
+
+        let mut tokens = Vec::new();
+        while let Some(token) = dset_reader.next_borrowed() {
+            tokens.push(token.expect("should parse without an error"));
+        }
+        assert_eq!(tokens, ground_truth);
+        assert_eq!(dset_reader.parser.bytes_read(), DATA.len() as u64);
+    }
+
+    #[test]
+    fn read_borrowed_values_with_reader_options() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x10, 0x00, 0x20, 0x00, b'L', b'O', 0x03, 0x00, // (0010,0020) PatientID, len = 3
+            b'I', b'D', b'1',
+            // -- 11 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T',
+            // -- 23 --
+        ];
+
+        // odd length values are preserved
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let options = DataSetReaderOptions::default().preserve_unknown(true);
+        let mut dset_reader = DataSetReader::new(parser, options);
+        let mut tokens = Vec::new();
+        while let Some(token) = dset_reader.next_borrowed() {
+            tokens.push(token.expect("should parse without an error"));
+        }
+        assert_eq!(
+            &tokens[1..],
+            &[
+                DataToken::ItemValue(b"ID1".to_vec()).into(),
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0020, 0x4000),
+                    VR::LT,
+                    Length(4),
+                ))
+                .into(),
+                DataTokenRef::PrimitiveValue(b"TEST"),
+            ]
+        );
+
+        // the byte limit is checked before borrowing the value
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default()).with_byte_limit(20);
+        assert_eq!(
+            (0..3)
+                .filter_map(|_| dset_reader.next_borrowed())
+                .filter(|t| t.is_ok())
+                .count(),
+            3
+        );
+        assert!(matches!(
+            dset_reader.next_borrowed(),
+            Some(Err(Error::ByteLimitExceeded {
+                limit: 20,
+                bytes_read: 23,
+                ..
+            }))
+        ));
+        assert!(dset_reader.next_borrowed().is_none());
+        assert_eq!(dset_reader.parser.bytes_read(), 19);
+    }
+
+    #[test]
+    fn read_raw_elements() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            // -- 16 --
+            0x18, 0x00, 0x11, 0x60, b'S', b'Q', 0x00, 0x00, // (0018,6011) SequenceOfUltrasoundRegions
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 28 --
+            0xfe, 0xff, 0x00, 0xe0, 0x0a, 0x00, 0x00, 0x00, // item start, length: 10
+            // -- 36 --
+            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x01, 0x00, // (0018,6012) RegionSpatialformat, value = 1
+            // -- 46 --
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 54 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T',
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default());
+
+        let elem = dset_reader.next_raw_element().unwrap().unwrap();
+        assert_eq!(elem.bytes, &DATA[..16]);
+        assert_eq!(
+            elem.tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0010),
+                    VR::PN,
+                    Length(8),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Strs(
+                    ["Doe^John".to_owned()].as_ref().into(),
+                )),
+            ]
+        );
+
+        let elem = dset_reader.next_raw_element().unwrap().unwrap();
+        assert_eq!(elem.bytes, &DATA[16..54]);
+        assert_eq!(elem.tokens.len(), 6);
+        assert_eq!(
+            elem.tokens.first(),
+            Some(&DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length::UNDEFINED,
+            })
+        );
+        assert_eq!(elem.tokens.last(), Some(&DataToken::SequenceEnd));
+
+        let elem = dset_reader.next_raw_element().unwrap().unwrap();
+        assert_eq!(elem.bytes, &DATA[54..]);
+        assert_eq!(elem.tokens.len(), 2);
+
+        assert!(dset_reader.next_raw_element().is_none());
+    }
+
+    #[test]
+    fn read_current_path() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x40, 0x00, 0x75, 0x02, b'S', b'Q', 0x00, 0x00, // (0040,0275) RequestAttributesSequence
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 --
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, length: undefined
+            // -- 20 --
+            0x40, 0x00, 0x08, 0x00, b'S', b'Q', 0x00, 0x00, // (0040,0008) ScheduledProtocolCodeSequence
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 32 --
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, length: undefined
+            // -- 40 --
+            0x08, 0x00, 0x00, 0x01, b'S', b'H', 0x04, 0x00, // (0008,0100) CodeValue, len = 4
+            b'T', b'1', b'2', b'3',
+            // -- 52 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 84 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T',
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default());
+        assert!(dset_reader.current_path().is_empty());
+
+        let mut paths = Vec::new();
+        while let Some(token) = dset_reader.next() {
+            match token.expect("should parse without an error") {
+                DataToken::SequenceStart { tag, .. } => {
+                    assert_eq!(dset_reader.current_path().last(), Some(&tag));
+                }
+                DataToken::ElementHeader(header) => {
+                    paths.push((header.tag, dset_reader.current_path().to_vec()));
+                }
+                _ => {}
+            }
+        }
+
+        assert_eq!(
+            paths,
+            vec![
+                (
+                    Tag(0x0008, 0x0100),
+                    vec![Tag(0x0040, 0x0275), Tag(0x0040, 0x0008)]
+                ),
+                (Tag(0x0020, 0x4000), vec![]),
+            ]
+        );
+        assert!(dset_reader.current_path().is_empty());
+    }
+
+    #[test]
+    fn restart_reading_from_the_start() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x40, 0x00, 0x75, 0x02, b'S', b'Q', 0x00, 0x00, // (0040,0275) RequestAttributesSequence
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 --
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, length: undefined
+            // -- 20 --
+            0x08, 0x00, 0x00, 0x01, b'S', b'H', 0x04, 0x00, // (0008,0100) CodeValue, len = 4
+            b'T', b'1', b'2', b'3',
+            // -- 32 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 48 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T',
+        ];
+
+        let mut cursor = std::io::Cursor::new(DATA);
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default());
+
+        let first_pass: Vec<_> = dset_reader
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(first_pass.len(), 8);
+
+        dset_reader.restart().unwrap();
+        let second_pass: Vec<_> = dset_reader
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(second_pass, first_pass);
+
+        // restart in the middle of a sequence
+        dset_reader.restart().unwrap();
+        dset_reader.by_ref().take(3).for_each(drop);
+        assert_eq!(dset_reader.current_path(), &[Tag(0x0040, 0x0275)]);
+        dset_reader.restart().unwrap();
+        assert!(dset_reader.current_path().is_empty());
+        let third_pass: Vec<_> = dset_reader
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(third_pass, first_pass);
+    }
+
+    #[test]
+    fn restart_reading_with_transfer_syntax() {
+        use dicom_dictionary_std::StandardDataDictionary;
+        use dicom_encoding::text::SpecificCharacterSet;
+        use dicom_encoding::transfer_syntax::{Codec, Endianness, TransferSyntax};
+
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x04, 0x00, 0x30, 0x11, b'L', b'O', 0x08, 0x00, // (0004,1130) FileSetID, len = 8
+            b'M', 0xc3, 0xbc, b'l', b'l', b'e', b'r', b'^',
+            // -- 16 --
+            0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0a, 0x00, // (0008,0005) SpecificCharacterSet, len = 10
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'9', b'2',
+            // -- 34 --
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'M', 0xc3, 0xbc, b'l', b'l', b'e', b'r', b'^', // value = "Müller^"
+        ];
+
+        let ts = TransferSyntax::new(
+            "1.2.840.10008.1.2.1",
+            "Explicit VR Little Endian",
+            Endianness::Little,
+            true,
+            Codec::None,
+        );
+        let mut dset_reader = DataSetReader::new_seekable_with_dictionary(
+            std::io::Cursor::new(DATA),
+            StandardDataDictionary,
+            &ts,
+            SpecificCharacterSet::Default,
+            Default::default(),
+        )
+        .expect("should create a reader");
+
+        let first_pass: Vec<_> = dset_reader
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        let text = |s: &str| {
+            DataToken::PrimitiveValue(PrimitiveValue::Strs([s.to_owned()].as_ref().into()))
+        };
+        assert_eq!(first_pass[1], text("MÃ¼ller^"));
+        assert_eq!(first_pass[5], text("Müller^"));
+
+        // text before the character set is decoded as in the first pass
+        dset_reader.restart().unwrap();
+        let second_pass: Vec<_> = dset_reader
+            .by_ref()
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(second_pass, first_pass);
+    }
+
+    #[test]
+    fn read_lazy_values_over_threshold() {
+        #[rustfmt::skip]
+        let mut data: Vec<u8> = vec![
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            // -- 16 --
+            0x42, 0x00, 0x11, 0x00, b'O', b'B', 0x00, 0x00, // (0042,0011) EncapsulatedDocument
+            0x00, 0x04, 0x00, 0x00, // length: 1024
+            // -- 28 --
+        ];
+        data.extend(vec![0xAB; 1024]);
+        #[rustfmt::skip]
+        data.extend(&[
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T',
+        ]);
+
+        let mut cursor = &data[..];
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>, // trait object
+        );
+        let options = DataSetReaderOptions::default().max_eager_value_len(16);
+        let tokens: Vec<_> = DataSetReader::new(parser, options)
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+
+        let doc_header = DataElementHeader::new(Tag(0x0042, 0x0011), VR::OB, Length(1024));
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0010),
+                    VR::PN,
+                    Length(8),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Strs(
+                    ["Doe^John".to_owned()].as_ref().into(),
+                )),
+                DataToken::ElementHeader(doc_header),
+                DataToken::LazyValue(DicomElementMarker {
+                    header: doc_header,
+                    pos: 28,
+                }),
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0020, 0x4000),
+                    VR::LT,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Str("TEST".to_owned())),
+            ]
+        );
+        assert!(data[28..28 + 1024].iter().all(|&b| b == 0xAB));
+    }
+
+    #[test]
+    fn lazy_value_marker_points_into_the_source() {
+        // the data set follows some other data in the file,
+        // such as the preamble and the file meta group
+        let mut file = vec![0x55; 132];
+        #[rustfmt::skip]
+        file.extend(&[
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            0x42, 0x00, 0x11, 0x00, b'O', b'B', 0x00, 0x00, // (0042,0011) EncapsulatedDocument
+            0x20, 0x00, 0x00, 0x00, // length: 32
+        ]);
+        let document: Vec<u8> = (0..32).collect();
+        file.extend(&document);
+
+        let mut cursor = &file[132..];
+        let parser = StatefulDecoder::new_with_position(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+            132,
+        );
+        let options = DataSetReaderOptions::default().max_eager_value_len(16);
+        let marker = DataSetReader::new(parser, options)
+            .find_map(|token| match token.expect("should parse without an error") {
+                DataToken::LazyValue(marker) => Some(marker),
+                _ => None,
+            })
+            .expect("should find a lazy value");
+        assert_eq!(marker.pos, 132 + 28);
+
+        // the value is found at the marker's position in the file
+        let mut source = std::io::Cursor::new(&file[..]);
+        marker.move_to_start(&mut source).unwrap();
+        let mut value = vec![0; marker.header.len.0 as usize];
+        std::io::Read::read_exact(&mut source, &mut value).unwrap();
+        assert_eq!(value, document);
+    }
+
+    #[test]
+    fn preserve_unknown_and_odd_length_values() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x09, 0x00, 0x01, 0x10, b'U', b'N', 0x00, 0x00, // (0009,1001) UN
+            0x03, 0x00, 0x00, 0x00, // length: 3
+            0x01, 0x02, 0x03,
+            // -- 15 --
+            0x10, 0x00, 0x20, 0x00, b'L', b'O', 0x03, 0x00, // (0010,0020) PatientID, len = 3
+            b'I', b'D', b'1',
+            // -- 26 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T',
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let options = DataSetReaderOptions::default().preserve_unknown(true);
+        let tokens: Vec<_> = DataSetReader::new(parser, options)
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0009, 0x1001),
+                    VR::UN,
+                    Length(3),
+                )),
+                DataToken::ItemValue(vec![1, 2, 3]),
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0020),
+                    VR::LO,
+                    Length(3),
+                )),
+                DataToken::ItemValue(b"ID1".to_vec()),
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0020, 0x4000),
+                    VR::LT,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Str("TEST".to_owned())),
+            ]
+        );
+
+        // the elements are written back as they were
+        let mut out = Vec::new();
+        DataSetWriter::new(
+            &mut out,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec,
+        )
+        .write_sequence(tokens)
+        .expect("should write without an error");
+        assert_eq!(out, DATA);
+    }
+
+    #[test]
+    fn read_preserved_values_into_index() {
+        use dicom_core::value::Value;
+
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x08, 0x00, 0x15, 0x11, b'S', b'Q', 0x00, 0x00, // (0008,1115) ReferencedSeriesSequence
+            0xff, 0xff, 0xff, 0xff, // len: undefined
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, len: undefined
+            0x09, 0x00, 0x01, 0x10, b'U', b'N', 0x00, 0x00, // (0009,1001) UN
+            0x02, 0x00, 0x00, 0x00, // length: 2
+            0x01, 0x02,
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 50 --
+            0x10, 0x00, 0x20, 0x00, b'L', b'O', 0x03, 0x00, // (0010,0020) PatientID, len = 3
+            b'I', b'D', b'1',
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>,
+        );
+        let options = DataSetReaderOptions::default().preserve_unknown(true);
+        let index = DataSetReader::new(parser, options)
+            .into_index()
+            .expect("should read the data set into an index");
+
+        // the raw bytes are kept under the VR of the header
+        let id = index.get(Tag(0x0010, 0x0020)).unwrap();
+        assert_eq!(id.vr(), VR::LO);
+        assert_eq!(
+            id.value(),
+            &Value::Primitive(PrimitiveValue::U8(b"ID1".as_ref().into()))
+        );
+        match index.get(Tag(0x0008, 0x1115)).map(|e| e.value()) {
+            Some(Value::Sequence { items, .. }) => {
+                let unknown = items[0].get(Tag(0x0009, 0x1001)).unwrap();
+                assert_eq!(unknown.vr(), VR::UN);
+                assert_eq!(
+                    unknown.value(),
+                    &Value::Primitive(PrimitiveValue::U8([1, 2].as_ref().into()))
+                );
+            }
+            value => panic!("unexpected value {:?}", value),
+        }
+    }
+
+    #[test]
+    fn read_coalesced_fragments() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x42, 0x00, 0x11, 0x00, b'O', b'B', 0x00, 0x00, // (0042,0011) EncapsulatedDocument
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 --
+            0xfe, 0xff, 0x00, 0xe0, 0x04, 0x00, 0x00, 0x00, // item start, length: 4
+            0x01, 0x02, 0x03, 0x04,
+            // -- 24 --
+            0xfe, 0xff, 0x00, 0xe0, 0x02, 0x00, 0x00, 0x00, // item start, length: 2
+            0x05, 0x06,
+            // -- 34 --
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 42 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T', // value = "TEST"
+        ];
+
+        let ground_truth = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0042, 0x0011),
+                VR::OB,
+                Length(6),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U8(
+                [0x01, 0x02, 0x03, 0x04, 0x05, 0x06].as_ref().into(),
+            )),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0020, 0x4000),
+                VR::LT,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::Str("TEST".into())),
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>, // trait object
+        );
+        let dset_reader = DataSetReader::new(
+            parser,
+            DataSetReaderOptions::default().coalesce_fragments(true),
+        );
+
+        let tokens: Vec<_> = dset_reader
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(tokens, ground_truth);
+    }
+
+    #[test]
+    fn resume_reading_in_chunks() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            // -- 16 --
+            0x18, 0x00, 0x11, 0x60, b'S', b'Q', 0x00, 0x00, // (0018,6011) SequenceOfUltrasoundRegions
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 28 --
+            0xfe, 0xff, 0x00, 0xe0, 0x0a, 0x00, 0x00, 0x00, // item start, length: 10
+            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, // (0018,6012) RegionSpatialFormat, len = 2
+            0x01, 0x00, // value = 1
+            // -- 46 --
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 54 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T', // value = "TEST"
+        ];
+
+        fn decoder<'a>(cursor: &'a mut &'static [u8], position: u64) -> impl StatefulDecode + 'a {
+            StatefulDecoder::new_with_position(
+                cursor,
+                ExplicitVRLittleEndianDecoder::default(),
+                LittleEndianBasicDecoder::default(),
+                Box::new(DefaultCharacterSetCodec::default()) as Box<_>, // trait object
+                position,
+            )
+        }
+
+        let mut cursor = DATA;
+        let ground_truth: Vec<_> = DataSetReader::new(decoder(&mut cursor, 0), Default::default())
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(ground_truth.len(), 10);
+
+        for split in 1..DATA.len() {
+            // read the first chunk until the data runs out
+            let mut cursor = &DATA[..split];
+            let mut dset_reader = DataSetReader::new(decoder(&mut cursor, 0), Default::default());
+            let mut tokens = Vec::new();
+            let mut state = dset_reader.state();
+            while let Some(Ok(token)) = dset_reader.next() {
+                tokens.push(token);
+                state = dset_reader.state();
+            }
+
+            if let Some(DataToken::ElementHeader(header)) = tokens.last() {
+                // the value did not fit in the first chunk
+                assert_eq!(state.value_remaining(), header.len.0);
+            }
+
+            // resume with the rest of the data
+            let position = state.position;
+            let mut cursor = &DATA[position as usize..];
+            let dset_reader =
+                DataSetReader::resume(decoder(&mut cursor, position), Default::default(), state)
+                    .expect("should resume at the same position");
+            for token in dset_reader {
+                tokens.push(token.expect("should parse without an error"));
+            }
+
+            assert_eq!(tokens, ground_truth, "failed when splitting at {}", split);
+        }
+    }
+
+    #[test]
+    fn resume_after_specific_character_set() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x08, 0x00, 0x05, 0x00, b'C', b'S', 0x0a, 0x00, // (0008,0005) SpecificCharacterSet, len = 10
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'9', b'2',
+            // -- 18 --
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'M', 0xc3, 0xbc, b'l', b'l', b'e', b'r', b'^', // value = "Müller^"
+        ];
+
+        fn decoder<'a>(cursor: &'a mut &'static [u8], position: u64) -> impl StatefulDecode + 'a {
+            StatefulDecoder::new_with_position(
+                cursor,
+                ExplicitVRLittleEndianDecoder::default(),
+                LittleEndianBasicDecoder,
+                Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+                position,
+            )
+        }
+
+        let raw_vrs = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&raw_vrs);
+
+        // the first chunk ends right after the character set
+        let mut cursor = &DATA[..18];
+        let mut dset_reader = DataSetReader::new(decoder(&mut cursor, 0), Default::default())
+            .on_raw_vr(move |_, _| counter.set(counter.get() + 1));
+        let mut state = dset_reader.state();
+        while let Some(Ok(_)) = dset_reader.next() {
+            state = dset_reader.state();
+        }
+        assert_eq!(state.position, 18);
+        assert_eq!(state.character_set, vec!["ISO_IR 192".to_owned()]);
+
+        let mut cursor = &DATA[18..];
+        let tokens: Vec<_> = dset_reader
+            .resume_with_decoder(decoder(&mut cursor, 18), state)
+            .expect("should resume at the same position")
+            .collect::<Result<_, _>>()
+            .expect("should parse without an error");
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0010, 0x0010),
+                    VR::PN,
+                    Length(8),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::Strs(
+                    ["Müller^".to_owned()].as_ref().into(),
+                )),
+            ]
+        );
+        // the callback was kept for the second chunk
+        assert_eq!(raw_vrs.get(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn state_round_trip_through_json() {
+        use super::{DataSetReaderState, DelimiterState, SeqTokenType};
+        use std::collections::BTreeMap;
+
+        let mut private_creators = BTreeMap::new();
+        private_creators.insert((0, 0x0009, 0x10), "ACME 1.0".to_owned());
+        let state = DataSetReaderState {
+            position: 28,
+            delimiters: vec![DelimiterState {
+                typ: SeqTokenType::Sequence,
+                len: Length(20),
+                pixel_data: false,
+                implicit_vr: true,
+                base_offset: 12,
+            }],
+            in_sequence: true,
+            delimiter_check_pending: false,
+            pending_header: Some(DataElementHeader::new(
+                Tag(0x0009, 0x1010),
+                VR::UN,
+                Length(4),
+            )),
+            pending_value: Some(PrimitiveValue::U16([1, 2].as_ref().into())),
+            private_creators,
+            path: vec![Tag(0x0009, 0x1010)],
+            character_set: vec!["ISO_IR 192".to_owned()],
+        };
+
+        let json = serde_json::to_string(&state).expect("should serialize the state");
+        let state2: DataSetReaderState =
+            serde_json::from_str(&json).expect("should deserialize the state");
+        assert_eq!(state2, state);
+    }
+
+    #[test]
+    fn read_top_level_headers() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'M', b'R', // (0008,0060) Modality
+            // -- 10 --
+            0x18, 0x00, 0x11, 0x60, b'S', b'Q', 0x00, 0x00, // (0018,6011) SequenceOfUltrasoundRegions
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 22 --
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, length: undefined
+            // -- 30 --
+            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x01, 0x00, // (0018,6012) RegionSpatialformat, value = 1
+            // -- 40 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 56 --
+            0x40, 0x00, 0x75, 0x02, b'S', b'Q', 0x00, 0x00, // (0040,0275) RequestAttributesSequence
+            0x12, 0x00, 0x00, 0x00, // length: 18
+            0xfe, 0xff, 0x00, 0xe0, 0x0a, 0x00, 0x00, 0x00, // item start, length: 10
+            0x40, 0x00, 0x09, 0x00, b'S', b'H', 0x02, 0x00, b'1', b'2', // (0040,0009) ScheduledProcedureStepID
+            // -- 86 --
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            // -- 102 --
+            0xe0, 0x7f, 0x10, 0x00, b'O', b'B', 0x00, 0x00, // (7FE0,0010) PixelData
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            0xfe, 0xff, 0x00, 0xe0, 0x00, 0x00, 0x00, 0x00, // offset table, empty
+            0xfe, 0xff, 0x00, 0xe0, 0x04, 0x00, 0x00, 0x00, // fragment, length: 4
+            0x99, 0x99, 0x99, 0x99,
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 146 --
+            0xfc, 0xff, 0xfc, 0xff, b'O', b'B', 0x00, 0x00, // (FFFC,FFFC) DataSetTrailingPadding
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default());
+
+        // start in the middle of the first element
+        assert_eq!(
+            dset_reader.next().unwrap().unwrap(),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0008, 0x0060),
+                VR::CS,
+                Length(2),
+            )),
+        );
+
+        let headers = dset_reader
+            .headers()
+            .map(|header| header.map(|h| (h.tag, h.vr, h.len.get())))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("should read the headers without an error");
+        assert_eq!(
+            headers,
+            vec![
+                (Tag(0x0018, 0x6011), VR::SQ, None),
+                (Tag(0x0040, 0x0275), VR::SQ, Some(18)),
+                (Tag(0x0010, 0x0010), VR::PN, Some(8)),
+                (Tag(0x7FE0, 0x0010), VR::OB, None),
+                (Tag(0xFFFC, 0xFFFC), VR::OB, Some(2)),
+            ]
+        );
+        assert_eq!(dset_reader.parser.bytes_read(), DATA.len() as u64);
+        assert!(dset_reader.current_path().is_empty());
+    }
+
+    #[test]
+    fn read_top_level_headers_past_un_sequence() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x09, 0x00, 0x10, 0x10, b'U', b'N', 0x00, 0x00, // (0009,1010) UN
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 --
+            0xfe, 0xff, 0x00, 0xe0, 0xff, 0xff, 0xff, 0xff, // item start, length: undefined
+            // -- 20 -- in implicit VR
+            0x10, 0x00, 0x20, 0x00, 0x02, 0x00, 0x00, 0x00, b'I', b'D', // (0010,0020) PatientID
+            // -- 30 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 46 -- back in explicit VR
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x04, 0x00, // (0010,0010) PatientName, len = 4
+            b'D', b'o', b'e', b' ',
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default());
+
+        let headers = dset_reader
+            .headers()
+            .map(|header| header.map(|h| (h.tag, h.vr, h.len.get())))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("should read the headers without an error");
+        assert_eq!(
+            headers,
+            vec![
+                (Tag(0x0009, 0x1010), VR::UN, None),
+                (Tag(0x0010, 0x0010), VR::PN, Some(4)),
+            ]
+        );
+        assert_eq!(dset_reader.parser.bytes_read(), DATA.len() as u64);
+    }
+
+    #[test]
+    fn read_up_to_byte_limit() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            // -- 16 --
+            0x09, 0x00, 0x10, 0x10, b'O', b'B', 0x00, 0x00, // (0009,1010) private, OB
+            0x00, 0x00, 0x00, 0xf0, // length: way beyond the end of the data
+            // -- 28 --
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default()).with_byte_limit(1024);
+
+        assert!(matches!(
+            dset_reader.next(),
+            Some(Ok(DataToken::ElementHeader(_)))
+        ));
+        assert!(matches!(
+            dset_reader.next(),
+            Some(Ok(DataToken::PrimitiveValue(_)))
+        ));
+        assert!(matches!(
+            dset_reader.next(),
+            Some(Ok(DataToken::ElementHeader(_)))
+        ));
+        // the value is not read
+        assert!(matches!(
+            dset_reader.next(),
+            Some(Err(Error::ByteLimitExceeded {
+                limit: 1024,
+                bytes_read: 0xf000_001c,
+                ..
+            }))
+        ));
+        assert!(dset_reader.next().is_none());
+        assert_eq!(dset_reader.parser.bytes_read(), 28);
+
+        // the limit also applies to headers
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        let mut dset_reader = DataSetReader::new(parser, Default::default()).with_byte_limit(20);
+        assert_eq!(
+            dset_reader.by_ref().take(2).filter(|t| t.is_ok()).count(),
+            2
+        );
+        assert!(matches!(
+            dset_reader.next(),
+            Some(Err(Error::ByteLimitExceeded {
+                limit: 20,
+                bytes_read: 28,
+                ..
+            }))
+        ));
+        assert!(dset_reader.next().is_none());
+    }
+
+    #[test]
+    fn read_attribute_tags_in_either_byte_order() {
+        static DATA_LE: &[u8] = &[
+            0x28, 0x00, 0x09, 0x00, // tag: (0028,0009) FrameIncrementPointer
+            b'A', b'T', // VR
+            0x08, 0x00, // length: 8
+            0x18, 0x00, 0x63, 0x10, // (0018,1063) FrameTime
+            0x18, 0x00, 0x65, 0x10, // (0018,1065) FrameTimeVector
+        ];
+        static DATA_BE: &[u8] = &[
+            0x00, 0x28, 0x00, 0x09, // tag: (0028,0009) FrameIncrementPointer
+            b'A', b'T', // VR
+            0x00, 0x08, // length: 8
+            0x00, 0x18, 0x10, 0x63, // (0018,1063) FrameTime
+            0x00, 0x18, 0x10, 0x65, // (0018,1065) FrameTimeVector
+        ];
+
+        let ground_truth = vec![
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0028, 0x0009),
+                vr: VR::AT,
+                len: Length(8),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::Tags(
+                vec![Tag(0x0018, 0x1063), Tag(0x0018, 0x1065)].into(),
+            )),
+        ];
+
+        validate_dataset_reader_explicit_vr(DATA_LE, ground_truth.clone());
+
+        let mut cursor = DATA_BE;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRBigEndianDecoder::default(),
+            BigEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>, // trait object
+        );
+        validate_dataset_reader(DATA_BE, parser, ground_truth);
+    }
 }
@@ -0,0 +1,225 @@
+//! Push-style processing of token streams.
+use crate::dataset::DataToken;
+use crate::marker::DicomElementMarker;
+use dicom_core::header::{DataElementHeader, Length};
+use dicom_core::value::PrimitiveValue;
+use dicom_core::Tag;
+
+/// A visitor of data set tokens,
+/// with one method per kind of token.
+///
+/// Each method does nothing by default,
+/// so that visitors only need to implement the ones they care about.
+/// Tokens are handed over by value,
+/// so a visitor may keep their contents without copying them.
+/// Use [`drive`] to feed a token stream to a visitor.
+///
+/// [`drive`]: ./fn.drive.html
+pub trait TokenVisitor {
+    /// Visit the header of a primitive data element.
+    fn visit_element_header(&mut self, _header: DataElementHeader) {}
+
+    /// Visit a primitive data element value.
+    fn visit_primitive(&mut self, _value: PrimitiveValue) {}
+
+    /// Visit a primitive value which was skipped by the reader.
+    fn visit_lazy_value(&mut self, _marker: DicomElementMarker) {}
+
+    /// Visit the beginning of a sequence element.
+    fn visit_sequence_start(&mut self, _tag: Tag, _len: Length) {}
+
+    /// Visit the beginning of an encapsulated pixel data element.
+    fn visit_pixel_sequence_start(&mut self) {}
+
+    /// Visit the end of a sequence or of encapsulated pixel data.
+    fn visit_sequence_end(&mut self) {}
+
+    /// Visit the beginning of an item.
+    fn visit_item_start(&mut self, _len: Length) {}
+
+    /// Visit the end of an item.
+    fn visit_item_end(&mut self) {}
+
+    /// Visit a piece of raw data,
+    /// such as an offset table, a pixel data fragment,
+    /// or a chunk of a primitive value.
+    fn visit_item_value(&mut self, _data: Vec<u8>) {}
+}
+
+/// Feed all of the given tokens to the visitor, in order,
+/// calling the visitor method for each kind of token.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+/// # use dicom_parser::dataset::{drive, DataToken, TokenVisitor};
+/// /// Collects the tags of all elements in the data set.
+/// #[derive(Default)]
+/// struct TagCollector(Vec<Tag>);
+///
+/// impl TokenVisitor for TagCollector {
+///     fn visit_element_header(&mut self, header: DataElementHeader) {
+///         self.0.push(header.tag);
+///     }
+///
+///     fn visit_sequence_start(&mut self, tag: Tag, _len: Length) {
+///         self.0.push(tag);
+///     }
+/// }
+///
+/// let tokens = vec![
+///     DataToken::SequenceStart { tag: Tag(0x0008, 0x1115), len: Length::UNDEFINED },
+///     DataToken::ItemStart { len: Length::UNDEFINED },
+///     DataToken::ElementHeader(DataElementHeader::new(Tag(0x0020, 0x000E), VR::UI, Length(6))),
+///     DataToken::PrimitiveValue(PrimitiveValue::from("1.2.3\0")),
+///     DataToken::ItemEnd,
+///     DataToken::SequenceEnd,
+/// ];
+///
+/// let mut collector = TagCollector::default();
+/// drive(tokens, &mut collector);
+/// assert_eq!(collector.0, vec![Tag(0x0008, 0x1115), Tag(0x0020, 0x000E)]);
+/// ```
+pub fn drive<V, I>(tokens: I, visitor: &mut V)
+where
+    V: TokenVisitor + ?Sized,
+    I: IntoIterator<Item = DataToken>,
+{
+    for token in tokens {
+        match token {
+            DataToken::ElementHeader(header) => visitor.visit_element_header(header),
+            DataToken::PrimitiveValue(value) => visitor.visit_primitive(value),
+            DataToken::LazyValue(marker) => visitor.visit_lazy_value(marker),
+            DataToken::SequenceStart { tag, len } => visitor.visit_sequence_start(tag, len),
+            DataToken::PixelSequenceStart => visitor.visit_pixel_sequence_start(),
+            DataToken::SequenceEnd => visitor.visit_sequence_end(),
+            DataToken::ItemStart { len } => visitor.visit_item_start(len),
+            DataToken::ItemEnd => visitor.visit_item_end(),
+            DataToken::ItemValue(data) => visitor.visit_item_value(data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{drive, TokenVisitor};
+    use crate::dataset::DataToken;
+    use dicom_core::header::{DataElementHeader, Length};
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+
+    /// Records every visit as a line of text.
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl TokenVisitor for Recorder {
+        fn visit_element_header(&mut self, header: DataElementHeader) {
+            self.0.push(format!("header {}", header.tag));
+        }
+
+        fn visit_primitive(&mut self, value: PrimitiveValue) {
+            self.0.push(format!("value {}", value.to_str()));
+        }
+
+        fn visit_sequence_start(&mut self, tag: Tag, _len: Length) {
+            self.0.push(format!("sequence {}", tag));
+        }
+
+        fn visit_pixel_sequence_start(&mut self) {
+            self.0.push("pixel sequence".to_string());
+        }
+
+        fn visit_sequence_end(&mut self) {
+            self.0.push("sequence end".to_string());
+        }
+
+        fn visit_item_start(&mut self, _len: Length) {
+            self.0.push("item".to_string());
+        }
+
+        fn visit_item_end(&mut self) {
+            self.0.push("item end".to_string());
+        }
+
+        fn visit_item_value(&mut self, data: Vec<u8>) {
+            self.0.push(format!("item value {}", data.len()));
+        }
+    }
+
+    /// Only counts primitive values, relying on the default methods.
+    #[derive(Default)]
+    struct ValueCounter(usize);
+
+    impl TokenVisitor for ValueCounter {
+        fn visit_primitive(&mut self, _value: PrimitiveValue) {
+            self.0 += 1;
+        }
+    }
+
+    fn tokens() -> Vec<DataToken> {
+        vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(8),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1115),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0020, 0x000E),
+                VR::UI,
+                Length(6),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.3\0")),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(4) },
+            DataToken::ItemValue(vec![0x99; 4]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ]
+    }
+
+    #[test]
+    fn visit_all_tokens_in_order() {
+        let mut recorder = Recorder::default();
+        drive(tokens(), &mut recorder);
+        assert_eq!(
+            recorder.0,
+            vec![
+                "header (0010,0010)",
+                "value Doe^John",
+                "sequence (0008,1115)",
+                "item",
+                "header (0020,000E)",
+                "value 1.2.3\0",
+                "item end",
+                "sequence end",
+                "pixel sequence",
+                "item",
+                "item end",
+                "item",
+                "item value 4",
+                "item end",
+                "sequence end",
+            ]
+        );
+    }
+
+    #[test]
+    fn visit_with_default_methods() {
+        let mut counter = ValueCounter::default();
+        drive(tokens(), &mut counter);
+        assert_eq!(counter.0, 2);
+    }
+}
@@ -1,10 +1,15 @@
 //! Module for the data set reader
+use crate::dataset::adaptor::chunks::padding_of;
+use crate::dataset::adaptor::{StripGroupLengths, WithDepth};
 use crate::dataset::*;
 use crate::stateful::encode::StatefulEncoder;
 use dicom_core::{DataElementHeader, Length, VR};
-use dicom_encoding::encode::EncodeTo;
+use dicom_encoding::encode::{EncodeTo, EncoderFor};
 use dicom_encoding::text::{SpecificCharacterSet, TextCodec};
+use dicom_encoding::transfer_syntax::explicit_le::ExplicitVRLittleEndianEncoder;
+use dicom_encoding::transfer_syntax::implicit_le::ImplicitVRLittleEndianEncoder;
 use dicom_encoding::transfer_syntax::DynEncoder;
+use dicom_encoding::transfer_syntax::PixelEncoding;
 use dicom_encoding::TransferSyntax;
 use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
 use std::io::Write;
@@ -59,6 +64,48 @@ pub enum Error {
         #[snafu(backtrace)]
         source: crate::stateful::encode::Error,
     },
+
+    /// A value token marked a value which was never read
+    #[snafu(display("Cannot write value at position {} which was not read", position))]
+    UnreadValue { position: u64, backtrace: Backtrace },
+
+    /// An element appeared out of ascending tag order
+    /// while strict ordering was requested
+    #[snafu(display("Tag {} is not in ascending order after {}", tag, previous))]
+    UnorderedTag {
+        tag: Tag,
+        previous: Tag,
+        backtrace: Backtrace,
+    },
+
+    /// A sequence or item of undefined length appeared
+    /// while the encoding policy does not allow them
+    #[snafu(display("Undefined length of {} not allowed by the encoding policy", tag))]
+    UndefinedLength { tag: Tag, backtrace: Backtrace },
+
+    /// Encapsulated pixel data appeared
+    /// while the encoding policy does not allow it
+    #[snafu(display("Encapsulated pixel data not allowed by the encoding policy"))]
+    UnexpectedPixelSequence { backtrace: Backtrace },
+
+    #[snafu(display("Could not write value padding"))]
+    WritePadding {
+        #[snafu(backtrace)]
+        source: crate::stateful::encode::Error,
+    },
+
+    /// The transfer syntax UID of the file meta group
+    /// does not match the encoding of the writer
+    #[snafu(display(
+        "Declared transfer syntax {} does not match the encoding {}",
+        declared,
+        expected
+    ))]
+    TransferSyntaxMismatch {
+        declared: String,
+        expected: &'static str,
+        backtrace: Backtrace,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -73,6 +120,206 @@ struct SeqToken {
     len: Length,
 }
 
+/// The tokens of a file meta information group
+/// held back until its group length is known.
+#[derive(Debug, Default)]
+struct MetaGroup {
+    /// the tokens of the group, starting with the group length element
+    tokens: Vec<DataToken>,
+    /// the number of sequences and items currently open in the group
+    depth: u32,
+}
+
+/// The rules on lengths, delimiters and padding
+/// which the data set writer follows,
+/// as they apply to a transfer syntax.
+///
+/// Delimiters are always written at the end of
+/// sequences and items of undefined length, and never otherwise.
+/// The policy states whether lengths may be undefined in the first place,
+/// and whether delimiters still missing at the end of the tokens
+/// are written by the writer.
+///
+/// The [default](#impl-Default) policy lets tokens through as they are.
+/// Presets are available for transfer syntaxes
+/// with native or encapsulated pixel data,
+/// or via [`for_transfer_syntax`](#method.for_transfer_syntax).
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct EncodingPolicy {
+    /// Whether sequences and items may have an undefined length.
+    /// If not, a sequence or item of undefined length is an error.
+    /// This does not apply to encapsulated pixel data,
+    /// which always has an undefined length.
+    pub allow_undefined_length: bool,
+    /// Whether encapsulated pixel data may be written.
+    pub allow_encapsulated_pixel_data: bool,
+    /// Whether to write the delimiters of the items and sequences
+    /// of undefined length still open
+    /// at the end of the tokens given to [`write_sequence`].
+    ///
+    /// [`write_sequence`]: ./struct.DataSetWriter.html#method.write_sequence
+    pub close_undefined_lengths: bool,
+    /// Whether to pad values of odd length to even length,
+    /// as required by the standard,
+    /// in the same way as the [`PadToEvenLength`] adaptor.
+    /// Textual values are always padded when encoded,
+    /// so this mostly fixes up the odd lengths declared in their headers.
+    /// The explicit lengths of sequences
+    /// and of items holding a data set are not recomputed.
+    ///
+    /// [`PadToEvenLength`]: ../adaptor/pad/struct.PadToEvenLength.html
+    pub pad_to_even_length: bool,
+}
+
+impl Default for EncodingPolicy {
+    fn default() -> Self {
+        EncodingPolicy {
+            allow_undefined_length: true,
+            allow_encapsulated_pixel_data: true,
+            close_undefined_lengths: false,
+            pad_to_even_length: false,
+        }
+    }
+}
+
+impl EncodingPolicy {
+    /// The policy for transfer syntaxes with native pixel data,
+    /// such as _Implicit VR Little Endian_, _Explicit VR Little Endian_
+    /// and _Deflated Explicit VR Little Endian_.
+    pub const NATIVE: EncodingPolicy = EncodingPolicy {
+        allow_undefined_length: true,
+        allow_encapsulated_pixel_data: false,
+        close_undefined_lengths: true,
+        pad_to_even_length: true,
+    };
+
+    /// The policy for transfer syntaxes with encapsulated pixel data,
+    /// such as those of JPEG or RLE.
+    pub const ENCAPSULATED: EncodingPolicy = EncodingPolicy {
+        allow_undefined_length: true,
+        allow_encapsulated_pixel_data: true,
+        close_undefined_lengths: true,
+        pad_to_even_length: true,
+    };
+
+    /// Obtain the policy preset for the given transfer syntax,
+    /// depending on the encoding of its pixel data.
+    /// Unrecognized transfer syntaxes may have encapsulated pixel data.
+    pub fn for_transfer_syntax<A>(ts: &TransferSyntax<A>) -> Self {
+        match ts.pixel_encoding() {
+            PixelEncoding::Native => EncodingPolicy::NATIVE,
+            _ => EncodingPolicy::ENCAPSULATED,
+        }
+    }
+
+    /// Replace the undefined length option.
+    pub fn allow_undefined_length(mut self, allow: bool) -> Self {
+        self.allow_undefined_length = allow;
+        self
+    }
+
+    /// Replace the encapsulated pixel data option.
+    pub fn allow_encapsulated_pixel_data(mut self, allow: bool) -> Self {
+        self.allow_encapsulated_pixel_data = allow;
+        self
+    }
+
+    /// Replace the option to close undefined lengths.
+    pub fn close_undefined_lengths(mut self, close: bool) -> Self {
+        self.close_undefined_lengths = close;
+        self
+    }
+
+    /// Replace the even length padding option.
+    pub fn pad_to_even_length(mut self, pad: bool) -> Self {
+        self.pad_to_even_length = pad;
+        self
+    }
+}
+
+/// What the data set writer does with the _Transfer Syntax UID_ `(0002,0010)`
+/// of the file meta group,
+/// when it knows the transfer syntax that it writes in.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum MetaTransferSyntax {
+    /// Write the UID as given.
+    #[default]
+    Keep,
+    /// Fail with [`Error::TransferSyntaxMismatch`]
+    /// if the UID is not the one of the encoding.
+    ///
+    /// [`Error::TransferSyntaxMismatch`]: ./enum.Error.html#variant.TransferSyntaxMismatch
+    Verify,
+    /// Replace the UID with the one of the encoding.
+    Rewrite,
+}
+
+/// The set of options for the data set writer.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct DataSetWriterOptions {
+    /// Whether to fail when an element does not come after
+    /// the previous element of the same data set in ascending tag order,
+    /// as required by the standard.
+    /// Tokens are otherwise written in the order given.
+    pub strict_ordering: bool,
+    /// Whether to replace the value of the file meta information
+    /// group length `(0002,0000)` with the encoded length
+    /// of the remaining group `0002` elements.
+    /// The elements after the group length are then held back
+    /// until the end of the group is reached.
+    pub recompute_meta_group_length: bool,
+    /// The rules on lengths, delimiters and padding to follow.
+    pub policy: EncodingPolicy,
+    /// What to do with the transfer syntax UID of the file meta group.
+    /// This only applies to writers created for a given transfer syntax
+    /// or VR encoding, such as with [`with_ts_cs_options`].
+    ///
+    /// [`with_ts_cs_options`]: ./struct.DataSetWriter.html#method.with_ts_cs_options
+    pub meta_transfer_syntax: MetaTransferSyntax,
+}
+
+impl DataSetWriterOptions {
+    /// Replace the strict tag ordering option.
+    pub fn strict_ordering(mut self, strict_ordering: bool) -> Self {
+        self.strict_ordering = strict_ordering;
+        self
+    }
+
+    /// Replace the meta group length recomputation option.
+    pub fn recompute_meta_group_length(mut self, recompute: bool) -> Self {
+        self.recompute_meta_group_length = recompute;
+        self
+    }
+
+    /// Replace the encoding policy.
+    pub fn policy(mut self, policy: EncodingPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Replace the option on the transfer syntax UID of the file meta group.
+    pub fn meta_transfer_syntax(mut self, meta_transfer_syntax: MetaTransferSyntax) -> Self {
+        self.meta_transfer_syntax = meta_transfer_syntax;
+        self
+    }
+}
+
+/// The way in which value representations are written
+/// in a little endian data set.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum VrEncoding {
+    /// Write the VR of each element,
+    /// as in _Explicit VR Little Endian_ (`1.2.840.10008.1.2.1`).
+    Explicit,
+    /// Omit the VR of each element and always use 4-byte lengths,
+    /// as in _Implicit VR Little Endian_ (`1.2.840.10008.1.2`).
+    /// The VRs in the element header tokens are then only used
+    /// for encoding the values.
+    Implicit,
+}
+
 /// A stateful device for printing a DICOM data set in sequential order.
 /// This is analogous to the `DatasetReader` type for converting data
 /// set tokens to bytes.
@@ -81,6 +328,24 @@ pub struct DataSetWriter<W, E, T> {
     printer: StatefulEncoder<W, E, T>,
     seq_tokens: Vec<SeqToken>,
     last_de: Option<DataElementHeader>,
+    options: DataSetWriterOptions,
+    /// the last tag written in each data set level
+    last_tags: Vec<Option<Tag>>,
+    /// the file meta group being held back, if any
+    meta_group: Option<MetaGroup>,
+    /// whether the items being written are pixel data fragments
+    in_pixel_sequence: bool,
+    /// the number of value bytes still to be written
+    /// before a padding byte, and the padding byte
+    pending_pad: Option<(u32, u8)>,
+    /// the UID of the transfer syntax written in, if known
+    ts_uid: Option<&'static str>,
+    /// the header of the transfer syntax UID element,
+    /// held back until its value is checked
+    pending_ts_header: Option<DataElementHeader>,
+    /// the header of pixel data of undefined length,
+    /// held back in case a pixel sequence start follows
+    pending_pixel_header: Option<DataElementHeader>,
 }
 
 impl<'w, W: 'w> DataSetWriter<W, DynEncoder<'w, W>, Box<dyn TextCodec>>
@@ -88,6 +353,22 @@ where
     W: Write,
 {
     pub fn with_ts_cs(to: W, ts: &TransferSyntax, charset: SpecificCharacterSet) -> Result<Self> {
+        DataSetWriter::with_ts_cs_options(to, ts, charset, Default::default())
+    }
+
+    /// Create a writer of data sets in the given transfer syntax
+    /// and character set, with the given options.
+    ///
+    /// The transfer syntax does not imply an encoding policy:
+    /// see [`EncodingPolicy::for_transfer_syntax`].
+    ///
+    /// [`EncodingPolicy::for_transfer_syntax`]: ./struct.EncodingPolicy.html#method.for_transfer_syntax
+    pub fn with_ts_cs_options(
+        to: W,
+        ts: &TransferSyntax,
+        charset: SpecificCharacterSet,
+        options: DataSetWriterOptions,
+    ) -> Result<Self> {
         let encoder = ts.encoder_for().context(UnsupportedTransferSyntax {
             ts_uid: ts.uid(),
             ts_alias: ts.name(),
@@ -95,17 +376,71 @@ where
         let text = charset
             .codec()
             .context(UnsupportedCharacterSet { charset })?;
-        Ok(DataSetWriter::new(to, encoder, text))
+        let mut writer = DataSetWriter::new_with_options(to, encoder, text, options);
+        writer.ts_uid = Some(ts.uid());
+        Ok(writer)
+    }
+
+    /// Create a writer of little endian data sets
+    /// with the given VR encoding and character set.
+    pub fn with_vr_encoding(
+        to: W,
+        vr_encoding: VrEncoding,
+        charset: SpecificCharacterSet,
+    ) -> Result<Self> {
+        let (encoder, uid): (DynEncoder<'w, W>, _) = match vr_encoding {
+            VrEncoding::Explicit => (
+                Box::new(EncoderFor::new(ExplicitVRLittleEndianEncoder::default())),
+                "1.2.840.10008.1.2.1",
+            ),
+            VrEncoding::Implicit => (
+                Box::new(EncoderFor::new(ImplicitVRLittleEndianEncoder::default())),
+                "1.2.840.10008.1.2",
+            ),
+        };
+        let text = charset
+            .codec()
+            .context(UnsupportedCharacterSet { charset })?;
+        let mut writer = DataSetWriter::new(to, encoder, text);
+        writer.ts_uid = Some(uid);
+        Ok(writer)
     }
 }
 
 impl<W, E, T> DataSetWriter<W, E, T> {
     pub fn new(to: W, encoder: E, text: T) -> Self {
+        DataSetWriter::new_with_options(to, encoder, text, Default::default())
+    }
+
+    /// Create a new data set writer with the given options.
+    pub fn new_with_options(to: W, encoder: E, text: T, options: DataSetWriterOptions) -> Self {
         DataSetWriter {
             printer: StatefulEncoder::new(to, encoder, text),
             seq_tokens: Vec::new(),
             last_de: None,
+            options,
+            last_tags: vec![None],
+            meta_group: None,
+            in_pixel_sequence: false,
+            pending_pad: None,
+            ts_uid: None,
+            pending_ts_header: None,
+            pending_pixel_header: None,
+        }
+    }
+
+    /// Keep track of the tag of the element about to be written,
+    /// checking for its order if requested.
+    fn check_tag_order(&mut self, tag: Tag) -> Result<()> {
+        if let Some(last_tag) = self.last_tags.last_mut() {
+            if let Some(previous) = *last_tag {
+                if self.options.strict_ordering && tag <= previous {
+                    return UnorderedTag { tag, previous }.fail();
+                }
+            }
+            *last_tag = Some(tag);
         }
+        Ok(())
     }
 }
 
@@ -116,6 +451,13 @@ where
     T: TextCodec,
 {
     /// Feed the given sequence of tokens which are part of the same data set.
+    ///
+    /// A file meta group still held back at the end of the sequence
+    /// is written as well,
+    /// and so are the delimiters of the sequences and items left open
+    /// if the [encoding policy] asks for it.
+    ///
+    /// [encoding policy]: ./struct.EncodingPolicy.html#structfield.close_undefined_lengths
     #[inline]
     pub fn write_sequence<I>(&mut self, tokens: I) -> Result<()>
     where
@@ -125,33 +467,202 @@ where
             self.write(token)?;
         }
 
+        if let Some(header) = self.pending_ts_header.take() {
+            self.write_token_inner(&DataToken::ElementHeader(header))?;
+        }
+        if let Some(header) = self.pending_pixel_header.take() {
+            self.write_element_header(&header)?;
+        }
+        self.flush_meta_group()?;
+        if self.options.policy.close_undefined_lengths {
+            self.close_open_sequences()?;
+        }
+        Ok(())
+    }
+
+    /// End all sequences and items still open,
+    /// writing the delimiters of those of undefined length.
+    fn close_open_sequences(&mut self) -> Result<()> {
+        while let Some(seq_token) = self.seq_tokens.last() {
+            let token = match seq_token.typ {
+                SeqTokenType::Sequence => DataToken::SequenceEnd,
+                SeqTokenType::Item => DataToken::ItemEnd,
+            };
+            self.write_token(&token)?;
+        }
         Ok(())
     }
 
     /// Feed the given data set token for writing the data set.
+    #[inline]
     pub fn write(&mut self, token: DataToken) -> Result<()> {
-        // adjust the logic of sequence printing:
-        // explicit length sequences or items should not print
-        // the respective delimiter
+        self.write_token(&token)
+    }
+
+    /// Write a single data set token, without taking ownership of it.
+    ///
+    /// The writer keeps track of the sequences and items open
+    /// and of the last element header between calls,
+    /// so that tokens may be pushed one at a time as they are produced,
+    /// with the same outcome as writing them all at once.
+    /// No bytes are held back by the writer:
+    /// once this function returns,
+    /// the token was passed in full to the underlying writer.
+    /// The only exceptions are the file meta group
+    /// when [`recompute_meta_group_length`] is enabled,
+    /// which is written once an element outside of the group arrives,
+    /// or when calling [`flush_meta_group`],
+    /// and the header of _Pixel Data_ of undefined length,
+    /// which is written with the next token.
+    /// A [`PixelSequenceStart`] right after that header
+    /// stands for the same header, which is then only written once.
+    ///
+    /// [`recompute_meta_group_length`]: ./struct.DataSetWriterOptions.html#structfield.recompute_meta_group_length
+    /// [`flush_meta_group`]: #method.flush_meta_group
+    /// [`PixelSequenceStart`]: ../enum.DataToken.html#variant.PixelSequenceStart
+    pub fn write_token(&mut self, token: &DataToken) -> Result<()> {
+        let ts_uid = match self.ts_uid {
+            Some(uid) if self.options.meta_transfer_syntax != MetaTransferSyntax::Keep => uid,
+            _ => return self.write_token_inner(token),
+        };
+        match (self.pending_ts_header.take(), token) {
+            (None, DataToken::ElementHeader(header))
+                if header.tag == Tag(0x0002, 0x0010)
+                    && self.seq_tokens.is_empty()
+                    && !matches!(&self.meta_group, Some(group) if group.depth > 0) =>
+            {
+                self.pending_ts_header = Some(*header);
+                Ok(())
+            }
+            (Some(header), DataToken::PrimitiveValue(value)) => {
+                let (header, value) = self.check_transfer_syntax(header, value, ts_uid)?;
+                self.write_token_inner(&DataToken::ElementHeader(header))?;
+                self.write_token_inner(&DataToken::PrimitiveValue(value))
+            }
+            (Some(header), token) => {
+                self.write_token_inner(&DataToken::ElementHeader(header))?;
+                self.write_token_inner(token)
+            }
+            (None, token) => self.write_token_inner(token),
+        }
+    }
+
+    /// Check the value of the transfer syntax UID of the file meta group
+    /// against the UID of the transfer syntax written in,
+    /// obtaining the element to write in its place.
+    fn check_transfer_syntax(
+        &self,
+        header: DataElementHeader,
+        value: &PrimitiveValue,
+        ts_uid: &'static str,
+    ) -> Result<(DataElementHeader, PrimitiveValue)> {
+        let declared = value.to_str();
+        let declared = declared.trim_end_matches(&['\0', ' '][..]);
+        if declared == ts_uid {
+            return Ok((header, value.clone()));
+        }
+        if self.options.meta_transfer_syntax == MetaTransferSyntax::Verify {
+            return TransferSyntaxMismatch {
+                declared,
+                expected: ts_uid,
+            }
+            .fail();
+        }
+        let mut uid = ts_uid.to_string();
+        if uid.len() & 1 == 1 {
+            uid.push('\0');
+        }
+        let header = DataElementHeader::new(header.tag, header.vr, Length(uid.len() as u32));
+        Ok((header, PrimitiveValue::from(uid)))
+    }
+
+    /// Write a single data set token,
+    /// once the transfer syntax UID of the file meta group is checked.
+    fn write_token_inner(&mut self, token: &DataToken) -> Result<()> {
+        if let Some(meta_group) = &mut self.meta_group {
+            if meta_group.depth > 0 || !starts_element_outside_meta_group(token) {
+                match token {
+                    DataToken::SequenceStart { .. }
+                    | DataToken::PixelSequenceStart
+                    | DataToken::ItemStart { .. } => meta_group.depth += 1,
+                    DataToken::SequenceEnd | DataToken::ItemEnd => {
+                        meta_group.depth = meta_group.depth.saturating_sub(1)
+                    }
+                    _ => {}
+                }
+                meta_group.tokens.push(token.clone());
+                return Ok(());
+            }
+            self.flush_meta_group()?;
+        } else if self.options.recompute_meta_group_length && self.seq_tokens.is_empty() {
+            if let DataToken::ElementHeader(header) = token {
+                if header.tag == Tag(0x0002, 0x0000) {
+                    self.meta_group = Some(MetaGroup {
+                        tokens: vec![token.clone()],
+                        depth: 0,
+                    });
+                    return Ok(());
+                }
+            }
+        }
+
+        // whether the header of the pixel data was already taken in
+        let mut pixel_header_checked = false;
+        if let Some(header) = self.pending_pixel_header.take() {
+            if *token == DataToken::PixelSequenceStart {
+                // the header of the pixel sequence, written with it
+                pixel_header_checked = true;
+            } else {
+                self.write_element_header(&header)?;
+            }
+        }
+
+        // explicit length sequences or items must not print
+        // the respective delimiter,
+        // so the starting length of each construct is kept in a stack
 
         match token {
-            DataToken::SequenceStart { len, .. } => {
+            DataToken::SequenceStart { tag, len } => {
+                self.check_tag_order(*tag)?;
+                if len.is_undefined() && !self.options.policy.allow_undefined_length {
+                    return UndefinedLength { tag: *tag }.fail();
+                }
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Sequence,
-                    len,
+                    len: *len,
                 });
                 self.write_impl(token)?;
                 Ok(())
             }
             DataToken::ItemStart { len } => {
+                if self.in_pixel_sequence {
+                    // a fragment is a value of its own
+                    let len = self.padded_length(*len, 0).unwrap_or(*len);
+                    self.seq_tokens.push(SeqToken {
+                        typ: SeqTokenType::Item,
+                        len,
+                    });
+                    return self.write_impl(&DataToken::ItemStart { len });
+                }
+                if len.is_undefined() && !self.options.policy.allow_undefined_length {
+                    return UndefinedLength {
+                        tag: Tag(0xFFFE, 0xE000),
+                    }
+                    .fail();
+                }
+                // each item is a new data set
+                self.last_tags.push(None);
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Item,
-                    len,
+                    len: *len,
                 });
                 self.write_impl(token)?;
                 Ok(())
             }
             DataToken::ItemEnd => {
+                if !self.in_pixel_sequence {
+                    self.last_tags.pop();
+                }
                 // only write if it's an unknown length item
                 if let Some(seq_start) = self.seq_tokens.pop() {
                     if seq_start.typ == SeqTokenType::Item && seq_start.len.is_undefined() {
@@ -161,6 +672,7 @@ where
                 Ok(())
             }
             DataToken::SequenceEnd => {
+                self.in_pixel_sequence = false;
                 // only write if it's an unknown length sequence
                 if let Some(seq_start) = self.seq_tokens.pop() {
                     if seq_start.typ == SeqTokenType::Sequence && seq_start.len.is_undefined() {
@@ -169,33 +681,161 @@ where
                 }
                 Ok(())
             }
+            DataToken::ElementHeader(de)
+                if de.tag == Tag(0x7fe0, 0x0010) && de.len.is_undefined() =>
+            {
+                self.check_tag_order(de.tag)?;
+                self.pending_pixel_header = Some(*de);
+                Ok(())
+            }
             DataToken::ElementHeader(de) => {
-                self.last_de = Some(de);
-                self.write_impl(token)
+                self.check_tag_order(de.tag)?;
+                self.write_element_header(de)
             }
-            token @ DataToken::PixelSequenceStart => {
+            DataToken::PixelSequenceStart => {
+                if !pixel_header_checked {
+                    self.check_tag_order(Tag(0x7fe0, 0x0010))?;
+                }
+                if !self.options.policy.allow_encapsulated_pixel_data {
+                    return UnexpectedPixelSequence.fail();
+                }
+                self.in_pixel_sequence = true;
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Sequence,
                     len: Length::UNDEFINED,
                 });
                 self.write_impl(token)
             }
-            token @ DataToken::ItemValue(_) | token @ DataToken::PrimitiveValue(_) => {
-                self.write_impl(token)
+            DataToken::PrimitiveValue(_) | DataToken::LazyValue(_) => {
+                let start = self.printer.bytes_written();
+                self.write_impl(token)?;
+                if (self.printer.bytes_written() - start) & 1 == 0 {
+                    // already padded by the encoder
+                    self.pending_pad = None;
+                }
+                self.write_padding(u32::MAX)
+            }
+            DataToken::ItemValue(data) => {
+                self.write_impl(token)?;
+                self.write_padding(data.len() as u32)
+            }
+        }
+    }
+
+    /// Write the given element header,
+    /// padding its length if required.
+    fn write_element_header(&mut self, de: &DataElementHeader) -> Result<()> {
+        match self.padded_length(de.len, padding_of(de.vr)) {
+            Some(len) => {
+                let de = DataElementHeader::new(de.tag, de.vr, len);
+                self.last_de = Some(de);
+                self.write_impl(&DataToken::ElementHeader(de))
+            }
+            None => {
+                self.last_de = Some(*de);
+                self.write_impl(&DataToken::ElementHeader(*de))
+            }
+        }
+    }
+
+    /// Obtain the even length to write in place of the given odd length
+    /// if padding values is required,
+    /// keeping track of the padding byte to write after the value.
+    fn padded_length(&mut self, len: Length, padding: u8) -> Option<Length> {
+        self.pending_pad = None;
+        if !self.options.policy.pad_to_even_length {
+            return None;
+        }
+        match len.get() {
+            Some(l) if l & 1 == 1 => {
+                self.pending_pad = Some((l, padding));
+                Some(Length(l + 1))
+            }
+            _ => None,
+        }
+    }
+
+    /// Write the pending padding byte
+    /// once the given number of value bytes has completed the value.
+    fn write_padding(&mut self, written: u32) -> Result<()> {
+        if let Some((remaining, padding)) = self.pending_pad {
+            if written >= remaining {
+                self.pending_pad = None;
+                self.printer.write_bytes(&[padding]).context(WritePadding)?;
+            } else {
+                self.pending_pad = Some((remaining - written, padding));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the file meta group held back by the writer, if any,
+    /// with the file meta information group length `(0002,0000)`
+    /// set to the encoded length of the remaining elements in the group.
+    ///
+    /// The group is written in _Explicit VR Little Endian_,
+    /// the encoding mandated for the file meta group,
+    /// whatever the encoding of the rest of the data set.
+    /// This only needs to be called when writing tokens one at a time
+    /// and no element outside of the group follows.
+    pub fn flush_meta_group(&mut self) -> Result<()> {
+        let meta_group = match self.meta_group.take() {
+            Some(meta_group) => meta_group,
+            None => return Ok(()),
+        };
+        let tokens: Vec<_> = StripGroupLengths::new(meta_group.tokens)
+            .strip_meta_group_length(true)
+            .collect();
+
+        let mut meta_data = Vec::new();
+        let mut meta_writer = DataSetWriter::with_vr_encoding(
+            &mut meta_data,
+            VrEncoding::Explicit,
+            SpecificCharacterSet::Default,
+        )?;
+        // the group is padded as the rest of the data set
+        meta_writer.options.policy = self.options.policy;
+        meta_writer.write_sequence(tokens.iter().cloned())?;
+        drop(meta_writer);
+
+        self.check_tag_order(Tag(0x0002, 0x0000))?;
+        for (depth, token) in WithDepth::new(tokens) {
+            if let Some(tag) = token.element_tag().filter(|_| depth == 0) {
+                self.check_tag_order(tag)?;
             }
         }
+
+        let mut group_length = Vec::new();
+        DataSetWriter::with_vr_encoding(
+            &mut group_length,
+            VrEncoding::Explicit,
+            SpecificCharacterSet::Default,
+        )?
+        .write_sequence(vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0000),
+                VR::UL,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(meta_data.len() as u32)),
+        ])?;
+        // the group, already encoded
+        self.printer
+            .write_bytes(&group_length)
+            .context(WriteValue)?;
+        self.printer.write_bytes(&meta_data).context(WriteValue)
     }
 
-    fn write_impl(&mut self, token: DataToken) -> Result<()> {
+    fn write_impl(&mut self, token: &DataToken) -> Result<()> {
         match token {
             DataToken::ElementHeader(header) => {
                 self.printer
-                    .encode_element_header(header)
+                    .encode_element_header(*header)
                     .context(WriteHeader)?;
             }
             DataToken::SequenceStart { tag, len } => {
                 self.printer
-                    .encode_element_header(DataElementHeader::new(tag, VR::SQ, len))
+                    .encode_element_header(DataElementHeader::new(*tag, VR::SQ, *len))
                     .context(WriteHeader)?;
             }
             DataToken::PixelSequenceStart => {
@@ -222,7 +862,7 @@ where
                     .encode_item_delimiter()
                     .context(WriteItemDelimiter)?;
             }
-            DataToken::PrimitiveValue(ref value) => {
+            DataToken::PrimitiveValue(value) => {
                 let last_de = self.last_de.as_ref().with_context(|| UnexpectedToken {
                     token: token.clone(),
                 })?;
@@ -232,25 +872,265 @@ where
                 self.last_de = None;
             }
             DataToken::ItemValue(data) => {
-                self.printer.write_bytes(&data).context(WriteValue)?;
+                self.printer.write_bytes(data).context(WriteValue)?;
+            }
+            DataToken::LazyValue(marker) => {
+                return UnreadValue {
+                    position: marker.pos,
+                }
+                .fail();
             }
         }
         Ok(())
     }
 }
 
+/// Give every item in the given tokens the defined length of its content
+/// as encoded with the given VR encoding,
+/// measuring the sequences of defined length again accordingly.
+///
+/// Content is measured as written with the default character set
+/// and values padded to even length.
+/// Constructs whose content cannot be written, or which are never closed,
+/// keep their length.
+/// Pixel data fragments are left as they are.
+pub(crate) fn define_item_lengths(
+    tokens: Vec<DataToken>,
+    vr_encoding: VrEncoding,
+) -> Vec<DataToken> {
+    // the start token and the content so far of each construct still open
+    let mut open: Vec<(DataToken, Vec<DataToken>)> = Vec::new();
+    let mut out = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let closes_open = matches!(
+            (open.last().map(|(start, _)| start), &token),
+            (Some(DataToken::ItemStart { .. }), DataToken::ItemEnd)
+                | (
+                    Some(DataToken::SequenceStart { .. }),
+                    DataToken::SequenceEnd
+                )
+                | (Some(DataToken::PixelSequenceStart), DataToken::SequenceEnd)
+        );
+        let in_pixel_sequence = matches!(open.last(), Some((DataToken::PixelSequenceStart, _)));
+        match token {
+            DataToken::SequenceStart { .. } | DataToken::PixelSequenceStart => {
+                open.push((token, Vec::new()))
+            }
+            DataToken::ItemStart { .. } if !in_pixel_sequence => open.push((token, Vec::new())),
+            token if closes_open => {
+                let (start, content) = open.pop().unwrap();
+                let start = match start {
+                    DataToken::ItemStart { len } => DataToken::ItemStart {
+                        len: encoded_length(&content, vr_encoding).unwrap_or(len),
+                    },
+                    DataToken::SequenceStart { tag, len } if len.is_defined() => {
+                        DataToken::SequenceStart {
+                            tag,
+                            len: encoded_length(&content, vr_encoding).unwrap_or(len),
+                        }
+                    }
+                    start => start,
+                };
+                let parent = open
+                    .last_mut()
+                    .map(|(_, content)| content)
+                    .unwrap_or(&mut out);
+                parent.push(start);
+                parent.extend(content);
+                parent.push(token);
+            }
+            token => open
+                .last_mut()
+                .map(|(_, content)| content)
+                .unwrap_or(&mut out)
+                .push(token),
+        }
+    }
+    for (start, content) in open {
+        out.push(start);
+        out.extend(content);
+    }
+    out
+}
+
+/// Measure the length of the given tokens
+/// as written with the given VR encoding and the default character set,
+/// padding values to even length.
+fn encoded_length(tokens: &[DataToken], vr_encoding: VrEncoding) -> Option<Length> {
+    let mut data = Vec::new();
+    let mut writer =
+        DataSetWriter::with_vr_encoding(&mut data, vr_encoding, SpecificCharacterSet::Default)
+            .ok()?;
+    writer.options.policy = EncodingPolicy::default().pad_to_even_length(true);
+    writer.write_sequence(tokens.iter().cloned()).ok()?;
+    drop(writer);
+    Some(Length(data.len() as u32)).filter(|len| len.is_defined())
+}
+
+/// Check whether the token starts an element
+/// outside of the file meta group.
+fn starts_element_outside_meta_group(token: &DataToken) -> bool {
+    match token {
+        DataToken::ElementHeader(DataElementHeader { tag, .. })
+        | DataToken::SequenceStart { tag, .. } => tag.group() != 0x0002,
+        DataToken::PixelSequenceStart => true,
+        _ => false,
+    }
+}
+
+/// Write a DIMSE message body, such as that of a C-STORE request:
+/// a command set followed by a data set.
+///
+/// The command set is written in _Implicit VR Little Endian_,
+/// starting with the command group length `(0000,0000)`,
+/// which is computed from the remaining command elements
+/// (a group length in `command` is replaced).
+/// The data set tokens are then written with the given transfer syntax
+/// and character set.
+/// Pass an empty data set for messages without one.
+pub fn write_command_and_dataset<W, C, D>(
+    mut to: W,
+    command: C,
+    dataset: D,
+    ts: &TransferSyntax,
+    charset: SpecificCharacterSet,
+) -> Result<()>
+where
+    W: Write,
+    C: IntoIterator<Item = DataToken>,
+    D: IntoIterator<Item = DataToken>,
+{
+    let mut command_data = Vec::new();
+    DataSetWriter::with_vr_encoding(
+        &mut command_data,
+        VrEncoding::Implicit,
+        SpecificCharacterSet::Default,
+    )?
+    .write_sequence(StripGroupLengths::new(command))?;
+
+    let mut writer = DataSetWriter::with_vr_encoding(
+        &mut to,
+        VrEncoding::Implicit,
+        SpecificCharacterSet::Default,
+    )?;
+    writer.write_sequence(vec![
+        DataToken::ElementHeader(DataElementHeader::new(
+            Tag(0x0000, 0x0000),
+            VR::UL,
+            Length(4),
+        )),
+        DataToken::PrimitiveValue(PrimitiveValue::from(command_data.len() as u32)),
+    ])?;
+    // the command elements, already encoded
+    writer
+        .printer
+        .write_bytes(&command_data)
+        .context(WriteValue)?;
+    drop(writer);
+
+    DataSetWriter::with_ts_cs(&mut to, ts, charset)?.write_sequence(dataset)
+}
+
+/// Write the given data set tokens in the given transfer syntax,
+/// returning the number of bytes written.
+///
+/// The encoding policy is that of the transfer syntax,
+/// as by [`EncodingPolicy::for_transfer_syntax`],
+/// and text values are encoded with the default character set.
+/// Use a [`DataSetWriter`] directly for any other options.
+///
+/// [`EncodingPolicy::for_transfer_syntax`]: ./struct.EncodingPolicy.html#method.for_transfer_syntax
+/// [`DataSetWriter`]: ./struct.DataSetWriter.html
+pub fn write_dataset<W, I>(to: W, ts: &TransferSyntax, tokens: I) -> Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = DataToken>,
+{
+    let options = DataSetWriterOptions::default().policy(EncodingPolicy::for_transfer_syntax(ts));
+    let mut writer =
+        DataSetWriter::with_ts_cs_options(to, ts, SpecificCharacterSet::Default, options)?;
+    writer.write_sequence(tokens)?;
+    Ok(writer.printer.bytes_written() as usize)
+}
+
+/// Measure the number of bytes which the given value takes
+/// when written with [`write_dataset`] in the given transfer syntax.
+///
+/// This is the same as [`measure`],
+/// for a value which is usually borrowed,
+/// such as a reference to an object or a slice of elements,
+/// so that it can be written afterwards.
+/// See [`encode_to_vec`] for writing it in the same way.
+///
+/// [`write_dataset`]: ./fn.write_dataset.html
+/// [`measure`]: ./fn.measure.html
+/// [`encode_to_vec`]: ./fn.encode_to_vec.html
+pub fn encoded_len<I>(value: I, ts: &TransferSyntax) -> Result<u64>
+where
+    I: IntoTokens + Copy,
+{
+    measure(value.into_tokens(), ts).map(|len| len as u64)
+}
+
+/// Write the given value to a new buffer
+/// with [`write_dataset`] in the given transfer syntax.
+///
+/// The value is measured with [`encoded_len`] first,
+/// so that the buffer is allocated once with the exact capacity needed.
+///
+/// [`write_dataset`]: ./fn.write_dataset.html
+/// [`encoded_len`]: ./fn.encoded_len.html
+pub fn encode_to_vec<I>(value: I, ts: &TransferSyntax) -> Result<Vec<u8>>
+where
+    I: IntoTokens + Copy,
+{
+    let len = encoded_len(value, ts)?;
+    let mut data = Vec::with_capacity(len as usize);
+    write_dataset(&mut data, ts, value.into_tokens())?;
+    Ok(data)
+}
+
+/// Measure the number of bytes which the given tokens take
+/// when written with [`write_dataset`] in the given transfer syntax,
+/// without writing them anywhere.
+///
+/// The tokens go through the same writer,
+/// including the padding, lengths and delimiters
+/// required by the policy of the transfer syntax,
+/// so the count is exactly that of `write_dataset`.
+///
+/// [`write_dataset`]: ./fn.write_dataset.html
+pub fn measure<I>(tokens: I, ts: &TransferSyntax) -> Result<usize>
+where
+    I: IntoIterator<Item = DataToken>,
+{
+    write_dataset(std::io::sink(), ts, tokens)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::read::DataSetReader;
     use super::super::DataToken;
-    use super::DataSetWriter;
+    use super::{
+        encode_to_vec, encoded_len, measure, write_dataset, DataSetWriter, DataSetWriterOptions,
+        EncodingPolicy, Error, MetaTransferSyntax, VrEncoding,
+    };
+    use crate::stateful::decode::StatefulDecoder;
     use dicom_core::{
         header::{DataElementHeader, Length},
         value::PrimitiveValue,
         Tag, VR,
     };
+    use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
     use dicom_encoding::encode::EncoderFor;
-    use dicom_encoding::text::DefaultCharacterSetCodec;
-    use dicom_encoding::transfer_syntax::explicit_le::ExplicitVRLittleEndianEncoder;
+    use dicom_encoding::text::{DefaultCharacterSetCodec, SpecificCharacterSet};
+    use dicom_encoding::transfer_syntax::explicit_le::{
+        ExplicitVRLittleEndianDecoder, ExplicitVRLittleEndianEncoder,
+    };
+    use dicom_encoding::transfer_syntax::implicit_le::{
+        ImplicitVRLittleEndianDecoder, ImplicitVRLittleEndianEncoder,
+    };
+    use dicom_encoding::transfer_syntax::{Codec, Endianness, TransferSyntax};
 
     fn validate_dataset_writer<I>(tokens: I, ground_truth: &[u8])
     where
@@ -331,7 +1211,7 @@ mod tests {
     }
 
     #[test]
-    fn write_sequence_implicit() {
+    fn write_tokens_one_at_a_time() {
         let tokens = vec![
             DataToken::SequenceStart {
                 tag: Tag(0x0018, 0x6011),
@@ -346,16 +1226,8 @@ mod tests {
                 len: Length(2),
             }),
             DataToken::PrimitiveValue(PrimitiveValue::U16([1].as_ref().into())),
-            DataToken::ElementHeader(DataElementHeader {
-                tag: Tag(0x0018, 0x6014),
-                vr: VR::US,
-                len: Length(2),
-            }),
-            DataToken::PrimitiveValue(PrimitiveValue::U16([2].as_ref().into())),
             DataToken::ItemEnd,
-            DataToken::ItemStart {
-                len: Length::UNDEFINED,
-            },
+            DataToken::ItemStart { len: Length(10) },
             DataToken::ElementHeader(DataElementHeader {
                 tag: Tag(0x0018, 0x6012),
                 vr: VR::US,
@@ -372,48 +1244,924 @@ mod tests {
             DataToken::PrimitiveValue(PrimitiveValue::Str("TEST".into())),
         ];
 
+        let mut all_at_once = Vec::new();
+        DataSetWriter::new(
+            &mut all_at_once,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec,
+        )
+        .write_sequence(tokens.clone())
+        .unwrap();
+
+        let mut one_at_a_time = Vec::new();
+        let mut dset_writer = DataSetWriter::new(
+            &mut one_at_a_time,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec,
+        );
+        for token in &tokens {
+            dset_writer.write_token(token).unwrap();
+        }
+
+        assert_eq!(one_at_a_time, all_at_once);
+        // item delimiters only for the undefined length item,
+        // plus the sequence delimiter
+        assert_eq!(one_at_a_time.len(), 12 + 8 + 10 + 8 + 8 + 10 + 8 + 12);
+    }
+
+    #[test]
+    fn write_command_with_dataset() {
+        use super::write_command_and_dataset;
+
+        let command = vec![
+            // replaced with the actual group length
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0000, 0x0000),
+                VR::UL,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(0_u32)),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0000, 0x0002),
+                VR::UI,
+                Length(26),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.840.10008.5.1.4.1.1.2\0")),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0000, 0x0100),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(0x0001_u16)),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0000, 0x0800),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(0x0000_u16)),
+        ];
+        let dataset = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(8),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+        ];
+        let ts: TransferSyntax = TransferSyntax::new(
+            "1.2.840.10008.1.2.1",
+            "Explicit VR Little Endian",
+            Endianness::Little,
+            true,
+            Codec::None,
+        );
+
+        let mut out = Vec::new();
+        write_command_and_dataset(
+            &mut out,
+            command,
+            dataset,
+            &ts,
+            SpecificCharacterSet::Default,
+        )
+        .unwrap();
+
         #[rustfmt::skip]
         static GROUND_TRUTH: &[u8] = &[
-            0x18, 0x00, 0x11, 0x60, // sequence tag: (0018,6011) SequenceOfUltrasoundRegions
-            b'S', b'Q', // VR 
-            0x00, 0x00, // reserved
-            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // command set in implicit VR
+            0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, // (0000,0000) CommandGroupLength, len = 4
+            0x36, 0x00, 0x00, 0x00, // value = 54
             // -- 12 --
-            0xfe, 0xff, 0x00, 0xe0, // item start tag
-            0xff, 0xff, 0xff, 0xff, // item length: undefined
-            // -- 20 --
-            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x01, 0x00, // (0018, 6012) RegionSpatialformat, len = 2, value = 1
-            // -- 30 --
-            0x18, 0x00, 0x14, 0x60, b'U', b'S', 0x02, 0x00, 0x02, 0x00, // (0018, 6012) RegionDataType, len = 2, value = 2
-            // -- 40 --
-            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
-            // -- 48 --
-            0xfe, 0xff, 0x00, 0xe0, // item start tag
-            0xff, 0xff, 0xff, 0xff, // item length: undefined
+            0x00, 0x00, 0x02, 0x00, 0x1a, 0x00, 0x00, 0x00, // (0000,0002) AffectedSOPClassUID, len = 26
+            b'1', b'.', b'2', b'.', b'8', b'4', b'0', b'.', b'1', b'0', b'0', b'0', b'8',
+            b'.', b'5', b'.', b'1', b'.', b'4', b'.', b'1', b'.', b'1', b'.', b'2', 0x00,
+            // -- 46 --
+            0x00, 0x00, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00, // (0000,0100) CommandField, len = 2
+            0x01, 0x00, // value = 1 (C-STORE-RQ)
             // -- 56 --
-            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x04, 0x00, // (0018, 6012) RegionSpatialformat, len = 2, value = 4
+            0x00, 0x00, 0x00, 0x08, 0x02, 0x00, 0x00, 0x00, // (0000,0800) CommandDataSetType, len = 2
+            0x00, 0x00, // value = 0 (data set present)
             // -- 66 --
-            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
-            // -- 74 --
-            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
-            // -- 82 --
-            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4  
-            b'T', b'E', b'S', b'T', // value = "TEST"
+            // data set in explicit VR
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
         ];
 
-        validate_dataset_writer(tokens, GROUND_TRUTH);
+        assert_eq!(out, GROUND_TRUTH);
     }
 
     #[test]
-    fn write_encapsulated_pixeldata() {
+    fn write_meta_group_with_recomputed_length() {
         let tokens = vec![
-            DataToken::PixelSequenceStart,
-            DataToken::ItemStart { len: Length(0) },
-            DataToken::ItemEnd,
-            DataToken::ItemStart { len: Length(32) },
-            DataToken::ItemValue(vec![0x99; 32]),
-            DataToken::ItemEnd,
-            DataToken::SequenceEnd,
+            // replaced with the actual group length
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0000),
+                VR::UL,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(0_u32)),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0001),
+                VR::OB,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(vec![0x00_u8, 0x01])),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0010),
+                VR::UI,
+                Length(20),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.840.10008.1.2.1\0")),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(8),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+        ];
+
+        let options = DataSetWriterOptions::default().recompute_meta_group_length(true);
+        let mut all_at_once = Vec::new();
+        DataSetWriter::new_with_options(
+            &mut all_at_once,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec,
+            options,
+        )
+        .write_sequence(tokens.clone())
+        .unwrap();
+
+        // written one at a time, ending with the meta group
+        let mut one_at_a_time = Vec::new();
+        let mut dset_writer = DataSetWriter::new_with_options(
+            &mut one_at_a_time,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec,
+            options,
+        );
+        for token in &tokens[..6] {
+            dset_writer.write_token(token).unwrap();
+        }
+        // all of the group was held back until now
+        dset_writer.flush_meta_group().unwrap();
+        for token in &tokens[6..] {
+            dset_writer.write_token(token).unwrap();
+        }
+        assert_eq!(one_at_a_time, all_at_once);
+
+        let mut cursor = &all_at_once[..];
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>,
+        );
+        let read: Vec<_> = DataSetReader::new(parser, Default::default())
+            .collect::<Result<_, _>>()
+            .expect("should read tokens without errors");
+
+        // (0002,0001) and (0002,0010) take 14 and 28 bytes
+        let group_length = 14_u32 + 28;
+        assert_eq!(
+            &read[..2],
+            &[
+                DataToken::ElementHeader(DataElementHeader::new(
+                    Tag(0x0002, 0x0000),
+                    VR::UL,
+                    Length(4),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from(group_length)),
+            ]
+        );
+        // the group length matches the bytes of the group
+        let patient_name = all_at_once.len() - 16;
+        assert_eq!(patient_name - 12, group_length as usize);
+        assert_eq!(
+            &all_at_once[patient_name..patient_name + 4],
+            &[0x10, 0x00, 0x10, 0x00]
+        );
+        let headers = |tokens: &[DataToken]| -> Vec<DataToken> {
+            tokens
+                .iter()
+                .filter(|token| matches!(token, DataToken::ElementHeader(_)))
+                .cloned()
+                .collect()
+        };
+        assert_eq!(headers(&read), headers(&tokens));
+    }
+
+    #[test]
+    fn write_meta_group_in_explicit_vr_little_endian() {
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0000),
+                VR::UL,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(0_u32)),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0010),
+                VR::UI,
+                Length(18),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.840.10008.1.2\0")),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(8),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+        ];
+
+        let mut out = Vec::new();
+        DataSetWriter::new_with_options(
+            &mut out,
+            EncoderFor::new(ImplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec,
+            DataSetWriterOptions::default().recompute_meta_group_length(true),
+        )
+        .write_sequence(tokens)
+        .unwrap();
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0x02, 0x00, 0x00, 0x00, b'U', b'L', 0x04, 0x00, // (0002,0000) UL, len = 4
+            0x1A, 0x00, 0x00, 0x00, // the group length in explicit VR
+            0x02, 0x00, 0x10, 0x00, b'U', b'I', 0x12, 0x00, // (0002,0010) UI, len = 18
+            b'1', b'.', b'2', b'.', b'8', b'4', b'0', b'.', b'1', b'0',
+            b'0', b'0', b'8', b'.', b'1', b'.', b'2', 0x00,
+            // the rest in implicit VR
+            0x10, 0x00, 0x10, 0x00, 0x08, 0x00, 0x00, 0x00, // (0010,0010), len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+        ];
+        assert_eq!(out, GROUND_TRUTH);
+    }
+
+    fn transfer_syntax(uid: &'static str, name: &'static str, explicit_vr: bool) -> TransferSyntax {
+        TransferSyntax::new(uid, name, Endianness::Little, explicit_vr, Codec::None)
+    }
+
+    /// Write the tokens with the policy of the given transfer syntax.
+    fn write_with_policy<I>(tokens: I, ts: &TransferSyntax) -> Result<Vec<u8>, Error>
+    where
+        I: IntoIterator<Item = DataToken>,
+    {
+        let mut out = Vec::new();
+        let options =
+            DataSetWriterOptions::default().policy(EncodingPolicy::for_transfer_syntax(ts));
+        DataSetWriter::with_ts_cs_options(&mut out, ts, SpecificCharacterSet::Default, options)?
+            .write_sequence(tokens)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn write_dataset_in_transfer_syntax() {
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(7),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^Jon")),
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ];
+
+        for &(uid, explicit_vr) in &[("1.2.840.10008.1.2.1", true), ("1.2.840.10008.1.2", false)] {
+            let ts = transfer_syntax(uid, "Test", explicit_vr);
+            let mut out = Vec::new();
+            let written = write_dataset(&mut out, &ts, tokens.clone()).unwrap();
+
+            // same as with the policy of the transfer syntax,
+            // padded and with the sequence closed
+            assert_eq!(out, write_with_policy(tokens.clone(), &ts).unwrap());
+            assert_eq!(written, out.len());
+            let header_len = if explicit_vr { 12 } else { 8 };
+            assert_eq!(written, 8 + 8 + header_len + 8 + 8 + 8);
+        }
+    }
+
+    #[test]
+    fn measure_same_as_written_dataset() {
+        let element = |tag, vr, value: &str| {
+            vec![
+                DataToken::ElementHeader(DataElementHeader::new(
+                    tag,
+                    vr,
+                    Length(value.len() as u32),
+                )),
+                DataToken::PrimitiveValue(PrimitiveValue::from(value)),
+            ]
+        };
+        // odd length values and open sequences of undefined length
+        let mut nested = element(Tag(0x0008, 0x0060), VR::CS, "MR");
+        nested.push(DataToken::SequenceStart {
+            tag: Tag(0x0008, 0x1140),
+            len: Length::UNDEFINED,
+        });
+        nested.push(DataToken::ItemStart {
+            len: Length::UNDEFINED,
+        });
+        nested.extend(element(Tag(0x0008, 0x1150), VR::UI, "1.2.3"));
+        // explicit lengths of sequences and items
+        let mut defined = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0008, 0x1140),
+                len: Length(18),
+            },
+            DataToken::ItemStart { len: Length(10) },
+        ];
+        defined.extend(element(Tag(0x0008, 0x1150), VR::UI, "1\0"));
+        defined.extend(vec![DataToken::ItemEnd, DataToken::SequenceEnd]);
+        // encapsulated pixel data
+        let encapsulated = vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(4) },
+            DataToken::ItemValue(vec![1, 2, 3, 4]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        let native = [
+            transfer_syntax("1.2.840.10008.1.2.1", "Explicit VR Little Endian", true),
+            TransferSyntax::new(
+                "1.2.840.10008.1.2.2",
+                "Explicit VR Big Endian",
+                Endianness::Big,
+                true,
+                Codec::None,
+            ),
+        ];
+        let implicit = transfer_syntax("1.2.840.10008.1.2", "Implicit VR Little Endian", false);
+        let rle = TransferSyntax::new(
+            "1.2.840.10008.1.2.5",
+            "RLE Lossless",
+            Endianness::Little,
+            true,
+            Codec::EncapsulatedPixelData,
+        );
+        let cases = vec![
+            (&native[0], element(Tag(0x0010, 0x0010), VR::PN, "Doe^Jon")),
+            (&native[0], nested.clone()),
+            (&native[1], nested.clone()),
+            (&native[1], defined.clone()),
+            (&implicit, element(Tag(0x0010, 0x0010), VR::PN, "Doe^Jon")),
+            (&implicit, nested),
+            (&implicit, defined),
+            (&rle, encapsulated.clone()),
+            (&rle, vec![]),
+        ];
+        for (ts, tokens) in cases {
+            let len = measure(tokens.clone(), ts).unwrap();
+            let mut out = Vec::new();
+            let written = write_dataset(&mut out, ts, tokens).unwrap();
+            assert_eq!(len, written, "in {}", ts.name());
+            assert_eq!(len, out.len(), "in {}", ts.name());
+        }
+        // the header, an empty offset table, a fragment and the delimiter
+        assert_eq!(measure(encapsulated, &rle).unwrap(), 12 + 8 + 12 + 8);
+
+        // errors are the same as when writing
+        let pixel_data = vec![DataToken::PixelSequenceStart, DataToken::SequenceEnd];
+        assert!(matches!(
+            measure(pixel_data, &native[0]),
+            Err(Error::UnexpectedPixelSequence { .. })
+        ));
+    }
+
+    #[test]
+    fn write_with_implicit_vr_le_policy() {
+        let ts = transfer_syntax("1.2.840.10008.1.2", "Implicit VR Little Endian", false);
+        assert_eq!(
+            EncodingPolicy::for_transfer_syntax(&ts),
+            EncodingPolicy::NATIVE
+        );
+
+        // the item and the sequence are left open
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(7),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^Jon")),
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0018, 0x6012),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(1_u16)),
+        ];
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0x10, 0x00, 0x10, 0x00, 0x08, 0x00, 0x00, 0x00, // (0010,0010) PatientName, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'n', b' ', // value padded with a space
+            // -- 16 --
+            0x18, 0x00, 0x11, 0x60, // sequence tag: (0018,6011) SequenceOfUltrasoundRegions
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 24 --
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0xff, 0xff, 0xff, 0xff, // item length: undefined
+            // -- 32 --
+            0x18, 0x00, 0x12, 0x60, 0x02, 0x00, 0x00, 0x00, 0x01, 0x00, // (0018,6012) RegionSpatialformat, len = 2, value = 1
+            // -- 42 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            // -- 50 --
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+        ];
+
+        assert_eq!(
+            write_with_policy(tokens.clone(), &ts).unwrap(),
+            GROUND_TRUTH
+        );
+
+        // the default policy writes the tokens as they are
+        let mut out = Vec::new();
+        DataSetWriter::with_ts_cs(&mut out, &ts, SpecificCharacterSet::Default)
+            .unwrap()
+            .write_sequence(tokens)
+            .unwrap();
+        // except for the text padding done by the encoder
+        assert_eq!(&out[..16], b"\x10\x00\x10\x00\x07\x00\x00\x00Doe^Jon ");
+        assert_eq!(&out[16..], &GROUND_TRUTH[16..GROUND_TRUTH.len() - 16]);
+    }
+
+    #[test]
+    fn write_with_explicit_vr_le_policy() {
+        let ts = transfer_syntax("1.2.840.10008.1.2.1", "Explicit VR Little Endian", true);
+
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1001),
+                VR::UN,
+                Length(3),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(vec![1_u8, 2, 3])),
+        ];
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0x09, 0x00, 0x01, 0x10, b'U', b'N', 0x00, 0x00, // (0009,1001), VR, reserved
+            0x04, 0x00, 0x00, 0x00, // len = 4
+            0x01, 0x02, 0x03, 0x00, // value padded with a null byte
+        ];
+
+        assert_eq!(write_with_policy(tokens, &ts).unwrap(), GROUND_TRUTH);
+
+        // no encapsulated pixel data in a native transfer syntax
+        let tokens = vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+        match write_with_policy(tokens, &ts) {
+            Err(Error::UnexpectedPixelSequence { .. }) => {}
+            other => panic!("unexpected outcome {:?}", other),
+        }
+
+        // undefined lengths can be ruled out
+        let mut out = Vec::new();
+        let options = DataSetWriterOptions::default()
+            .policy(EncodingPolicy::NATIVE.allow_undefined_length(false));
+        let mut dset_writer = DataSetWriter::with_ts_cs_options(
+            &mut out,
+            &ts,
+            SpecificCharacterSet::Default,
+            options,
+        )
+        .unwrap();
+        dset_writer
+            .write(DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length(0),
+            })
+            .unwrap();
+        dset_writer.write(DataToken::SequenceEnd).unwrap();
+        match dset_writer.write(DataToken::SequenceStart {
+            tag: Tag(0x0018, 0x6012),
+            len: Length::UNDEFINED,
+        }) {
+            Err(Error::UndefinedLength { tag, .. }) => assert_eq!(tag, Tag(0x0018, 0x6012)),
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn write_meta_group_with_matching_transfer_syntax() {
+        let ts = transfer_syntax("1.2.840.10008.1.2.1", "Explicit VR Little Endian", true);
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0000),
+                VR::UL,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from(0_u32)),
+            // stale, from an implicit VR source
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0002, 0x0010),
+                VR::UI,
+                Length(18),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("1.2.840.10008.1.2\0")),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Length(8),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("Doe^John")),
+        ];
+        let write = |tokens: Vec<DataToken>, meta_transfer_syntax| {
+            let options = DataSetWriterOptions::default()
+                .recompute_meta_group_length(true)
+                .meta_transfer_syntax(meta_transfer_syntax);
+            let mut out = Vec::new();
+            DataSetWriter::with_ts_cs_options(
+                &mut out,
+                &ts,
+                SpecificCharacterSet::Default,
+                options,
+            )?
+            .write_sequence(tokens)?;
+            Ok::<_, Error>(out)
+        };
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0x02, 0x00, 0x00, 0x00, b'U', b'L', 0x04, 0x00, // (0002,0000) UL, len = 4
+            0x1C, 0x00, 0x00, 0x00, // the group length includes the new UID
+            0x02, 0x00, 0x10, 0x00, b'U', b'I', 0x14, 0x00, // (0002,0010) UI, len = 20
+            b'1', b'.', b'2', b'.', b'8', b'4', b'0', b'.', b'1', b'0',
+            b'0', b'0', b'8', b'.', b'1', b'.', b'2', b'.', b'1', 0x00,
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00, // (0010,0010) PN, len = 8
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+        ];
+
+        let out = write(tokens.clone(), MetaTransferSyntax::Rewrite).unwrap();
+        assert_eq!(out, GROUND_TRUTH);
+
+        match write(tokens, MetaTransferSyntax::Verify) {
+            Err(Error::TransferSyntaxMismatch {
+                declared, expected, ..
+            }) => {
+                assert_eq!(declared, "1.2.840.10008.1.2");
+                assert_eq!(expected, "1.2.840.10008.1.2.1");
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+
+        // the UID written by the rewriting writer passes verification
+        let mut cursor = GROUND_TRUTH;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as Box<_>,
+        );
+        let tokens: Vec<_> = DataSetReader::new(parser, Default::default())
+            .collect::<Result<_, _>>()
+            .expect("should read tokens without errors");
+        let out = write(tokens, MetaTransferSyntax::Verify).unwrap();
+        assert_eq!(out, GROUND_TRUTH);
+    }
+
+    #[test]
+    fn write_with_deflated_explicit_vr_le_policy() {
+        let ts = transfer_syntax(
+            "1.2.840.10008.1.2.1.99",
+            "Deflated Explicit VR Little Endian",
+            true,
+        );
+        assert_eq!(
+            EncodingPolicy::for_transfer_syntax(&ts),
+            EncodingPolicy::NATIVE
+        );
+
+        // a value in chunks is padded after the last chunk
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0009, 0x1001),
+                VR::OB,
+                Length(5),
+            )),
+            DataToken::ItemValue(vec![1, 2, 3]),
+            DataToken::ItemValue(vec![4, 5]),
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0020, 0x4000),
+                VR::LT,
+                Length(3),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::from("ABC")),
+        ];
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0x09, 0x00, 0x01, 0x10, b'O', b'B', 0x00, 0x00, // (0009,1001), VR, reserved
+            0x06, 0x00, 0x00, 0x00, // len = 6
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x00, // value padded with a null byte
+            // -- 18 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'A', b'B', b'C', b' ', // value padded with a space
+        ];
+
+        assert_eq!(write_with_policy(tokens, &ts).unwrap(), GROUND_TRUTH);
+    }
+
+    #[test]
+    fn write_with_encapsulated_policy() {
+        let ts = transfer_syntax("1.2.840.10008.1.2.5", "RLE Lossless", true);
+        assert_eq!(
+            EncodingPolicy::for_transfer_syntax(&ts),
+            EncodingPolicy::ENCAPSULATED
+        );
+
+        // odd fragments are padded with a null byte
+        let tokens = vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(3) },
+            DataToken::ItemValue(vec![0x99; 3]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+        ];
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0xe0, 0x7f, 0x10, 0x00, b'O', b'B', 0x00, 0x00, // (7FE0,0010) PixelData, VR, reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 -- Basic offset table
+            0xfe, 0xff, 0x00, 0xe0, 0x00, 0x00, 0x00, 0x00,
+            // -- 20 -- Fragment of length 4
+            0xfe, 0xff, 0x00, 0xe0, 0x04, 0x00, 0x00, 0x00,
+            0x99, 0x99, 0x99, 0x00,
+            // -- 32 -- End of pixel data
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        assert_eq!(write_with_policy(tokens, &ts).unwrap(), GROUND_TRUTH);
+    }
+
+    #[test]
+    fn roundtrip_sequence_explicit_length() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x18, 0x00, 0x11, 0x60, // sequence tag: (0018,6011) SequenceOfUltrasoundRegions
+            b'S', b'Q', // VR
+            0x00, 0x00, // reserved
+            0x22, 0x00, 0x00, 0x00, // length: 18 + 16 = 34
+            // -- 12 --
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x0a, 0x00, 0x00, 0x00, // item length: 10
+            // -- 20 --
+            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x01, 0x00, // (0018, 6012) RegionSpatialformat, len = 2, value = 1
+            // -- 30 --
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0xff, 0xff, 0xff, 0xff, // item length: undefined
+            // -- 38 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            // -- 46 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T', // value = "TEST"
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>,
+        );
+        let tokens: Vec<_> = DataSetReader::new(parser, Default::default())
+            .collect::<Result<_, _>>()
+            .expect("should read tokens without errors");
+
+        // the token stream has a sequence end token,
+        // which must not be written out
+        assert_eq!(tokens.iter().filter(|t| t.is_sequence_end()).count(), 1);
+
+        validate_dataset_writer(tokens, DATA);
+    }
+
+    #[test]
+    fn roundtrip_empty_sequences_and_items() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x08, 0x00, 0x15, 0x11, // sequence tag: (0008,1115) ReferencedSeriesSequence
+            b'S', b'Q', 0x00, 0x00, // VR, reserved
+            0x00, 0x00, 0x00, 0x00, // length: 0
+            // -- 12 --
+            0x08, 0x00, 0x40, 0x11, // sequence tag: (0008,1140) ReferencedImageSequence
+            b'S', b'Q', 0x00, 0x00, // VR, reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 24 --
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0xff, 0xff, 0xff, 0xff, // item length: undefined
+            // -- 32 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            // -- 40 --
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 48 --
+            0x08, 0x00, 0x99, 0x11, // sequence tag: (0008,1199) ReferencedSOPSequence
+            b'S', b'Q', 0x00, 0x00, // VR, reserved
+            0x08, 0x00, 0x00, 0x00, // length: 8
+            // -- 60 --
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x00, 0x00, 0x00, 0x00, // item length: 0
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>,
+        );
+        let tokens: Vec<_> = DataSetReader::new(parser, Default::default())
+            .collect::<Result<_, _>>()
+            .expect("should read tokens without errors");
+
+        assert_eq!(
+            tokens,
+            vec![
+                DataToken::SequenceStart {
+                    tag: Tag(0x0008, 0x1115),
+                    len: Length(0),
+                },
+                DataToken::SequenceEnd,
+                DataToken::SequenceStart {
+                    tag: Tag(0x0008, 0x1140),
+                    len: Length::UNDEFINED,
+                },
+                DataToken::ItemStart {
+                    len: Length::UNDEFINED,
+                },
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+                DataToken::SequenceStart {
+                    tag: Tag(0x0008, 0x1199),
+                    len: Length(8),
+                },
+                DataToken::ItemStart { len: Length(0) },
+                DataToken::ItemEnd,
+                DataToken::SequenceEnd,
+            ]
+        );
+
+        validate_dataset_writer(tokens, DATA);
+    }
+
+    #[test]
+    fn roundtrip_implicit_vr() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x08, 0x00, 0x05, 0x00, 0x0a, 0x00, 0x00, 0x00, // (0008,0005) SpecificCharacterSet, len = 10
+            b'I', b'S', b'O', b'_', b'I', b'R', b' ', b'1', b'0', b'0',
+            // -- 18 --
+            0x18, 0x00, 0x11, 0x60, // sequence tag: (0018,6011) SequenceOfUltrasoundRegions
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 26 --
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0xff, 0xff, 0xff, 0xff, // item length: undefined
+            // -- 34 --
+            0x18, 0x00, 0x12, 0x60, 0x02, 0x00, 0x00, 0x00, 0x01, 0x00, // (0018,6012) RegionSpatialformat, len = 2, value = 1
+            // -- 44 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            // -- 52 --
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 60 --
+            0x20, 0x00, 0x00, 0x40, 0x04, 0x00, 0x00, 0x00, // (0020,4000) ImageComments, len = 4
+            b'T', b'E', b'S', b'T', // value = "TEST"
+        ];
+
+        let mut cursor = DATA;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ImplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder::default(),
+            Box::new(DefaultCharacterSetCodec::default()) as Box<_>,
+        );
+        let tokens: Vec<_> = DataSetReader::new(parser, Default::default())
+            .collect::<Result<_, _>>()
+            .expect("should read tokens without errors");
+
+        let write_with = |vr_encoding, tokens| {
+            let mut raw_out: Vec<u8> = vec![];
+            DataSetWriter::with_vr_encoding(&mut raw_out, vr_encoding, Default::default())
+                .unwrap()
+                .write_sequence(tokens)
+                .unwrap();
+            raw_out
+        };
+
+        assert_eq!(write_with(VrEncoding::Implicit, tokens.clone()), DATA);
+
+        // the same tokens in explicit VR encoding carry the VRs
+        let raw_out = write_with(VrEncoding::Explicit, tokens);
+        assert_eq!(&raw_out[4..6], b"CS");
+        // only the sequence header grows, by the VR and reserved bytes
+        assert_eq!(raw_out.len(), DATA.len() + 4);
+    }
+
+    #[test]
+    fn write_sequence_implicit() {
+        let tokens = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0018, 0x6012),
+                vr: VR::US,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([1].as_ref().into())),
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0018, 0x6014),
+                vr: VR::US,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([2].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0018, 0x6012),
+                vr: VR::US,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([4].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0020, 0x4000),
+                vr: VR::LT,
+                len: Length(4),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::Str("TEST".into())),
+        ];
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0x18, 0x00, 0x11, 0x60, // sequence tag: (0018,6011) SequenceOfUltrasoundRegions
+            b'S', b'Q', // VR 
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 --
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0xff, 0xff, 0xff, 0xff, // item length: undefined
+            // -- 20 --
+            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x01, 0x00, // (0018, 6012) RegionSpatialformat, len = 2, value = 1
+            // -- 30 --
+            0x18, 0x00, 0x14, 0x60, b'U', b'S', 0x02, 0x00, 0x02, 0x00, // (0018, 6012) RegionDataType, len = 2, value = 2
+            // -- 40 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            // -- 48 --
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0xff, 0xff, 0xff, 0xff, // item length: undefined
+            // -- 56 --
+            0x18, 0x00, 0x12, 0x60, b'U', b'S', 0x02, 0x00, 0x04, 0x00, // (0018, 6012) RegionSpatialformat, len = 2, value = 4
+            // -- 66 --
+            0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+            // -- 74 --
+            0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+            // -- 82 --
+            0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4  
+            b'T', b'E', b'S', b'T', // value = "TEST"
+        ];
+
+        validate_dataset_writer(tokens, GROUND_TRUTH);
+    }
+
+    #[test]
+    fn write_encapsulated_pixeldata() {
+        let tokens = vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(0) },
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(32) },
+            DataToken::ItemValue(vec![0x99; 32]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
             DataToken::ElementHeader(DataElementHeader::new(
                 Tag(0xfffc, 0xfffc),
                 VR::OB,
@@ -450,6 +2198,198 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
 
-        validate_dataset_writer(tokens, GROUND_TRUTH);
+        validate_dataset_writer(tokens.clone(), GROUND_TRUTH);
+
+        // a pixel data header right before the pixel sequence
+        // is the header of the pixel sequence, written once
+        let mut with_header = vec![DataToken::ElementHeader(DataElementHeader::new(
+            Tag(0x7fe0, 0x0010),
+            VR::OB,
+            Length::UNDEFINED,
+        ))];
+        with_header.extend(tokens);
+        validate_dataset_writer(with_header, GROUND_TRUTH);
+    }
+
+    #[test]
+    fn write_strict_ordering() {
+        let tokens = vec![
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0020, 0x4000),
+                VR::LT,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::Str("TEST".into())),
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length::UNDEFINED,
+            },
+        ];
+
+        // tokens are written as is if not strict
+        let mut raw_out: Vec<u8> = vec![];
+        let mut dset_writer = DataSetWriter::new(
+            &mut raw_out,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec::default(),
+        );
+        dset_writer.write_sequence(tokens.clone()).unwrap();
+
+        // strict writer fails on the sequence
+        let mut raw_out: Vec<u8> = vec![];
+        let mut dset_writer = DataSetWriter::new_with_options(
+            &mut raw_out,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec::default(),
+            DataSetWriterOptions::default().strict_ordering(true),
+        );
+        match dset_writer.write_sequence(tokens) {
+            Err(Error::UnorderedTag { tag, previous, .. }) => {
+                assert_eq!(tag, Tag(0x0018, 0x6011));
+                assert_eq!(previous, Tag(0x0020, 0x4000));
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn write_strict_ordering_per_item() {
+        // tag order is reset in each item
+        let tokens = vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0018, 0x6011),
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0018, 0x6014),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([2].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0018, 0x6012),
+                VR::US,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U16([1].as_ref().into())),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0x0020, 0x4000),
+                VR::LT,
+                Length(4),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::Str("TEST".into())),
+        ];
+
+        let mut raw_out: Vec<u8> = vec![];
+        let mut dset_writer = DataSetWriter::new_with_options(
+            &mut raw_out,
+            EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+            DefaultCharacterSetCodec::default(),
+            DataSetWriterOptions::default().strict_ordering(true),
+        );
+        dset_writer.write_sequence(tokens).unwrap();
+    }
+
+    #[test]
+    fn encode_to_vec_with_exact_capacity() {
+        use crate::dataset::DataElement;
+        use dicom_core::header::EmptyObject;
+        use dicom_core::value::Value;
+
+        let elements: Vec<DataElement<EmptyObject, [u8; 0]>> = vec![
+            DataElement::new(
+                Tag(0x0010, 0x0010),
+                VR::PN,
+                Value::Primitive(PrimitiveValue::from("Doe^John")),
+            ),
+            // odd length, padded by the policy
+            DataElement::new(
+                Tag(0x0010, 0x0020),
+                VR::LO,
+                Value::Primitive(PrimitiveValue::from("ABC")),
+            ),
+            DataElement::new(
+                Tag(0x0028, 0x0010),
+                VR::US,
+                Value::Primitive(PrimitiveValue::from(512_u16)),
+            ),
+        ];
+        let ts = transfer_syntax("1.2.840.10008.1.2.1", "Explicit VR Little Endian", true);
+
+        let len = encoded_len(&elements[..], &ts).unwrap();
+        assert_eq!(len, 16 + 12 + 10);
+
+        let data = encode_to_vec(&elements[..], &ts).unwrap();
+        assert_eq!(data.len() as u64, len);
+        // written without growing the buffer
+        assert_eq!(data.capacity(), data.len());
+        assert_eq!(&data[..8], &[0x10, 0x00, 0x10, 0x00, b'P', b'N', 8, 0]);
+        assert_eq!(&data[8..16], b"Doe^John");
+        assert_eq!(&data[22..28], &[4, 0, b'A', b'B', b'C', b' ']);
+
+        let ts = transfer_syntax("1.2.840.10008.1.2", "Implicit VR Little Endian", false);
+        let data = encode_to_vec(&elements[..], &ts).unwrap();
+        assert_eq!(data.len() as u64, encoded_len(&elements[..], &ts).unwrap());
+        assert_eq!(data.capacity(), data.len());
+        assert_eq!(&data[..8], &[0x10, 0x00, 0x10, 0x00, 8, 0, 0, 0]);
+    }
+
+    /// Pseudo-random bytes for building arbitrary values,
+    /// the same for each seed.
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary_bytes(seed: u64, len: usize) -> Vec<u8> {
+        // xorshift64*
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state >> 12;
+                state ^= state << 25;
+                state ^= state >> 27;
+                (state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn write_arbitrary_tokens_without_panicking() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let policies = [
+            EncodingPolicy::default(),
+            EncodingPolicy::NATIVE,
+            EncodingPolicy::ENCAPSULATED,
+        ];
+        for seed in 0..512 {
+            let bytes = arbitrary_bytes(seed, 1024);
+            let mut u = Unstructured::new(&bytes);
+            let mut tokens = Vec::new();
+            while !u.is_empty() {
+                tokens.push(DataToken::arbitrary(&mut u).unwrap());
+            }
+            for (i, policy) in policies.iter().enumerate() {
+                let options = DataSetWriterOptions::default()
+                    .strict_ordering(i > 0)
+                    .policy(*policy);
+                let mut out = Vec::new();
+                let mut dset_writer = DataSetWriter::new_with_options(
+                    &mut out,
+                    EncoderFor::new(ExplicitVRLittleEndianEncoder::default()),
+                    DefaultCharacterSetCodec,
+                    options,
+                );
+                // either outcome is fine, as long as it does not panic
+                let _ = dset_writer.write_sequence(tokens.clone());
+            }
+        }
     }
 }
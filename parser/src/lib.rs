@@ -14,5 +14,8 @@ pub mod stateful;
 mod util;
 
 pub use dataset::DataSetReader;
-pub use stateful::decode::{DynStatefulDecoder, StatefulDecode, StatefulDecoder};
+pub use stateful::decode::{
+    decode_primitive_value, DynSeekStatefulDecoder, DynStatefulDecoder, StatefulDecode,
+    StatefulDecoder,
+};
 pub use stateful::encode::StatefulEncoder;
@@ -30,6 +30,16 @@ pub struct DicomElementMarker {
     pub pos: u64,
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DicomElementMarker {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(DicomElementMarker {
+            header: u.arbitrary()?,
+            pos: u.arbitrary()?,
+        })
+    }
+}
+
 impl DicomElementMarker {
     /// Obtain an interval of the raw data associated to this element's data value.
     pub fn get_data_stream<S: ?Sized, B: DerefMut<Target = S>>(
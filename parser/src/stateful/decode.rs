@@ -1,7 +1,7 @@
 //! Module holding a stateful DICOM data decoding abstraction,
 //! which also supports text decoding.
 
-use crate::util::n_times;
+use crate::util::{n_times, ReadSeek};
 use chrono::FixedOffset;
 use dicom_core::header::{DataElementHeader, HasLength, Length, SequenceItemHeader, Tag, VR};
 use dicom_core::value::{PrimitiveValue, C};
@@ -10,14 +10,15 @@ use dicom_encoding::decode::primitive_value::*;
 use dicom_encoding::decode::{BasicDecode, DecodeFrom};
 use dicom_encoding::text::{
     validate_da, validate_dt, validate_tm, DefaultCharacterSetCodec, DynamicTextCodec,
-    SpecificCharacterSet, TextCodec, TextValidationOutcome,
+    Iso2022CharacterSetCodec, SpecificCharacterSet, TextCodec, TextValidationOutcome,
 };
 use dicom_encoding::transfer_syntax::explicit_le::ExplicitVRLittleEndianDecoder;
+use dicom_encoding::transfer_syntax::implicit_le::ImplicitVRLittleEndianDecoder;
 use dicom_encoding::transfer_syntax::{DynDecoder, TransferSyntax};
 use smallvec::smallvec;
 use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
 use std::fmt::Debug;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::iter::Iterator;
 
 #[derive(Debug, Snafu)]
@@ -119,6 +120,13 @@ pub enum Error {
         string: String,
         backtrace: Backtrace,
     },
+
+    #[snafu(display("Could not seek back to position {}", position))]
+    SeekReader {
+        position: u64,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -132,6 +140,67 @@ pub trait StatefulDecode {
     /// Same as `Decode::decode_item_header` over the bound source.
     fn decode_item_header(&mut self) -> Result<SequenceItemHeader>;
 
+    /// Decode the next data element header in implicit VR little endian,
+    /// regardless of the transfer syntax.
+    ///
+    /// This is how the items of a `UN` element of undefined length
+    /// are encoded.
+    /// By default, the 8 bytes of the header are read
+    /// with [`read_bytes`](#tymethod.read_bytes) and decoded in memory.
+    fn decode_header_implicit_vr(&mut self) -> Result<DataElementHeader> {
+        let position = self.bytes_read();
+        let mut buf = [0; 8];
+        self.read_bytes(&mut buf)?;
+        ImplicitVRLittleEndianDecoder::default()
+            .decode_header(&mut &buf[..])
+            .context(DecodeElementHeader { position })
+            .map(|(header, _)| header)
+    }
+
+    /// Decode the item headers and values which follow
+    /// in little endian, regardless of the transfer syntax,
+    /// until this is disabled again.
+    ///
+    /// This is enabled within a `UN` element of undefined length,
+    /// whose content is in implicit VR little endian,
+    /// while its element headers are read
+    /// with [`decode_header_implicit_vr`](#method.decode_header_implicit_vr).
+    /// By default, this does nothing,
+    /// which is only correct for sources in little endian.
+    fn set_implicit_vr(&mut self, implicit_vr: bool) {
+        let _ = implicit_vr;
+    }
+
+    /// Retrieve the codes of the _Specific Character Set_
+    /// which the decoder switched to while reading,
+    /// empty if it has not found one.
+    ///
+    /// By default, no codes are reported.
+    fn character_set(&self) -> &[String] {
+        &[]
+    }
+
+    /// Switch to the character set with the given codes,
+    /// as if they were read from a _Specific Character Set_ element.
+    ///
+    /// By default, this does nothing.
+    fn declare_character_set(&mut self, codes: &[String]) -> Result<()> {
+        let _ = codes;
+        Ok(())
+    }
+
+    /// Same as `Decode::decode_header_with_raw_vr` over the bound source:
+    /// decode the next data element header,
+    /// along with the value representation bytes found in the source
+    /// when the encoding is explicit VR.
+    ///
+    /// By default, the header is decoded
+    /// with [`decode_header`](#tymethod.decode_header)
+    /// and no VR bytes are reported.
+    fn decode_header_with_raw_vr(&mut self) -> Result<(DataElementHeader, Option<[u8; 2]>)> {
+        self.decode_header().map(|header| (header, None))
+    }
+
     /// Eagerly read the following data in the source as a primitive data
     /// value. When reading values in text form, a conversion to a more
     /// maleable type is attempted. Namely, numbers in text form (IS, DS) are
@@ -176,6 +245,21 @@ pub trait StatefulDecode {
     /// Read the exact amount of bytes to fill the buffer.
     fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()>;
 
+    /// Skip the given number of bytes from the source,
+    /// without keeping them in memory.
+    ///
+    /// By default, the bytes are read in small chunks and discarded.
+    fn skip_bytes(&mut self, len: u32) -> Result<()> {
+        let mut buf = [0; 512];
+        let mut remaining = len as usize;
+        while remaining > 0 {
+            let chunk = remaining.min(buf.len());
+            self.read_bytes(&mut buf[..chunk])?;
+            remaining -= chunk;
+        }
+        Ok(())
+    }
+
     /// Retrieve the exact number of bytes read so far by the stateful decoder.
     fn bytes_read(&self) -> u64;
 }
@@ -186,6 +270,16 @@ pub trait StatefulDecode {
 pub type DynStatefulDecoder<'s> =
     StatefulDecoder<DynDecoder<dyn Read + 's>, BasicDecoder, Box<dyn Read + 's>, DynamicTextCodec>;
 
+/// Alias for a dynamically resolved DICOM stateful decoder
+/// over a seekable data source,
+/// which can be moved back to the start with [`rewind`](struct.StatefulDecoder.html#method.rewind).
+pub type DynSeekStatefulDecoder<'s> = StatefulDecoder<
+    DynDecoder<dyn ReadSeek + 's>,
+    BasicDecoder,
+    Box<dyn ReadSeek + 's>,
+    DynamicTextCodec,
+>;
+
 /// The initial capacity of the `DicomParser` buffer.
 const PARSER_BUFFER_CAPACITY: usize = 2048;
 
@@ -206,6 +300,16 @@ pub struct StatefulDecoder<D, BD, S, TC> {
     dt_utc_offset: FixedOffset,
     buffer: Vec<u8>,
     bytes_read: u64,
+    /// whether the item headers and values which follow
+    /// are in little endian, as within a `UN` element of undefined length
+    implicit_vr: bool,
+    /// the codes of the last _Specific Character Set_ switched to
+    character_set: Vec<String>,
+    /// the text codec the decoder was created with,
+    /// once it is replaced by another character set
+    initial_text: Option<TC>,
+    /// the value of `bytes_read` when the decoder was created
+    start_position: u64,
 }
 
 pub type DicomParser<D, BD, S, TC> = StatefulDecoder<D, BD, S, TC>;
@@ -237,6 +341,34 @@ impl<'s> DynStatefulDecoder<'s> {
     }
 }
 
+impl<'s> DynSeekStatefulDecoder<'s> {
+    /// Create a new DICOM parser over a seekable source
+    /// for the given transfer syntax and character set.
+    pub fn new_seekable_with<S>(
+        from: S,
+        ts: &TransferSyntax,
+        charset: SpecificCharacterSet,
+    ) -> Result<Self>
+    where
+        S: 's + Read + Seek,
+    {
+        let basic = ts.basic_decoder();
+        let decoder = ts
+            .decoder_for()
+            .context(UnsupportedTransferSyntax { ts: ts.name() })?;
+        let text = charset
+            .codec()
+            .context(UnsupportedCharacterSet { charset })?;
+
+        Ok(DynSeekStatefulDecoder::new(
+            Box::from(from),
+            decoder,
+            basic,
+            text,
+        ))
+    }
+}
+
 /// Type alias for the DICOM parser of a file's Meta group.
 pub type FileHeaderParser<S> = StatefulDecoder<
     ExplicitVRLittleEndianDecoder,
@@ -260,6 +392,10 @@ where
             dt_utc_offset: FixedOffset::east(0),
             buffer: Vec::with_capacity(PARSER_BUFFER_CAPACITY),
             bytes_read: 0,
+            implicit_vr: false,
+            character_set: Vec::new(),
+            initial_text: None,
+            start_position: 0,
         }
     }
 }
@@ -275,7 +411,88 @@ impl<D, BD, S, TC> StatefulDecoder<D, BD, S, TC> {
             dt_utc_offset: FixedOffset::east(0),
             buffer: Vec::with_capacity(PARSER_BUFFER_CAPACITY),
             bytes_read: 0,
+            implicit_vr: false,
+            character_set: Vec::new(),
+            initial_text: None,
+            start_position: 0,
+        }
+    }
+
+    /// Create a new DICOM stateful decoder from its parts,
+    /// for a source which begins at the given position
+    /// of the original data.
+    ///
+    /// This is useful when resuming reading a data set
+    /// from a different source.
+    pub fn new_with_position(
+        from: S,
+        decoder: D,
+        basic: BD,
+        text: TC,
+        position: u64,
+    ) -> StatefulDecoder<D, BD, S, TC> {
+        DicomParser {
+            bytes_read: position,
+            start_position: position,
+            ..Self::new(from, decoder, basic, text)
+        }
+    }
+}
+
+impl<D, BD, S, T, TC> StatefulDecoder<D, BD, S, TC>
+where
+    S: std::ops::DerefMut<Target = T>,
+    T: ?Sized + Seek,
+{
+    /// Move the source back to where the decoder began reading,
+    /// so that the same data can be decoded again.
+    ///
+    /// The number of bytes read is reset accordingly,
+    /// and elements are decoded in the encoding of the transfer syntax again,
+    /// with the text codec which the decoder was created with.
+    pub fn rewind(&mut self) -> Result<()> {
+        let offset = self.bytes_read - self.start_position;
+        self.from
+            .seek(SeekFrom::Current(-(offset as i64)))
+            .context(SeekReader {
+                position: self.start_position,
+            })?;
+        self.bytes_read = self.start_position;
+        self.implicit_vr = false;
+        if let Some(text) = self.initial_text.take() {
+            self.text = text;
         }
+        self.character_set.clear();
+        Ok(())
+    }
+}
+
+impl<'a, D, BD, TC> StatefulDecoder<D, BD, &mut &'a [u8], TC> {
+    /// Retrieve the bytes of the in-memory source
+    /// which have not been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.from
+    }
+
+    /// Take the next `len` bytes directly from the in-memory source,
+    /// without copying them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source has less than `len` bytes left.
+    pub fn read_slice(&mut self, len: usize) -> Result<&'a [u8]> {
+        let data: &'a [u8] = self.from;
+        if data.len() < len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)).context(
+                ReadValueData {
+                    position: self.bytes_read,
+                },
+            );
+        }
+        let (value, rest) = data.split_at(len);
+        *self.from = rest;
+        self.bytes_read += len as u64;
+        Ok(value)
     }
 }
 
@@ -289,6 +506,17 @@ where
 {
     // ---------------- private methods ---------------------
 
+    /// The basic decoder of the values which follow:
+    /// little endian within a `UN` element of undefined length,
+    /// and in the byte order of the transfer syntax otherwise.
+    fn basic_decoder(&self) -> BasicDecoder {
+        if self.implicit_vr {
+            BasicDecoder::LE(LittleEndianBasicDecoder)
+        } else {
+            BasicDecoder::new(self.basic.endianness())
+        }
+    }
+
     fn require_known_length(&self, header: &DataElementHeader) -> Result<usize> {
         header
             .length()
@@ -305,9 +533,10 @@ where
 
         // tags
         let ntags = len >> 2;
+        let basic = self.basic_decoder();
         let parts: Result<C<Tag>> = n_times(ntags)
             .map(|_| {
-                self.basic
+                basic
                     .decode_tag(&mut self.from)
                     .context(ReadValueData {
                         position: self.bytes_read,
@@ -353,8 +582,9 @@ where
                 })
                 .collect(),
             _ => self
-                .buffer
-                .split(|v| *v == b'\\')
+                .text
+                .split_values(&self.buffer)
+                .into_iter()
                 .map(|slice| {
                     self.text.decode(slice).context(DecodeText {
                         position: self.bytes_read,
@@ -390,9 +620,10 @@ where
         let len = self.require_known_length(header)?;
 
         let n = len >> 1;
+        let basic = self.basic_decoder();
         let vec: Result<C<_>> = n_times(n)
             .map(|_| {
-                self.basic.decode_ss(&mut self.from).context(ReadValueData {
+                basic.decode_ss(&mut self.from).context(ReadValueData {
                     position: self.bytes_read,
                 })
             })
@@ -405,9 +636,10 @@ where
         let len = self.require_known_length(header)?;
         // sequence of 32-bit floats
         let n = len >> 2;
+        let basic = self.basic_decoder();
         let vec: Result<C<_>> = n_times(n)
             .map(|_| {
-                self.basic.decode_fl(&mut self.from).context(ReadValueData {
+                basic.decode_fl(&mut self.from).context(ReadValueData {
                     position: self.bytes_read,
                 })
             })
@@ -599,9 +831,10 @@ where
         let len = self.require_known_length(header)?;
         // sequence of 64-bit floats
         let n = len >> 3;
+        let basic = self.basic_decoder();
         let vec: Result<C<_>> = n_times(n)
             .map(|_| {
-                self.basic.decode_fd(&mut self.from).context(ReadValueData {
+                basic.decode_fd(&mut self.from).context(ReadValueData {
                     position: self.bytes_read,
                 })
             })
@@ -615,9 +848,10 @@ where
         // sequence of 32-bit unsigned integers
 
         let n = len >> 2;
+        let basic = self.basic_decoder();
         let vec: Result<C<_>> = n_times(n)
             .map(|_| {
-                self.basic.decode_ul(&mut self.from).context(ReadValueData {
+                basic.decode_ul(&mut self.from).context(ReadValueData {
                     position: self.bytes_read,
                 })
             })
@@ -631,9 +865,10 @@ where
         // sequence of 16-bit unsigned integers
 
         let n = len >> 1;
+        let basic = self.basic_decoder();
         let vec: Result<C<_>> = n_times(n)
             .map(|_| {
-                self.basic.decode_us(&mut self.from).context(ReadValueData {
+                basic.decode_us(&mut self.from).context(ReadValueData {
                     position: self.bytes_read,
                 })
             })
@@ -647,9 +882,10 @@ where
         // sequence of 64-bit unsigned integers
 
         let n = len >> 3;
+        let basic = self.basic_decoder();
         let vec: Result<C<_>> = n_times(n)
             .map(|_| {
-                self.basic.decode_uv(&mut self.from).context(ReadValueData {
+                basic.decode_uv(&mut self.from).context(ReadValueData {
                     position: self.bytes_read,
                 })
             })
@@ -663,9 +899,10 @@ where
         // sequence of 32-bit signed integers
 
         let n = len >> 2;
+        let basic = self.basic_decoder();
         let vec: Result<C<_>> = n_times(n)
             .map(|_| {
-                self.basic.decode_sl(&mut self.from).context(ReadValueData {
+                basic.decode_sl(&mut self.from).context(ReadValueData {
                     position: self.bytes_read,
                 })
             })
@@ -679,9 +916,10 @@ where
         // sequence of 64-bit signed integers
 
         let n = len >> 3;
+        let basic = self.basic_decoder();
         let vec: Result<C<_>> = n_times(n)
             .map(|_| {
-                self.basic.decode_sv(&mut self.from).context(ReadValueData {
+                basic.decode_sv(&mut self.from).context(ReadValueData {
                     position: self.bytes_read,
                 })
             })
@@ -699,12 +937,22 @@ where
     T: ?Sized + Read,
 {
     fn set_character_set(&mut self, charset: SpecificCharacterSet) -> Result<()> {
-        self.text = charset
+        let text = charset
             .codec()
             .context(UnsupportedCharacterSet { charset })?;
+        self.replace_text(text);
         Ok(())
     }
 
+    /// Decode text with the given codec from now on,
+    /// keeping the initial one for when the decoder is rewound.
+    fn replace_text(&mut self, text: DynamicTextCodec) {
+        let previous = std::mem::replace(&mut self.text, text);
+        if self.initial_text.is_none() {
+            self.initial_text = Some(previous);
+        }
+    }
+
     /// Read a sequence of Code String values. Similar to `read_value_strs`, but also
     /// triggers a character set change when it finds the _SpecificCharacterSet_
     /// attribute.
@@ -718,22 +966,45 @@ where
 
         // if it's a Specific Character Set, update the decoder immediately.
         if header.tag == Tag(0x0008, 0x0005) {
-            // Edge case handling strategies for
-            // unsupported specific character sets should probably be considered
-            // in the future. See #40 for discussion.
-            if let Some(charset) = parts.first().map(|x| x.as_ref()).and_then(|name| {
-                SpecificCharacterSet::from_code(name).or_else(|| {
-                    // TODO(#49) log this as a warning
-                    eprintln!("Unsupported character set `{}`, ignoring", name);
-                    None
-                })
-            }) {
-                self.set_character_set(charset)?;
-            }
+            self.switch_character_set(parts)?;
         }
 
         Ok(out)
     }
+
+    /// Switch to the character set with the given codes,
+    /// keeping the current one if they are not supported.
+    fn switch_character_set(&mut self, parts: &[String]) -> Result<()> {
+        // Edge case handling strategies for
+        // unsupported specific character sets should probably be considered
+        // in the future. See #40 for discussion.
+        if parts.len() > 1 {
+            // code extensions with ISO 2022 escape sequences
+            match Iso2022CharacterSetCodec::from_codes(parts.iter().map(|x| x.as_ref())) {
+                Some(codec) => self.replace_text(Box::new(codec)),
+                None => {
+                    // TODO(#49) log this as a warning
+                    eprintln!(
+                        "Unsupported character sets `{}`, ignoring",
+                        parts.join("\\")
+                    );
+                    return Ok(());
+                }
+            }
+        } else if let Some(charset) = parts.first().map(|x| x.as_ref()).and_then(|name| {
+            SpecificCharacterSet::from_code(name).or_else(|| {
+                // TODO(#49) log this as a warning
+                eprintln!("Unsupported character set `{}`, ignoring", name);
+                None
+            })
+        }) {
+            self.set_character_set(charset)?;
+        } else {
+            return Ok(());
+        }
+        self.character_set = parts.to_vec();
+        Ok(())
+    }
 }
 
 impl<S, T, D, BD> StatefulDecode for StatefulDecoder<D, BD, S, DynamicTextCodec>
@@ -758,9 +1029,37 @@ where
             .map_err(From::from)
     }
 
-    fn decode_item_header(&mut self) -> Result<SequenceItemHeader> {
+    fn decode_header_with_raw_vr(&mut self) -> Result<(DataElementHeader, Option<[u8; 2]>)> {
         self.decoder
-            .decode_item_header(&mut self.from)
+            .decode_header_with_raw_vr(&mut self.from)
+            .context(DecodeElementHeader {
+                position: self.bytes_read,
+            })
+            .map(|(header, bytes_read, raw_vr)| {
+                self.bytes_read += bytes_read as u64;
+                (header, raw_vr)
+            })
+    }
+
+    fn decode_header_implicit_vr(&mut self) -> Result<DataElementHeader> {
+        ImplicitVRLittleEndianDecoder::default()
+            .decode_header(&mut self.from)
+            .context(DecodeElementHeader {
+                position: self.bytes_read,
+            })
+            .map(|(header, bytes_read)| {
+                self.bytes_read += bytes_read as u64;
+                header
+            })
+    }
+
+    fn decode_item_header(&mut self) -> Result<SequenceItemHeader> {
+        let header = if self.implicit_vr {
+            ImplicitVRLittleEndianDecoder::default().decode_item_header(&mut self.from)
+        } else {
+            self.decoder.decode_item_header(&mut self.from)
+        };
+        header
             .context(DecodeItemHeader {
                 position: self.bytes_read,
             })
@@ -897,11 +1196,87 @@ where
         Ok(())
     }
 
+    fn skip_bytes(&mut self, len: u32) -> Result<()> {
+        let skipped = std::io::copy(
+            &mut self.from.by_ref().take(u64::from(len)),
+            &mut std::io::sink(),
+        )
+        .context(ReadValueData {
+            position: self.bytes_read,
+        })?;
+        if skipped < u64::from(len) {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)).context(
+                ReadValueData {
+                    position: self.bytes_read,
+                },
+            );
+        }
+        self.bytes_read += skipped;
+        Ok(())
+    }
+
+    fn set_implicit_vr(&mut self, implicit_vr: bool) {
+        self.implicit_vr = implicit_vr;
+    }
+
+    fn character_set(&self) -> &[String] {
+        &self.character_set
+    }
+
+    fn declare_character_set(&mut self, codes: &[String]) -> Result<()> {
+        self.switch_character_set(codes)
+    }
+
     fn bytes_read(&self) -> u64 {
         self.bytes_read
     }
 }
 
+/// Decode the raw bytes of a single element value with the given VR,
+/// in the same way as the data set reader does,
+/// without the need for a full data set.
+///
+/// This is useful when values are fetched apart from the rest of the object,
+/// such as bulk data in DICOMweb.
+/// Binary values are expected in little endian,
+/// and text values are decoded with the given character set.
+///
+/// # Errors
+///
+/// Returns an error if the character set is not supported,
+/// if the VR describes a sequence,
+/// or if the bytes do not make up a valid value of the given VR.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{PrimitiveValue, VR};
+/// # use dicom_encoding::text::SpecificCharacterSet;
+/// # use dicom_parser::stateful::decode::decode_primitive_value;
+/// let value = decode_primitive_value(VR::US, &[0x00, 0x02, 0x01, 0x00], &SpecificCharacterSet::Default)?;
+/// assert_eq!(value, PrimitiveValue::from([512_u16, 1]));
+/// # Ok::<(), dicom_parser::stateful::decode::Error>(())
+/// ```
+pub fn decode_primitive_value(
+    vr: VR,
+    bytes: &[u8],
+    charset: &SpecificCharacterSet,
+) -> Result<PrimitiveValue> {
+    let text = charset
+        .codec()
+        .context(UnsupportedCharacterSet { charset: *charset })?;
+    let mut source = bytes;
+    let mut decoder = StatefulDecoder::new(
+        &mut source,
+        ExplicitVRLittleEndianDecoder::default(),
+        LittleEndianBasicDecoder,
+        text,
+    );
+    // the tag is not relevant to how the value is decoded
+    let header = DataElementHeader::new(Tag(0xFFFF, 0xFFFF), vr, Length(bytes.len() as u32));
+    decoder.read_value(&header)
+}
+
 /// Remove trailing spaces and null characters.
 fn trim_trail_empty_bytes(mut x: &[u8]) -> &[u8] {
     while x.last() == Some(&b' ') || x.last() == Some(&b'\0') {
@@ -913,7 +1288,8 @@ fn trim_trail_empty_bytes(mut x: &[u8]) -> &[u8] {
 #[cfg(test)]
 mod tests {
     use super::{StatefulDecode, StatefulDecoder};
-    use dicom_core::header::{DataElementHeader, HasLength, Header, Length};
+    use dicom_core::header::{DataElementHeader, HasLength, Header, Length, SequenceItemHeader};
+    use dicom_core::value::PrimitiveValue;
     use dicom_core::{Tag, VR};
     use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
     use dicom_encoding::text::{DefaultCharacterSetCodec, DynamicTextCodec};
@@ -944,6 +1320,108 @@ mod tests {
     {
     }
 
+    /// A stateful decoder which only implements the required methods,
+    /// so that the provided ones are tested.
+    struct RequiredOnly<D>(D);
+
+    impl<D: StatefulDecode> StatefulDecode for RequiredOnly<D> {
+        type Reader = D::Reader;
+
+        fn decode_header(&mut self) -> super::Result<DataElementHeader> {
+            self.0.decode_header()
+        }
+
+        fn decode_item_header(&mut self) -> super::Result<SequenceItemHeader> {
+            self.0.decode_item_header()
+        }
+
+        fn read_value(&mut self, header: &DataElementHeader) -> super::Result<PrimitiveValue> {
+            self.0.read_value(header)
+        }
+
+        fn read_value_preserved(
+            &mut self,
+            header: &DataElementHeader,
+        ) -> super::Result<PrimitiveValue> {
+            self.0.read_value_preserved(header)
+        }
+
+        fn read_value_bytes(
+            &mut self,
+            header: &DataElementHeader,
+        ) -> super::Result<PrimitiveValue> {
+            self.0.read_value_bytes(header)
+        }
+
+        fn value_reader(
+            &mut self,
+            header: &DataElementHeader,
+        ) -> super::Result<std::io::Take<&mut Self::Reader>> {
+            self.0.value_reader(header)
+        }
+
+        fn read_bytes(&mut self, buf: &mut [u8]) -> super::Result<()> {
+            self.0.read_bytes(buf)
+        }
+
+        fn bytes_read(&self) -> u64 {
+            self.0.bytes_read()
+        }
+    }
+
+    #[test]
+    fn provided_methods() {
+        let mut cursor = &RAW[..];
+        let mut decoder = RequiredOnly(StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as DynamicTextCodec,
+        ));
+
+        // skip the first element's value
+        let (elem, raw_vr) = decoder
+            .decode_header_with_raw_vr()
+            .expect("should find an element");
+        assert_eq!(elem.tag(), Tag(2, 2));
+        assert_eq!(elem.vr(), VR::UI);
+        assert_eq!(raw_vr, None);
+        decoder.skip_bytes(26).expect("should skip the value");
+        assert_eq!(decoder.bytes_read(), 8 + 26);
+
+        let elem = decoder.decode_header().expect("should find an element");
+        assert_eq!(elem.tag(), Tag(2, 16));
+        let value = decoder
+            .read_value(&elem)
+            .expect("value after element header");
+        assert_eq!(value.string(), Ok("1.2.840.10008.1.2.1\0"));
+
+        // nothing left to skip
+        assert!(decoder.skip_bytes(1).is_err());
+
+        // a header in implicit VR little endian amid explicit VR
+        #[rustfmt::skip]
+        static IMPLICIT: &[u8] = &[
+            0x08, 0x00, 0x60, 0x00, // (0008,0060) Modality
+            0x02, 0x00, 0x00, 0x00, // length: 2
+            b'M', b'R',
+        ];
+        let mut cursor = IMPLICIT;
+        let mut decoder = RequiredOnly(StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as DynamicTextCodec,
+        ));
+        let elem = decoder
+            .decode_header_implicit_vr()
+            .expect("should find an element");
+        assert_eq!(elem.tag(), Tag(0x0008, 0x0060));
+        assert_eq!(elem.vr(), VR::CS);
+        assert_eq!(elem.length(), Length(2));
+        assert_eq!(decoder.bytes_read(), 8);
+    }
+
     #[test]
     fn decode_data_elements() {
         let mut cursor = &RAW[..];
@@ -1036,4 +1514,123 @@ mod tests {
         assert_eq!(value.string(), Ok("ISO_IR 192"));
         assert_eq!(decoder.text.name(), "ISO_IR 192",);
     }
+
+    /// Test that the stateful decoder decodes text values
+    /// with ISO 2022 escape sequences
+    /// after reaching a multi-valued Specific Character Set element.
+    #[test]
+    fn update_character_set_with_code_extensions() {
+        #[rustfmt::skip]
+        const RAW: &[u8] = b"\
+            \x08\x00\x05\x00CS\x10\x00\\ISO 2022 IR 87 \
+            \x10\x00\x10\x00PN\x2c\x00\
+            Yamada^Tarou=\x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B\\\x1b$B$\\\x1b(B ";
+
+        let mut cursor = RAW;
+        let mut decoder = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRLittleEndianDecoder::default(),
+            LittleEndianBasicDecoder,
+            Box::new(DefaultCharacterSetCodec) as DynamicTextCodec,
+        );
+
+        let header = decoder
+            .decode_header()
+            .expect("should find an element header");
+        let value = decoder
+            .read_value_preserved(&header)
+            .expect("should read a value");
+        assert_eq!(value.multiplicity(), 2);
+        assert_eq!(decoder.text.name(), "ISO 2022 IR 6");
+
+        let header = decoder
+            .decode_header()
+            .expect("should find an element header");
+        assert_eq!(header.tag, Tag(0x0010, 0x0010));
+        let value = decoder.read_value(&header).expect("should read a value");
+        assert_eq!(
+            value.strings().unwrap(),
+            &["Yamada^Tarou=山田^太郎".to_string(), "ぼ ".to_string()][..],
+        );
+    }
+
+    #[test]
+    fn decode_primitive_values_per_vr() {
+        use super::decode_primitive_value;
+        use dicom_core::value::PrimitiveValue;
+        use dicom_encoding::text::SpecificCharacterSet;
+
+        let decode = |vr, bytes: &[u8]| {
+            decode_primitive_value(vr, bytes, &SpecificCharacterSet::Default)
+                .unwrap_or_else(|e| panic!("{} value should decode: {}", vr, e))
+        };
+
+        // text, with multiple values
+        let value = decode(VR::CS, b"ORIGINAL\\PRIMARY");
+        assert_eq!(value.strings().unwrap(), &["ORIGINAL", "PRIMARY"][..]);
+        let value = decode(VR::PN, b"Doe^John");
+        assert_eq!(value.string().unwrap(), "Doe^John");
+        // the backslash is not a delimiter in LT
+        let value = decode(VR::LT, b"a\\b ");
+        assert_eq!(value.multiplicity(), 1);
+
+        // numbers as text
+        assert_eq!(
+            decode(VR::IS, b"12\\-3 ").to_multi_int::<i32>().unwrap(),
+            vec![12, -3]
+        );
+        assert_eq!(decode(VR::DS, b"1.5 ").to_float64().unwrap(), 1.5);
+
+        // binary numbers, in little endian
+        assert_eq!(
+            decode(VR::US, &[0x00, 0x02, 0x01, 0x00]),
+            PrimitiveValue::from([512_u16, 1])
+        );
+        assert_eq!(
+            decode(VR::SL, &[0xFE, 0xFF, 0xFF, 0xFF]),
+            PrimitiveValue::from(-2_i32)
+        );
+        assert_eq!(
+            decode(VR::FD, &1.25_f64.to_le_bytes()),
+            PrimitiveValue::from(1.25_f64)
+        );
+
+        // attribute tags
+        assert_eq!(
+            decode(VR::AT, &[0x10, 0x00, 0x20, 0x00]),
+            PrimitiveValue::Tags(dicom_core::smallvec::smallvec![Tag(0x0010, 0x0020)])
+        );
+
+        // dates
+        let value = decode(VR::DA, b"20201231");
+        assert_eq!(value.to_str(), "20201231");
+        assert!(matches!(value, PrimitiveValue::Date(_)));
+
+        // raw bytes
+        assert_eq!(
+            decode(VR::OB, &[1, 2, 3, 4]),
+            PrimitiveValue::from(vec![1_u8, 2, 3, 4])
+        );
+
+        // no bytes at all
+        assert_eq!(decode(VR::FL, &[]), PrimitiveValue::Empty);
+    }
+
+    #[test]
+    fn decode_primitive_value_with_character_set() {
+        use super::{decode_primitive_value, Error};
+        use dicom_encoding::text::SpecificCharacterSet;
+
+        let value = decode_primitive_value(
+            VR::PN,
+            "Wang^XiaoDong=王^小東".as_bytes(),
+            &SpecificCharacterSet::IsoIr192,
+        )
+        .unwrap();
+        assert_eq!(value.string().unwrap(), "Wang^XiaoDong=王^小東");
+
+        // sequences are not primitive values
+        let result = decode_primitive_value(VR::SQ, &[0; 8], &SpecificCharacterSet::Default);
+        assert!(matches!(result, Err(Error::NonPrimitiveType { .. })));
+    }
 }
@@ -35,3 +35,20 @@ fn contains_base_ts() {
     // contains explicit VR big endian and is fully supported
     assert_fully_supported(registry, "1.2.840.10008.1.2.2", "Explicit VR Big Endian");
 }
+
+#[test]
+fn identifies_pixel_encoding() {
+    use dicom_encoding::transfer_syntax::PixelEncoding;
+
+    let registry = TransferSyntaxRegistry;
+    let encoding_of = |uid| registry.get(uid).unwrap().pixel_encoding();
+
+    assert_eq!(encoding_of("1.2.840.10008.1.2.1"), PixelEncoding::Native);
+    assert_eq!(encoding_of("1.2.840.10008.1.2.4.50"), PixelEncoding::Jpeg);
+    assert_eq!(encoding_of("1.2.840.10008.1.2.4.80"), PixelEncoding::JpegLs);
+    assert_eq!(
+        encoding_of("1.2.840.10008.1.2.4.90"),
+        PixelEncoding::Jpeg2000
+    );
+    assert_eq!(encoding_of("1.2.840.10008.1.2.5\0"), PixelEncoding::Rle);
+}